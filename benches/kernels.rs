@@ -0,0 +1,223 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use mandybrot::{
+    render_attractor, render_flame, render_fractal, render_ifs, render_zoom, set_progress_bars,
+    Attractor, ColourMap, Complex, Flame, FlameTransform, Fractal, Ifs, Precision, RngStrategy,
+    SamplePattern, Variation, Viewport, ZoomPath,
+};
+
+/// Named viewports as `(name, centre, scale)`.
+const VIEWPORTS: [(&str, [f64; 2], f64); 2] = [
+    ("full_set", [-0.75, 0.0], 3.0),
+    (
+        "deep_zoom",
+        [-0.743_643_887_037_151, 0.131_825_904_205_330],
+        1.0e-10,
+    ),
+];
+
+const RESOLUTIONS: [[u32; 2]; 3] = [[64, 64], [256, 256], [1024, 1024]];
+
+fn fractals() -> Vec<(&'static str, Fractal<f64>)> {
+    vec![
+        ("mandelbrot", Fractal::Mandelbrot),
+        ("burning_ship", Fractal::BurningShip),
+        (
+            "julia",
+            Fractal::Julia {
+                c: Complex::new(-0.8, 0.156),
+            },
+        ),
+        ("tricorn", Fractal::Tricorn),
+        ("multibrot", Fractal::Multibrot { power: 3 }),
+        ("newton", Fractal::Newton { epsilon: 1.0e-6 }),
+        (
+            "phoenix",
+            Fractal::Phoenix {
+                c: Complex::new(0.5667, -0.5),
+            },
+        ),
+        ("celtic_mandelbrot", Fractal::CelticMandelbrot),
+    ]
+}
+
+/// Single-point sampling of each kernel, at an escaping and a non-escaping point.
+fn bench_kernels(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kernel");
+    let points = [
+        ("exterior", Complex::new(0.3, 0.6)),
+        ("interior", Complex::new(-0.1, 0.1)),
+    ];
+    for (name, fractal) in fractals() {
+        for (point_name, p) in points {
+            group.bench_with_input(BenchmarkId::new(name, point_name), &p, |b, &p| {
+                b.iter(|| fractal.sample(black_box(p), black_box(1000), None))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Whole-image renders at several resolutions over the named viewports.
+fn bench_render_fractal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_fractal");
+    group.sample_size(10);
+    for (name, centre, scale) in VIEWPORTS {
+        for resolution in RESOLUTIONS {
+            let id = BenchmarkId::new(name, format!("{}x{}", resolution[0], resolution[1]));
+            group.bench_with_input(id, &resolution, |b, &resolution| {
+                b.iter(|| {
+                    render_fractal(
+                        Complex::new(centre[0], centre[1]),
+                        1000,
+                        scale,
+                        resolution,
+                        Fractal::Mandelbrot,
+                        1,
+                        SamplePattern::Grid,
+                        None,
+                    )
+                    .unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_render_attractor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_attractor");
+    group.sample_size(10);
+    let attractor = Attractor::Clifford {
+        a: -1.7,
+        b: 1.8,
+        c: -1.9,
+        d: 0.4,
+    };
+    for resolution in RESOLUTIONS {
+        let id = BenchmarkId::new("clifford", format!("{}x{}", resolution[0], resolution[1]));
+        group.bench_with_input(id, &resolution, |b, &resolution| {
+            b.iter(|| {
+                render_attractor(
+                    Complex::new(0.0, 0.0),
+                    3.0,
+                    resolution,
+                    Complex::new(0.0, 0.0),
+                    1.0,
+                    100,
+                    1000,
+                    10,
+                    &attractor,
+                    &RngStrategy::Seeded(0),
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_render_flame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_flame");
+    group.sample_size(10);
+    // The swirl flame of `input/flame/swirl.yaml`
+    let flame = Flame {
+        transforms: vec![
+            FlameTransform {
+                weight: 1.0,
+                affine: [0.5, 0.0, -0.5, 0.0, 0.5, 0.5],
+                variations: vec![(Variation::Linear, 0.5), (Variation::Swirl, 0.5)],
+                colour: 0.0,
+            },
+            FlameTransform {
+                weight: 1.0,
+                affine: [0.5, 0.0, 0.5, 0.0, 0.5, 0.5],
+                variations: vec![(Variation::Spherical, 1.0)],
+                colour: 0.5,
+            },
+            FlameTransform {
+                weight: 1.0,
+                affine: [0.5, 0.0, 0.0, 0.0, 0.5, -0.5],
+                variations: vec![(Variation::Sinusoidal, 1.0)],
+                colour: 1.0,
+            },
+        ],
+        gamma: 2.2,
+    };
+    let colour_map = ColourMap::preset("plasma").unwrap();
+    for resolution in RESOLUTIONS {
+        let id = BenchmarkId::new("swirl", format!("{}x{}", resolution[0], resolution[1]));
+        let viewport = Viewport::new(Complex::new(0.0, 0.0), 3.0, resolution);
+        group.bench_with_input(id, &viewport, |b, viewport| {
+            b.iter(|| {
+                render_flame(
+                    viewport,
+                    &flame,
+                    100,
+                    1000,
+                    1,
+                    &colour_map,
+                    &RngStrategy::Seeded(0),
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_render_ifs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_ifs");
+    group.sample_size(10);
+    let fern = Ifs::preset("barnsley_fern").unwrap();
+    for resolution in RESOLUTIONS {
+        let id = BenchmarkId::new(
+            "barnsley_fern",
+            format!("{}x{}", resolution[0], resolution[1]),
+        );
+        let viewport = Viewport::new(Complex::new(0.0, 5.0), 10.0, resolution);
+        group.bench_with_input(id, &viewport, |b, viewport| {
+            b.iter(|| render_ifs(viewport, &fern, 100, 10_000, &RngStrategy::Seeded(0)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Single-frame zooms deep enough to be rendered by perturbation about a reference orbit.
+fn bench_render_perturbation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_perturbation");
+    group.sample_size(10);
+    let (_, centre, _) = VIEWPORTS[1];
+    let target = Complex::new(centre[0].into(), centre[1].into());
+    let zoom = ZoomPath::new(target, 1.0e-20, 1);
+    for resolution in RESOLUTIONS {
+        assert_eq!(zoom.precision(0, resolution), Precision::Perturbation);
+        let id = BenchmarkId::new("deep_zoom", format!("{}x{}", resolution[0], resolution[1]));
+        group.bench_with_input(id, &resolution, |b, &resolution| {
+            b.iter(|| {
+                render_zoom(&zoom, resolution, 1000, |_, _, image| {
+                    black_box(image);
+                })
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Turns off progress bars, so they are neither drawn nor timed inside the benchmark loops.
+fn quiet(_: &mut Criterion) {
+    set_progress_bars(false);
+}
+
+criterion_group!(
+    benches,
+    quiet,
+    bench_kernels,
+    bench_render_fractal,
+    bench_render_attractor,
+    bench_render_flame,
+    bench_render_ifs,
+    bench_render_perturbation
+);
+criterion_main!(benches);
@@ -1,6 +1,6 @@
-use mandybrot::{render_fractal, Complex, Fractal};
+use mandybrot::{render_fractal, Complex, Fractal, MandybrotError, SamplePattern};
 
-fn main() {
+fn main() -> Result<(), MandybrotError> {
     let fractal = Fractal::Mandelbrot;
 
     let centre = Complex::new(-0.75, 0.0);
@@ -8,7 +8,16 @@ fn main() {
     let scale = 3.0;
     let resolution = [21, 21];
     let super_samples = 1;
-    let data = render_fractal(centre, max_iter, scale, resolution, fractal, super_samples);
+    let data = render_fractal(
+        centre,
+        max_iter,
+        scale,
+        resolution,
+        fractal,
+        super_samples,
+        SamplePattern::Grid,
+        None,
+    )?;
 
     let rows = data.shape()[0];
     for y in 0..rows {
@@ -18,4 +27,6 @@ fn main() {
         }
         println!();
     }
+
+    Ok(())
 }
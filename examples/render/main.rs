@@ -0,0 +1,511 @@
+use ndarray::{Array2, Array3, Zip};
+use ndarray_images::Image;
+use num_traits::Float;
+use palette::{Darken, LinSrgba};
+use serde::{Deserialize, Serialize};
+use std::{fs::create_dir_all, path::Path};
+
+use mandybrot::{
+    blend_layers, composite, downsample, normalise_percentiles, post_process,
+    render_attractor_coloured, render_attractor_pixels, render_attractor_viewport, render_batch,
+    render_bifurcation, render_buddhabrot_viewport, render_flame, render_fractal_mirrored,
+    render_fractal_viewport, render_gallery, render_ifs, render_lsystem, AttractorSeeding,
+    AttractorSweep, Background, Blend, ColourEncoding, Complex, Dither, Fade, Job, MandybrotError,
+    PaletteCycle, Parameters, RenderCache, RenderKey, Sweep, ThumbnailStrip, ToneMap, Transparency,
+};
+
+mod shading;
+mod shared;
+mod tui;
+use shading::{create_ambient_occlusion_map, create_shadow_map};
+use shared::{
+    create_colour_map, find_colour_map, input_path, read_command, read_input_file, set_yaml_path,
+    Command, OUTPUT_DIR,
+};
+
+type Precision = f64;
+
+/// Settings of how a render is shaded, animated and saved, read from the same file as its `Parameters`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Output<T> {
+    // Escape-time fractals
+    pub light_dir: Option<[T; 3]>,
+    /// Directory in which to cache the iteration counts, so re-colouring does not re-render.
+    pub cache: Option<String>,
+
+    // Animation, sweeping the attractor parameters by `shift` every `frames_per_step` frames and the palette offset by `cycle_speed` each frame
+    pub num_frames: Option<usize>,
+    pub shift: Option<T>,
+    pub frames_per_step: Option<usize>,
+    /// Fraction of the previous frame's attractor hits kept in each new frame, leaving fading trails.
+    pub decay: Option<f64>,
+    pub cycle_speed: Option<T>,
+    pub num_thumbnails: Option<usize>,
+    pub thumbnail_resolution: Option<[u32; 2]>,
+
+    // Output, in greyscale if no colour map is given
+    pub image_name: String,
+    #[serde(default)]
+    pub log: bool,
+    /// Percentiles of the data mapped to either end of the colour map, such as `[1.0, 99.0]`, clipping the outliers beyond them.
+    pub clip: Option<[f64; 2]>,
+    pub tone_map: Option<ToneMap<T>>,
+    #[serde(default = "one")]
+    pub gamma: T,
+    pub colour_map: Option<String>,
+    pub palette_cycle: Option<PaletteCycle<T>>,
+    /// Noise added as the image is rounded to 8 bits, to break up the bands of smooth gradients.
+    #[serde(default)]
+    pub dither: Dither,
+    /// Transfer function the image is saved in, sRGB unless `Linear` is asked for.
+    #[serde(default)]
+    pub encoding: ColourEncoding,
+    /// Alpha of the interior and exterior of escape-time fractals.
+    pub transparency: Option<Transparency>,
+    /// Backdrop the image is placed over, leaving transparent pixels see-through if not given.
+    pub background: Option<Background>,
+}
+
+fn one<T: Float>() -> T {
+    T::one()
+}
+
+fn main() {
+    match read_command() {
+        Command::Render(params_file) => {
+            render_frames(load_parameters(&params_file), read_input_file(&params_file))
+        }
+        Command::Batch(params_file) => render_sweeps(read_input_file(&params_file)),
+        Command::Tui(params_file) => {
+            tui::preview(load_parameters(&params_file), read_input_file(&params_file))
+        }
+        Command::Gallery => render_contact_sheet(),
+    }
+}
+
+/// Resolution of each thumbnail of the gallery, and the number of thumbnails in each of its rows.
+const GALLERY_THUMBNAIL_RESOLUTION: [u32; 2] = [160, 160];
+const GALLERY_COLUMNS: u32 = 8;
+
+/// Renders a labelled thumbnail of every built-in fractal, attractor and preset into a single image.
+fn render_contact_sheet() {
+    let image = render_gallery(
+        GALLERY_THUMBNAIL_RESOLUTION,
+        GALLERY_COLUMNS,
+        &create_colour_map("magma"),
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Failed to render gallery: {}", err);
+        std::process::exit(1);
+    });
+    save(
+        &image,
+        "gallery.png",
+        Dither::default(),
+        ColourEncoding::default(),
+    );
+}
+
+/// Reads and validates the `Parameters` of a parameters file, exiting if they are invalid.
+fn load_parameters(params_file: &str) -> Parameters<Precision> {
+    Parameters::load(input_path(params_file)).unwrap_or_else(|err| {
+        eprintln!("Failed to read parameters file '{}': {}", params_file, err);
+        std::process::exit(1);
+    })
+}
+
+/// Renders every combination of the values swept by the `sweeps` list of a parameters file in parallel, naming each image after its step of each sweep.
+fn render_sweeps(mut base: serde_yaml::Value) {
+    let sweeps: Vec<Sweep<Precision>> = base
+        .as_mapping_mut()
+        .and_then(|mapping| mapping.remove("sweeps"))
+        .map(|sweeps| serde_yaml::from_value(sweeps).expect("Failed to parse sweeps"))
+        .expect("Batch parameters need a list of 'sweeps'.");
+
+    let result = render_batch(&sweeps, |job| {
+        let mut value = base.clone();
+        for (sweep, &number) in sweeps.iter().zip(&job.values) {
+            set_yaml_path(&mut value, &sweep.parameter, number)
+                .unwrap_or_else(|| panic!("Swept parameter '{}' not found.", sweep.parameter));
+        }
+        let params: Parameters<Precision> =
+            serde_yaml::from_value(value.clone()).expect("Failed to parse swept parameters");
+        params.validate()?;
+        let mut output: Output<Precision> =
+            serde_yaml::from_value(value).expect("Failed to parse swept output settings");
+        output.image_name = frame_filename(&output.image_name, &job.name(&sweeps));
+        // Give each job a palette of its own if the colour map is random but unseeded
+        if let Some(colour_map) = output.colour_map.as_mut() {
+            if colour_map.starts_with("random:") && colour_map.matches(':').count() == 1 {
+                *colour_map = format!("{}:{}", colour_map, job.index(&sweeps));
+            }
+        }
+
+        let image = render(&params, &output, None)?;
+        save(&image, &output.image_name, output.dither, output.encoding);
+        Ok(())
+    });
+    if let Err(err) = result {
+        eprintln!("Failed to render batch: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Renders each frame of an animation, or a single image.
+fn render_frames(mut params: Parameters<Precision>, mut output: Output<Precision>) {
+    let num_frames = output.num_frames.unwrap_or(1);
+    let mut thumbnails = output.num_thumbnails.map(|num_thumbnails| {
+        ThumbnailStrip::new(
+            num_thumbnails,
+            output.thumbnail_resolution.unwrap_or([64, 64]),
+            LinSrgba::new(0.0, 0.0, 0.0, 0.0),
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Invalid thumbnails: {}", err);
+            std::process::exit(1);
+        })
+    });
+
+    let sweep = AttractorSweep::new(
+        output.shift.unwrap_or(0.001),
+        num_frames as u32,
+        output.frames_per_step.unwrap_or(1) as u32,
+        output.decay.unwrap_or(0.0),
+    );
+    let mut fade = output.decay.map(|decay| {
+        Fade::new(decay).unwrap_or_else(|err| {
+            eprintln!("Invalid decay: {}", err);
+            std::process::exit(1);
+        })
+    });
+    let base_job = params.job.clone();
+
+    for frame in 0..num_frames {
+        if frame > 0 {
+            if let (
+                Job::Attractor { attractor, .. },
+                Job::Attractor {
+                    attractor: base, ..
+                },
+            ) = (&mut params.job, &base_job)
+            {
+                *attractor = sweep.attractor_at(base, frame as u32);
+            }
+            if let (Some(cycle), Some(cycle_speed)) = (output.palette_cycle, output.cycle_speed) {
+                output.palette_cycle = Some(cycle.shifted(cycle_speed));
+            }
+        }
+
+        let image = render(&params, &output, fade.as_mut()).unwrap_or_else(|err| {
+            eprintln!("Failed to render '{}': {}", output.image_name, err);
+            std::process::exit(1);
+        });
+
+        // Record the frame in the thumbnail strip
+        if let Some(thumbnails) = thumbnails.as_mut() {
+            let slot = thumbnails.slot(frame, num_frames);
+            if frame + 1 == num_frames || thumbnails.slot(frame + 1, num_frames) != slot {
+                thumbnails.set(slot, &image).unwrap_or_else(|err| {
+                    eprintln!("Failed to record thumbnail {}: {}", slot, err);
+                    std::process::exit(1);
+                });
+            }
+        }
+
+        let filename = if num_frames > 1 {
+            frame_filename(&output.image_name, &format!("{:0>6}", frame))
+        } else {
+            output.image_name.clone()
+        };
+        save(&image, &filename, output.dither, output.encoding);
+    }
+
+    if let Some(thumbnails) = thumbnails {
+        save(
+            thumbnails.data(),
+            &frame_filename(&output.image_name, "thumbnails"),
+            output.dither,
+            output.encoding,
+        );
+    }
+}
+
+/// Renders and colours a single image, blending any layers over it, fading attractor hits into those of the previous frames if a `fade` is given.
+fn render(
+    params: &Parameters<Precision>,
+    output: &Output<Precision>,
+    fade: Option<&mut Fade>,
+) -> Result<Array2<LinSrgba>, MandybrotError> {
+    let mut image = render_layer(params, output, fade)?;
+
+    if !params.layers.is_empty() {
+        let mut layers = vec![(image, Blend::default())];
+        for layer in &params.layers {
+            let layer_params = Parameters {
+                job: layer.job.clone(),
+                pipeline: layer.pipeline.clone(),
+                layers: Vec::new(),
+                ..params.clone()
+            };
+            layers.push((render_layer(&layer_params, output, None)?, layer.blend));
+        }
+        image = blend_layers(&layers)?;
+    }
+
+    // Place the image over the background
+    if let Some(background) = &output.background {
+        image = composite(&image, background);
+    }
+
+    Ok(image)
+}
+
+/// Renders and colours the image of a single job.
+fn render_layer(
+    params: &Parameters<Precision>,
+    output: &Output<Precision>,
+    fade: Option<&mut Fade>,
+) -> Result<Array2<LinSrgba>, MandybrotError> {
+    let super_samples = params.super_samples;
+    let viewport = params.viewport();
+    // Orbit densities are rendered large and downsampled, while escape-time fractals are anti-aliased while sampling.
+    let large_viewport = viewport.with_resolution([
+        params.resolution[0] * super_samples,
+        params.resolution[1] * super_samples,
+    ]);
+
+    let (data, shading, downsample_factor) = match &params.job {
+        Job::Fractal {
+            fractal,
+            sample_pattern,
+            mirror,
+        } => {
+            let render = || {
+                let render_fractal = match mirror {
+                    true => render_fractal_mirrored,
+                    false => render_fractal_viewport,
+                };
+                render_fractal(
+                    &viewport,
+                    fractal,
+                    params.max_iter,
+                    super_samples,
+                    *sample_pattern,
+                    params.periodicity,
+                )
+            };
+            let data = match &output.cache {
+                Some(directory) => {
+                    let key = RenderKey {
+                        viewport,
+                        fractal: *fractal,
+                        max_iter: params.max_iter,
+                        samples_per_pixel: super_samples,
+                        pattern: *sample_pattern,
+                        periodicity: params.periodicity,
+                    };
+                    RenderCache::new(directory).get_or_render(&key, render)?
+                }
+                None => render()?,
+            };
+            let shading = output.light_dir.map(|light_dir| {
+                create_shadow_map(&data, &light_dir)
+                    * create_ambient_occlusion_map(&data, 4, 4, 1.0e-1)
+            });
+            (data, shading, 1)
+        }
+        Job::Attractor {
+            attractor,
+            start,
+            radius,
+            num_samples,
+            draw_after,
+            seeding,
+            bifurcation,
+            provenance,
+        } => {
+            let start = Complex::new(start[0], start[1]);
+            if let (None, Some(provenance)) = (bifurcation, provenance) {
+                let image = render_attractor_coloured(
+                    &large_viewport,
+                    start,
+                    *radius,
+                    *num_samples,
+                    params.max_iter,
+                    *draw_after,
+                    attractor,
+                    *provenance,
+                    &create_colour_map(output.colour_map.as_deref().unwrap_or("twilight")),
+                    &params.rng,
+                )?;
+                return downsample(&image, super_samples as usize);
+            }
+            let data = match (bifurcation, seeding) {
+                (Some(parameter), _) => render_bifurcation(
+                    &large_viewport,
+                    attractor,
+                    *parameter,
+                    start,
+                    *radius,
+                    *num_samples,
+                    params.max_iter,
+                    *draw_after,
+                    &params.rng,
+                )?,
+                (None, AttractorSeeding::Pixels { spacing }) => render_attractor_pixels(
+                    &large_viewport,
+                    *spacing,
+                    params.max_iter,
+                    *draw_after,
+                    attractor,
+                )?,
+                (None, AttractorSeeding::Disc) => render_attractor_viewport(
+                    &large_viewport,
+                    start,
+                    *radius,
+                    *num_samples,
+                    params.max_iter,
+                    *draw_after,
+                    attractor,
+                    &params.rng,
+                )?,
+            };
+            let data = match fade {
+                Some(fade) => fade.add(&data)?.mapv(|hits| hits.round() as u32),
+                None => data,
+            };
+            (data, None, super_samples)
+        }
+        Job::OrbitDensity {
+            sampling,
+            num_samples,
+        } => {
+            let data = render_buddhabrot_viewport(
+                &large_viewport,
+                *num_samples,
+                params.max_iter,
+                sampling,
+                &params.rng,
+            )?;
+            (data, None, super_samples)
+        }
+        Job::Flame { flame, num_samples } => {
+            return render_flame(
+                &viewport,
+                flame,
+                *num_samples,
+                params.max_iter,
+                super_samples,
+                &create_colour_map(output.colour_map.as_deref().unwrap_or("inferno")),
+                &params.rng,
+            );
+        }
+        Job::Ifs { ifs, num_samples } => {
+            let data = render_ifs(
+                &large_viewport,
+                &ifs.resolve()?,
+                *num_samples,
+                params.max_iter,
+                &params.rng,
+            )?;
+            (data, None, super_samples)
+        }
+        Job::LSystem { lsystem, depth } => {
+            let data = render_lsystem(&large_viewport, &lsystem.resolve()?, *depth)?;
+            (data, None, super_samples)
+        }
+    };
+
+    // A pipeline in the parameters file replaces the fixed sequence of output settings
+    if !params.pipeline.is_empty() {
+        let mut coloured_data = post_process(&data, &params.pipeline, find_colour_map)?;
+        if downsample_factor > 1 {
+            coloured_data = downsample(&coloured_data, downsample_factor as usize)?;
+        }
+        return Ok(coloured_data);
+    }
+
+    // Keep the iteration counts to set the alpha of the interior and exterior
+    let transparency = match &params.job {
+        Job::Fractal { .. } => output.transparency,
+        _ => None,
+    };
+    let counts = transparency.map(|_| data.clone());
+
+    // Normalise the data, compressing its dynamic range if a tone map is given
+    let max = (*data.iter().max().unwrap()).max(1) as Precision;
+    let data = if let Some(tone_map) = &output.tone_map {
+        tone_map.apply(&data.mapv(|v| v as Precision))?
+    } else if let Some([low, high]) = output.clip {
+        normalise_percentiles(&data.mapv(|v| v as Precision), low, high)?
+    } else if output.log {
+        data.mapv(|v| (v as Precision).ln().max(0.0) / max.ln())
+    } else {
+        data.mapv(|v| v as Precision / max)
+    };
+
+    // Apply gamma correction
+    let data = data.mapv(|v| v.powf(output.gamma));
+
+    // Repeat and offset the palette
+    let data = match &output.palette_cycle {
+        Some(cycle) => data.mapv(|v| cycle.apply(v)),
+        None => data,
+    };
+
+    // Apply the colour map to convert greyscale values to RGB
+    let mut coloured_data = match &output.colour_map {
+        Some(colour_map) => {
+            let cmap = create_colour_map(colour_map);
+            data.mapv(|v| cmap.sample(v as f32))
+        }
+        None => data.mapv(|v| LinSrgba::new(v as f32, v as f32, v as f32, 1.0)),
+    };
+
+    // Darken by the shadow and ambient occlusion maps
+    if let Some(shading) = shading {
+        coloured_data = Zip::from(&coloured_data)
+            .and(&shading)
+            .map_collect(|&colour, &s| colour.darken(s as f32));
+    }
+
+    if let (Some(transparency), Some(counts)) = (transparency, counts) {
+        transparency.apply(&mut coloured_data, &counts, params.max_iter)?;
+    }
+
+    // Average the super samples
+    if downsample_factor > 1 {
+        coloured_data = downsample(&coloured_data, downsample_factor as usize)?;
+    }
+
+    Ok(coloured_data)
+}
+
+/// Inserts a suffix before the extension of an image name, e.g. `scan.png` to `scan-000001.png`.
+fn frame_filename(image_name: &str, suffix: &str) -> String {
+    let path = Path::new(image_name);
+    let stem = path.file_stem().unwrap().to_string_lossy();
+    let extension = path
+        .extension()
+        .map_or("png".into(), |extension| extension.to_string_lossy());
+    path.with_file_name(format!("{}-{}.{}", stem, suffix, extension))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Saves a colour image to the output directory.
+fn save(image: &Array2<LinSrgba>, image_name: &str, dither: Dither, encoding: ColourEncoding) {
+    // Encode from linear light and convert to `Array3<f32>`, dithering by a fraction of an 8-bit level
+    let encoded = encoding.encode_image(image);
+    let (height, width) = image.dim();
+    let data: Array3<f32> = Array3::from_shape_fn((height, width, 4), |(y, x, channel)| {
+        (encoded[(y, x)][channel] + dither.threshold([x, y]) / 255.0).clamp(0.0, 1.0)
+    });
+
+    let filename = format!("{}/{}", OUTPUT_DIR, image_name);
+    let path = Path::new(&filename);
+    create_dir_all(path.parent().unwrap()).unwrap();
+    data.save(&filename).unwrap();
+    println!("Image saved to '{}'.", filename);
+}
@@ -1,95 +1,8 @@
-use enterpolation::Generator;
-use ndarray::{Array2, Array3, Zip};
-use ndarray_images::Image;
-use palette::Darken;
-use serde::{Deserialize, Serialize};
-
-use mandybrot::{render_fractal, Complex, Fractal};
-
-mod shared;
-use shared::{create_colour_map, read_input_args, OUTPUT_DIR};
+use ndarray::Array2;
 
 type Precision = f64;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Parameters<T> {
-    pub centre: [T; 2],
-    pub scale: T,
-    pub resolution: [u32; 2],
-    pub super_samples: u32,
-
-    pub max_iter: u32,
-    pub light_dir: [T; 3],
-
-    pub fractal: Fractal<T>,
-
-    pub image_name: String,
-    pub log: bool,
-    pub gamma: T,
-    pub colour_map: String,
-}
-
-fn main() {
-    // Read parameters from file
-    let params = read_input_args::<Parameters<Precision>>();
-
-    // Create the colour map
-    let cmap = create_colour_map(&params.colour_map);
-
-    // Render the attractor
-    let data = render_fractal(
-        Complex::new(params.centre[0], params.centre[1]),
-        params.max_iter,
-        params.scale,
-        params.resolution,
-        params.fractal,
-        params.super_samples,
-    );
-    let shadow_map = create_shadow_map(&data, &params.light_dir);
-    // let ao_map = create_ambient_occlusion_map(
-    //     &data, 16, 16, 1.0e-1, // params.scale / params.resolution[0] as Precision,
-    // );
-    let ao_map = create_ambient_occlusion_map(
-        &data, 4, 4, 1.0e-1, // params.scale / params.resolution[0] as Precision,
-    );
-    let shadow_map = shadow_map * &ao_map;
-    // let shadow_map = ao_map;
-
-    // Normalise the data
-    let max = *data.iter().max().unwrap() as Precision;
-    let data = if params.log {
-        data.mapv(|v| (v as Precision).ln().max(0.0) / (max as Precision).ln())
-    } else {
-        data.mapv(|v| v as Precision / max as Precision)
-    };
-
-    // Apply gamma correction
-    let data = data.mapv(|v| v.powf(params.gamma as Precision));
-
-    // Create colours from samples plus shadow map
-    let coloured_data = Zip::from(&data).and(&shadow_map).map_collect(|&v, &s| {
-        let colour = cmap.gen(v as f32);
-        colour.darken(s as f32)
-    });
-
-    // Convert from `Array2<LinSrgb<Precision>>` to `Array3<Precision>`
-    let (height, width) = coloured_data.dim();
-    let data: Array3<f32> = Array3::from_shape_fn((height, width, 3), |(y, x, channel)| {
-        let pixel = &coloured_data[(y, x)];
-        match channel {
-            0 => pixel.red,
-            1 => pixel.green,
-            2 => pixel.blue,
-            _ => unreachable!(),
-        }
-    });
-
-    // Save the image
-    let filename = format!("{}/{}", OUTPUT_DIR, params.image_name);
-    data.save(filename).unwrap();
-}
-
-fn create_shadow_map(samples: &Array2<u32>, light_dir: &[Precision; 3]) -> Array2<Precision> {
+pub fn create_shadow_map(samples: &Array2<u32>, light_dir: &[Precision; 3]) -> Array2<Precision> {
     let (height, width) = samples.dim();
     let mut shadow_map = Array2::<Precision>::zeros((height, width));
 
@@ -124,7 +37,7 @@ fn create_shadow_map(samples: &Array2<u32>, light_dir: &[Precision; 3]) -> Array
     shadow_map
 }
 
-fn create_ambient_occlusion_map(
+pub fn create_ambient_occlusion_map(
     samples: &Array2<u32>,
     num_angles: usize,
     max_radius: usize,
@@ -0,0 +1,153 @@
+use mandybrot::{ColourMap, ConfigFormat, GradientFormat, PaletteScheme};
+use palette::{LinSrgba, Srgba};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::HashMap, fs::read_to_string};
+
+type Precision = f32;
+
+const INPUT_DIR: &str = "input";
+pub const OUTPUT_DIR: &str = "output";
+
+/// Number of stops in each randomly generated colour map.
+const RANDOM_PALETTE_COLOURS: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColourMaps(HashMap<String, Vec<String>>);
+
+/// Command given on the command line: a parameters file to render, optionally as a batch of parameter sweeps or as an interactive preview in the terminal, or a gallery of every built-in fractal.
+pub enum Command {
+    Render(String),
+    Batch(String),
+    Tui(String),
+    Gallery,
+}
+
+pub fn read_command() -> Command {
+    let args: Vec<String> = std::env::args().collect();
+    match args.as_slice() {
+        [_, command] if command == "gallery" => Command::Gallery,
+        [_, params_file] => Command::Render(params_file.clone()),
+        [_, command, params_file] if command == "batch" => Command::Batch(params_file.clone()),
+        [_, command, params_file] if command == "tui" => Command::Tui(params_file.clone()),
+        _ => {
+            eprintln!(
+                "Usage: {} [batch | tui] <parameters file> | gallery",
+                args[0]
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Path of a parameters file in the input directory.
+pub fn input_path(params_file: &str) -> String {
+    format!("{}/{}", INPUT_DIR, params_file)
+}
+
+/// Reads settings from a parameters file, as YAML, JSON or TOML by its extension.
+pub fn read_input_file<Settings: DeserializeOwned>(params_file: &str) -> Settings {
+    let params_filepath = input_path(params_file);
+    let file_contents = read_to_string(&params_filepath)
+        .unwrap_or_else(|_| panic!("Failed to read parameters file: {}", params_filepath));
+    ConfigFormat::from_path(&params_filepath)
+        .and_then(|format| format.parse(&file_contents))
+        .unwrap_or_else(|err| {
+            panic!(
+                "Failed to parse parameters file: {}: {}",
+                params_filepath, err
+            )
+        })
+}
+
+/// Sets the number at a dot-separated path in a YAML document, such as `job.fractal.c.real` or `centre.0`, looking through any tags.
+pub fn set_yaml_path(value: &mut serde_yaml::Value, path: &str, number: f64) -> Option<()> {
+    let mut value = value;
+    for key in path.split('.') {
+        while let serde_yaml::Value::Tagged(tagged) = value {
+            value = &mut tagged.value;
+        }
+        value = match value {
+            serde_yaml::Value::Sequence(sequence) => {
+                sequence.get_mut(key.parse::<usize>().ok()?)?
+            }
+            serde_yaml::Value::Mapping(mapping) => mapping.get_mut(key)?,
+            _ => return None,
+        };
+    }
+    *value = number.into();
+    Some(())
+}
+
+/// Looks up a colour map in the colour maps file, falling back to the built-in presets.
+pub fn create_colour_map(colour_map_name: &str) -> ColourMap {
+    find_colour_map(colour_map_name)
+        .unwrap_or_else(|| panic!("Colour map '{}' not found.", colour_map_name))
+}
+
+/// Looks up a colour map in the colour maps file, falling back to the built-in presets, if it exists.
+/// Names ending in a gradient file extension, such as `fire.ggr`, are read from that file in the input directory instead.
+/// Names of the form `random:<scheme>:<seed>`, such as `random:hue_spread:7`, are generated with `ColourMap::random`, from a seed of zero if none is given.
+pub fn find_colour_map(colour_map_name: &str) -> Option<ColourMap> {
+    if let Some(random) = colour_map_name.strip_prefix("random:") {
+        let (scheme, seed) = random.split_once(':').unwrap_or((random, "0"));
+        let scheme = PaletteScheme::from_name(scheme).ok()?;
+        let seed = seed.parse().ok()?;
+        return ColourMap::random(scheme, RANDOM_PALETTE_COLOURS, seed).ok();
+    }
+
+    if GradientFormat::from_path(colour_map_name).is_ok() {
+        let gradient_filepath = format!("{}/{}", INPUT_DIR, colour_map_name);
+        return Some(
+            ColourMap::load_gradient(&gradient_filepath)
+                .unwrap_or_else(|_| panic!("Failed to read gradient file: {}", gradient_filepath)),
+        );
+    }
+
+    let cmap_filepath = format!("{}/colour_maps.yaml", INPUT_DIR);
+    let colour_maps: ColourMaps = match read_to_string(&cmap_filepath) {
+        Ok(contents) => serde_yaml::from_str(&contents)
+            .unwrap_or_else(|_| panic!("Failed to parse colour maps file: {}", cmap_filepath)),
+        Err(_) => ColourMaps(HashMap::new()),
+    };
+
+    match colour_maps.0.get(colour_map_name) {
+        Some(colour_hexes) => Some(build_colour_map(colour_hexes)),
+        None => ColourMap::preset(colour_map_name),
+    }
+}
+
+fn hex_to_lin_srgba(hex: &str) -> LinSrgba<Precision> {
+    let hex = hex.trim_start_matches('#');
+    let r =
+        u8::from_str_radix(&hex[0..2], 16).unwrap_or_else(|_| panic!("Invalid hex code: {}", hex));
+    let g =
+        u8::from_str_radix(&hex[2..4], 16).unwrap_or_else(|_| panic!("Invalid hex code: {}", hex));
+    let b =
+        u8::from_str_radix(&hex[4..6], 16).unwrap_or_else(|_| panic!("Invalid hex code: {}", hex));
+    let a = if hex.len() == 8 {
+        u8::from_str_radix(&hex[6..8], 16).unwrap_or_else(|_| panic!("Invalid hex code: {}", hex))
+    } else {
+        255
+    };
+    Srgba::new(
+        r as Precision / 255.0,
+        g as Precision / 255.0,
+        b as Precision / 255.0,
+        a as Precision / 255.0,
+    )
+    .into_linear()
+}
+
+fn build_colour_map(colour_hexes: &[String]) -> ColourMap {
+    assert!(
+        colour_hexes.len() >= 2,
+        "A colour map needs at least two colours"
+    );
+    ColourMap::new(
+        colour_hexes
+            .iter()
+            .map(|hex| hex_to_lin_srgba(hex))
+            .collect(),
+    )
+    .unwrap_or_else(|err| panic!("Invalid colour map: {}", err))
+}
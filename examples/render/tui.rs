@@ -0,0 +1,75 @@
+use console::{Key, Term};
+use mandybrot::{render_ansi, AnsiColour, BookmarkStack, Parameters};
+
+use super::{render, Output, Precision};
+
+/// Fraction of the height of the view moved by each press of an arrow key.
+const PAN_STEP: Precision = 0.1;
+
+/// Factor by which the scale shrinks with each press of `+`.
+const ZOOM_STEP: Precision = 0.8;
+
+/// Previews a render in the terminal, two pixels to each character, panning with the arrow keys and zooming with `+` and `-` until `q` or escape is pressed.
+/// `b` bookmarks the view and backspace returns to the last bookmark, and the bookmarks left are printed as shareable tokens on quitting.
+/// Truecolour is used if `COLORTERM` says the terminal understands it, and the 256-colour palette otherwise.
+pub fn preview(mut params: Parameters<Precision>, output: Output<Precision>) {
+    let term = Term::stdout();
+    let colour = match std::env::var("COLORTERM").as_deref() {
+        Ok("truecolor" | "24bit") => AnsiColour::TrueColour,
+        _ => AnsiColour::Palette256,
+    };
+
+    let mut bookmarks = BookmarkStack::new();
+    term.hide_cursor().ok();
+    term.clear_screen().ok();
+    loop {
+        // Fill the terminal, leaving the last line for the status
+        let (rows, columns) = term.size();
+        params.resolution = [columns as u32, rows.saturating_sub(1).max(1) as u32 * 2];
+        let text = match render(&params, &output, None) {
+            Ok(image) => render_ansi(&image, colour),
+            Err(err) => format!("Failed to render: {}\n", err),
+        };
+        let status = format!(
+            "centre [{}, {}], scale {:e}, {} bookmarks: arrows pan, +/- zoom, b bookmarks, backspace returns, q quits",
+            params.centre[0],
+            params.centre[1],
+            params.scale,
+            bookmarks.len()
+        );
+        let status: String = status.chars().take(columns as usize).collect();
+        term.move_cursor_to(0, 0).ok();
+        term.write_str(&text).ok();
+        term.write_str(&status).ok();
+        term.clear_to_end_of_screen().ok();
+
+        let step = PAN_STEP * params.scale;
+        match term.read_key() {
+            Ok(Key::ArrowLeft) => params.centre[0] -= step,
+            Ok(Key::ArrowRight) => params.centre[0] += step,
+            Ok(Key::ArrowUp) => params.centre[1] -= step,
+            Ok(Key::ArrowDown) => params.centre[1] += step,
+            Ok(Key::Char('+' | '=')) => params.scale *= ZOOM_STEP,
+            Ok(Key::Char('-' | '_')) => params.scale /= ZOOM_STEP,
+            Ok(Key::Char('b')) => {
+                bookmarks.push(format!("bookmark {}", bookmarks.len() + 1), params.clone())
+            }
+            Ok(Key::Backspace) => {
+                if let Some(bookmark) = bookmarks.pop() {
+                    params = bookmark.parameters;
+                }
+            }
+            Ok(Key::Char('q') | Key::Escape | Key::CtrlC) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    term.show_cursor().ok();
+    term.clear_screen().ok();
+
+    for bookmark in bookmarks.iter() {
+        match bookmark.token() {
+            Ok(token) => println!("{}: {}", bookmark.name, token),
+            Err(err) => eprintln!("Failed to encode {}: {}", bookmark.name, err),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use ndarray::{Array2, Zip};
+use num_traits::Float;
+
+use crate::{error::check_count, MandybrotError, Viewport};
+
+/// Running per-pixel mean of successive stochastic renders of a viewport, such as Buddhabrot densities or randomly anti-aliased frames.
+/// Each render added refines the estimate, so a preview converges over time, and moving the viewport carries the samples which remain in view over to the next frame.
+#[derive(Debug, Clone)]
+pub struct Accumulator<T> {
+    viewport: Viewport<T>,
+    mean: Array2<f64>,
+    num_samples: Array2<u32>,
+}
+
+impl<T: Float> Accumulator<T> {
+    /// Empty accumulator for renders of the given viewport.
+    pub fn new(viewport: Viewport<T>) -> Result<Self, MandybrotError> {
+        viewport.validate()?;
+        let [x_res, y_res] = viewport.resolution;
+        let shape = (y_res as usize, x_res as usize);
+        Ok(Self {
+            viewport,
+            mean: Array2::zeros(shape),
+            num_samples: Array2::zeros(shape),
+        })
+    }
+
+    pub fn viewport(&self) -> &Viewport<T> {
+        &self.viewport
+    }
+
+    /// Mean of the renders added at each pixel, or zero where none have been.
+    pub fn mean(&self) -> &Array2<f64> {
+        &self.mean
+    }
+
+    /// Number of samples behind the mean at each pixel.
+    pub fn num_samples(&self) -> &Array2<u32> {
+        &self.num_samples
+    }
+
+    /// Blends in a render of the current viewport, which was itself the mean of `samples` samples per pixel.
+    pub fn add<A: Copy + Into<f64>>(
+        &mut self,
+        frame: &Array2<A>,
+        samples: u32,
+    ) -> Result<(), MandybrotError> {
+        check_count("samples", samples)?;
+        if frame.dim() != self.mean.dim() {
+            return Err(MandybrotError::BufferSize {
+                expected: self.mean.len(),
+                found: frame.len(),
+            });
+        }
+        Zip::from(&mut self.mean)
+            .and(&mut self.num_samples)
+            .and(frame)
+            .for_each(|mean, n, &value| {
+                let total = n.saturating_add(samples);
+                *mean += (value.into() - *mean) * samples as f64 / total as f64;
+                *n = total;
+            });
+        Ok(())
+    }
+
+    /// Moves to a new viewport, keeping the mean of each new pixel whose centre lay within the old image.
+    /// Kept pixels count for at most `max_history` samples, so that new renders soon outweigh history blurred by the move.
+    /// Pixels newly brought into view start empty.
+    pub fn move_to(
+        &mut self,
+        viewport: Viewport<T>,
+        max_history: u32,
+    ) -> Result<(), MandybrotError> {
+        viewport.validate()?;
+        let [x_res, y_res] = viewport.resolution;
+        let shape = (y_res as usize, x_res as usize);
+        let mut mean = Array2::zeros(shape);
+        let mut num_samples = Array2::zeros(shape);
+        Zip::indexed(&mut mean)
+            .and(&mut num_samples)
+            .for_each(|(y, x), mean, n| {
+                let centre = viewport.pixel_centre(x as u32, y as u32);
+                if let Some([old_x, old_y]) = self.viewport.complex_to_pixel(&centre) {
+                    *mean = self.mean[[old_y, old_x]];
+                    *n = self.num_samples[[old_y, old_x]].min(max_history);
+                }
+            });
+        *self = Self {
+            viewport,
+            mean,
+            num_samples,
+        };
+        Ok(())
+    }
+
+    /// Discards every sample, as when the scene changes completely.
+    pub fn clear(&mut self) {
+        self.mean.fill(0.0);
+        self.num_samples.fill(0);
+    }
+}
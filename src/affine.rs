@@ -0,0 +1,117 @@
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::check_finite, Complex, MandybrotError};
+
+/// Affine map of the plane, `p → matrix · p + translation`, such as to rotate, shear or stretch a viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Affine<T> {
+    /// Rows of the linear part, so the real part of the image of `p` is `matrix[0][0] * p.real + matrix[0][1] * p.imag`.
+    pub matrix: [[T; 2]; 2],
+    pub translation: Complex<T>,
+}
+
+impl<T: Float> Affine<T> {
+    pub const fn new(matrix: [[T; 2]; 2], translation: Complex<T>) -> Self {
+        Self {
+            matrix,
+            translation,
+        }
+    }
+
+    /// The map which leaves every point where it is.
+    pub fn identity() -> Self {
+        Self::linear([[T::one(), T::zero()], [T::zero(), T::one()]])
+    }
+
+    /// Linear map with no translation.
+    pub fn linear(matrix: [[T; 2]; 2]) -> Self {
+        Self::new(matrix, Complex::new(T::zero(), T::zero()))
+    }
+
+    /// Anticlockwise rotation by `angle` radians.
+    pub fn rotation(angle: T) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::linear([[cos, -sin], [sin, cos]])
+    }
+
+    /// Stretch by `x` along the real axis and `y` along the imaginary axis.
+    pub fn scaling(x: T, y: T) -> Self {
+        Self::linear([[x, T::zero()], [T::zero(), y]])
+    }
+
+    /// Shear moving each point by `x` times its imaginary part along the real axis, and `y` times its real part along the imaginary axis.
+    pub fn shear(x: T, y: T) -> Self {
+        Self::linear([[T::one(), x], [y, T::one()]])
+    }
+
+    /// Shift by `offset`.
+    pub fn translation(offset: Complex<T>) -> Self {
+        Self::new([[T::one(), T::zero()], [T::zero(), T::one()]], offset)
+    }
+
+    /// Map applying this one and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let [[a, b], [c, d]] = next.matrix;
+        let [[e, f], [g, h]] = self.matrix;
+        Self::new(
+            [
+                [a * e + b * g, a * f + b * h],
+                [c * e + d * g, c * f + d * h],
+            ],
+            next.apply(self.translation),
+        )
+    }
+
+    pub fn apply(&self, p: Complex<T>) -> Complex<T> {
+        let [[a, b], [c, d]] = self.matrix;
+        Complex::new(
+            a * p.real + b * p.imag + self.translation.real,
+            c * p.real + d * p.imag + self.translation.imag,
+        )
+    }
+
+    /// Factor by which the map scales areas, negative if it also reflects them.
+    pub fn determinant(&self) -> T {
+        let [[a, b], [c, d]] = self.matrix;
+        a * d - b * c
+    }
+
+    /// Map undoing this one, or `None` if it collapses the plane onto a line or point.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == T::zero() || !det.is_finite() {
+            return None;
+        }
+        let [[a, b], [c, d]] = self.matrix;
+        let linear = Self::linear([[d / det, -b / det], [-c / det, a / det]]);
+        let t = linear.apply(self.translation);
+        Some(Self::new(linear.matrix, Complex::new(-t.real, -t.imag)))
+    }
+
+    /// Checks that the map is finite and can be undone.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        for row in self.matrix {
+            for x in row {
+                check_finite("transform.matrix", x)?;
+            }
+        }
+        check_finite("transform.translation.real", self.translation.real)?;
+        check_finite("transform.translation.imag", self.translation.imag)?;
+        let det = self.determinant();
+        if det == T::zero() || !det.is_finite() {
+            return Err(MandybrotError::InvalidParameter {
+                name: "transform.determinant",
+                value: det.to_f64().unwrap_or(f64::NAN),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T: Float> Default for Affine<T> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
@@ -0,0 +1,150 @@
+use core::ops::{Add, Mul, Sub};
+use serde::{Deserialize, Serialize};
+
+use crate::Complex;
+
+/// Two dimensional algebra over the reals, whose elements `a + b u` are drawn as the point `(a, b)` of the plane.
+/// Escape-time kernels are generic over the algebra, so the Mandelbrot and Julia sets can be iterated with any rule for `u²`.
+pub trait Algebra<T>: Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    /// Element drawn at the given point of the plane.
+    fn from_plane(p: Complex<T>) -> Self;
+
+    /// Point of the plane at which the element is drawn.
+    fn to_plane(self) -> Complex<T>;
+
+    /// Squared Euclidean distance of the element from the origin of the plane, which decides escape.
+    /// The algebra's own modulus is not used, as for dual and split-complex numbers it can vanish far from the origin.
+    fn escape_norm_sqr(self) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<Output = T>,
+    {
+        self.to_plane().norm_sqr()
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Algebra<T> for Complex<T> {
+    #[inline(always)]
+    fn from_plane(p: Complex<T>) -> Self {
+        p
+    }
+
+    #[inline(always)]
+    fn to_plane(self) -> Complex<T> {
+        self
+    }
+}
+
+/// Dual number `a + b ε`, where `ε² = 0`.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DualNumber<T> {
+    pub real: T,
+    pub dual: T,
+}
+
+impl<T> DualNumber<T> {
+    pub const fn new(real: T, dual: T) -> Self {
+        Self { real, dual }
+    }
+}
+
+impl<T: Copy + Add<Output = T>> Add for DualNumber<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.real + other.real, self.dual + other.dual)
+    }
+}
+
+impl<T: Copy + Sub<Output = T>> Sub for DualNumber<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.real - other.real, self.dual - other.dual)
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Mul<Output = T>> Mul for DualNumber<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.real * other.real,
+            self.real * other.dual + self.dual * other.real,
+        )
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Algebra<T> for DualNumber<T> {
+    #[inline(always)]
+    fn from_plane(p: Complex<T>) -> Self {
+        Self::new(p.real, p.imag)
+    }
+
+    #[inline(always)]
+    fn to_plane(self) -> Complex<T> {
+        Complex::new(self.real, self.dual)
+    }
+}
+
+/// Split-complex (hyperbolic) number `a + b j`, where `j² = 1`.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitComplex<T> {
+    pub real: T,
+    pub hyperbolic: T,
+}
+
+impl<T> SplitComplex<T> {
+    pub const fn new(real: T, hyperbolic: T) -> Self {
+        Self { real, hyperbolic }
+    }
+}
+
+impl<T: Copy + Add<Output = T>> Add for SplitComplex<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.real + other.real, self.hyperbolic + other.hyperbolic)
+    }
+}
+
+impl<T: Copy + Sub<Output = T>> Sub for SplitComplex<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.real - other.real, self.hyperbolic - other.hyperbolic)
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Mul<Output = T>> Mul for SplitComplex<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.real * other.real + self.hyperbolic * other.hyperbolic,
+            self.real * other.hyperbolic + self.hyperbolic * other.real,
+        )
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Algebra<T> for SplitComplex<T> {
+    #[inline(always)]
+    fn from_plane(p: Complex<T>) -> Self {
+        Self::new(p.real, p.imag)
+    }
+
+    #[inline(always)]
+    fn to_plane(self) -> Complex<T> {
+        Complex::new(self.real, self.hyperbolic)
+    }
+}
+
+/// Number system in which a Mandelbrot or Julia set is iterated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberSystem {
+    /// Complex numbers, where `i² = -1`.
+    Complex,
+    /// Dual numbers, where `ε² = 0`.
+    Dual,
+    /// Split-complex numbers, where `j² = 1`.
+    SplitComplex,
+}
@@ -0,0 +1,364 @@
+use color_quant::NeuQuant;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{ColourSpace, Dither, MandybrotError};
+
+/// Container format of an exported animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimationFormat {
+    /// Animated GIF, limited to a palette of at most 256 opaque colours.
+    Gif,
+    /// Animated PNG, in full colour with alpha unless a palette size is set.
+    Apng,
+}
+
+/// Frames of an animation, such as a zoom or parameter sweep, to be written as an animated GIF or APNG.
+/// Every frame must have the same resolution, and all frames share one palette so colours do not flicker between frames.
+#[derive(Debug)]
+pub struct Animation {
+    frame_delay: u16,
+    palette_size: Option<u16>,
+    dither: Dither,
+    resolution: Option<(usize, usize)>,
+    frames: Vec<Vec<[u8; 4]>>,
+}
+
+impl Animation {
+    /// Creates an empty animation which shows each frame for `frame_delay` milliseconds.
+    pub fn new(frame_delay: u16) -> Self {
+        Self {
+            frame_delay,
+            palette_size: None,
+            dither: Dither::None,
+            resolution: None,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Quantises every frame to a shared palette of at most `palette_size` colours, between 64 and 256.
+    /// GIFs always use a palette, of 256 colours if none is set, while APNGs are written in full colour unless one is set.
+    pub fn quantise(&mut self, palette_size: u16) -> Result<&mut Self, MandybrotError> {
+        if !(MIN_PALETTE_SIZE..=256).contains(&palette_size) {
+            return Err(MandybrotError::InvalidParameter {
+                name: "palette_size",
+                value: palette_size as f64,
+            });
+        }
+        self.palette_size = Some(palette_size);
+        Ok(self)
+    }
+
+    /// Dithers the frames, breaking up the bands of smooth gradients as they are rounded to 8 bits and to the colours of any palette.
+    /// Frames are rounded as they are added, so the dither must be set before adding them.
+    pub fn dither(&mut self, dither: Dither) -> Result<&mut Self, MandybrotError> {
+        dither.validate()?;
+        self.dither = dither;
+        Ok(self)
+    }
+
+    /// Appends a frame to the animation, which must have the same resolution as the frames before it.
+    pub fn add_frame<C: ColourSpace>(
+        &mut self,
+        frame: &Array2<C>,
+    ) -> Result<&mut Self, MandybrotError> {
+        let resolution = *self.resolution.get_or_insert(frame.dim());
+        if frame.dim() != resolution {
+            let (height, width) = resolution;
+            let (frame_height, frame_width) = frame.dim();
+            return Err(MandybrotError::FrameResolution {
+                expected: [width, height],
+                found: [frame_width, frame_height],
+            });
+        }
+        let pixels = self.dither.encode_image(frame).into_iter().collect();
+        self.frames.push(pixels);
+        Ok(self)
+    }
+
+    /// Appends each frame of a sequence to the animation.
+    pub fn add_frames<C: ColourSpace>(
+        &mut self,
+        frames: impl IntoIterator<Item = Array2<C>>,
+    ) -> Result<&mut Self, MandybrotError> {
+        for frame in frames {
+            self.add_frame(&frame)?;
+        }
+        Ok(self)
+    }
+
+    /// Number of frames added so far.
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Writes the animation to the given path.
+    pub fn save(&self, path: impl AsRef<Path>, format: AnimationFormat) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file, format)?;
+        file.flush()
+    }
+
+    /// Writes the animation in the given format, looping forever.
+    /// GIF frames are limited to 65535 pixels on each side.
+    pub fn write(&self, writer: &mut impl Write, format: AnimationFormat) -> io::Result<()> {
+        let (height, width) = self
+            .resolution
+            .filter(|&(height, width)| height > 0 && width > 0)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "Animation has no pixels")
+            })?;
+
+        match format {
+            AnimationFormat::Gif => {
+                let width = u16::try_from(width)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Frames too wide"))?;
+                let height = u16::try_from(height)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Frames too tall"))?;
+                self.write_gif(writer, width, height)
+                    .map_err(|err| match err {
+                        gif::EncodingError::Io(err) => err,
+                        err => io::Error::other(err),
+                    })
+            }
+            AnimationFormat::Apng => {
+                let width = u32::try_from(width)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Frames too wide"))?;
+                let height = u32::try_from(height)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Frames too tall"))?;
+                self.write_apng(writer, width, height)
+            }
+        }
+    }
+
+    fn write_gif(
+        &self,
+        writer: &mut impl Write,
+        width: u16,
+        height: u16,
+    ) -> Result<(), gif::EncodingError> {
+        // GIF transparency is all or nothing, so colours are flattened onto black
+        let frames: Vec<Vec<[u8; 4]>> = self
+            .frames
+            .iter()
+            .map(|pixels| pixels.iter().map(|&pixel| opaque(pixel)).collect())
+            .collect();
+        let (palette, indices) = quantise(
+            &frames,
+            self.palette_size.unwrap_or(256) as usize,
+            self.dither,
+            width as usize,
+        );
+        let palette: Vec<u8> = palette
+            .iter()
+            .flat_map(|colour| colour[..3].to_vec())
+            .collect();
+
+        let mut encoder = gif::Encoder::new(writer, width, height, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for frame in &indices {
+            encoder.write_frame(&gif::Frame {
+                width,
+                height,
+                delay: self.frame_delay.div_ceil(10), // Hundredths of a second
+                dispose: gif::DisposalMethod::Keep,
+                buffer: Cow::Borrowed(frame),
+                ..gif::Frame::default()
+            })?;
+        }
+        Ok(())
+    }
+
+    fn write_apng(&self, writer: &mut impl Write, width: u32, height: u32) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as u32, 0)?;
+        encoder.set_frame_delay(self.frame_delay, 1000)?;
+
+        match self.palette_size {
+            Some(palette_size) => {
+                let (palette, indices) = quantise(
+                    &self.frames,
+                    palette_size as usize,
+                    self.dither,
+                    width as usize,
+                );
+                encoder.set_color(png::ColorType::Indexed);
+                encoder.set_palette(
+                    palette
+                        .iter()
+                        .flat_map(|colour| colour[..3].to_vec())
+                        .collect::<Vec<u8>>(),
+                );
+                encoder.set_trns(palette.iter().map(|colour| colour[3]).collect::<Vec<u8>>());
+                let mut writer = encoder.write_header()?;
+                for frame in &indices {
+                    writer.write_image_data(frame)?;
+                }
+                writer.finish()?;
+            }
+            None => {
+                encoder.set_color(png::ColorType::Rgba);
+                let mut writer = encoder.write_header()?;
+                for pixels in &self.frames {
+                    writer.write_image_data(pixels.as_flattened())?;
+                }
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Smallest palette the NeuQuant quantiser builds well.
+const MIN_PALETTE_SIZE: u16 = 64;
+
+/// Number of pixels the palette is learnt from.
+const TRAINING_PIXELS: usize = 1 << 20;
+
+/// Sampling factor of the NeuQuant quantiser, from 1 (slowest and best) to 30.
+const QUANTISER_SAMPLING: i32 = 10;
+
+/// Composites a colour over black, dropping its alpha.
+fn opaque([red, green, blue, alpha]: [u8; 4]) -> [u8; 4] {
+    let scale = |channel: u8| ((channel as u32 * alpha as u32 + 127) / 255) as u8;
+    [scale(red), scale(green), scale(blue), u8::MAX]
+}
+
+/// Reduces the colours of all frames to a shared palette of `palette_size` colours with the NeuQuant quantiser.
+/// Pixels are mapped to the nearest palette colour, after adding the dither's noise if there is one.
+/// Returns the palette and the palette index of each pixel of each frame, of the given width.
+fn quantise(
+    frames: &[Vec<[u8; 4]>],
+    palette_size: usize,
+    dither: Dither,
+    width: usize,
+) -> (Vec<[u8; 4]>, Vec<Vec<u8>>) {
+    // Pixels are taken evenly from across every frame, and small animations repeated so the quantiser learns for long enough
+    let num_pixels: usize = frames.iter().map(Vec::len).sum();
+    let training: Vec<u8> = frames
+        .iter()
+        .flatten()
+        .step_by(num_pixels.div_ceil(TRAINING_PIXELS).max(1))
+        .cycle()
+        .take(TRAINING_PIXELS)
+        .flatten()
+        .copied()
+        .collect();
+    let quantiser = NeuQuant::new(QUANTISER_SAMPLING, palette_size, &training);
+    let palette: Vec<[u8; 4]> = (0..palette_size)
+        .map(|index| quantiser.lookup(index).unwrap_or([0; 4]))
+        .collect();
+
+    // Palette colours lie roughly this many levels apart, which the noise is scaled to span
+    let spread = 255.0 / (palette.len() as f32).cbrt();
+    let indices = frames
+        .iter()
+        .map(|pixels| {
+            pixels
+                .iter()
+                .enumerate()
+                .map(|(i, pixel)| {
+                    let offset = dither.threshold([i % width, i / width]) * spread;
+                    let target = pixel
+                        .map(|channel| (channel as f32 + offset).round().clamp(0.0, 255.0) as u8);
+                    quantiser.index_of(&target) as u8
+                })
+                .collect()
+        })
+        .collect();
+
+    (palette, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use palette::LinSrgba;
+
+    fn gradient(width: usize, height: usize, shift: f32) -> Array2<LinSrgba> {
+        Array2::from_shape_fn((height, width), |(y, x)| {
+            LinSrgba::new(
+                x as f32 / width as f32,
+                y as f32 / height as f32,
+                shift,
+                1.0,
+            )
+        })
+    }
+
+    #[test]
+    fn gifs_decode_to_the_frames_written() {
+        let frames = [gradient(40, 30, 0.0), gradient(40, 30, 1.0)];
+        let mut animation = Animation::new(40);
+        animation.add_frames(frames.clone()).unwrap();
+        let mut bytes = Vec::new();
+        animation.write(&mut bytes, AnimationFormat::Gif).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(bytes.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (40, 30));
+        let mut num_frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 4);
+            let expected = Dither::None.encode_image(&frames[num_frames]);
+            let errors: Vec<i32> = frame
+                .buffer
+                .chunks(4)
+                .zip(expected.iter())
+                .flat_map(|(pixel, expected)| {
+                    (0..3).map(move |channel| {
+                        (pixel[channel] as i32 - expected[channel] as i32).abs()
+                    })
+                })
+                .collect();
+            let mean = errors.iter().sum::<i32>() as f32 / errors.len() as f32;
+            assert!(mean < 8.0, "mean error {}", mean);
+            assert!(errors.iter().all(|&error| error < 100));
+            num_frames += 1;
+        }
+        assert_eq!(num_frames, 2);
+    }
+
+    #[test]
+    fn apngs_may_be_wider_than_gifs() {
+        let mut animation = Animation::new(40);
+        animation.add_frame(&gradient(70_000, 1, 0.5)).unwrap();
+
+        let mut bytes = Vec::new();
+        assert!(animation.write(&mut bytes, AnimationFormat::Gif).is_err());
+        animation.write(&mut bytes, AnimationFormat::Apng).unwrap();
+
+        let reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (70_000, 1));
+        assert_eq!(reader.info().animation_control.unwrap().num_frames, 1);
+    }
+
+    #[test]
+    fn invalid_settings_and_frames_are_errors() {
+        let mut animation = Animation::new(40);
+        assert!(animation.quantise(16).is_err());
+        assert!(animation.quantise(257).is_err());
+        assert!(animation.quantise(64).is_ok());
+        assert!(animation
+            .dither(Dither::Ordered { strength: -1.0 })
+            .is_err());
+
+        animation.add_frame(&gradient(4, 3, 0.0)).unwrap();
+        assert!(matches!(
+            animation.add_frame(&gradient(3, 4, 0.0)),
+            Err(MandybrotError::FrameResolution {
+                expected: [4, 3],
+                found: [3, 4],
+            })
+        ));
+        assert_eq!(animation.num_frames(), 1);
+    }
+}
@@ -1,17 +1,120 @@
-use num_traits::{Float, NumCast, One};
+use core::ops::{Add, Mul, Sub};
+use num_traits::{Float, FloatConst, NumCast, One};
 use serde::{Deserialize, Serialize};
-use std::ops::{Add, Mul, Sub};
 
-use crate::Complex;
+use crate::{error::check_finite, Complex, MandybrotError};
 
 /// Enum representing different attractors that can be iterated.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Attractor<T> {
-    Clifford { a: T, b: T, c: T, d: T },
-    DeJong { a: T, b: T, c: T, d: T },
-    Henon { a: T, b: T },
-    Ikeda { u: T },
-    Tinkerbell { a: T, b: T, c: T, d: T },
+    Clifford {
+        a: T,
+        b: T,
+        c: T,
+        d: T,
+    },
+    DeJong {
+        a: T,
+        b: T,
+        c: T,
+        d: T,
+    },
+    Henon {
+        a: T,
+        b: T,
+    },
+    Ikeda {
+        u: T,
+    },
+    Tinkerbell {
+        a: T,
+        b: T,
+        c: T,
+        d: T,
+    },
+    /// The one-dimensional logistic map `x → r x (1 - x)`, acting on the real part of each point.
+    Logistic {
+        r: T,
+    },
+    /// Pickover's popcorn map `x → x - h sin(y + tan 3y)`, `y → y - h sin(x + tan 3x)`, a step of size `h` along a field which is drawn by short orbits from every pixel.
+    Popcorn {
+        h: T,
+    },
+    /// Barry Martin's hopalong map `x → y - sign(x) √|b x - c|`, `y → a - x`.
+    Hopalong {
+        a: T,
+        b: T,
+        c: T,
+    },
+    /// Barry Martin's sine variant of the hopalong map, `x → y - sin x`, `y → a - x`.
+    Martin {
+        a: T,
+    },
+}
+
+/// Name of a single parameter of an attractor, such as the one swept across a bifurcation diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttractorParameter {
+    A,
+    B,
+    C,
+    D,
+    U,
+    R,
+    H,
+}
+
+/// Property of an orbit's starting point by which the orbit is coloured, to show which starting points reach which parts of an attractor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Provenance {
+    /// Angle of the starting point around the centre of the starting region, suiting a cyclic colour map.
+    #[default]
+    Angle,
+    /// Distance of the starting point from the centre of the starting region, as a fraction of its radius.
+    Distance,
+}
+
+/// Where the orbits of an attractor render start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttractorSeeding {
+    /// Random points within a radius of a starting point, suiting maps whose orbits all settle onto the same attractor.
+    #[default]
+    Disc,
+    /// The centre of every `spacing`th pixel of the image in each direction, for maps such as the popcorn whose pictures are made of short orbits from everywhere, see `render_attractor_pixels`.
+    Pixels { spacing: u32 },
+}
+
+impl Provenance {
+    /// Position in the range `[0, 1]` of a starting point `p` within `radius` of `start`.
+    pub fn position<T: Float + FloatConst>(
+        &self,
+        p: Complex<T>,
+        start: Complex<T>,
+        radius: T,
+    ) -> T {
+        let (dx, dy) = (p.real - start.real, p.imag - start.imag);
+        match self {
+            Provenance::Angle => {
+                let turn = dy.atan2(dx) / T::TAU();
+                turn - turn.floor()
+            }
+            Provenance::Distance => ((dx * dx + dy * dy).sqrt() / radius).min(T::one()),
+        }
+    }
+}
+
+impl AttractorParameter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttractorParameter::A => "a",
+            AttractorParameter::B => "b",
+            AttractorParameter::C => "c",
+            AttractorParameter::D => "d",
+            AttractorParameter::U => "u",
+            AttractorParameter::R => "r",
+            AttractorParameter::H => "h",
+        }
+    }
 }
 
 impl<T: Add<Output = T> + Copy> Attractor<T> {
@@ -42,6 +145,22 @@ impl<T: Add<Output = T> + Copy> Attractor<T> {
             Attractor::Ikeda { u } => {
                 *self = Attractor::Ikeda { u: *u + delta };
             }
+            Attractor::Logistic { r } => {
+                *self = Attractor::Logistic { r: *r + delta };
+            }
+            Attractor::Popcorn { h } => {
+                *self = Attractor::Popcorn { h: *h + delta };
+            }
+            Attractor::Hopalong { a, b, c } => {
+                *self = Attractor::Hopalong {
+                    a: *a + delta,
+                    b: *b + delta,
+                    c: *c + delta,
+                };
+            }
+            Attractor::Martin { a } => {
+                *self = Attractor::Martin { a: *a + delta };
+            }
             Attractor::Tinkerbell { a, b, c, d } => {
                 *self = Attractor::Tinkerbell {
                     a: *a + delta,
@@ -54,6 +173,122 @@ impl<T: Add<Output = T> + Copy> Attractor<T> {
     }
 }
 
+impl<T: Float> Attractor<T> {
+    /// Classic parameters of the same kind of attractor, known to draw a good image.
+    pub fn default_parameters(&self) -> Self {
+        let p = |x: f64| T::from(x).unwrap();
+        match self {
+            Attractor::Clifford { .. } => Attractor::Clifford {
+                a: p(-1.4),
+                b: p(1.6),
+                c: p(1.0),
+                d: p(0.7),
+            },
+            Attractor::DeJong { .. } => Attractor::DeJong {
+                a: p(1.4),
+                b: p(-2.3),
+                c: p(2.4),
+                d: p(-2.1),
+            },
+            Attractor::Henon { .. } => Attractor::Henon {
+                a: p(1.4),
+                b: p(0.3),
+            },
+            Attractor::Ikeda { .. } => Attractor::Ikeda { u: p(0.918) },
+            Attractor::Tinkerbell { .. } => Attractor::Tinkerbell {
+                a: p(0.9),
+                b: p(-0.6013),
+                c: p(2.0),
+                d: p(0.5),
+            },
+            Attractor::Logistic { .. } => Attractor::Logistic { r: p(3.9) },
+            Attractor::Popcorn { .. } => Attractor::Popcorn { h: p(0.05) },
+            Attractor::Hopalong { .. } => Attractor::Hopalong {
+                a: p(0.4),
+                b: p(1.0),
+                c: p(0.0),
+            },
+            Attractor::Martin { .. } => Attractor::Martin {
+                a: p(core::f64::consts::PI),
+            },
+        }
+    }
+
+    /// Checks that the parameters of the attractor are finite.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            Attractor::Clifford { a, b, c, d }
+            | Attractor::DeJong { a, b, c, d }
+            | Attractor::Tinkerbell { a, b, c, d } => {
+                check_finite("a", a)?;
+                check_finite("b", b)?;
+                check_finite("c", c)?;
+                check_finite("d", d)
+            }
+            Attractor::Henon { a, b } => {
+                check_finite("a", a)?;
+                check_finite("b", b)
+            }
+            Attractor::Ikeda { u } => check_finite("u", u),
+            Attractor::Logistic { r } => check_finite("r", r),
+            Attractor::Popcorn { h } => check_finite("h", h),
+            Attractor::Hopalong { a, b, c } => {
+                check_finite("a", a)?;
+                check_finite("b", b)?;
+                check_finite("c", c)
+            }
+            Attractor::Martin { a } => check_finite("a", a),
+        }
+    }
+
+    /// Sets a single parameter, failing if this attractor does not have it.
+    pub fn set_parameter(
+        &mut self,
+        parameter: AttractorParameter,
+        value: T,
+    ) -> Result<(), MandybrotError> {
+        use AttractorParameter as P;
+        let field = match (self, parameter) {
+            (
+                Attractor::Clifford { a, .. }
+                | Attractor::DeJong { a, .. }
+                | Attractor::Henon { a, .. }
+                | Attractor::Tinkerbell { a, .. }
+                | Attractor::Hopalong { a, .. }
+                | Attractor::Martin { a },
+                P::A,
+            ) => a,
+            (
+                Attractor::Clifford { b, .. }
+                | Attractor::DeJong { b, .. }
+                | Attractor::Henon { b, .. }
+                | Attractor::Tinkerbell { b, .. }
+                | Attractor::Hopalong { b, .. },
+                P::B,
+            ) => b,
+            (
+                Attractor::Clifford { c, .. }
+                | Attractor::DeJong { c, .. }
+                | Attractor::Tinkerbell { c, .. }
+                | Attractor::Hopalong { c, .. },
+                P::C,
+            ) => c,
+            (
+                Attractor::Clifford { d, .. }
+                | Attractor::DeJong { d, .. }
+                | Attractor::Tinkerbell { d, .. },
+                P::D,
+            ) => d,
+            (Attractor::Ikeda { u }, P::U) => u,
+            (Attractor::Logistic { r }, P::R) => r,
+            (Attractor::Popcorn { h }, P::H) => h,
+            _ => return Err(MandybrotError::UnknownParameter(parameter.name())),
+        };
+        *field = value;
+        Ok(())
+    }
+}
+
 impl<T> Attractor<T>
 where
     T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
@@ -66,8 +301,114 @@ where
             Attractor::Henon { a, b } => henon(p, *a, *b),
             Attractor::Ikeda { u } => ikeda(p, *u),
             Attractor::Tinkerbell { a, b, c, d } => tinkerbell(p, *a, *b, *c, *d),
+            Attractor::Logistic { r } => logistic(p, *r),
+            Attractor::Popcorn { h } => popcorn(p, *h),
+            Attractor::Hopalong { a, b, c } => hopalong(p, *a, *b, *c),
+            Attractor::Martin { a } => martin(p, *a),
         }
     }
+
+    /// Estimates the largest Lyapunov exponent along the orbit from `start`, by carrying a tangent vector through the Jacobian of each step.
+    /// Positive exponents indicate chaos, and negative exponents an attracting fixed point or cycle.
+    /// `start` should already lie on the attractor, such as the last point of a settled orbit.
+    /// Returns `NaN` if the orbit diverges or `iters` is zero.
+    pub fn lyapunov_exponent(&self, start: Complex<T>, iters: u32) -> T {
+        let mut p = start;
+        let mut tangent = [T::one(), T::zero()];
+        let mut log_growth = T::zero();
+        for _ in 0..iters {
+            let [[j00, j01], [j10, j11]] = self.jacobian(p);
+            tangent = [
+                j00 * tangent[0] + j01 * tangent[1],
+                j10 * tangent[0] + j11 * tangent[1],
+            ];
+            let length = tangent[0].hypot(tangent[1]);
+            if !length.is_finite() {
+                return T::nan();
+            }
+            if length == T::zero() {
+                // The map collapses every direction, as at a superattracting point.
+                return T::neg_infinity();
+            }
+            log_growth = log_growth + length.ln();
+            tangent = [tangent[0] / length, tangent[1] / length];
+            p = self.iterate(p);
+        }
+        log_growth / T::from(iters).unwrap()
+    }
+
+    /// Jacobian of a single step of the attractor at `p`, as rows of partial derivatives.
+    fn jacobian(&self, p: Complex<T>) -> [[T; 2]; 2] {
+        let x = p.real;
+        let y = p.imag;
+        let two = T::from(2.0).unwrap();
+        match *self {
+            Attractor::Clifford { a, b, c, d } => [
+                [-c * a * (a * x).sin(), a * (a * y).cos()],
+                [b * (b * x).cos(), -d * b * (b * y).sin()],
+            ],
+            Attractor::DeJong { a, b, c, d } => [
+                [b * (b * x).sin(), a * (a * y).cos()],
+                [c * (c * x).cos(), d * (d * y).sin()],
+            ],
+            Attractor::Henon { a, b } => [[-two * a * x, T::one()], [b, T::zero()]],
+            Attractor::Ikeda { u } => {
+                let r_sq_1 = T::one() + x * x + y * y;
+                let t = T::from(0.4).unwrap() - T::from(6.0).unwrap() / r_sq_1;
+                let (sin_t, cos_t) = t.sin_cos();
+                let dt = T::from(12.0).unwrap() / (r_sq_1 * r_sq_1);
+                let (t_x, t_y) = (dt * x, dt * y);
+                // Derivatives of the rotated point `(x cos t - y sin t, x sin t + y cos t)` with respect to `t`.
+                let (rx_t, ry_t) = (-x * sin_t - y * cos_t, x * cos_t - y * sin_t);
+                [
+                    [u * (cos_t + rx_t * t_x), u * (-sin_t + rx_t * t_y)],
+                    [u * (sin_t + ry_t * t_x), u * (cos_t + ry_t * t_y)],
+                ]
+            }
+            Attractor::Tinkerbell { a, b, c, d } => {
+                [[two * x + a, b - two * y], [two * y + c, two * x + d]]
+            }
+            Attractor::Logistic { r } => [
+                [r * (T::one() - two * x), T::zero()],
+                [T::zero(), T::zero()],
+            ],
+            Attractor::Popcorn { h } => {
+                let three = T::from(3.0).unwrap();
+                // Derivative of `sin(t + tan 3t)` with respect to `t`.
+                let slope = |t: T| {
+                    (t + (three * t).tan()).cos() * (T::one() + three / (three * t).cos().powi(2))
+                };
+                [[T::one(), -h * slope(y)], [-h * slope(x), T::one()]]
+            }
+            Attractor::Hopalong { b, c, .. } => {
+                let u = b * x - c;
+                [
+                    [
+                        -x.signum() * u.signum() * b / (two * u.abs().sqrt()),
+                        T::one(),
+                    ],
+                    [-T::one(), T::zero()],
+                ]
+            }
+            Attractor::Martin { .. } => [[-x.cos(), T::one()], [-T::one(), T::zero()]],
+        }
+    }
+
+    /// Points visited by iterating the attractor `max_iter` times from `start`, not including `start` itself.
+    /// The orbit stops early, without the offending point, if it diverges to a point which is not finite.
+    #[cfg(feature = "std")]
+    pub fn orbit(&self, start: Complex<T>, max_iter: u32) -> Vec<Complex<T>> {
+        let mut orbit = Vec::with_capacity(max_iter as usize);
+        let mut p = start;
+        for _ in 0..max_iter {
+            p = self.iterate(p);
+            if !p.real.is_finite() || !p.imag.is_finite() {
+                break;
+            }
+            orbit.push(p);
+        }
+        orbit
+    }
 }
 
 #[inline(always)]
@@ -139,3 +480,55 @@ where
         imag: T::from(2.0).unwrap() * x * y + c * x + d * y,
     }
 }
+
+#[inline(always)]
+fn logistic<T>(p: Complex<T>, r: T) -> Complex<T>
+where
+    T: Copy + Sub<Output = T> + Mul<Output = T> + Float + NumCast + One,
+{
+    let x = p.real;
+    Complex {
+        real: r * x * (T::one() - x),
+        imag: T::zero(),
+    }
+}
+
+#[inline(always)]
+fn popcorn<T>(p: Complex<T>, h: T) -> Complex<T>
+where
+    T: Copy + Sub<Output = T> + Mul<Output = T> + Float + NumCast,
+{
+    let x = p.real;
+    let y = p.imag;
+    let three = T::from(3.0).unwrap();
+    Complex {
+        real: x - h * (y + (three * y).tan()).sin(),
+        imag: y - h * (x + (three * x).tan()).sin(),
+    }
+}
+
+#[inline(always)]
+fn hopalong<T>(p: Complex<T>, a: T, b: T, c: T) -> Complex<T>
+where
+    T: Copy + Sub<Output = T> + Mul<Output = T> + Float + NumCast,
+{
+    let x = p.real;
+    let y = p.imag;
+    Complex {
+        real: y - x.signum() * (b * x - c).abs().sqrt(),
+        imag: a - x,
+    }
+}
+
+#[inline(always)]
+fn martin<T>(p: Complex<T>, a: T) -> Complex<T>
+where
+    T: Copy + Sub<Output = T> + Float + NumCast,
+{
+    let x = p.real;
+    let y = p.imag;
+    Complex {
+        real: y - x.sin(),
+        imag: a - x,
+    }
+}
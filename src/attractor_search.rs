@@ -0,0 +1,193 @@
+use core::{
+    cmp::Ordering,
+    ops::{Add, Mul, Sub},
+};
+use ndarray::Array2;
+use num_traits::{Float, NumCast};
+use rand::{
+    distr::{uniform::SampleUniform, Distribution, Uniform},
+    Rng,
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_count, check_positive},
+    render::progress_bar,
+    rng_strategy::SEARCH_DOMAIN,
+    Attractor, Complex, MandybrotError, RngStrategy, Viewport,
+};
+
+/// Iterations discarded while each candidate's orbit settles onto its attractor.
+const TRANSIENT_ITER: u32 = 1000;
+
+/// Family of attractors to search, whose parameters are all drawn at random.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttractorFamily {
+    Clifford,
+    DeJong,
+    Henon,
+    Ikeda,
+    Tinkerbell,
+    Logistic,
+    Popcorn,
+    Hopalong,
+    Martin,
+}
+
+impl AttractorFamily {
+    /// Attractor of this family with every parameter drawn uniformly from `[-range, range]`.
+    pub fn random<T: Float + SampleUniform>(&self, range: T, rng: &mut impl Rng) -> Attractor<T> {
+        let distribution = Uniform::new_inclusive(-range, range).unwrap();
+        let mut sample = || distribution.sample(rng);
+        match self {
+            AttractorFamily::Clifford => Attractor::Clifford {
+                a: sample(),
+                b: sample(),
+                c: sample(),
+                d: sample(),
+            },
+            AttractorFamily::DeJong => Attractor::DeJong {
+                a: sample(),
+                b: sample(),
+                c: sample(),
+                d: sample(),
+            },
+            AttractorFamily::Henon => Attractor::Henon {
+                a: sample(),
+                b: sample(),
+            },
+            AttractorFamily::Ikeda => Attractor::Ikeda { u: sample() },
+            AttractorFamily::Tinkerbell => Attractor::Tinkerbell {
+                a: sample(),
+                b: sample(),
+                c: sample(),
+                d: sample(),
+            },
+            AttractorFamily::Logistic => Attractor::Logistic { r: sample() },
+            AttractorFamily::Popcorn => Attractor::Popcorn { h: sample() },
+            AttractorFamily::Hopalong => Attractor::Hopalong {
+                a: sample(),
+                b: sample(),
+                c: sample(),
+            },
+            AttractorFamily::Martin => Attractor::Martin { a: sample() },
+        }
+    }
+}
+
+/// Settings of a search for visually interesting attractors, see `search_attractors`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct AttractorSearch<T> {
+    pub family: AttractorFamily,
+    /// Parameters are drawn uniformly from `[-parameter_range, parameter_range]`.
+    pub parameter_range: T,
+    /// Number of random parameter sets to try.
+    pub num_candidates: u32,
+    /// Number of the best parameter sets to return.
+    pub num_results: usize,
+    /// Number of points of each candidate's orbit to score, after it has settled.
+    pub max_iter: u32,
+    /// Region over which coverage is measured, and thumbnails drawn.
+    pub viewport: Viewport<T>,
+}
+
+/// A parameter set found by `search_attractors`, with the measurements it was scored by.
+#[derive(Debug, Clone)]
+pub struct AttractorCandidate<T> {
+    pub attractor: Attractor<T>,
+    /// Estimated largest Lyapunov exponent, positive for chaotic attractors.
+    pub lyapunov: T,
+    /// Fraction of the viewport's pixels visited by the orbit.
+    pub coverage: T,
+    /// Coverage weighted by the Lyapunov exponent, capped at one, so that broad and strongly chaotic attractors score highest.
+    pub score: T,
+    /// Number of times the orbit visited each pixel of the viewport, if thumbnails were requested.
+    pub thumbnail: Option<Array2<u32>>,
+}
+
+/// Samples random parameter sets and returns the highest scoring, most interesting first.
+/// Candidates whose orbits diverge, or settle onto fixed points, cycles or other non-chaotic attractors, are discarded.
+pub fn search_attractors<T>(
+    search: &AttractorSearch<T>,
+    thumbnails: bool,
+    rng_strategy: &RngStrategy,
+) -> Result<Vec<AttractorCandidate<T>>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + PartialOrd
+        + Float
+        + NumCast
+        + SampleUniform
+        + Send
+        + Sync,
+{
+    check_positive("parameter_range", search.parameter_range)?;
+    check_count("num_candidates", search.num_candidates)?;
+    check_count("max_iter", search.max_iter)?;
+    search.viewport.validate()?;
+
+    let pb = progress_bar(search.num_candidates as u64)?;
+    let mut candidates: Vec<AttractorCandidate<T>> = (0..search.num_candidates)
+        .into_par_iter()
+        .filter_map(|index| {
+            let mut rng = rng_strategy.stream(SEARCH_DOMAIN, index as u64);
+            let attractor = search.family.random(search.parameter_range, &mut rng);
+            let candidate = score_attractor(attractor, search, thumbnails);
+            pb.inc(1);
+            candidate
+        })
+        .collect();
+    pb.finish();
+
+    // Candidates are collected in index order, so ties keep a reproducible order.
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    candidates.truncate(search.num_results);
+    Ok(candidates)
+}
+
+/// Measures the orbit of an attractor from the origin, or returns `None` if it is not chaotic or leaves every finite bound.
+fn score_attractor<T>(
+    attractor: Attractor<T>,
+    search: &AttractorSearch<T>,
+    thumbnails: bool,
+) -> Option<AttractorCandidate<T>>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
+{
+    let mut p = Complex::new(T::zero(), T::zero());
+    for _ in 0..TRANSIENT_ITER {
+        p = attractor.iterate(p);
+        if !p.real.is_finite() || !p.imag.is_finite() {
+            return None;
+        }
+    }
+
+    let lyapunov = attractor.lyapunov_exponent(p, search.max_iter);
+    // Rejects NaN exponents from orbits which diverge as well as non-chaotic ones
+    if lyapunov.is_nan() || lyapunov <= T::zero() {
+        return None;
+    }
+
+    let [width, height] = search.viewport.resolution;
+    let mut visits = Array2::<u32>::zeros((height as usize, width as usize));
+    for _ in 0..search.max_iter {
+        p = attractor.iterate(p);
+        if let Some([x, y]) = search.viewport.complex_to_pixel(&p) {
+            visits[[y, x]] += 1;
+        }
+    }
+    let visited = visits.iter().filter(|&&n| n > 0).count();
+    let coverage = T::from(visited).unwrap() / T::from(visits.len()).unwrap();
+    Some(AttractorCandidate {
+        attractor,
+        lyapunov,
+        coverage,
+        score: coverage * lyapunov.min(T::one()),
+        thumbnail: thumbnails.then_some(visits),
+    })
+}
@@ -0,0 +1,167 @@
+use ndarray::Array2;
+use num_traits::{Float, NumCast};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_count, check_positive},
+    render::par_map_pixels,
+    Attractor, Complex, MandybrotError, Viewport,
+};
+
+/// Settings of a render of the basins of attraction of an attractor, see `render_basins`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BasinSettings<T> {
+    /// Iterations each orbit is given to settle before it is classified.
+    pub max_iter: u32,
+    /// Orbits which reach this distance from the origin, or stop being finite, have escaped.
+    pub escape_radius: T,
+    /// Longest cycle to look for once an orbit has settled.
+    pub max_period: u32,
+    /// Distance within which an orbit has returned to where it was, and two cycles are the same.
+    pub tolerance: T,
+}
+
+impl<T: Float> BasinSettings<T> {
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_positive("escape_radius", self.escape_radius)?;
+        check_count("max_period", self.max_period)?;
+        check_positive("tolerance", self.tolerance)
+    }
+}
+
+/// Eventual behaviour of the orbit from a starting point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fate {
+    /// The orbit left the escape radius.
+    Escaped,
+    /// The orbit settled onto the cycle of the given index in `BasinMap::cycles`.
+    Cycle(usize),
+    /// The orbit stayed bounded without settling onto a cycle, as on a chaotic attractor.
+    Wandering,
+}
+
+/// Attracting cycle found by `render_basins`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Cycle<T> {
+    /// Number of steps around the cycle, one for a fixed point.
+    pub period: u32,
+    /// Point of the cycle with the smallest real part.
+    pub point: Complex<T>,
+    /// Number of pixels whose orbits settled onto the cycle.
+    pub num_pixels: u64,
+}
+
+/// Fate of the orbit from each pixel of a viewport, with the cycles they settled onto.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasinMap<T> {
+    pub fates: Array2<Fate>,
+    /// Every cycle reached, in the order they were first reached scanning the image row by row.
+    pub cycles: Vec<Cycle<T>>,
+}
+
+impl<T> BasinMap<T> {
+    /// Fates as labels for colouring: zero for escaped orbits, one for wandering orbits, and two onwards for each cycle in turn.
+    pub fn labels(&self) -> Array2<u32> {
+        self.fates.mapv(|fate| match fate {
+            Fate::Escaped => 0,
+            Fate::Wandering => 1,
+            Fate::Cycle(index) => index as u32 + 2,
+        })
+    }
+}
+
+/// Outcome of a single orbit, before its cycle is matched against those of other orbits.
+#[derive(Clone, Copy)]
+enum Outcome<T> {
+    Escaped,
+    Cycle(u32, Complex<T>),
+    Wandering,
+}
+
+/// Renders the basins of attraction of an attractor, iterating from each pixel centre as a starting point and classifying where its orbit ends up.
+/// Complements the density renders, which show the attractors themselves rather than which starting points reach them.
+pub fn render_basins<T>(
+    viewport: &Viewport<T>,
+    attractor: &Attractor<T>,
+    settings: &BasinSettings<T>,
+) -> Result<BasinMap<T>, MandybrotError>
+where
+    T: Float + NumCast + Send + Sync,
+{
+    viewport.validate()?;
+    attractor.validate()?;
+    settings.validate()?;
+
+    let outcomes = par_map_pixels(viewport.resolution, true, |[x, y]| {
+        classify(attractor, viewport.pixel_centre(x, y), settings)
+    })?;
+
+    // Matching cycles in row-major order keeps their indices reproducible.
+    let tolerance_sqr = settings.tolerance * settings.tolerance;
+    let mut cycles: Vec<Cycle<T>> = Vec::new();
+    let fates = outcomes.mapv(|outcome| match outcome {
+        Outcome::Escaped => Fate::Escaped,
+        Outcome::Wandering => Fate::Wandering,
+        Outcome::Cycle(period, point) => {
+            let index = cycles
+                .iter()
+                .position(|cycle| {
+                    cycle.period == period && (cycle.point - point).norm_sqr() < tolerance_sqr
+                })
+                .unwrap_or_else(|| {
+                    cycles.push(Cycle {
+                        period,
+                        point,
+                        num_pixels: 0,
+                    });
+                    cycles.len() - 1
+                });
+            cycles[index].num_pixels += 1;
+            Fate::Cycle(index)
+        }
+    });
+
+    Ok(BasinMap { fates, cycles })
+}
+
+/// Settles the orbit from `start`, then looks for a cycle through where it settled.
+fn classify<T>(
+    attractor: &Attractor<T>,
+    start: Complex<T>,
+    settings: &BasinSettings<T>,
+) -> Outcome<T>
+where
+    T: Float + NumCast,
+{
+    let escape_radius_sqr = settings.escape_radius * settings.escape_radius;
+    let tolerance_sqr = settings.tolerance * settings.tolerance;
+    let escaped = |p: Complex<T>| {
+        let r_sqr = p.norm_sqr();
+        r_sqr >= escape_radius_sqr || r_sqr.is_nan()
+    };
+
+    let mut p = start;
+    for _ in 0..settings.max_iter {
+        p = attractor.iterate(p);
+        if escaped(p) {
+            return Outcome::Escaped;
+        }
+    }
+
+    let settled = p;
+    let mut point = settled;
+    for period in 1..=settings.max_period {
+        p = attractor.iterate(p);
+        if escaped(p) {
+            return Outcome::Escaped;
+        }
+        if p.real < point.real || (p.real == point.real && p.imag < point.imag) {
+            point = p;
+        }
+        if (p - settled).norm_sqr() < tolerance_sqr {
+            return Outcome::Cycle(period, point);
+        }
+    }
+    Outcome::Wandering
+}
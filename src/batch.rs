@@ -0,0 +1,129 @@
+use num_traits::Float;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_count, check_finite},
+    render::progress_bar,
+    MandybrotError,
+};
+
+/// A parameter swept over `steps` evenly spaced values from `start` to `end` inclusive.
+/// The parameter is named by its path in the settings being swept, such as `fractal.c.real`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sweep<T> {
+    pub parameter: String,
+    pub start: T,
+    pub end: T,
+    pub steps: u32,
+}
+
+impl<T: Float> Sweep<T> {
+    pub fn new(parameter: &str, start: T, end: T, steps: u32) -> Self {
+        Self {
+            parameter: parameter.to_string(),
+            start,
+            end,
+            steps,
+        }
+    }
+
+    /// Checks that the range is finite and has at least one step.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_finite("start", self.start)?;
+        check_finite("end", self.end)?;
+        check_count("steps", self.steps)
+    }
+
+    /// Value at the given step, which is `start` for a sweep of a single step.
+    pub fn value(&self, step: u32) -> T {
+        if self.steps <= 1 {
+            return self.start;
+        }
+        let t = T::from(step).unwrap() / T::from(self.steps - 1).unwrap();
+        self.start + (self.end - self.start) * t
+    }
+}
+
+/// One combination of swept values in a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchJob<T> {
+    /// Step of each sweep.
+    pub steps: Vec<u32>,
+    /// Value of each swept parameter.
+    pub values: Vec<T>,
+}
+
+impl<T> BatchJob<T> {
+    /// Name identifying the job, built from each parameter's name and zero-padded step, such as `fractal-c-real-007`.
+    /// Names sort in the order the jobs are generated.
+    pub fn name(&self, sweeps: &[Sweep<T>]) -> String {
+        sweeps
+            .iter()
+            .zip(&self.steps)
+            .map(|(sweep, step)| {
+                let width = (sweep.steps.max(1) - 1).to_string().len();
+                let parameter = sweep.parameter.replace(|c: char| !c.is_alphanumeric(), "-");
+                format!("{}-{:0>width$}", parameter, step, width = width)
+            })
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+
+    /// Position of the job in the order of `batch_jobs`, such as to seed `ColourMap::random` so each job gets its own palette.
+    pub fn index(&self, sweeps: &[Sweep<T>]) -> u64 {
+        sweeps
+            .iter()
+            .zip(&self.steps)
+            .fold(0, |index, (sweep, &step)| {
+                index * sweep.steps.max(1) as u64 + step as u64
+            })
+    }
+}
+
+/// Every combination of the swept values, varying the last sweep fastest.
+pub fn batch_jobs<T: Float>(sweeps: &[Sweep<T>]) -> Result<Vec<BatchJob<T>>, MandybrotError> {
+    for sweep in sweeps {
+        sweep.validate()?;
+    }
+    let mut jobs = vec![BatchJob {
+        steps: Vec::new(),
+        values: Vec::new(),
+    }];
+    for sweep in sweeps {
+        jobs = jobs
+            .into_iter()
+            .flat_map(|job| {
+                (0..sweep.steps).map(move |step| {
+                    let mut job = job.clone();
+                    job.steps.push(step);
+                    job.values.push(sweep.value(step));
+                    job
+                })
+            })
+            .collect();
+    }
+    Ok(jobs)
+}
+
+/// Runs `render` for every combination of the swept values in parallel, with a progress bar counting finished jobs.
+/// Results are returned in the order of `batch_jobs`, and the first error encountered is returned instead if any job fails.
+pub fn render_batch<T, R, F>(sweeps: &[Sweep<T>], render: F) -> Result<Vec<R>, MandybrotError>
+where
+    T: Float + Send + Sync,
+    R: Send,
+    F: Fn(&BatchJob<T>) -> Result<R, MandybrotError> + Sync,
+{
+    let jobs = batch_jobs(sweeps)?;
+    let pb = progress_bar(jobs.len() as u64)?;
+    let results = jobs
+        .par_iter()
+        .map(|job| {
+            let result = render(job);
+            pb.inc(1);
+            result
+        })
+        .collect();
+    pb.finish();
+    results
+}
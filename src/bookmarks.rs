@@ -0,0 +1,111 @@
+use num_traits::Float;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{decode_state, encode_state, MandybrotError, Parameters};
+
+/// Render state saved under a name while exploring, such as a view to come back to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Bookmark<T> {
+    pub name: String,
+    pub parameters: Parameters<T>,
+}
+
+impl<T: Serialize> Bookmark<T> {
+    /// Token of the bookmarked state for sharing, as by `encode_state`.
+    pub fn token(&self) -> Result<String, MandybrotError> {
+        encode_state(&self.parameters)
+    }
+}
+
+/// Stack of bookmarks for explorer frontends, with the most recent on top.
+/// Pushing the current view before zooming or panning away, and popping to return, gives a back button; bookmarks are shared as tokens with `Bookmark::token` and `push_token`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct BookmarkStack<T> {
+    bookmarks: Vec<Bookmark<T>>,
+    /// Greatest number of bookmarks kept, beyond which the oldest are forgotten.
+    capacity: Option<usize>,
+}
+
+impl<T> Default for BookmarkStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> BookmarkStack<T> {
+    /// Stack which keeps every bookmark pushed onto it.
+    pub fn new() -> Self {
+        Self {
+            bookmarks: Vec::new(),
+            capacity: None,
+        }
+    }
+
+    /// Stack which keeps only the most recent `capacity` bookmarks, as for an undo history.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bookmarks: Vec::with_capacity(capacity),
+            capacity: Some(capacity),
+        }
+    }
+
+    /// Saves a state on top of the stack, forgetting the oldest bookmark if the stack is full.
+    pub fn push(&mut self, name: impl Into<String>, parameters: Parameters<T>) {
+        if self.capacity == Some(0) {
+            return;
+        }
+        if Some(self.bookmarks.len()) == self.capacity {
+            self.bookmarks.remove(0);
+        }
+        self.bookmarks.push(Bookmark {
+            name: name.into(),
+            parameters,
+        });
+    }
+
+    /// Removes and returns the most recent bookmark, such as to go back to it.
+    pub fn pop(&mut self) -> Option<Bookmark<T>> {
+        self.bookmarks.pop()
+    }
+
+    /// Most recent bookmark, left on the stack.
+    pub fn last(&self) -> Option<&Bookmark<T>> {
+        self.bookmarks.last()
+    }
+
+    /// Bookmark by its position from the bottom of the stack, the oldest first.
+    pub fn get(&self, index: usize) -> Option<&Bookmark<T>> {
+        self.bookmarks.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bookmarks.clear();
+    }
+
+    /// Bookmarks from the oldest to the most recent.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Bookmark<T>> {
+        self.bookmarks.iter()
+    }
+}
+
+impl<T: Float + DeserializeOwned> BookmarkStack<T> {
+    /// Saves the state of a shared token on top of the stack, as by `decode_state`.
+    pub fn push_token(
+        &mut self,
+        name: impl Into<String>,
+        token: &str,
+    ) -> Result<(), MandybrotError> {
+        self.push(name, decode_state(token)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use ndarray::Array2;
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{Fractal, MandybrotError, Periodicity, SamplePattern, Viewport};
+
+/// First bytes of every cache file, identifying the format, and of every hashed key, so entries of other versions are never found.
+const MAGIC: &[u8; 8] = b"MBCACHE3";
+
+/// Number of entries this process has started writing, so concurrent writers never share a temporary file.
+static NUM_WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// Settings which determine the samples of an escape-time render, used to look them up in a `RenderCache`.
+/// The fields match the arguments of `render_fractal_viewport`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RenderKey<T> {
+    pub viewport: Viewport<T>,
+    pub fractal: Fractal<T>,
+    pub max_iter: u32,
+    pub samples_per_pixel: u32,
+    pub pattern: SamplePattern,
+    pub periodicity: Option<Periodicity<T>>,
+}
+
+impl<T: Serialize> RenderKey<T> {
+    /// Bytes stored in each entry: the key serialised as JSON, which records every field so that distinct keys never share an entry.
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+}
+
+/// Directory of previously computed sample arrays, keyed by the settings which produced them.
+/// Re-rendering with the same settings, for example while only the colouring changes, loads the samples instead of recomputing them.
+///
+/// Each key is hashed to a file name, and stored in full in the file so that hash collisions are detected rather than returning the wrong samples.
+/// Entries which are truncated, corrupt or from another version of the format are treated as missing, and replaced when next stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderCache {
+    pub directory: PathBuf,
+}
+
+impl RenderCache {
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Loads the samples stored under `key`, or renders and stores them if there are none.
+    pub fn get_or_render<T: Serialize>(
+        &self,
+        key: &RenderKey<T>,
+        render: impl FnOnce() -> Result<Array2<u32>, MandybrotError>,
+    ) -> Result<Array2<u32>, MandybrotError> {
+        if let Some(samples) = self.load(key)? {
+            return Ok(samples);
+        }
+        let samples = render()?;
+        self.store(key, &samples)?;
+        Ok(samples)
+    }
+
+    /// Samples stored under `key`, if any.
+    pub fn load<T: Serialize>(&self, key: &RenderKey<T>) -> io::Result<Option<Array2<u32>>> {
+        let key = key.encode()?;
+        let file = match File::open(self.path(&key)) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let len = file.metadata()?.len();
+        match read_entry(&mut BufReader::new(file), len, &key) {
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof
+                ) =>
+            {
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+
+    /// Stores samples under `key`, replacing any already stored.
+    /// The file is written in full under a name unique to this write before it replaces the old one, so an interrupted or concurrent write never leaves a truncated entry.
+    pub fn store<T: Serialize>(&self, key: &RenderKey<T>, samples: &Array2<u32>) -> io::Result<()> {
+        let key = key.encode()?;
+        fs::create_dir_all(&self.directory)?;
+        let path = self.path(&key);
+        let partial = path.with_extension(format!(
+            "{}-{}.partial",
+            process::id(),
+            NUM_WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        writer.write_all(MAGIC)?;
+        write_bytes(&mut writer, &key)?;
+        let (height, width) = samples.dim();
+        for size in [height, width] {
+            let size = u32::try_from(size)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Samples too large"))?;
+            writer.write_all(&size.to_le_bytes())?;
+        }
+        for sample in samples {
+            writer.write_all(&sample.to_le_bytes())?;
+        }
+        let written = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)
+            .and_then(|_| fs::rename(&partial, path));
+        if written.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+        written
+    }
+
+    /// Removes every entry from the cache.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.directory) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// File holding the entry for an encoded key, named by the hash of the format and the key.
+    fn path(&self, key: &[u8]) -> PathBuf {
+        let hash = fnv1a(MAGIC.iter().chain(key));
+        self.directory.join(format!("{:016x}.bin", hash))
+    }
+}
+
+/// 64-bit FNV-1a hash, which unlike the standard library's hasher is stable across runs and Rust versions.
+fn fnv1a<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u64 {
+    bytes
+        .into_iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Reads an entry of `len` bytes, returning `None` if it was stored under a different key.
+/// Sizes are checked against the length of the entry before anything is allocated, so a corrupt header cannot request a huge allocation.
+fn read_entry(reader: &mut impl Read, len: u64, key: &[u8]) -> io::Result<Option<Array2<u32>>> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("Not a cache entry"));
+    }
+    if read_u32(reader)? as usize != key.len() {
+        return Ok(None);
+    }
+    let mut stored_key = vec![0; key.len()];
+    reader.read_exact(&mut stored_key)?;
+    if stored_key != key {
+        return Ok(None);
+    }
+
+    let height = read_u32(reader)? as usize;
+    let width = read_u32(reader)? as usize;
+    let size = height
+        .checked_mul(width)
+        .and_then(|count| count.checked_mul(4))
+        .ok_or_else(|| invalid_data("Samples too large"))?;
+    let header = MAGIC.len() + 4 + key.len() + 8;
+    if header as u64 + size as u64 != len {
+        return Err(invalid_data("Entry length does not match its samples"));
+    }
+
+    let mut bytes = vec![0; size];
+    reader.read_exact(&mut bytes)?;
+    let samples = bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    Array2::from_shape_vec((height, width), samples)
+        .map(Some)
+        .map_err(invalid_data)
+}
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Key too long"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Complex;
+
+    fn key(max_iter: u32) -> RenderKey<f64> {
+        RenderKey {
+            viewport: Viewport::new(Complex::new(-0.5, 0.0), 2.0, [3, 2]),
+            fractal: Fractal::Mandelbrot,
+            max_iter,
+            samples_per_pixel: 1,
+            pattern: SamplePattern::Grid,
+            periodicity: None,
+        }
+    }
+
+    fn cache(name: &str) -> RenderCache {
+        let cache = RenderCache::new(std::env::temp_dir().join(format!(
+            "mandybrot-cache-{}-{}",
+            name,
+            std::process::id()
+        )));
+        cache.clear().unwrap();
+        cache
+    }
+
+    fn samples(value: u32) -> Array2<u32> {
+        Array2::from_elem((2, 3), value)
+    }
+
+    #[test]
+    fn stored_samples_are_loaded_by_the_same_key_only() {
+        let cache = cache("keys");
+        cache.store(&key(100), &samples(7)).unwrap();
+        assert_eq!(cache.load(&key(100)).unwrap(), Some(samples(7)));
+        assert_eq!(cache.load(&key(200)).unwrap(), None);
+
+        let data = cache
+            .get_or_render(&key(100), || panic!("cached samples were re-rendered"))
+            .unwrap();
+        assert_eq!(data, samples(7));
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn damaged_entries_are_misses_which_are_replaced() {
+        let cache = cache("damaged");
+        let path = cache.path(&key(100).encode().unwrap());
+        cache.store(&key(100), &samples(7)).unwrap();
+        let entry = fs::read(&path).unwrap();
+
+        let header = MAGIC.len() + 4 + key(100).encode().unwrap().len();
+        let mut huge = entry.clone();
+        huge[header..header + 8].copy_from_slice(&[0xff; 8]);
+        let mut wrong_magic = entry.clone();
+        wrong_magic[0] = b'X';
+        for damaged in [
+            entry[..entry.len() - 1].to_vec(),
+            entry[..5].to_vec(),
+            huge,
+            wrong_magic,
+            Vec::new(),
+        ] {
+            fs::write(&path, damaged).unwrap();
+            assert_eq!(cache.load(&key(100)).unwrap(), None);
+        }
+
+        let data = cache.get_or_render(&key(100), || Ok(samples(9))).unwrap();
+        assert_eq!(data, samples(9));
+        assert_eq!(cache.load(&key(100)).unwrap(), Some(samples(9)));
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn concurrent_stores_each_write_their_own_file() {
+        let cache = cache("concurrent");
+        std::thread::scope(|scope| {
+            for value in 0..8 {
+                let cache = &cache;
+                scope.spawn(move || cache.store(&key(100), &samples(value)).unwrap());
+            }
+        });
+
+        let stored = cache.load(&key(100)).unwrap().unwrap();
+        assert!((0..8).any(|value| stored == samples(value)));
+        let files: Vec<_> = fs::read_dir(&cache.directory).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn keys_are_encoded_by_their_serialised_fields() {
+        let mut nudged = key(100);
+        nudged.viewport.scale = f64::from_bits(2.0f64.to_bits() + 1);
+        let encoded = key(100).encode().unwrap();
+        assert_ne!(encoded, nudged.encode().unwrap());
+        assert_eq!(encoded, serde_json::to_vec(&key(100)).unwrap());
+    }
+}
@@ -0,0 +1,178 @@
+use ndarray::Array2;
+use num_traits::{Float, NumCast};
+use rayon::prelude::*;
+use std::{
+    ops::{Add, Div, Mul, Sub},
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    render::{sample_pixel, validate_escape_time},
+    Fractal, MandybrotError, Periodicity, SamplePattern, Viewport,
+};
+
+/// Render which makes progress only when asked, a few rows at a time, so that it can share a machine or an event loop with other work.
+///
+/// Each call to `poll_render` renders one chunk of rows, and `render_for` renders chunks until a time budget is spent, so a GUI can render between frames and a scheduler can interleave several renders by polling each in turn.
+/// The host can pause and resume the render between calls, and lower its priority so that each chunk runs on fewer threads.
+pub struct ChunkedRender<T> {
+    viewport: Viewport<T>,
+    fractal: Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+    image: Array2<u32>,
+    /// First row not yet rendered.
+    next_row: u32,
+    paused: bool,
+    priority: u32,
+}
+
+impl<T> ChunkedRender<T>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    /// Render of a fractal over a viewport, of which nothing is drawn until it is polled.
+    pub fn new(
+        viewport: Viewport<T>,
+        fractal: Fractal<T>,
+        max_iter: u32,
+        samples_per_pixel: u32,
+        pattern: SamplePattern,
+        periodicity: Option<Periodicity<T>>,
+    ) -> Result<Self, MandybrotError> {
+        validate_escape_time(&viewport, &fractal, samples_per_pixel, periodicity.as_ref())?;
+        let [x_res, y_res] = viewport.resolution;
+        Ok(Self {
+            viewport,
+            fractal,
+            max_iter,
+            samples_per_pixel,
+            pattern,
+            periodicity,
+            image: Array2::zeros((y_res as usize, x_res as usize)),
+            next_row: 0,
+            paused: false,
+            priority: 100,
+        })
+    }
+
+    /// Renders the next chunk of rows, unless the render is paused, returning the image once every row is done.
+    pub fn poll_render(&mut self) -> Poll<&Array2<u32>> {
+        if !self.paused && !self.is_finished() {
+            self.render_chunk();
+        }
+        self.state()
+    }
+
+    /// Renders chunks of rows until `budget` has passed or the render is done, returning the image once every row is done.
+    /// At least one chunk is rendered unless the render is paused, so every call makes progress, and a call may overrun its budget by up to one chunk.
+    pub fn render_for(&mut self, budget: Duration) -> Poll<&Array2<u32>> {
+        let start = Instant::now();
+        while !self.paused && !self.is_finished() {
+            self.render_chunk();
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+        self.state()
+    }
+
+    /// Stops further polls from rendering anything until the render is resumed.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Percentage (1 to 100) of the worker threads each chunk may use.
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    /// Sets the share of the worker threads each chunk may use, from 1 to 100 percent.
+    /// Lower priorities render fewer rows at a time, which leaves cores idle for other work and keeps each poll short.
+    pub fn set_priority(&mut self, percent: u32) {
+        self.priority = percent.clamp(1, 100);
+    }
+
+    /// Number of completed and total work items (rows) of the render.
+    pub fn progress(&self) -> [u64; 2] {
+        [self.next_row as u64, self.viewport.resolution[1] as u64]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_row >= self.viewport.resolution[1]
+    }
+
+    /// Partially rendered image; unrendered pixels are zero.
+    pub fn image(&self) -> &Array2<u32> {
+        &self.image
+    }
+
+    pub fn into_image(self) -> Array2<u32> {
+        self.image
+    }
+
+    fn state(&self) -> Poll<&Array2<u32>> {
+        if self.is_finished() {
+            Poll::Ready(&self.image)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Renders the next rows in parallel, one row for each thread the priority allows.
+    fn render_chunk(&mut self) {
+        let [x_res, y_res] = self.viewport.resolution;
+        let batch = (rayon::current_num_threads() * self.priority as usize / 100).max(1);
+        let (start, end) = (self.next_row, (self.next_row + batch as u32).min(y_res));
+
+        let (viewport, fractal) = (&self.viewport, &self.fractal);
+        let (max_iter, samples_per_pixel, pattern) =
+            (self.max_iter, self.samples_per_pixel, self.pattern);
+        let periodicity = self.periodicity.as_ref();
+        let rows: Vec<Vec<u32>> = (start..end)
+            .into_par_iter()
+            .map(|y| {
+                (0..x_res)
+                    .map(|x| {
+                        sample_pixel(
+                            viewport,
+                            [x, y],
+                            fractal,
+                            max_iter,
+                            samples_per_pixel,
+                            pattern,
+                            periodicity,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (row, values) in (start as usize..).zip(rows) {
+            for (x, value) in values.into_iter().enumerate() {
+                self.image[(row, x)] = value;
+            }
+        }
+        self.next_row = end;
+    }
+}
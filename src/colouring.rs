@@ -0,0 +1,244 @@
+use num_traits::Float;
+#[cfg(feature = "std")]
+use palette::{LinSrgba, Srgba};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::{error::check_positive, MandybrotError, Transparency};
+
+/// Mapping of normalised iteration values onto a cyclic colour palette.
+/// Repeating the palette reveals fine structure near the boundary without raising `max_iter`, and animating the `offset` cycles the colours.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaletteCycle<T> {
+    /// Number of times the palette is repeated over the range `[0, 1]`.
+    pub repeat: T,
+    /// Phase offset, as a fraction of one repeat of the palette.
+    pub offset: T,
+    /// Run the palette backwards on every other repeat, so palettes whose ends differ have no seams.
+    #[serde(default)]
+    pub mirror: bool,
+}
+
+impl<T: Float> PaletteCycle<T> {
+    pub const fn new(repeat: T, offset: T, mirror: bool) -> Self {
+        Self {
+            repeat,
+            offset,
+            mirror,
+        }
+    }
+
+    /// Maps a normalised value onto its position in the palette, in the range `[0, 1]`.
+    pub fn apply(&self, t: T) -> T {
+        let position = t * self.repeat + self.offset;
+        if self.mirror {
+            // Triangle wave with period two.
+            let two = T::one() + T::one();
+            let phase = position - two * (position / two).floor();
+            if phase > T::one() {
+                two - phase
+            } else {
+                phase
+            }
+        } else {
+            position - position.floor()
+        }
+    }
+
+    /// The same cycle with its offset advanced by `delta`, such as for the next frame of a colour-cycling animation.
+    pub fn shifted(&self, delta: T) -> Self {
+        Self {
+            offset: self.offset + delta,
+            ..*self
+        }
+    }
+}
+
+/// Sample stops of the built-in colour maps, as sRGB hex codes evenly spaced over `[0, 1]`.
+#[cfg(feature = "std")]
+const PRESETS: &[(&str, &[u32])] = &[
+    (
+        "viridis",
+        &[
+            0x440154, 0x482878, 0x3E4A89, 0x31688E, 0x26828E, 0x1F9E89, 0x35B779, 0x6DCD59,
+            0xB4DE2C, 0xFDE725,
+        ],
+    ),
+    (
+        "magma",
+        &[
+            0x000004, 0x180F3E, 0x451077, 0x721F81, 0x9F2F7F, 0xCD4071, 0xF1605D, 0xFD9567,
+            0xFEC98D, 0xFCFDBF,
+        ],
+    ),
+    (
+        "inferno",
+        &[
+            0x000004, 0x1B0C42, 0x4B0C6B, 0x781C6D, 0xA52C60, 0xCF4446, 0xED6925, 0xFB9A06,
+            0xF7D03C, 0xFCFFA4,
+        ],
+    ),
+    (
+        "plasma",
+        &[
+            0x0D0887, 0x47039F, 0x7301A8, 0x9C179E, 0xBD3786, 0xD8576B, 0xED7953, 0xFA9E3B,
+            0xFDC926, 0xF0F921,
+        ],
+    ),
+    (
+        "turbo",
+        &[
+            0x30123B, 0x4662D7, 0x36AAF9, 0x1AE4B6, 0x72FE5E, 0xC7EF34, 0xFABA39, 0xF66B19,
+            0xCB2A04, 0x7A0403,
+        ],
+    ),
+    (
+        "twilight",
+        &[
+            0xE2D9E2, 0xA5B9D0, 0x6A84C2, 0x5E4AA6, 0x2F1436, 0x6B1F4D, 0xA8434B, 0xCB8E78,
+            0xE2D9E2,
+        ],
+    ),
+];
+
+/// Gradient through evenly spaced colours, interpolated in linear light.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColourMap {
+    colours: Vec<LinSrgba>,
+}
+
+#[cfg(feature = "std")]
+impl ColourMap {
+    /// Colour map through the given colours, of which there must be at least one.
+    pub fn new(colours: Vec<LinSrgba>) -> Result<Self, MandybrotError> {
+        if colours.is_empty() {
+            return Err(MandybrotError::ZeroCount("colours"));
+        }
+        Ok(Self { colours })
+    }
+
+    /// Built-in colour map by name: one of `viridis`, `magma`, `inferno`, `plasma`, `turbo` or the cyclic `twilight`.
+    pub fn preset(name: &str) -> Option<Self> {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, hexes)| {
+                Self::new(
+                    hexes
+                        .iter()
+                        .map(|&hex| {
+                            let [_, red, green, blue] = hex.to_be_bytes();
+                            Srgba::new(red, green, blue, u8::MAX)
+                                .into_format::<f32, f32>()
+                                .into_linear()
+                        })
+                        .collect(),
+                )
+                .expect("presets have colours")
+            })
+    }
+
+    /// Names of the built-in colour maps.
+    pub fn presets() -> impl Iterator<Item = &'static str> {
+        PRESETS.iter().map(|(name, _)| *name)
+    }
+
+    /// Colour at position `t`, clamped to the range `[0, 1]`.
+    pub fn sample(&self, t: f32) -> LinSrgba {
+        let last = self.colours.len() - 1;
+        let position = t.clamp(0.0, 1.0) * last as f32;
+        let index = (position.floor() as usize).min(last.saturating_sub(1));
+        let next = (index + 1).min(last);
+        let weight = position - index as f32;
+        self.colours[index] * (1.0 - weight) + self.colours[next] * weight
+    }
+
+    /// Colours the map passes through, evenly spaced over `[0, 1]`.
+    pub fn colours(&self) -> &[LinSrgba] {
+        &self.colours
+    }
+}
+
+/// Colouring of a single iteration count, normalised by `max_iter` rather than by the rest of the image.
+/// Each sub-sample of a pixel can then be coloured on its own and the colours averaged, which keeps high-contrast boundaries the right colour where averaging the counts would not.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct EscapeColouring<T> {
+    /// Compress the counts logarithmically, lifting the colours of quickly escaping points.
+    #[serde(default)]
+    pub log: bool,
+    #[serde(default = "unit")]
+    pub gamma: T,
+    pub palette_cycle: Option<PaletteCycle<T>>,
+    /// Alpha of the interior and exterior, opaque if not given.
+    #[serde(default)]
+    pub transparency: Option<Transparency>,
+}
+
+#[cfg(feature = "std")]
+fn unit<T: Float>() -> T {
+    T::one()
+}
+
+#[cfg(feature = "std")]
+impl<T: Float> Default for EscapeColouring<T> {
+    fn default() -> Self {
+        Self::new(false, T::one(), None)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Float> EscapeColouring<T> {
+    pub const fn new(log: bool, gamma: T, palette_cycle: Option<PaletteCycle<T>>) -> Self {
+        Self {
+            log,
+            gamma,
+            palette_cycle,
+            transparency: None,
+        }
+    }
+
+    /// The same colouring, with the alphas of the interior and exterior set by `transparency`.
+    pub const fn with_transparency(self, transparency: Transparency) -> Self {
+        Self {
+            transparency: Some(transparency),
+            ..self
+        }
+    }
+
+    /// Checks that the gamma is positive and the alphas are within `[0, 1]`.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_positive("gamma", self.gamma)?;
+        match &self.transparency {
+            Some(transparency) => transparency.validate(),
+            None => Ok(()),
+        }
+    }
+
+    /// Position of an iteration count in the colour map, from zero for points which escape at once to one for points which never escape.
+    pub fn value(&self, count: u32, max_iter: u32) -> T {
+        let count = T::from(count.min(max_iter)).unwrap();
+        let max_iter = T::from(max_iter.max(1)).unwrap();
+        let t = if self.log {
+            count.ln_1p() / max_iter.ln_1p()
+        } else {
+            count / max_iter
+        };
+        let t = t.powf(self.gamma);
+        match &self.palette_cycle {
+            Some(cycle) => cycle.apply(t),
+            None => t,
+        }
+    }
+
+    /// Colour of an iteration count.
+    pub fn colour(&self, count: u32, max_iter: u32, colour_map: &ColourMap) -> LinSrgba {
+        let mut colour = colour_map.sample(self.value(count, max_iter).to_f32().unwrap_or(0.0));
+        if let Some(transparency) = &self.transparency {
+            colour.alpha *= transparency.alpha(count, max_iter);
+        }
+        colour
+    }
+}
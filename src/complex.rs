@@ -1,8 +1,19 @@
-use num_traits::Float;
-use serde::{Deserialize, Serialize};
-use std::ops::{Add, Div, Mul, Neg, Sub};
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Add, Div, Mul, Neg, Sub},
+    str::FromStr,
+};
+use num_traits::{Float, Num};
+use serde::{
+    de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+/// Complex number `real + imag i`.
+///
+/// Settings files may write it as a table `{real: 1.5, imag: -0.3}`, a pair `[1.5, -0.3]`, or a string `"1.5-0.3i"` as parsed by `FromStr`, and it is always written back as a table.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
 pub struct Complex<T> {
     pub real: T,
     pub imag: T,
@@ -12,6 +23,11 @@ impl<T> Complex<T> {
     pub const fn new(real: T, imag: T) -> Self {
         Self { real, imag }
     }
+
+    /// Applies a function to both parts, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Complex<U> {
+        Complex::new(f(self.real), f(self.imag))
+    }
 }
 
 /// Negation
@@ -124,15 +140,44 @@ impl<T: Float> Complex<T> {
     }
 }
 
-/// Float power
-impl Complex<f32> {
-    pub fn powf(self, n: f32) -> Self {
-        let r = self.norm();
+/// Real and complex powers, taking the principal branch
+impl<T: Float> Complex<T> {
+    pub fn powf(self, n: T) -> Self {
+        let r = self.abs();
         let theta = self.imag.atan2(self.real);
         let new_r = r.powf(n);
         let new_theta = theta * n;
         Self::new(new_r * new_theta.cos(), new_r * new_theta.sin())
     }
+
+    pub fn powc(self, n: Self) -> Self {
+        if self.real.is_zero() && self.imag.is_zero() {
+            return self;
+        }
+        let ln_r = self.abs().ln();
+        let theta = self.imag.atan2(self.real);
+        let new_ln_r = n.real * ln_r - n.imag * theta;
+        let new_theta = n.imag * ln_r + n.real * theta;
+        let new_r = new_ln_r.exp();
+        Self::new(new_r * new_theta.cos(), new_r * new_theta.sin())
+    }
+}
+
+/// Trigonometric functions
+impl<T: Float> Complex<T> {
+    pub fn cos(self) -> Self {
+        Self::new(
+            self.real.cos() * self.imag.cosh(),
+            -self.real.sin() * self.imag.sinh(),
+        )
+    }
+
+    pub fn sin(self) -> Self {
+        Self::new(
+            self.real.sin() * self.imag.cosh(),
+            self.real.cos() * self.imag.sinh(),
+        )
+    }
 }
 
 /// Absolute value
@@ -141,9 +186,182 @@ impl<T: Float> Complex<T> {
         self.norm_sqr().sqrt()
     }
 
+    /// Principal square root
+    pub fn sqrt(self) -> Self {
+        let two = T::from(2.0).unwrap();
+        let r = self.abs();
+        let real = ((r + self.real) / two).sqrt();
+        let imag = ((r - self.real) / two).sqrt();
+        Self::new(real, if self.imag < T::zero() { -imag } else { imag })
+    }
+
     // Reciprocal/inverse
     pub fn inv(self) -> Self {
         let norm = self.norm_sqr();
         Self::new(self.real / norm, -self.imag / norm)
     }
 }
+
+/// Writes the number as `1.5-0.3i`, applying any precision, such as `{:.3}`, to both parts.
+impl<T: Float + fmt::Display> fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.imag.is_sign_negative() {
+            '-'
+        } else {
+            '+'
+        };
+        match f.precision() {
+            Some(precision) => write!(
+                f,
+                "{:.*}{}{:.*}i",
+                precision,
+                self.real,
+                sign,
+                precision,
+                self.imag.abs()
+            ),
+            None => write!(f, "{}{}{}i", self.real, sign, self.imag.abs()),
+        }
+    }
+}
+
+/// Error returned when a string is not a complex number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseComplexError;
+
+impl fmt::Display for ParseComplexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid complex number")
+    }
+}
+
+impl core::error::Error for ParseComplexError {}
+
+/// Parses a complex number such as `-0.743643+0.131825i`, `1.5e-3 - 2i`, `0.5`, `-i` or `−0.7+0.1i` with a Unicode minus sign.
+/// Each part is parsed as its own type would parse a decimal, so `DoubleDouble` parts keep every digit.
+impl<T: Num + Neg<Output = T>> FromStr for Complex<T> {
+    type Err = ParseComplexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // The second term starts at the first sign which neither starts the text nor belongs to an exponent.
+        let split = s
+            .char_indices()
+            .skip(1)
+            .find(|&(i, c)| is_sign(c) && !s[..i].ends_with(['e', 'E']))
+            .map(|(i, _)| i);
+        let (first, second) = match split {
+            Some(i) => (&s[..i], Some(&s[i..])),
+            None => (s, None),
+        };
+
+        match (parse_term::<T>(first)?, second.map(parse_term).transpose()?) {
+            ((real, false), None) => Ok(Self::new(real, T::zero())),
+            ((imag, true), None) => Ok(Self::new(T::zero(), imag)),
+            ((real, false), Some((imag, true))) | ((imag, true), Some((real, false))) => {
+                Ok(Self::new(real, imag))
+            }
+            _ => Err(ParseComplexError),
+        }
+    }
+}
+
+fn is_sign(c: char) -> bool {
+    matches!(c, '+' | '-' | '\u{2212}')
+}
+
+/// Value of one signed term of a complex number, and whether it is the imaginary part.
+fn parse_term<T: Num + Neg<Output = T>>(term: &str) -> Result<(T, bool), ParseComplexError> {
+    let term = term.trim();
+    let (negative, unsigned) = match term.chars().next() {
+        Some(c) if is_sign(c) => (c != '+', term[c.len_utf8()..].trim_start()),
+        _ => (false, term),
+    };
+    let (imaginary, digits) = match unsigned.strip_suffix('i') {
+        Some(digits) => (true, digits.trim_end()),
+        None => (false, unsigned),
+    };
+    if digits.starts_with(is_sign) || (digits.is_empty() && !imaginary) {
+        return Err(ParseComplexError);
+    }
+    let value = if digits.is_empty() {
+        T::one()
+    } else {
+        T::from_str_radix(digits, 10).map_err(|_| ParseComplexError)?
+    };
+    Ok((if negative { -value } else { value }, imaginary))
+}
+
+impl<'de, T> Deserialize<'de> for Complex<T>
+where
+    T: Num + Neg<Output = T> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "lowercase")]
+        enum Field {
+            Real,
+            Imag,
+            #[serde(other)]
+            Other,
+        }
+
+        struct ComplexVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for ComplexVisitor<T>
+        where
+            T: Num + Neg<Output = T> + Deserialize<'de>,
+        {
+            type Value = Complex<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a complex number as {{real, imag}}, [real, imag] or a string such as \"1.5-0.3i\""
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let real = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let imag = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                if seq.next_element::<IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(3, &self));
+                }
+                Ok(Complex::new(real, imag))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let (mut real, mut imag) = (None, None);
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Real if real.is_some() => {
+                            return Err(de::Error::duplicate_field("real"))
+                        }
+                        Field::Imag if imag.is_some() => {
+                            return Err(de::Error::duplicate_field("imag"))
+                        }
+                        Field::Real => real = Some(map.next_value()?),
+                        Field::Imag => imag = Some(map.next_value()?),
+                        Field::Other => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(Complex::new(
+                    real.ok_or_else(|| de::Error::missing_field("real"))?,
+                    imag.ok_or_else(|| de::Error::missing_field("imag"))?,
+                ))
+            }
+        }
+
+        deserializer.deserialize_any(ComplexVisitor(PhantomData))
+    }
+}
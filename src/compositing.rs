@@ -0,0 +1,264 @@
+use ndarray::{Array2, Zip};
+use palette::{LinSrgba, Srgba};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "png")]
+use std::{fs::File, io::BufReader, path::Path};
+
+use crate::{
+    error::{check_count, check_fraction},
+    MandybrotError,
+};
+
+/// Opacity of the pixels of an escape-time render by whether their orbits escaped, such as to cut the set out and place it over a background.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Transparency {
+    /// Alpha of pixels whose orbits did not escape.
+    #[serde(default = "opaque")]
+    pub interior: f32,
+    /// Alpha of pixels whose orbits escaped.
+    #[serde(default = "opaque")]
+    pub exterior: f32,
+}
+
+fn opaque() -> f32 {
+    1.0
+}
+
+impl Transparency {
+    pub const fn new(interior: f32, exterior: f32) -> Self {
+        Self { interior, exterior }
+    }
+
+    /// Checks that both alphas are within `[0, 1]`.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_fraction("interior", self.interior)?;
+        check_fraction("exterior", self.exterior)
+    }
+
+    /// Alpha of a pixel whose orbit escaped after `count` iterations, or did not escape if `count` is at least `max_iter`.
+    pub fn alpha(&self, count: u32, max_iter: u32) -> f32 {
+        if count >= max_iter {
+            self.interior
+        } else {
+            self.exterior
+        }
+    }
+
+    /// Scales the alpha of each colour by that of its pixel's iteration count.
+    /// Counts averaged over super samples only count as interior if every sample was; colour each sample with an `EscapeColouring` to blend the edges of the set.
+    pub fn apply(
+        &self,
+        colours: &mut Array2<LinSrgba>,
+        counts: &Array2<u32>,
+        max_iter: u32,
+    ) -> Result<(), MandybrotError> {
+        self.validate()?;
+        if colours.dim() != counts.dim() {
+            return Err(MandybrotError::BufferSize {
+                expected: colours.len(),
+                found: counts.len(),
+            });
+        }
+        Zip::from(colours).and(counts).for_each(|colour, &count| {
+            colour.alpha *= self.alpha(count, max_iter);
+        });
+        Ok(())
+    }
+}
+
+/// Backdrop for a render with transparent pixels, see `composite`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Background {
+    /// A single sRGB colour with alpha, in 8-bit channels.
+    Solid([u8; 4]),
+    /// Blend from the `top` to the `bottom` of the image in linear light, with colours as for `Solid`.
+    Gradient { top: [u8; 4], bottom: [u8; 4] },
+    /// An image stretched over the whole render, such as one read by `load_png`.
+    #[serde(skip)]
+    Image(Array2<LinSrgba>),
+}
+
+impl Background {
+    /// Colour of the background at pixel `(x, y)` of an image of `[width, height]` pixels.
+    pub fn colour(&self, [x, y]: [usize; 2], [width, height]: [usize; 2]) -> LinSrgba {
+        match self {
+            Background::Solid(colour) => from_bytes(*colour),
+            Background::Gradient { top, bottom } => {
+                let t = (y as f32 + 0.5) / height as f32;
+                from_bytes(*top) * (1.0 - t) + from_bytes(*bottom) * t
+            }
+            Background::Image(image) => {
+                let (image_height, image_width) = image.dim();
+                let x = ((x as f32 + 0.5) * image_width as f32 / width as f32 - 0.5)
+                    .clamp(0.0, (image_width - 1) as f32);
+                let y = ((y as f32 + 0.5) * image_height as f32 / height as f32 - 0.5)
+                    .clamp(0.0, (image_height - 1) as f32);
+                let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+                let (x1, y1) = (
+                    (x0 + 1).min(image_width - 1),
+                    (y0 + 1).min(image_height - 1),
+                );
+                let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+                (image[(y0, x0)] * (1.0 - fx) + image[(y0, x1)] * fx) * (1.0 - fy)
+                    + (image[(y1, x0)] * (1.0 - fx) + image[(y1, x1)] * fx) * fy
+            }
+        }
+    }
+}
+
+/// Places a colour over another, weighting each by its alpha, as the Porter-Duff over operator.
+pub fn over(foreground: LinSrgba, background: LinSrgba) -> LinSrgba {
+    let alpha = foreground.alpha + background.alpha * (1.0 - foreground.alpha);
+    if alpha <= 0.0 {
+        return LinSrgba::new(0.0, 0.0, 0.0, 0.0);
+    }
+    let weight = background.alpha * (1.0 - foreground.alpha);
+    let channel = |f: f32, b: f32| (f * foreground.alpha + b * weight) / alpha;
+    LinSrgba::new(
+        channel(foreground.red, background.red),
+        channel(foreground.green, background.green),
+        channel(foreground.blue, background.blue),
+        alpha,
+    )
+}
+
+/// Way each channel of a layer combines with the colour beneath it, in linear light.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// The layer's colour, covering what is beneath.
+    #[default]
+    Normal,
+    /// Product of the channels, only ever darkening.
+    Multiply,
+    /// Inverse of the product of the inverted channels, only ever lightening.
+    Screen,
+    /// Multiplies dark and screens light parts of the colour beneath, raising its contrast.
+    Overlay,
+    /// Sum of the channels, clamped to one, such as for glowing orbit densities.
+    Add,
+    /// The darker of the channels.
+    Darken,
+    /// The lighter of the channels.
+    Lighten,
+}
+
+impl BlendMode {
+    /// Blend of a channel of a layer, `top`, with the same channel beneath it, `bottom`.
+    pub fn channel(&self, top: f32, bottom: f32) -> f32 {
+        match self {
+            BlendMode::Normal => top,
+            BlendMode::Multiply => top * bottom,
+            BlendMode::Screen => top + bottom - top * bottom,
+            BlendMode::Overlay if bottom <= 0.5 => 2.0 * top * bottom,
+            BlendMode::Overlay => 1.0 - 2.0 * (1.0 - top) * (1.0 - bottom),
+            BlendMode::Add => (top + bottom).min(1.0),
+            BlendMode::Darken => top.min(bottom),
+            BlendMode::Lighten => top.max(bottom),
+        }
+    }
+}
+
+/// How a layer is placed over the layers beneath it, see `blend_layers`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Blend {
+    #[serde(default)]
+    pub mode: BlendMode,
+    /// Factor of the layer's alpha, fading the whole layer.
+    #[serde(default = "opaque")]
+    pub opacity: f32,
+}
+
+impl Default for Blend {
+    fn default() -> Self {
+        Self::new(BlendMode::Normal, 1.0)
+    }
+}
+
+impl Blend {
+    pub const fn new(mode: BlendMode, opacity: f32) -> Self {
+        Self { mode, opacity }
+    }
+
+    /// Checks that the opacity is within `[0, 1]`.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_fraction("opacity", self.opacity)
+    }
+
+    /// Places a colour over another, with their channels blended by the mode where the colour beneath is opaque, as in the W3C compositing model.
+    pub fn apply(&self, top: LinSrgba, bottom: LinSrgba) -> LinSrgba {
+        let channel =
+            |t: f32, b: f32| (1.0 - bottom.alpha) * t + bottom.alpha * self.mode.channel(t, b);
+        over(
+            LinSrgba::new(
+                channel(top.red, bottom.red),
+                channel(top.green, bottom.green),
+                channel(top.blue, bottom.blue),
+                top.alpha * self.opacity,
+            ),
+            bottom,
+        )
+    }
+}
+
+/// Combines images of the same size, such as renders of a fractal's escape times, orbit traps and boundary, into one, bottom layer first.
+/// Each layer is placed over those beneath it by its `Blend`, starting from transparency, so the bottom layer is only faded by its opacity.
+pub fn blend_layers(
+    layers: &[(Array2<LinSrgba>, Blend)],
+) -> Result<Array2<LinSrgba>, MandybrotError> {
+    check_count("layers", layers.len() as u32)?;
+    let dim = layers[0].0.dim();
+    let mut image = Array2::from_elem(dim, LinSrgba::new(0.0, 0.0, 0.0, 0.0));
+    for (layer, blend) in layers {
+        blend.validate()?;
+        if layer.dim() != dim {
+            return Err(MandybrotError::BufferSize {
+                expected: image.len(),
+                found: layer.len(),
+            });
+        }
+        Zip::from(&mut image)
+            .and(layer)
+            .for_each(|bottom, &top| *bottom = blend.apply(top, *bottom));
+    }
+    Ok(image)
+}
+
+/// Places an image over a background in linear light.
+/// The result is opaque wherever the background is, so it saves the same as RGB or RGBA.
+pub fn composite(image: &Array2<LinSrgba>, background: &Background) -> Array2<LinSrgba> {
+    let (height, width) = image.dim();
+    Array2::from_shape_fn((height, width), |(y, x)| {
+        over(image[(y, x)], background.colour([x, y], [width, height]))
+    })
+}
+
+/// Reads an 8-bit or 16-bit PNG into linear colours, such as for a `Background::Image`.
+#[cfg(feature = "png")]
+pub fn load_png(path: impl AsRef<Path>) -> Result<Array2<LinSrgba>, MandybrotError> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(std::io::Error::from)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(std::io::Error::from)?;
+
+    let channels = info.color_type.samples();
+    let pixels: Vec<LinSrgba> = buffer[..info.buffer_size()]
+        .chunks_exact(channels)
+        .map(|pixel| match *pixel {
+            [grey] => from_bytes([grey, grey, grey, u8::MAX]),
+            [grey, alpha] => from_bytes([grey, grey, grey, alpha]),
+            [red, green, blue] => from_bytes([red, green, blue, u8::MAX]),
+            [red, green, blue, alpha] => from_bytes([red, green, blue, alpha]),
+            _ => unreachable!(),
+        })
+        .collect();
+    Ok(Array2::from_shape_vec((info.height as usize, info.width as usize), pixels).unwrap())
+}
+
+fn from_bytes([red, green, blue, alpha]: [u8; 4]) -> LinSrgba {
+    Srgba::new(red, green, blue, alpha)
+        .into_format::<f32, f32>()
+        .into_linear()
+}
@@ -0,0 +1,116 @@
+use serde::{de, Deserialize, Serialize};
+use std::path::Path;
+
+use crate::MandybrotError;
+
+/// Text format of a settings file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConfigFormat {
+    /// YAML, with each enum variant given as a tag such as `job: !Fractal`. Reading it needs the `yaml` feature.
+    Yaml,
+    /// JSON, with each enum variant given as the name of a unit variant or as an object with the variant's name as its only key, such as `"job": {"Fractal": {..}}`. Reading it needs the `json` feature.
+    Json,
+    /// TOML, with enum variants given as in JSON, such as a `[job.Fractal]` table. Reading it needs the `toml` feature.
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Format of a file, from its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, MandybrotError> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map_or(String::new(), |extension| {
+                extension.to_string_lossy().to_lowercase()
+            });
+        match extension.as_str() {
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "json" => Ok(ConfigFormat::Json),
+            "toml" => Ok(ConfigFormat::Toml),
+            _ => Err(MandybrotError::UnsupportedFormat(extension)),
+        }
+    }
+
+    /// Parses any settings written in this format, naming the offending line or key if they are invalid.
+    pub fn parse<S: de::DeserializeOwned>(&self, text: &str) -> Result<S, MandybrotError> {
+        match self {
+            #[cfg(feature = "yaml")]
+            ConfigFormat::Yaml => Ok(serde_yaml::from_str(text)?),
+            #[cfg(not(feature = "yaml"))]
+            ConfigFormat::Yaml => Err(MandybrotError::UnsupportedFormat("yaml".into())),
+            ConfigFormat::Json => from_json_str(text),
+            ConfigFormat::Toml => from_toml_str(text),
+        }
+    }
+}
+
+/// Deserialises settings written as JSON, naming the offending line or key if they are invalid.
+pub(crate) fn from_json_str<S: de::DeserializeOwned>(text: &str) -> Result<S, MandybrotError> {
+    #[cfg(feature = "json")]
+    {
+        let mut deserializer = serde_json::Deserializer::from_str(text);
+        let settings = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|err| json_error(err.path().to_string(), err.into_inner()))?;
+        deserializer
+            .end()
+            .map_err(|err| json_error(String::new(), err))?;
+        Ok(settings)
+    }
+    #[cfg(not(feature = "json"))]
+    {
+        let _ = text;
+        Err(MandybrotError::UnsupportedFormat("json".into()))
+    }
+}
+
+/// Error for invalid JSON, or for a value which does not match the settings at the given key.
+#[cfg(feature = "json")]
+fn json_error(key: String, err: serde_json::Error) -> MandybrotError {
+    if err.is_data() {
+        return MandybrotError::ConfigValue {
+            key,
+            message: err.to_string(),
+        };
+    }
+    // The message ends with the position, which is reported separately.
+    let position = format!(" at line {} column {}", err.line(), err.column());
+    let message = err.to_string();
+    MandybrotError::ConfigSyntax {
+        line: err.line(),
+        column: err.column(),
+        message: message
+            .strip_suffix(&position)
+            .unwrap_or(&message)
+            .to_string(),
+    }
+}
+
+/// Deserialises settings written as TOML, naming the offending line or key if they are invalid.
+pub(crate) fn from_toml_str<S: de::DeserializeOwned>(text: &str) -> Result<S, MandybrotError> {
+    #[cfg(feature = "toml")]
+    {
+        let deserializer = toml::Deserializer::parse(text).map_err(|err| {
+            // Line and column, counting from one, of the start of the invalid text.
+            let start = err.span().map_or(0, |span| span.start).min(text.len());
+            let before = text.get(..start).unwrap_or_default();
+            MandybrotError::ConfigSyntax {
+                line: before.matches('\n').count() + 1,
+                column: before
+                    .rsplit('\n')
+                    .next()
+                    .map_or(0, |line| line.chars().count())
+                    + 1,
+                message: err.message().to_string(),
+            }
+        })?;
+        serde_path_to_error::deserialize(deserializer).map_err(|err| MandybrotError::ConfigValue {
+            key: err.path().to_string(),
+            message: err.into_inner().message().to_string(),
+        })
+    }
+    #[cfg(not(feature = "toml"))]
+    {
+        let _ = text;
+        Err(MandybrotError::UnsupportedFormat("toml".into()))
+    }
+}
@@ -0,0 +1,182 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+
+/// A line of constant iteration count, in pixel coordinates with the origin at the top-left corner of the image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    /// Iteration count which the contour separates: pixels on one side have at least this count, and those on the other fewer.
+    pub level: u32,
+    pub points: Vec<[f64; 2]>,
+    /// Whether the last point joins back to the first, which is false only for contours which run off the edge of the image.
+    pub closed: bool,
+}
+
+impl Contour {
+    /// Contour with points removed by the Ramer-Douglas-Peucker algorithm, so that it deviates from the original by no more than `tolerance` pixels.
+    pub fn simplify(&self, tolerance: f64) -> Self {
+        if self.points.len() < 3 {
+            return self.clone();
+        }
+        let mut points = self.points.clone();
+        if self.closed {
+            points.push(points[0]);
+        }
+        let mut keep = vec![false; points.len()];
+        keep[0] = true;
+        keep[points.len() - 1] = true;
+        mark_simplified(&points, tolerance, &mut keep);
+        if self.closed {
+            points.pop();
+            keep.pop();
+        }
+
+        Self {
+            level: self.level,
+            points: points
+                .into_iter()
+                .zip(keep)
+                .filter_map(|(point, keep)| keep.then_some(point))
+                .collect(),
+            closed: self.closed,
+        }
+    }
+}
+
+/// Extracts the contours between pixels below and at or above each of the given iteration levels, by marching squares.
+/// Contours pass between pixel centres, interpolated linearly between their counts, and ambiguous saddle cells are resolved by the mean of their corners.
+pub fn contours(data: &Array2<u32>, levels: &[u32]) -> Vec<Contour> {
+    levels
+        .iter()
+        .flat_map(|&level| contours_at(data, level))
+        .collect()
+}
+
+/// Crossing of a contour with the edge between two neighbouring pixels, identified by the first pixel's row and column and whether the edge runs down from it.
+type Crossing = (usize, usize, bool);
+
+fn contours_at(data: &Array2<u32>, level: u32) -> Vec<Contour> {
+    let (rows, cols) = data.dim();
+    let iso = level as f64 - 0.5;
+    let inside = |row: usize, col: usize| data[[row, col]] >= level;
+
+    // Join crossings on the edges of each cell of four pixel centres.
+    let mut neighbours: HashMap<Crossing, Vec<Crossing>> = HashMap::new();
+    for row in 0..rows.saturating_sub(1) {
+        for col in 0..cols.saturating_sub(1) {
+            let top = (row, col, false);
+            let right = (row, col + 1, true);
+            let bottom = (row + 1, col, false);
+            let left = (row, col, true);
+            let case = (inside(row, col) as u8)
+                | (inside(row, col + 1) as u8) << 1
+                | (inside(row + 1, col + 1) as u8) << 2
+                | (inside(row + 1, col) as u8) << 3;
+            let segments: &[(Crossing, Crossing)] = match case {
+                0 | 15 => &[],
+                1 | 14 => &[(left, top)],
+                2 | 13 => &[(top, right)],
+                3 | 12 => &[(left, right)],
+                4 | 11 => &[(right, bottom)],
+                6 | 9 => &[(top, bottom)],
+                7 | 8 => &[(left, bottom)],
+                5 | 10 => {
+                    let mean = [
+                        (row, col),
+                        (row, col + 1),
+                        (row + 1, col),
+                        (row + 1, col + 1),
+                    ]
+                    .iter()
+                    .map(|&index| data[index] as f64)
+                    .sum::<f64>()
+                        / 4.0;
+                    // Separate the inside corners unless the centre of the cell joins them.
+                    if (mean >= iso) == (case == 5) {
+                        &[(left, bottom), (top, right)]
+                    } else {
+                        &[(left, top), (right, bottom)]
+                    }
+                }
+                _ => unreachable!(),
+            };
+            for &(a, b) in segments {
+                neighbours.entry(a).or_default().push(b);
+                neighbours.entry(b).or_default().push(a);
+            }
+        }
+    }
+
+    let position = |(row, col, down): Crossing| {
+        let (next_row, next_col) = if down { (row + 1, col) } else { (row, col + 1) };
+        let a = data[[row, col]] as f64;
+        let b = data[[next_row, next_col]] as f64;
+        let t = (iso - a) / (b - a);
+        [
+            col as f64 + 0.5 + t * (next_col - col) as f64,
+            row as f64 + 0.5 + t * (next_row - row) as f64,
+        ]
+    };
+
+    // Trace open contours from their ends at the edge of the image first, then the remaining closed loops.
+    let mut starts: Vec<Crossing> = neighbours
+        .iter()
+        .filter(|(_, next)| next.len() == 1)
+        .map(|(&crossing, _)| crossing)
+        .collect();
+    starts.sort_unstable();
+    let mut loops: Vec<Crossing> = neighbours.keys().copied().collect();
+    loops.sort_unstable();
+
+    let mut contours = Vec::new();
+    let starts = starts.into_iter().map(|start| (start, false));
+    for (start, closed) in starts.chain(loops.into_iter().map(|start| (start, true))) {
+        let mut points = Vec::new();
+        let mut current = Some(start);
+        while let Some(next) = current.and_then(|crossing| neighbours.remove(&crossing)) {
+            points.push(position(current.unwrap()));
+            current = next.into_iter().find(|next| neighbours.contains_key(next));
+        }
+        if points.is_empty() {
+            continue;
+        }
+        contours.push(Contour {
+            level,
+            points,
+            closed,
+        });
+    }
+    contours
+}
+
+/// Marks the points to keep between the first and last, recursively keeping the furthest from the chord until all are within `tolerance`.
+fn mark_simplified(points: &[[f64; 2]], tolerance: f64, keep: &mut [bool]) {
+    if points.len() < 3 {
+        return;
+    }
+    let [start, end] = [points[0], points[points.len() - 1]];
+    let (index, distance) = points[1..points.len() - 1]
+        .iter()
+        .map(|&point| distance_to_segment(point, start, end))
+        .enumerate()
+        .fold(
+            (0, 0.0),
+            |best, (i, d)| if d > best.1 { (i + 1, d) } else { best },
+        );
+    if distance > tolerance {
+        keep[index] = true;
+        mark_simplified(&points[..=index], tolerance, &mut keep[..=index]);
+        mark_simplified(&points[index..], tolerance, &mut keep[index..]);
+    }
+}
+
+fn distance_to_segment(point: [f64; 2], start: [f64; 2], end: [f64; 2]) -> f64 {
+    let [dx, dy] = [end[0] - start[0], end[1] - start[1]];
+    let length_sqr = dx * dx + dy * dy;
+    let t = if length_sqr > 0.0 {
+        (((point[0] - start[0]) * dx + (point[1] - start[1]) * dy) / length_sqr).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let [x, y] = [start[0] + t * dx - point[0], start[1] + t * dy - point[1]];
+    (x * x + y * y).sqrt()
+}
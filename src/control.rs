@@ -0,0 +1,243 @@
+use ndarray::Array2;
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How often paused renders and the idle control server check for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Shared state through which a running render can be monitored and steered from another thread.
+#[derive(Debug)]
+pub struct RenderControl {
+    completed: AtomicU64,
+    total: AtomicU64,
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+    finished: AtomicBool,
+    priority: AtomicU32,
+    image: Mutex<Array2<u32>>,
+}
+
+impl Default for RenderControl {
+    fn default() -> Self {
+        Self {
+            completed: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            paused: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            priority: AtomicU32::new(100),
+            image: Mutex::new(Array2::zeros((0, 0))),
+        }
+    }
+}
+
+impl RenderControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of completed and total work items (rows) of the render.
+    pub fn progress(&self) -> [u64; 2] {
+        [
+            self.completed.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        ]
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Stops the render at the next opportunity; the render then returns `None`.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Whether the render has completed or been cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// Percentage (1 to 100) of the worker threads the render may use.
+    pub fn priority(&self) -> u32 {
+        self.priority.load(Ordering::Relaxed)
+    }
+
+    pub fn set_priority(&self, percent: u32) {
+        self.priority
+            .store(percent.clamp(1, 100), Ordering::Relaxed);
+    }
+
+    /// Copy of the partially rendered image; unrendered pixels are zero.
+    pub fn snapshot(&self) -> Array2<u32> {
+        self.image.lock().unwrap().clone()
+    }
+
+    /// Resets the state for a new render of the given shape, clearing any pause or cancellation left by the last one.
+    pub(crate) fn start(&self, shape: (usize, usize)) {
+        self.completed.store(0, Ordering::Relaxed);
+        self.total.store(shape.0 as u64, Ordering::Relaxed);
+        self.paused.store(false, Ordering::Relaxed);
+        self.cancelled.store(false, Ordering::Relaxed);
+        self.finished.store(false, Ordering::Relaxed);
+        *self.image.lock().unwrap() = Array2::zeros(shape);
+    }
+
+    /// Blocks while the render is paused, returning false if it has been cancelled.
+    pub(crate) fn proceed(&self) -> bool {
+        while self.is_paused() && !self.is_cancelled() {
+            thread::sleep(POLL_INTERVAL);
+        }
+        !self.is_cancelled()
+    }
+
+    /// Number of rows to render concurrently at the current priority.
+    pub(crate) fn batch_size(&self) -> usize {
+        (rayon::current_num_threads() * self.priority() as usize / 100).max(1)
+    }
+
+    /// Stores finished rows, starting at row `y`.
+    pub(crate) fn write_rows(&self, y: usize, rows: &[Vec<u32>]) {
+        let mut image = self.image.lock().unwrap();
+        for (i, row) in rows.iter().enumerate() {
+            for (x, &value) in row.iter().enumerate() {
+                image[(y + i, x)] = value;
+            }
+        }
+        self.completed
+            .fetch_add(rows.len() as u64, Ordering::Relaxed);
+    }
+
+    /// Marks the render as finished, returning the image unless it was cancelled.
+    pub(crate) fn finish(&self) -> Option<Array2<u32>> {
+        self.finished.store(true, Ordering::Relaxed);
+        if self.is_cancelled() {
+            None
+        } else {
+            Some(self.snapshot())
+        }
+    }
+}
+
+/// Serves a line-based text protocol for controlling a render on a localhost TCP address, until the render finishes.
+///
+/// Commands are `progress`, `pause`, `resume`, `cancel`, `priority <percent>` and `snapshot`.
+/// Each is answered with a single line, except `snapshot` which replies `snapshot <width> <height>` followed by the pixels as little-endian `u32`s in row-major order.
+pub fn serve_control(
+    control: Arc<RenderControl>,
+    address: impl ToSocketAddrs,
+) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+
+    Ok(thread::spawn(move || {
+        while !control.is_finished() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let control = Arc::clone(&control);
+                    thread::spawn(move || {
+                        // A dropped connection only affects that client.
+                        let _ = handle_connection(&control, stream);
+                    });
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL)
+                }
+                Err(_) => break,
+            }
+        }
+    }))
+}
+
+fn handle_connection(control: &RenderControl, stream: TcpStream) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("progress"), None) => {
+                let [completed, total] = control.progress();
+                writeln!(writer, "{}/{}", completed, total)?;
+            }
+            (Some("pause"), None) => {
+                control.pause();
+                writeln!(writer, "ok")?;
+            }
+            (Some("resume"), None) => {
+                control.resume();
+                writeln!(writer, "ok")?;
+            }
+            (Some("cancel"), None) => {
+                control.cancel();
+                writeln!(writer, "ok")?;
+            }
+            (Some("priority"), Some(percent)) => match percent.parse() {
+                Ok(percent) => {
+                    control.set_priority(percent);
+                    writeln!(writer, "ok")?;
+                }
+                Err(_) => writeln!(writer, "error: invalid priority '{}'", percent)?,
+            },
+            (Some("snapshot"), None) => {
+                let image = control.snapshot();
+                let (height, width) = image.dim();
+                writeln!(writer, "snapshot {} {}", width, height)?;
+                let bytes: Vec<u8> = image.iter().flat_map(|v| v.to_le_bytes()).collect();
+                writer.write_all(&bytes)?;
+            }
+            _ => writeln!(writer, "error: unknown command '{}'", line.trim())?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render_fractal_controlled, Complex, Fractal, SamplePattern, Viewport};
+
+    #[test]
+    fn controls_can_be_reused_after_a_cancelled_render() {
+        let viewport = Viewport::new(Complex::new(-0.75, 0.0), 2.5, [16, 8]);
+        let render = |control: &RenderControl| {
+            render_fractal_controlled(
+                &viewport,
+                &Fractal::Mandelbrot,
+                32,
+                1,
+                SamplePattern::Grid,
+                None,
+                control,
+            )
+            .unwrap()
+        };
+
+        let control = RenderControl::new();
+        control.cancel();
+        control.pause();
+        assert!(render(&control).is_some());
+        assert!(!control.is_cancelled() && !control.is_paused());
+        assert_eq!(control.progress(), [8, 8]);
+    }
+}
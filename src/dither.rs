@@ -0,0 +1,195 @@
+use ndarray::Array2;
+use palette::LinSrgba;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::{
+    error::{check_finite, check_non_negative},
+    rng_strategy::BLUE_NOISE_DOMAIN,
+    ColourEncoding, ColourSpace, MandybrotError, RngStrategy,
+};
+
+/// Side of the tiled blue-noise threshold mask, in pixels.
+const BLUE_NOISE_SIZE: usize = 64;
+
+/// Seed of the blue-noise mask, fixed so that dithered images are reproducible.
+const BLUE_NOISE_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Standard deviation, in pixels, of the Gaussian which measures how clustered the mask's pixels are.
+const BLUE_NOISE_SIGMA: f64 = 1.5;
+
+/// Threshold noise added to colours before they are rounded to a few levels, such as the 256 of each channel of an 8-bit image.
+/// Smooth gradients otherwise round into visible bands, which the noise breaks up into fine grain.
+/// The `strength` scales the noise, with one spreading each colour over exactly the two nearest levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Dither {
+    /// Plain rounding to the nearest level.
+    #[default]
+    None,
+    /// An 8 × 8 Bayer matrix: a regular cross-hatch, which compresses well.
+    Ordered {
+        #[serde(default = "unit")]
+        strength: f32,
+    },
+    /// A tiled blue-noise mask: grain without any visible structure.
+    BlueNoise {
+        #[serde(default = "unit")]
+        strength: f32,
+    },
+}
+
+fn unit() -> f32 {
+    1.0
+}
+
+impl Dither {
+    /// Checks that the strength is finite and non-negative.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            Dither::None => Ok(()),
+            Dither::Ordered { strength } | Dither::BlueNoise { strength } => {
+                check_finite("strength", strength)?;
+                check_non_negative("strength", strength)
+            }
+        }
+    }
+
+    /// Noise at pixel `(x, y)`, in the range `[-strength / 2, strength / 2)` levels.
+    pub fn threshold(&self, [x, y]: [usize; 2]) -> f32 {
+        match *self {
+            Dither::None => 0.0,
+            Dither::Ordered { strength } => {
+                let rank = BAYER[y % 8][x % 8] as f32;
+                strength * ((rank + 0.5) / 64.0 - 0.5)
+            }
+            Dither::BlueNoise { strength } => {
+                let size = BLUE_NOISE_SIZE;
+                let rank = blue_noise_mask()[(y % size) * size + x % size] as f32;
+                strength * ((rank + 0.5) / (size * size) as f32 - 0.5)
+            }
+        }
+    }
+
+    /// Encodes a colour as 8-bit sRGB with alpha, dithered for pixel `(x, y)`.
+    pub fn encode(&self, colour: LinSrgba, pixel: [usize; 2]) -> [u8; 4] {
+        self.encode_as(colour, pixel, ColourEncoding::Srgb)
+    }
+
+    /// Encodes a colour as 8 bits per channel with alpha in the given `encoding`, dithered for pixel `(x, y)`.
+    pub fn encode_as(
+        &self,
+        colour: LinSrgba,
+        pixel: [usize; 2],
+        encoding: ColourEncoding,
+    ) -> [u8; 4] {
+        let offset = self.threshold(pixel);
+        encoding
+            .encode(colour)
+            .map(|channel| (channel * 255.0 + offset).round().clamp(0.0, 255.0) as u8)
+    }
+
+    /// Encodes an image as 8-bit sRGB with alpha, such as to be written to a PNG.
+    pub fn encode_image<C: ColourSpace>(&self, image: &Array2<C>) -> Array2<[u8; 4]> {
+        Array2::from_shape_fn(image.dim(), |(y, x)| {
+            self.encode(image[[y, x]].into_linear(), [x, y])
+        })
+    }
+}
+
+/// Order in which the cells of an 8 × 8 block are switched on by ordered dithering.
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Ranks of the pixels of the blue-noise mask, generated on first use.
+fn blue_noise_mask() -> &'static [u32] {
+    static MASK: OnceLock<Vec<u32>> = OnceLock::new();
+    MASK.get_or_init(void_and_cluster)
+}
+
+/// Ranks every pixel of a tileable mask by Ulichney's void-and-cluster method, so that the pixels of any rank or below are spread evenly.
+/// Starting from a random tenth of the pixels settled into an even pattern, ranks below it are given by removing the tightest cluster in turn, and ranks above by filling the largest void.
+fn void_and_cluster() -> Vec<u32> {
+    let size = BLUE_NOISE_SIZE;
+    let count = size * size;
+
+    // Gaussian weights by wrapped offset, so the mask tiles without seams
+    let kernel: Vec<f64> = (0..count)
+        .map(|i| {
+            let wrap = |d: usize| d.min(size - d) as f64;
+            let (dx, dy) = (wrap(i % size), wrap(i / size));
+            (-(dx * dx + dy * dy) / (2.0 * BLUE_NOISE_SIGMA * BLUE_NOISE_SIGMA)).exp()
+        })
+        .collect();
+    let toggle = |energy: &mut [f64], index: usize, sign: f64| {
+        let (x, y) = (index % size, index / size);
+        for (j, e) in energy.iter_mut().enumerate() {
+            let dx = (j % size + size - x) % size;
+            let dy = (j / size + size - y) % size;
+            *e += sign * kernel[dy * size + dx];
+        }
+    };
+    // The tightest cluster is the most crowded pixel which is on, and the largest void the emptiest which is off
+    let tightest_cluster = |energy: &[f64], on: &[bool]| {
+        (0..count)
+            .filter(|&i| on[i])
+            .max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+    let largest_void = |energy: &[f64], on: &[bool]| {
+        (0..count)
+            .filter(|&i| !on[i])
+            .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+
+    let mut rng = RngStrategy::Seeded(BLUE_NOISE_SEED).stream(BLUE_NOISE_DOMAIN, 0);
+    let mut on = vec![false; count];
+    let mut energy = vec![0.0; count];
+    let initial = count / 10;
+    while on.iter().filter(|&&pixel| pixel).count() < initial {
+        let index = rng.random_range(0..count);
+        if !on[index] {
+            on[index] = true;
+            toggle(&mut energy, index, 1.0);
+        }
+    }
+
+    // Move the tightest cluster into the largest void until it is already the largest void
+    loop {
+        let cluster = tightest_cluster(&energy, &on);
+        on[cluster] = false;
+        toggle(&mut energy, cluster, -1.0);
+        let void = largest_void(&energy, &on);
+        on[void] = true;
+        toggle(&mut energy, void, 1.0);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0u32; count];
+    let (initial_on, initial_energy) = (on.clone(), energy.clone());
+    for rank in (0..initial).rev() {
+        let cluster = tightest_cluster(&energy, &on);
+        on[cluster] = false;
+        toggle(&mut energy, cluster, -1.0);
+        ranks[cluster] = rank as u32;
+    }
+    let (mut on, mut energy) = (initial_on, initial_energy);
+    for rank in initial..count {
+        let void = largest_void(&energy, &on);
+        on[void] = true;
+        toggle(&mut energy, void, 1.0);
+        ranks[void] = rank as u32;
+    }
+    ranks
+}
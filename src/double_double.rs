@@ -0,0 +1,584 @@
+use num_traits::{Float, Num, NumCast, One, ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    num::FpCategory,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+    str::FromStr,
+};
+
+/// Extended precision number stored as the unevaluated sum of two `f64`s, giving about 32 significant digits.
+/// Implements `Float`, so it can be used as the precision of any fractal to zoom to around `1e-30`.
+/// Arithmetic, rounding and square roots are accurate to the full precision, while transcendental functions are only accurate to `f64` precision.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    pub const fn new(hi: f64, lo: f64) -> Self {
+        Self { hi, lo }
+    }
+
+    /// Exact value of an `f64`.
+    pub const fn from_f64(value: f64) -> Self {
+        Self::new(value, 0.0)
+    }
+
+    /// Nearest `f64` to the value.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        Self::from_f64(value)
+    }
+}
+
+/// Exact sum of two `f64`s, as the rounded sum and its error.
+#[inline(always)]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let v = s - a;
+    (s, (a - (s - v)) + (b - v))
+}
+
+/// Exact sum of two `f64`s where `|a| >= |b|`.
+#[inline(always)]
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// Exact product of two `f64`s, as the rounded product and its error.
+#[inline(always)]
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+/// Addition
+impl Add for DoubleDouble {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        let (s, e) = two_sum(self.hi, rhs.hi);
+        let (t, f) = two_sum(self.lo, rhs.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        let (hi, lo) = quick_two_sum(s, e + f);
+        Self::new(hi, lo)
+    }
+}
+
+/// Negation
+impl Neg for DoubleDouble {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self::new(-self.hi, -self.lo)
+    }
+}
+
+/// Subtraction
+impl Sub for DoubleDouble {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+/// Multiplication
+impl Mul for DoubleDouble {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (p, e) = two_prod(self.hi, rhs.hi);
+        let e = e + (self.hi * rhs.lo + self.lo * rhs.hi);
+        let (hi, lo) = quick_two_sum(p, e);
+        Self::new(hi, lo)
+    }
+}
+
+/// Division
+impl Div for DoubleDouble {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        // Long division, refining the quotient one `f64` at a time.
+        let q1 = self.hi / rhs.hi;
+        let r = self - rhs * Self::from_f64(q1);
+        let q2 = r.hi / rhs.hi;
+        let r = r - rhs * Self::from_f64(q2);
+        let q3 = r.hi / rhs.hi;
+        let (hi, lo) = quick_two_sum(q1, q2);
+        Self::new(hi, lo) + Self::from_f64(q3)
+    }
+}
+
+/// Remainder
+impl Rem for DoubleDouble {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self::Output {
+        self - (self / rhs).trunc() * rhs
+    }
+}
+
+impl Zero for DoubleDouble {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.hi == 0.0
+    }
+}
+
+impl One for DoubleDouble {
+    fn one() -> Self {
+        Self::from_f64(1.0)
+    }
+}
+
+impl Num for DoubleDouble {
+    type FromStrRadixErr = ParseDoubleDoubleError;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        if radix != 10 {
+            return Err(ParseDoubleDoubleError);
+        }
+        s.parse()
+    }
+}
+
+impl ToPrimitive for DoubleDouble {
+    fn to_i64(&self) -> Option<i64> {
+        let t = self.trunc();
+        t.hi.to_i64()?.checked_add(t.lo.to_i64()?)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        let t = self.trunc();
+        t.hi.to_u64()?.checked_add_signed(t.lo.to_i64()?)
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.hi + self.lo)
+    }
+}
+
+impl NumCast for DoubleDouble {
+    fn from<N: ToPrimitive>(n: N) -> Option<Self> {
+        let value = n.to_f64()?;
+        // Integers wider than an `f64` mantissa are split so that no bits are lost.
+        if value.abs() >= 9_007_199_254_740_992.0 {
+            if let Some(i) = n.to_i64() {
+                return Some(
+                    Self::from_f64(value) + Self::from_f64(i.wrapping_sub(value as i64) as f64),
+                );
+            }
+            if let Some(u) = n.to_u64() {
+                return Some(
+                    Self::from_f64(value)
+                        + Self::from_f64(u.wrapping_sub(value as u64) as i64 as f64),
+                );
+            }
+        }
+        Some(Self::from_f64(value))
+    }
+}
+
+/// Functions which are only evaluated to `f64` precision.
+macro_rules! via_f64 {
+    ($($name:ident),*) => {
+        $(
+            fn $name(self) -> Self {
+                Self::from_f64(self.to_f64().$name())
+            }
+        )*
+    };
+}
+
+impl Float for DoubleDouble {
+    fn nan() -> Self {
+        Self::from_f64(f64::NAN)
+    }
+
+    fn infinity() -> Self {
+        Self::from_f64(f64::INFINITY)
+    }
+
+    fn neg_infinity() -> Self {
+        Self::from_f64(f64::NEG_INFINITY)
+    }
+
+    fn neg_zero() -> Self {
+        Self::from_f64(-0.0)
+    }
+
+    fn min_value() -> Self {
+        Self::from_f64(f64::MIN)
+    }
+
+    fn min_positive_value() -> Self {
+        Self::from_f64(f64::MIN_POSITIVE)
+    }
+
+    fn epsilon() -> Self {
+        // 2^-104, half an ulp of the 106-bit mantissa.
+        Self::from_f64(4.930_380_657_631_324e-32)
+    }
+
+    fn max_value() -> Self {
+        Self::from_f64(f64::MAX)
+    }
+
+    fn is_nan(self) -> bool {
+        self.hi.is_nan()
+    }
+
+    fn is_infinite(self) -> bool {
+        self.hi.is_infinite()
+    }
+
+    fn is_finite(self) -> bool {
+        self.hi.is_finite()
+    }
+
+    fn is_normal(self) -> bool {
+        self.hi.is_normal()
+    }
+
+    fn classify(self) -> FpCategory {
+        self.hi.classify()
+    }
+
+    fn floor(self) -> Self {
+        let hi = self.hi.floor();
+        if hi == self.hi {
+            let (hi, lo) = quick_two_sum(hi, self.lo.floor());
+            Self::new(hi, lo)
+        } else {
+            Self::from_f64(hi)
+        }
+    }
+
+    fn ceil(self) -> Self {
+        let hi = self.hi.ceil();
+        if hi == self.hi {
+            let (hi, lo) = quick_two_sum(hi, self.lo.ceil());
+            Self::new(hi, lo)
+        } else {
+            Self::from_f64(hi)
+        }
+    }
+
+    fn round(self) -> Self {
+        let half = Self::from_f64(0.5);
+        if self.is_sign_negative() {
+            -(-self + half).floor()
+        } else {
+            (self + half).floor()
+        }
+    }
+
+    fn trunc(self) -> Self {
+        if self.is_sign_negative() {
+            self.ceil()
+        } else {
+            self.floor()
+        }
+    }
+
+    fn fract(self) -> Self {
+        self - self.trunc()
+    }
+
+    fn abs(self) -> Self {
+        if self.is_sign_negative() {
+            -self
+        } else {
+            self
+        }
+    }
+
+    fn signum(self) -> Self {
+        Self::from_f64(self.hi.signum())
+    }
+
+    fn is_sign_positive(self) -> bool {
+        self.hi.is_sign_positive()
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.hi.is_sign_negative()
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn recip(self) -> Self {
+        Self::one() / self
+    }
+
+    fn powi(self, n: i32) -> Self {
+        let mut base = self;
+        let mut result = Self::one();
+        let mut exponent = n.unsigned_abs();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        if n < 0 {
+            result.recip()
+        } else {
+            result
+        }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        match n.to_i32() {
+            Some(i) if Self::from_f64(i as f64) == n => self.powi(i),
+            _ => Self::from_f64(self.to_f64().powf(n.to_f64())),
+        }
+    }
+
+    fn sqrt(self) -> Self {
+        if self.hi <= 0.0 || !self.hi.is_finite() {
+            return Self::from_f64(self.hi.sqrt());
+        }
+        // One Newton step from the `f64` root doubles the number of correct digits.
+        let root = Self::from_f64(self.hi.sqrt());
+        root + (self - root * root) * Self::from_f64(0.5 / root.hi)
+    }
+
+    via_f64!(
+        exp, exp2, ln, log2, log10, cbrt, sin, cos, tan, asin, acos, atan, exp_m1, ln_1p, sinh,
+        cosh, tanh, asinh, acosh, atanh
+    );
+
+    fn log(self, base: Self) -> Self {
+        Self::from_f64(self.to_f64().log(base.to_f64()))
+    }
+
+    fn max(self, other: Self) -> Self {
+        if self.is_nan() || other > self {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if self.is_nan() || other < self {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn abs_sub(self, other: Self) -> Self {
+        (self - other).max(Self::zero())
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        (self * self + other * other).sqrt()
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        Self::from_f64(self.to_f64().atan2(other.to_f64()))
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        (self.sin(), self.cos())
+    }
+
+    fn integer_decode(self) -> (u64, i16, i8) {
+        self.hi.integer_decode()
+    }
+}
+
+/// Formats the value in scientific notation to its full precision of 32 significant digits.
+impl fmt::Display for DoubleDouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.hi.is_finite() || self.hi == 0.0 {
+            return write!(f, "{}", self.hi);
+        }
+        if self.is_sign_negative() {
+            write!(f, "-")?;
+        }
+
+        // Scale the magnitude into [1, 10), then read off one digit at a time.
+        let ten = Self::from_f64(10.0);
+        let mut exponent = self.hi.abs().log10().floor() as i32;
+        let mut x = self.abs() / ten.powi(exponent);
+        if x.hi >= 10.0 {
+            x = x / ten;
+            exponent += 1;
+        } else if x.hi < 1.0 {
+            x = x * ten;
+            exponent -= 1;
+        }
+
+        for n in 0..32 {
+            let digit = x.floor().hi.clamp(0.0, 9.0);
+            write!(f, "{}", digit as u8)?;
+            if n == 0 {
+                write!(f, ".")?;
+            }
+            x = (x - Self::from_f64(digit)) * ten;
+        }
+        write!(f, "e{}", exponent)
+    }
+}
+
+/// Error returned when a string is not a decimal number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDoubleDoubleError;
+
+impl fmt::Display for ParseDoubleDoubleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid decimal number")
+    }
+}
+
+impl std::error::Error for ParseDoubleDoubleError {}
+
+/// Largest power of ten applied at once when parsing, well within the range of an `f64`.
+const MAX_SCALE_STEP: i32 = 256;
+
+/// Parses a decimal number such as `-0.743643887037158704752191506114774` or `1.5e-20` to full precision.
+/// Numbers whose magnitude is outside the normal range of an `f64` are rejected, rather than rounded to zero or infinity.
+impl FromStr for DoubleDouble {
+    type Err = ParseDoubleDoubleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(index) => (
+                &s[..index],
+                s[index + 1..]
+                    .parse::<i32>()
+                    .map_err(|_| ParseDoubleDoubleError)?,
+            ),
+            None => (s, 0),
+        };
+        let (negative, mantissa) = match mantissa.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+
+        let ten = Self::from_f64(10.0);
+        let mut value = Self::default();
+        let mut num_digits = 0;
+        let mut decimal_places: i32 = 0;
+        let mut seen_point = false;
+        for ch in mantissa.chars() {
+            match ch {
+                '.' if !seen_point => seen_point = true,
+                '0'..='9' => {
+                    value = value * ten + Self::from_f64((ch as u8 - b'0') as f64);
+                    num_digits += 1;
+                    if seen_point {
+                        decimal_places = decimal_places
+                            .checked_add(1)
+                            .ok_or(ParseDoubleDoubleError)?;
+                    }
+                }
+                _ => return Err(ParseDoubleDoubleError),
+            }
+        }
+        if num_digits == 0 {
+            return Err(ParseDoubleDoubleError);
+        }
+
+        if value.hi == 0.0 {
+            return Ok(if negative { -value } else { value });
+        }
+        if !value.hi.is_finite() {
+            return Err(ParseDoubleDoubleError);
+        }
+
+        // Values beyond the normal range of an `f64` are rejected before scaling, so huge exponents fail quickly.
+        let shift = exponent
+            .checked_sub(decimal_places)
+            .ok_or(ParseDoubleDoubleError)?;
+        let magnitude = value.hi.log10().floor() as i64 + shift as i64;
+        if !(f64::MIN_10_EXP as i64 - 1..=f64::MAX_10_EXP as i64).contains(&magnitude) {
+            return Err(ParseDoubleDoubleError);
+        }
+
+        // Scaled in steps whose powers of ten stay finite, each by exponentiation by squaring.
+        let mut remaining = shift;
+        while remaining != 0 {
+            let step = remaining.clamp(-MAX_SCALE_STEP, MAX_SCALE_STEP);
+            let scale = ten.powi(step.abs());
+            value = if step < 0 {
+                value / scale
+            } else {
+                value * scale
+            };
+            remaining -= step;
+        }
+        if !(f64::MIN_POSITIVE..=f64::MAX).contains(&value.hi.abs()) {
+            return Err(ParseDoubleDoubleError);
+        }
+
+        Ok(if negative { -value } else { value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_to_full_precision() {
+        let third: DoubleDouble = format!("0.{}", "3".repeat(32)).parse().unwrap();
+        let error = DoubleDouble::from_f64(1.0) - third * DoubleDouble::from_f64(3.0);
+        // An `f64` third is out by about 1e-17
+        assert!(error.to_f64().abs() < 1e-31);
+
+        let small: DoubleDouble = "1.5e-20".parse().unwrap();
+        assert_eq!(small.hi, 1.5e-20);
+        let tiny: DoubleDouble = "0.000000000000000000000000000001".parse().unwrap();
+        assert_eq!(tiny.hi, 1e-30);
+        assert_eq!("0e999999".parse::<DoubleDouble>().unwrap().hi, 0.0);
+    }
+
+    #[test]
+    fn scales_beyond_a_single_power_of_ten() {
+        let digits = format!("0.{}1", "0".repeat(299));
+        assert_eq!(digits.parse::<DoubleDouble>().unwrap().hi, 1e-300);
+        assert_eq!("1e300".parse::<DoubleDouble>().unwrap().hi, 1e300);
+        assert_eq!(
+            "12345e-300".parse::<DoubleDouble>().unwrap().hi,
+            1.2345e-296
+        );
+    }
+
+    #[test]
+    fn rejects_numbers_out_of_range() {
+        for text in [
+            "1e309",
+            "1e-320",
+            "1e-300000000",
+            "1e300000000",
+            "1.5e-2147483648",
+            "1.5e2147483647",
+            "abc",
+            "",
+            "1e",
+        ] {
+            assert_eq!(
+                text.parse::<DoubleDouble>(),
+                Err(ParseDoubleDoubleError),
+                "{}",
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use core::ops::{Add, Div, Mul, Neg, Sub};
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::Complex;
+
+/// Complex number carrying its derivative with respect to a pixel's position.
+/// Any formula built from these operations differentiates itself as it is evaluated, without its derivative being written out by hand.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct DualComplex<T> {
+    pub value: Complex<T>,
+    pub derivative: Complex<T>,
+}
+
+impl<T> DualComplex<T> {
+    pub const fn new(value: Complex<T>, derivative: Complex<T>) -> Self {
+        Self { value, derivative }
+    }
+
+    /// Applies a function to every part, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> DualComplex<U> {
+        DualComplex::new(self.value.map(&f), self.derivative.map(&f))
+    }
+}
+
+impl<T: Float> DualComplex<T> {
+    /// A value which does not depend on the pixel's position.
+    pub fn constant(value: Complex<T>) -> Self {
+        Self::new(value, Complex::new(T::zero(), T::zero()))
+    }
+
+    /// The pixel's position itself, whose derivative is one.
+    pub fn variable(value: Complex<T>) -> Self {
+        Self::new(value, Complex::new(T::one(), T::zero()))
+    }
+
+    /// Complex conjugate, carrying the conjugate of the derivative.
+    /// Conjugation has no complex derivative, so derivatives of anti-holomorphic maps such as the Tricorn's are only approximate, though still good enough to estimate distances.
+    pub fn conj(self) -> Self {
+        Self::new(
+            Complex::new(self.value.real, -self.value.imag),
+            Complex::new(self.derivative.real, -self.derivative.imag),
+        )
+    }
+
+    pub fn powi(self, n: u32) -> Self {
+        if n == 0 {
+            return Self::constant(Complex::new(T::one(), T::zero()));
+        }
+        let n_real = Complex::new(T::from(n).unwrap(), T::zero());
+        Self::new(
+            self.value.powi(n),
+            n_real * self.value.powi(n - 1) * self.derivative,
+        )
+    }
+
+    /// Real power, taking the principal branch.
+    pub fn powf(self, n: T) -> Self {
+        let n_complex = Complex::new(n, T::zero());
+        Self::new(
+            self.value.powf(n),
+            n_complex * self.value.powf(n - T::one()) * self.derivative,
+        )
+    }
+
+    /// Complex power, taking the principal branch.
+    pub fn powc(self, n: Complex<T>) -> Self {
+        let one = Complex::new(T::one(), T::zero());
+        Self::new(
+            self.value.powc(n),
+            n * self.value.powc(n - one) * self.derivative,
+        )
+    }
+
+    pub fn cos(self) -> Self {
+        Self::new(self.value.cos(), -(self.value.sin() * self.derivative))
+    }
+
+    pub fn sin(self) -> Self {
+        Self::new(self.value.sin(), self.value.cos() * self.derivative)
+    }
+
+    /// Principal square root
+    pub fn sqrt(self) -> Self {
+        let root = self.value.sqrt();
+        let two = Complex::new(T::from(2.0).unwrap(), T::zero());
+        Self::new(root, self.derivative / (two * root))
+    }
+
+    // Reciprocal/inverse
+    pub fn inv(self) -> Self {
+        let inv = self.value.inv();
+        Self::new(inv, -(inv * inv * self.derivative))
+    }
+
+    /// Estimated distance from the pixel to the boundary of the set, once the orbit has escaped far beyond it.
+    /// Measured in the units of the plane the derivative was taken over, and only meaningful for holomorphic maps.
+    pub fn distance_estimate(&self) -> T {
+        let r = self.value.abs();
+        r * r.ln() / self.derivative.abs()
+    }
+
+    /// Unit direction in which the escape potential grows fastest at the pixel, pointing away from the set.
+    pub fn normal(&self) -> Complex<T> {
+        let u = self.value / self.derivative;
+        u / u.abs()
+    }
+}
+
+impl<T: Copy + Neg<Output = T>> Neg for DualComplex<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.value, -self.derivative)
+    }
+}
+
+impl<T: Copy + Add<Output = T>> Add for DualComplex<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.value + other.value, self.derivative + other.derivative)
+    }
+}
+
+impl<T: Copy + Sub<Output = T>> Sub for DualComplex<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.value - other.value, self.derivative - other.derivative)
+    }
+}
+
+/// Product rule
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Mul for DualComplex<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.value * other.value,
+            self.derivative * other.value + self.value * other.derivative,
+        )
+    }
+}
+
+/// Quotient rule
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>> Div
+    for DualComplex<T>
+{
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Self::new(
+            self.value / other.value,
+            (self.derivative * other.value - self.value * other.derivative)
+                / (other.value * other.value),
+        )
+    }
+}
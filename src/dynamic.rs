@@ -0,0 +1,97 @@
+use ndarray::Array2;
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::{
+    render_fractal, Complex, DoubleDouble, Fractal, MandybrotError, Periodicity, SamplePattern,
+};
+
+/// Floating point type an escape-time fractal is iterated in, chosen at runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FloatPrecision {
+    /// `f32`, the fastest, which resolves pixels down to about `1e-5`.
+    Single,
+    /// `f64`, which resolves pixels down to about `1e-13`.
+    #[default]
+    Double,
+    /// `DoubleDouble`, which resolves pixels down to about `1e-30` at many times the cost.
+    DoubleDouble,
+}
+
+/// Coordinate of the image centre, given either as a number or as text which keeps every digit of a deep zoom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Coordinate {
+    Number(f64),
+    Text(String),
+}
+
+impl Coordinate {
+    /// Value of the coordinate in the given precision.
+    fn parse<T: Float + FromStr>(&self, name: &'static str) -> Result<T, MandybrotError> {
+        match self {
+            Coordinate::Number(value) => T::from(*value),
+            Coordinate::Text(text) => text.parse().ok(),
+        }
+        .ok_or(MandybrotError::UnparsableNumber(name))
+    }
+}
+
+impl From<f64> for Coordinate {
+    fn from(value: f64) -> Self {
+        Coordinate::Number(value)
+    }
+}
+
+/// Settings of an escape-time render whose precision is chosen at runtime, such as from a configuration file.
+/// Every real parameter other than the centre is given as an `f64` and converted to the chosen precision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParametersDyn {
+    #[serde(default)]
+    pub precision: FloatPrecision,
+    pub centre: [Coordinate; 2],
+    pub scale: f64,
+    pub resolution: [u32; 2],
+    pub max_iter: u32,
+    pub fractal: Fractal<f64>,
+    #[serde(default = "default_samples_per_pixel")]
+    pub samples_per_pixel: u32,
+    #[serde(default)]
+    pub sample_pattern: SamplePattern,
+    #[serde(default)]
+    pub periodicity: Option<Periodicity<f64>>,
+}
+
+fn default_samples_per_pixel() -> u32 {
+    1
+}
+
+/// Renders an escape-time fractal with `render_fractal`, iterated in the precision chosen by the parameters.
+pub fn render_fractal_dyn(params: &ParametersDyn) -> Result<Array2<u32>, MandybrotError> {
+    match params.precision {
+        FloatPrecision::Single => render_in::<f32>(params),
+        FloatPrecision::Double => render_in::<f64>(params),
+        FloatPrecision::DoubleDouble => render_in::<DoubleDouble>(params),
+    }
+}
+
+/// Converts the parameters to the given precision and renders them.
+fn render_in<T>(params: &ParametersDyn) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Float + FromStr + Send + Sync,
+{
+    let cast = |value: f64| T::from(value).unwrap_or_else(T::nan);
+    let [real, imag] = &params.centre;
+    let centre = Complex::new(real.parse("centre.real")?, imag.parse("centre.imag")?);
+    render_fractal(
+        centre,
+        params.max_iter,
+        cast(params.scale),
+        params.resolution,
+        params.fractal.map(cast),
+        params.samples_per_pixel,
+        params.sample_pattern,
+        params.periodicity.map(|periodicity| periodicity.map(cast)),
+    )
+}
@@ -0,0 +1,71 @@
+use ndarray::Array2;
+use palette::{LinSrgba, Srgba};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "png")]
+use std::{fs::File, io::BufWriter, path::Path};
+
+use crate::ColourSpace;
+#[cfg(feature = "png")]
+use crate::{Dither, MandybrotError};
+
+/// Transfer function by which colours, blended and averaged in linear light, are encoded as they are saved.
+/// Image files and viewers expect sRGB, so writing linear values directly makes images look too dark and saturated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColourEncoding {
+    /// The sRGB transfer function, matching other renderers and how images are displayed.
+    #[default]
+    Srgb,
+    /// Linear light unchanged, for tools which do their own colour management.
+    Linear,
+}
+
+impl ColourEncoding {
+    /// Red, green, blue and alpha channels of a colour in this encoding, each in `[0, 1]` for colours within gamut.
+    pub fn encode(&self, colour: LinSrgba) -> [f32; 4] {
+        let (red, green, blue, alpha) = match self {
+            ColourEncoding::Srgb => Srgba::from_linear(colour).into_components(),
+            ColourEncoding::Linear => colour.into_components(),
+        };
+        [red, green, blue, alpha]
+    }
+
+    /// Encodes every pixel of an image, such as before handing it to an image library which writes its channels unchanged.
+    pub fn encode_image<C: ColourSpace>(&self, image: &Array2<C>) -> Array2<[f32; 4]> {
+        image.map(|&colour| self.encode(colour.into_linear()))
+    }
+}
+
+/// Saves an image as an 8-bit RGBA PNG, in the given `encoding` and rounded with the given `dither`.
+/// The file is tagged with its encoding, so colour-managed viewers display linear images correctly too.
+#[cfg(feature = "png")]
+pub fn save_png<C: ColourSpace>(
+    image: &Array2<C>,
+    path: impl AsRef<Path>,
+    dither: Dither,
+    encoding: ColourEncoding,
+) -> Result<(), MandybrotError> {
+    dither.validate()?;
+    let (height, width) = image.dim();
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        width as u32,
+        height as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    match encoding {
+        ColourEncoding::Srgb => encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual),
+        ColourEncoding::Linear => encoder.set_source_gamma(png::ScaledFloat::new(1.0)),
+    }
+
+    let bytes: Vec<u8> = image
+        .indexed_iter()
+        .flat_map(|((y, x), &colour)| dither.encode_as(colour.into_linear(), [x, y], encoding))
+        .collect();
+    let mut writer = encoder.write_header().map_err(std::io::Error::from)?;
+    writer
+        .write_image_data(&bytes)
+        .map_err(std::io::Error::from)?;
+    writer.finish().map_err(std::io::Error::from)?;
+    Ok(())
+}
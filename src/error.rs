@@ -0,0 +1,278 @@
+use core::{error::Error, fmt};
+#[cfg(feature = "std")]
+use indicatif::style::TemplateError;
+use num_traits::Float;
+#[cfg(feature = "std")]
+use rayon::ThreadPoolBuildError;
+
+/// Errors returned when a render is requested with invalid settings.
+#[derive(Debug)]
+pub enum MandybrotError {
+    /// The requested resolution has no pixels.
+    EmptyResolution([u32; 2]),
+    /// A count which must be at least one, such as a number of samples, was zero.
+    ZeroCount(&'static str),
+    /// A parameter was not finite or outside of its valid range.
+    InvalidParameter { name: &'static str, value: f64 },
+    /// Both image axes of a slice select the same coordinate.
+    DuplicateAxis(&'static str),
+    /// A named parameter does not exist for the chosen attractor.
+    UnknownParameter(&'static str),
+    /// A number given as text could not be parsed.
+    UnparsableNumber(&'static str),
+    /// The fractal's map has no complex derivative to estimate distances or normals from.
+    NoDerivative,
+    /// The fractal's orbits cannot be started from an arbitrary seed and stopped on escape, as a seeded render needs.
+    NotSeedable,
+    /// A post-processing step which acts on values came after the colour map.
+    PipelineOrder(&'static str),
+    /// An output buffer does not hold exactly one value per pixel.
+    BufferSize { expected: usize, found: usize },
+    /// A fixed-size tile does not have the resolution of its viewport.
+    TileShape { expected: [u32; 2], found: [u32; 2] },
+    /// A frame of an animation does not have the resolution of the frames before it.
+    #[cfg(feature = "std")]
+    FrameResolution {
+        expected: [usize; 2],
+        found: [usize; 2],
+    },
+    /// A parameters file was written for a newer format than this version of the crate reads.
+    UnsupportedVersion(u32),
+    /// No built-in system has the given name.
+    #[cfg(feature = "std")]
+    UnknownPreset(String),
+    /// A parameters file is not valid JSON or TOML.
+    #[cfg(feature = "std")]
+    ConfigSyntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A setting in a parameters file is missing, misspelt or of the wrong type.
+    #[cfg(feature = "std")]
+    ConfigValue { key: String, message: String },
+    /// A gradient file is not valid in its format.
+    #[cfg(feature = "std")]
+    GradientSyntax { line: usize, message: String },
+    /// A file's extension is not one of the formats which can be read.
+    #[cfg(feature = "std")]
+    UnsupportedFormat(String),
+    /// A shared state token was cut short, mistyped or not written by `encode_state`.
+    #[cfg(feature = "std")]
+    InvalidToken(&'static str),
+    /// The progress bar template could not be parsed.
+    #[cfg(feature = "std")]
+    ProgressTemplate(TemplateError),
+    /// The threads to render on could not be started.
+    #[cfg(feature = "std")]
+    ThreadPool(ThreadPoolBuildError),
+    /// Output could not be written, or an external encoder failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// A YAML parameters file could not be parsed.
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+}
+
+impl fmt::Display for MandybrotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MandybrotError::EmptyResolution([x_res, y_res]) => {
+                write!(f, "resolution {}x{} has no pixels", x_res, y_res)
+            }
+            MandybrotError::ZeroCount(name) => write!(f, "'{}' must be at least one", name),
+            MandybrotError::InvalidParameter { name, value } => {
+                write!(f, "invalid value {} for '{}'", value, name)
+            }
+            MandybrotError::DuplicateAxis(name) => {
+                write!(f, "'{}' is used for both image axes", name)
+            }
+            MandybrotError::UnknownParameter(name) => {
+                write!(f, "parameter '{}' does not exist", name)
+            }
+            MandybrotError::UnparsableNumber(name) => {
+                write!(f, "'{}' is not a number", name)
+            }
+            MandybrotError::NoDerivative => {
+                write!(f, "the fractal has no complex derivative")
+            }
+            MandybrotError::NotSeedable => {
+                write!(f, "the fractal's orbits cannot be seeded per pixel")
+            }
+            MandybrotError::PipelineOrder(name) => {
+                write!(f, "'{}' must come before the colour map", name)
+            }
+            MandybrotError::BufferSize { expected, found } => {
+                write!(
+                    f,
+                    "buffer holds {} values but {} are needed",
+                    found, expected
+                )
+            }
+            MandybrotError::TileShape {
+                expected: [x_res, y_res],
+                found: [width, height],
+            } => write!(
+                f,
+                "tile is {}x{} but the viewport is {}x{}",
+                width, height, x_res, y_res
+            ),
+            #[cfg(feature = "std")]
+            MandybrotError::FrameResolution {
+                expected: [width, height],
+                found: [frame_width, frame_height],
+            } => write!(
+                f,
+                "frame is {}x{} but the animation is {}x{}",
+                frame_width, frame_height, width, height
+            ),
+            MandybrotError::UnsupportedVersion(version) => {
+                write!(f, "parameters format version {} is not supported", version)
+            }
+            #[cfg(feature = "std")]
+            MandybrotError::UnknownPreset(name) => write!(f, "no preset is named '{}'", name),
+            #[cfg(feature = "std")]
+            MandybrotError::ConfigSyntax {
+                line,
+                column,
+                message,
+            } => write!(f, "line {} column {}: {}", line, column, message),
+            #[cfg(feature = "std")]
+            MandybrotError::ConfigValue { key, message } if key.is_empty() => {
+                write!(f, "invalid parameters: {}", message)
+            }
+            #[cfg(feature = "std")]
+            MandybrotError::ConfigValue { key, message } => {
+                write!(f, "invalid parameters at '{}': {}", key, message)
+            }
+            #[cfg(feature = "std")]
+            MandybrotError::GradientSyntax { line, message } => {
+                write!(f, "invalid gradient at line {}: {}", line, message)
+            }
+            #[cfg(feature = "std")]
+            MandybrotError::UnsupportedFormat(extension) => {
+                write!(f, "files ending '.{}' cannot be read", extension)
+            }
+            #[cfg(feature = "std")]
+            MandybrotError::InvalidToken(reason) => write!(f, "invalid state token: {}", reason),
+            #[cfg(feature = "std")]
+            MandybrotError::ProgressTemplate(err) => {
+                write!(f, "invalid progress bar template: {}", err)
+            }
+            #[cfg(feature = "std")]
+            MandybrotError::ThreadPool(err) => write!(f, "could not start threads: {}", err),
+            #[cfg(feature = "std")]
+            MandybrotError::Io(err) => write!(f, "i/o error: {}", err),
+            #[cfg(feature = "yaml")]
+            MandybrotError::Yaml(err) => write!(f, "invalid parameters: {}", err),
+        }
+    }
+}
+
+impl Error for MandybrotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            MandybrotError::ProgressTemplate(err) => Some(err),
+            #[cfg(feature = "std")]
+            MandybrotError::ThreadPool(err) => Some(err),
+            #[cfg(feature = "std")]
+            MandybrotError::Io(err) => Some(err),
+            #[cfg(feature = "yaml")]
+            MandybrotError::Yaml(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<TemplateError> for MandybrotError {
+    fn from(err: TemplateError) -> Self {
+        MandybrotError::ProgressTemplate(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ThreadPoolBuildError> for MandybrotError {
+    fn from(err: ThreadPoolBuildError) -> Self {
+        MandybrotError::ThreadPool(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for MandybrotError {
+    fn from(err: std::io::Error) -> Self {
+        MandybrotError::Io(err)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Error> for MandybrotError {
+    fn from(err: serde_yaml::Error) -> Self {
+        MandybrotError::Yaml(err)
+    }
+}
+
+/// Checks that a count is at least one.
+pub(crate) fn check_count(name: &'static str, value: u32) -> Result<(), MandybrotError> {
+    if value == 0 {
+        return Err(MandybrotError::ZeroCount(name));
+    }
+    Ok(())
+}
+
+/// Checks that a parameter is finite.
+pub(crate) fn check_finite<T: Float>(name: &'static str, value: T) -> Result<(), MandybrotError> {
+    if !value.is_finite() {
+        return Err(invalid(name, value));
+    }
+    Ok(())
+}
+
+/// Checks that a parameter is finite and greater than zero.
+pub(crate) fn check_positive<T: Float>(name: &'static str, value: T) -> Result<(), MandybrotError> {
+    if !value.is_finite() || value <= T::zero() {
+        return Err(invalid(name, value));
+    }
+    Ok(())
+}
+
+/// Checks that a parameter is finite and not negative.
+pub(crate) fn check_non_negative<T: Float>(
+    name: &'static str,
+    value: T,
+) -> Result<(), MandybrotError> {
+    if !value.is_finite() || value < T::zero() {
+        return Err(invalid(name, value));
+    }
+    Ok(())
+}
+
+/// Checks that a parameter is finite and within `[0, 1]`.
+#[cfg(feature = "std")]
+pub(crate) fn check_fraction<T: Float>(name: &'static str, value: T) -> Result<(), MandybrotError> {
+    if !value.is_finite() || value < T::zero() || value > T::one() {
+        return Err(invalid(name, value));
+    }
+    Ok(())
+}
+
+/// Checks that a light direction is finite and not zero, and scales it to unit length.
+#[cfg(feature = "std")]
+pub(crate) fn check_light<T: Float>(light: [T; 3]) -> Result<[T; 3], MandybrotError> {
+    for (name, component) in ["light.x", "light.y", "light.z"].into_iter().zip(light) {
+        check_finite(name, component)?;
+    }
+    let norm = (light[0] * light[0] + light[1] * light[1] + light[2] * light[2]).sqrt();
+    if norm <= T::zero() {
+        return Err(invalid("light", norm));
+    }
+    Ok(light.map(|component| component / norm))
+}
+
+fn invalid<T: Float>(name: &'static str, value: T) -> MandybrotError {
+    MandybrotError::InvalidParameter {
+        name,
+        value: value.to_f64().unwrap_or(f64::NAN),
+    }
+}
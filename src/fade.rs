@@ -0,0 +1,152 @@
+use core::fmt::Display;
+use ndarray::{Array2, Zip};
+use num_traits::{Float, FloatConst};
+use rand::distr::uniform::SampleUniform;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_count, check_finite},
+    render_attractor_viewport, Attractor, Complex, MandybrotError, RngStrategy, Viewport,
+};
+
+/// Running sum of successive frames which fades exponentially, each new frame becoming `decay * previous + new`.
+/// Accumulating the hits of an animated attractor this way leaves trails behind its moving orbits, for smooth, flowing animations.
+#[derive(Debug, Clone)]
+pub struct Fade {
+    decay: f64,
+    grid: Option<Array2<f64>>,
+}
+
+impl Fade {
+    /// Empty accumulator keeping a fraction `decay`, between zero and one, of the previous frame.
+    pub fn new(decay: f64) -> Result<Self, MandybrotError> {
+        check_decay(decay)?;
+        Ok(Self { decay, grid: None })
+    }
+
+    pub fn decay(&self) -> f64 {
+        self.decay
+    }
+
+    /// Faded sum of the frames added so far, or `None` before the first.
+    pub fn grid(&self) -> Option<&Array2<f64>> {
+        self.grid.as_ref()
+    }
+
+    /// Fades the previous frames and adds a new one, returning the sum.
+    pub fn add<A: Copy + Into<f64>>(
+        &mut self,
+        frame: &Array2<A>,
+    ) -> Result<&Array2<f64>, MandybrotError> {
+        let decay = self.decay;
+        let grid = self.grid.get_or_insert_with(|| Array2::zeros(frame.dim()));
+        if grid.dim() != frame.dim() {
+            return Err(MandybrotError::BufferSize {
+                expected: grid.len(),
+                found: frame.len(),
+            });
+        }
+        Zip::from(&mut *grid)
+            .and(frame)
+            .for_each(|sum, &value| *sum = decay * *sum + value.into());
+        Ok(grid)
+    }
+
+    /// Discards every frame, as when the scene changes completely.
+    pub fn clear(&mut self) {
+        self.grid = None;
+    }
+}
+
+fn check_decay(decay: f64) -> Result<(), MandybrotError> {
+    check_finite("decay", decay)?;
+    if !(0.0..=1.0).contains(&decay) {
+        return Err(MandybrotError::InvalidParameter {
+            name: "decay",
+            value: decay,
+        });
+    }
+    Ok(())
+}
+
+/// Animation of an attractor sweeping through its parameters, with the hits of earlier frames fading behind each new one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AttractorSweep<T> {
+    /// Change of every parameter over one step, as by `Attractor::shift`.
+    pub shift: T,
+    pub num_frames: u32,
+    /// Number of frames over which the parameters move by one `shift`, with more giving a slower, smoother sweep.
+    #[serde(default = "default_frames_per_step")]
+    pub frames_per_step: u32,
+    /// Fraction of the previous frame kept in each new one: zero for no fading, and towards one for long trails.
+    #[serde(default)]
+    pub decay: f64,
+}
+
+fn default_frames_per_step() -> u32 {
+    1
+}
+
+impl<T: Float> AttractorSweep<T> {
+    pub const fn new(shift: T, num_frames: u32, frames_per_step: u32, decay: f64) -> Self {
+        Self {
+            shift,
+            num_frames,
+            frames_per_step,
+            decay,
+        }
+    }
+
+    /// Checks that the shift is finite, that there are frames, and that the decay is between zero and one.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_finite("shift", self.shift)?;
+        check_count("num_frames", self.num_frames)?;
+        check_count("frames_per_step", self.frames_per_step)?;
+        check_decay(self.decay)
+    }
+
+    /// Attractor shown in a frame of the sweep starting from `attractor`.
+    pub fn attractor_at(&self, attractor: &Attractor<T>, frame: u32) -> Attractor<T> {
+        let mut attractor = *attractor;
+        attractor
+            .shift(self.shift * T::from(frame).unwrap() / T::from(self.frames_per_step).unwrap());
+        attractor
+    }
+}
+
+/// Renders each frame of an attractor sweep, passing the faded sum of hits to `on_frame` with the frame's index as soon as it is rendered.
+/// Every frame iterates the same starting points, so orbits move smoothly from frame to frame rather than flickering.
+#[allow(clippy::too_many_arguments)]
+pub fn render_attractor_sweep<T, F>(
+    viewport: &Viewport<T>,
+    start: Complex<T>,
+    radius: T,
+    num_samples: u32,
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &Attractor<T>,
+    sweep: &AttractorSweep<T>,
+    rng_strategy: &RngStrategy,
+    mut on_frame: F,
+) -> Result<(), MandybrotError>
+where
+    T: Float + FloatConst + SampleUniform + Send + Sync + Display,
+    F: FnMut(u32, &Array2<f64>) -> Result<(), MandybrotError>,
+{
+    sweep.validate()?;
+    let mut fade = Fade::new(sweep.decay)?;
+    for frame in 0..sweep.num_frames {
+        let hits = render_attractor_viewport(
+            viewport,
+            start,
+            radius,
+            num_samples,
+            max_iter,
+            draw_after,
+            &sweep.attractor_at(attractor, frame),
+            rng_strategy,
+        )?;
+        on_frame(frame, fade.add(&hits)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use num_traits::{Float, FloatConst};
+use rand::{distr::uniform::SampleUniform, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_finite, check_non_negative, check_positive},
+    Complex, MandybrotError,
+};
+
+/// Non-linear function applied after the affine part of a flame transform, from Draves and Reckase's "The Fractal Flame Algorithm".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Variation {
+    Linear,
+    Sinusoidal,
+    Spherical,
+    Swirl,
+    Horseshoe,
+    Polar,
+    Handkerchief,
+    Heart,
+    Disc,
+    Spiral,
+    Hyperbolic,
+    Diamond,
+}
+
+impl Variation {
+    /// Applies the variation to a point.
+    pub fn apply<T: Float + FloatConst>(&self, p: Complex<T>) -> Complex<T> {
+        let (x, y) = (p.real, p.imag);
+        let r_sq = x * x + y * y;
+        let r = r_sq.sqrt();
+        // The paper measures the angle from the `y` axis.
+        let theta = x.atan2(y);
+        let pi = T::PI();
+        let one = T::one();
+
+        let (x, y) = match self {
+            Variation::Linear => (x, y),
+            Variation::Sinusoidal => (x.sin(), y.sin()),
+            Variation::Spherical => (x / r_sq, y / r_sq),
+            Variation::Swirl => {
+                let (sin, cos) = r_sq.sin_cos();
+                (x * sin - y * cos, x * cos + y * sin)
+            }
+            Variation::Horseshoe => ((x - y) * (x + y) / r, (x + x) * y / r),
+            Variation::Polar => (theta / pi, r - one),
+            Variation::Handkerchief => (r * (theta + r).sin(), r * (theta - r).cos()),
+            Variation::Heart => (r * (theta * r).sin(), -r * (theta * r).cos()),
+            Variation::Disc => {
+                let (sin, cos) = (pi * r).sin_cos();
+                (theta / pi * sin, theta / pi * cos)
+            }
+            Variation::Spiral => {
+                let (sin_r, cos_r) = r.sin_cos();
+                ((theta.cos() + sin_r) / r, (theta.sin() - cos_r) / r)
+            }
+            Variation::Hyperbolic => (theta.sin() / r, r * theta.cos()),
+            Variation::Diamond => (theta.sin() * r.cos(), theta.cos() * r.sin()),
+        };
+        Complex::new(x, y)
+    }
+}
+
+/// One function of a fractal flame: an affine map followed by a weighted blend of variations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlameTransform<T> {
+    /// Relative probability of the transform being chosen at each iteration.
+    pub weight: T,
+    /// Coefficients `[a, b, c, d, e, f]` of the affine map `(a x + b y + c, d x + e y + f)`.
+    pub affine: [T; 6],
+    /// Variations and their blending weights.
+    pub variations: Vec<(Variation, T)>,
+    /// Position in the colour map, in `[0, 1]`, which points are pulled towards when this transform is applied.
+    pub colour: T,
+}
+
+impl<T: Float + FloatConst> FlameTransform<T> {
+    /// Applies the transform to a point.
+    pub fn apply(&self, p: Complex<T>) -> Complex<T> {
+        let [a, b, c, d, e, f] = self.affine;
+        let q = Complex::new(a * p.real + b * p.imag + c, d * p.real + e * p.imag + f);
+        self.variations.iter().fold(
+            Complex::new(T::zero(), T::zero()),
+            |sum, &(variation, weight)| {
+                let v = variation.apply(q);
+                Complex::new(sum.real + weight * v.real, sum.imag + weight * v.imag)
+            },
+        )
+    }
+}
+
+/// Fractal flame: a set of randomly chosen non-linear transforms, whose attractor is coloured by the history of transforms applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Flame<T> {
+    pub transforms: Vec<FlameTransform<T>>,
+    /// Gamma applied to the log density, brightening faint regions for values above one.
+    pub gamma: T,
+}
+
+impl<T> Flame<T>
+where
+    T: Float + FloatConst + SampleUniform,
+{
+    /// Checks that there is at least one transform, that the weights are non-negative with a positive total, and that all parameters are finite.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        if self.transforms.is_empty() {
+            return Err(MandybrotError::ZeroCount("transforms"));
+        }
+        let mut total = T::zero();
+        for transform in &self.transforms {
+            check_non_negative("weight", transform.weight)?;
+            total = total + transform.weight;
+            for coefficient in transform.affine {
+                check_finite("affine", coefficient)?;
+            }
+            for &(_, weight) in &transform.variations {
+                check_finite("variation weight", weight)?;
+            }
+            check_non_negative("colour", transform.colour)?;
+            if transform.colour > T::one() {
+                return Err(MandybrotError::InvalidParameter {
+                    name: "colour",
+                    value: transform.colour.to_f64().unwrap_or(f64::NAN),
+                });
+            }
+        }
+        check_positive("total weight", total)?;
+        check_positive("gamma", self.gamma)
+    }
+
+    /// Applies a randomly chosen transform to a point, blending its colour index towards the transform's colour.
+    pub fn iterate(&self, p: Complex<T>, colour: T, rng: &mut impl Rng) -> (Complex<T>, T) {
+        let total = self
+            .transforms
+            .iter()
+            .fold(T::zero(), |total, transform| total + transform.weight);
+        let mut choice = rng.random_range(T::zero()..total);
+        let transform = self
+            .transforms
+            .iter()
+            .find(|transform| {
+                choice = choice - transform.weight;
+                choice < T::zero()
+            })
+            .unwrap_or(&self.transforms[self.transforms.len() - 1]);
+
+        let half = T::from(0.5).unwrap();
+        (transform.apply(p), (colour + transform.colour) * half)
+    }
+}
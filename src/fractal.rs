@@ -1,20 +1,344 @@
+use core::ops::{Add, Mul, Sub};
 use num_traits::{Float, NumCast};
 use serde::{Deserialize, Serialize};
-use std::ops::{Add, Mul, Sub};
 
-use crate::Complex;
+use crate::{
+    error::{check_finite, check_positive},
+    hybrid::hybrid,
+    kernel::{
+        burning_ship_step, celtic_step, collatz_step, lambda_step, mandelbrot_step, multibrot_step,
+        multicorn_step, newton_step, phoenix_step, tricorn_step,
+    },
+    periodicity::CycleDetector,
+    plane_slice::{sample_slice, validate_slice},
+    quaternion::{quaternion_julia, validate_quaternion_slice},
+    Algebra, Complex, DualComplex, DualNumber, EscapeMap, HybridSequence, Location, MandybrotError,
+    NumberSystem, OrbitState, Periodicity, PlaneAxis, Quaternion, QuaternionAxis, RationalMap,
+    SlicePoint, SplitComplex,
+};
 
 /// Enum representing different fractals that can be sampled.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
 pub enum Fractal<T> {
     Mandelbrot,
     BurningShip,
-    Julia { c: Complex<T> },
+    Julia {
+        c: Complex<T>,
+    },
     Tricorn,
-    Multibrot { power: u32 },
-    Newton { epsilon: T },
-    Phoenix { c: Complex<T> },
+    Multibrot {
+        power: u32,
+    },
+    /// Multicorn set `z → conj(z)^power + c`, generalising the Tricorn to the whole Mandelbar family.
+    Multicorn {
+        power: u32,
+    },
+    /// Multibrot set `z → z^power + c` of any real power above one, such as to animate the power smoothly.
+    RealMultibrot {
+        power: T,
+    },
+    /// Multibrot set `z → z^power + c` of a complex power, whose real part is above one.
+    ComplexMultibrot {
+        power: Complex<T>,
+    },
+    Newton {
+        epsilon: T,
+    },
+    Phoenix {
+        c: Complex<T>,
+    },
     CelticMandelbrot,
+    /// The Collatz fractal, iterating `z → (z/2)cos²(πz/2) + (3z + 1)sin²(πz/2)`, which halves even integers and sends odd ones to `3z + 1`.
+    /// Orbits escape once `|Im z|` exceeds `bailout`, beyond which the cosine grows without limit.
+    Collatz {
+        bailout: T,
+    },
+    /// The Mandelbrot set over the λ-plane of the logistic map `z → λz(1 − z)`, iterated from its critical point `z0 = 1/2`.
+    /// Each λ has the same iteration count as the point `c = λ/2 − λ²/4` of the Mandelbrot set.
+    Lambda,
+    /// The Julia set of the logistic map `z → λz(1 − z)` for a fixed `lambda`.
+    LambdaJulia {
+        lambda: Complex<T>,
+    },
+    /// The Mandelbrot set iterated in another number system, with `z` and `c` drawn as points of the plane.
+    MandelbrotOver {
+        system: NumberSystem,
+    },
+    /// A Julia set iterated in another number system, with `z` and `c` drawn as points of the plane.
+    JuliaOver {
+        system: NumberSystem,
+        c: Complex<T>,
+    },
+    /// Any rational map `z → numerator(z) / denominator(z) + c`, such as Newton's method for a polynomial, run until its orbits diverge or converge.
+    /// Without a `start` each pixel is the starting point of an orbit with `c = 0`; with one each pixel is the `c` of an orbit from `start`, as for Nova fractals.
+    Rational {
+        map: RationalMap<T>,
+        #[serde(default)]
+        start: Option<Complex<T>>,
+    },
+    /// A plane through the space of parameters and starting points of a map, spanned by two chosen coordinates with the others fixed.
+    /// The Mandelbrot set is the `c` plane of the quadratic map through `z0 = 0`, and each Julia set is a `z0` plane.
+    Slice {
+        map: EscapeMap,
+        axes: [PlaneAxis; 2],
+        fixed: SlicePoint<T>,
+    },
+    /// A hybrid of escape-time formulas, iterating each step of the `sequence` from `z0 = 0` in turn and repeating it until the orbit escapes.
+    /// Two Mandelbrot steps then a Burning Ship step, for example, give a shape found in neither.
+    Hybrid {
+        sequence: HybridSequence<T>,
+    },
+    /// A plane through the Mandelbrot set of the quaternions, iterating `z → z² + c` from `z0 = 0` with `c` spanning two `axes` and taking its other components from `fixed`.
+    QuaternionMandelbrot {
+        axes: [QuaternionAxis; 2],
+        fixed: Quaternion<T>,
+    },
+    /// A plane through a Julia set of the quaternions for a fixed `c`, with `z0` spanning two `axes` and taking its other components from `fixed`.
+    QuaternionJulia {
+        c: Quaternion<T>,
+        axes: [QuaternionAxis; 2],
+        fixed: Quaternion<T>,
+    },
+}
+
+impl<T: Copy> Fractal<T> {
+    /// The Julia set corresponding to the point `c` of the Mandelbrot parameter plane.
+    pub const fn julia_of(c: Complex<T>) -> Self {
+        Fractal::Julia { c }
+    }
+
+    /// The point of the Mandelbrot parameter plane which this fractal corresponds to, if it is a Julia set.
+    pub fn julia_parameter(&self) -> Option<Complex<T>> {
+        match self {
+            Fractal::Julia { c } => Some(*c),
+            _ => None,
+        }
+    }
+
+    /// Applies a function to every real parameter of the fractal, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Fractal<U> {
+        match self {
+            Fractal::Mandelbrot => Fractal::Mandelbrot,
+            Fractal::BurningShip => Fractal::BurningShip,
+            Fractal::Julia { c } => Fractal::Julia { c: c.map(f) },
+            Fractal::Tricorn => Fractal::Tricorn,
+            Fractal::Multibrot { power } => Fractal::Multibrot { power },
+            Fractal::Multicorn { power } => Fractal::Multicorn { power },
+            Fractal::RealMultibrot { power } => Fractal::RealMultibrot { power: f(power) },
+            Fractal::ComplexMultibrot { power } => Fractal::ComplexMultibrot {
+                power: power.map(f),
+            },
+            Fractal::Newton { epsilon } => Fractal::Newton {
+                epsilon: f(epsilon),
+            },
+            Fractal::Phoenix { c } => Fractal::Phoenix { c: c.map(f) },
+            Fractal::CelticMandelbrot => Fractal::CelticMandelbrot,
+            Fractal::Collatz { bailout } => Fractal::Collatz {
+                bailout: f(bailout),
+            },
+            Fractal::Lambda => Fractal::Lambda,
+            Fractal::LambdaJulia { lambda } => Fractal::LambdaJulia {
+                lambda: lambda.map(f),
+            },
+            Fractal::MandelbrotOver { system } => Fractal::MandelbrotOver { system },
+            Fractal::JuliaOver { system, c } => Fractal::JuliaOver {
+                system,
+                c: c.map(f),
+            },
+            Fractal::Rational { map, start } => Fractal::Rational {
+                map: map.map(&f),
+                start: start.map(|start| start.map(&f)),
+            },
+            Fractal::Slice { map, axes, fixed } => Fractal::Slice {
+                map,
+                axes,
+                fixed: fixed.map(f),
+            },
+            Fractal::Hybrid { sequence } => Fractal::Hybrid {
+                sequence: sequence.map(f),
+            },
+            Fractal::QuaternionMandelbrot { axes, fixed } => Fractal::QuaternionMandelbrot {
+                axes,
+                fixed: fixed.map(f),
+            },
+            Fractal::QuaternionJulia { c, axes, fixed } => Fractal::QuaternionJulia {
+                c: c.map(&f),
+                axes,
+                fixed: fixed.map(&f),
+            },
+        }
+    }
+}
+
+impl<T: Float> Fractal<T> {
+    /// Rate at which `|z|` grows once an orbit has escaped, as `|z|^degree`, for smooth colouring with `SampleBuffer::smooth_iterations_of_degree`.
+    /// Returns `None` for fractals whose orbits do not grow as a power of `|z|`.
+    pub fn escape_degree(&self) -> Option<T> {
+        match *self {
+            Fractal::Mandelbrot
+            | Fractal::BurningShip
+            | Fractal::Julia { .. }
+            | Fractal::Tricorn
+            | Fractal::Phoenix { .. }
+            | Fractal::CelticMandelbrot
+            | Fractal::Lambda
+            | Fractal::LambdaJulia { .. }
+            | Fractal::MandelbrotOver { .. }
+            | Fractal::JuliaOver { .. }
+            | Fractal::QuaternionMandelbrot { .. }
+            | Fractal::QuaternionJulia { .. } => T::from(2.0),
+            Fractal::Multibrot { power } | Fractal::Multicorn { power } => T::from(power),
+            Fractal::RealMultibrot { power } => Some(power),
+            Fractal::ComplexMultibrot { power } => Some(power.real),
+            Fractal::Hybrid { sequence } => Some(sequence.escape_degree()),
+            Fractal::Newton { .. }
+            | Fractal::Collatz { .. }
+            | Fractal::Rational { .. }
+            | Fractal::Slice { .. } => None,
+        }
+    }
+
+    /// Whether every point has the same iteration count as its mirror image in the real axis, so a view straddling the axis need only be half rendered.
+    /// Maps with complex parameters lose the symmetry, as does the Burning Ship, whose absolute values fold the plane unevenly.
+    pub fn is_mirror_symmetric(&self) -> bool {
+        let real = |z: &Complex<T>| z.imag == T::zero();
+        match self {
+            Fractal::Mandelbrot
+            | Fractal::Tricorn
+            | Fractal::Multibrot { .. }
+            | Fractal::Multicorn { .. }
+            | Fractal::RealMultibrot { .. }
+            | Fractal::Newton { .. }
+            | Fractal::CelticMandelbrot
+            | Fractal::Collatz { .. }
+            | Fractal::Lambda
+            | Fractal::MandelbrotOver { .. } => true,
+            Fractal::Julia { c } | Fractal::Phoenix { c } | Fractal::JuliaOver { c, .. } => real(c),
+            Fractal::ComplexMultibrot { power } => real(power),
+            Fractal::LambdaJulia { lambda } => real(lambda),
+            Fractal::Rational { map, start } => {
+                map.numerator.coefficients().iter().all(real)
+                    && map.denominator.coefficients().iter().all(real)
+                    && start.as_ref().is_none_or(real)
+            }
+            Fractal::Hybrid { sequence } => sequence.is_mirror_symmetric(),
+            // Squaring depends on the imaginary components only through their modulus, so negating one of them commutes with it.
+            Fractal::QuaternionMandelbrot { axes, .. } => axes[1] != QuaternionAxis::Real,
+            Fractal::QuaternionJulia { c, axes, .. } => {
+                axes[1] != QuaternionAxis::Real && c.component(axes[1]) == T::zero()
+            }
+            Fractal::BurningShip | Fractal::Slice { .. } => false,
+        }
+    }
+
+    /// Framing which shows the whole fractal, with enough iterations to draw its outline.
+    pub fn default_viewport(&self) -> Location<T> {
+        let (real, imag, scale, max_iter) = match self {
+            Fractal::Mandelbrot => (-0.5, 0.0, 3.0, 100),
+            Fractal::BurningShip => (-0.5, -0.5, 3.0, 100),
+            Fractal::Tricorn | Fractal::Julia { .. } | Fractal::JuliaOver { .. } => {
+                (0.0, 0.0, 4.0, 100)
+            }
+            Fractal::Multicorn { .. } => (0.0, 0.0, 3.0, 100),
+            Fractal::Multibrot { .. }
+            | Fractal::RealMultibrot { .. }
+            | Fractal::ComplexMultibrot { .. } => (0.0, 0.0, 2.5, 100),
+            Fractal::Newton { .. } => (0.0, 0.0, 2.0, 100),
+            Fractal::Phoenix { .. } => (0.0, 0.0, 4.0, 200),
+            Fractal::CelticMandelbrot => (-0.7, 0.0, 5.0, 50),
+            Fractal::Collatz { .. } => (0.0, 0.0, 2.5, 50),
+            Fractal::Lambda => (1.0, 0.0, 4.0, 100),
+            Fractal::LambdaJulia { .. } => (0.5, 0.0, 2.5, 100),
+            Fractal::MandelbrotOver { .. } => (-0.5, 0.0, 4.0, 100),
+            Fractal::Rational { .. } => (0.0, 0.0, 4.0, 100),
+            Fractal::Slice { .. } => (0.0, 0.0, 4.0, 200),
+            Fractal::Hybrid { .. } => (-0.5, 0.0, 4.0, 100),
+            Fractal::QuaternionMandelbrot { .. } => (-0.5, 0.0, 3.0, 100),
+            Fractal::QuaternionJulia { .. } => (0.0, 0.0, 4.0, 100),
+        };
+        Location::new(
+            Complex::new(T::from(real).unwrap(), T::from(imag).unwrap()),
+            T::from(scale).unwrap(),
+            max_iter,
+        )
+    }
+
+    /// Checks that the parameters of the fractal are finite and within range.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            Fractal::Julia { c } | Fractal::Phoenix { c } | Fractal::JuliaOver { c, .. } => {
+                check_finite("c.real", c.real)?;
+                check_finite("c.imag", c.imag)
+            }
+            Fractal::LambdaJulia { lambda } => {
+                check_finite("lambda.real", lambda.real)?;
+                check_finite("lambda.imag", lambda.imag)
+            }
+            Fractal::Multibrot { power } | Fractal::Multicorn { power } if power < 2 => {
+                Err(MandybrotError::InvalidParameter {
+                    name: "power",
+                    value: power as f64,
+                })
+            }
+            Fractal::RealMultibrot { power } => check_power(power),
+            Fractal::ComplexMultibrot { power } => {
+                check_power(power.real)?;
+                check_finite("power.imag", power.imag)
+            }
+            Fractal::Newton { epsilon } => check_positive("epsilon", epsilon),
+            Fractal::Collatz { bailout } => {
+                check_finite("bailout", bailout)?;
+                check_positive("bailout", bailout)
+            }
+            Fractal::Rational { map, start } => {
+                if let Some(start) = start {
+                    check_finite("start.real", start.real)?;
+                    check_finite("start.imag", start.imag)?;
+                }
+                map.validate()
+            }
+            Fractal::Slice { axes, fixed, .. } => validate_slice(axes, &fixed),
+            Fractal::Hybrid { sequence } => sequence.validate(),
+            Fractal::QuaternionMandelbrot { axes, fixed } => {
+                validate_quaternion_slice(axes, &fixed)
+            }
+            Fractal::QuaternionJulia { c, axes, fixed } => {
+                c.validate(["c.real", "c.i", "c.j", "c.k"])?;
+                validate_quaternion_slice(axes, &fixed)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The point `c = λ/2 − λ²/4` of the Mandelbrot parameter plane whose quadratic map is conjugate to the logistic map of `lambda`.
+    pub fn lambda_to_c(lambda: Complex<T>) -> Complex<T> {
+        let half = T::from(0.5).unwrap();
+        let quarter = T::from(0.25).unwrap();
+        Complex::new(lambda.real * half, lambda.imag * half)
+            - (lambda * lambda).map(|x| x * quarter)
+    }
+
+    /// The value `λ = 1 + √(1 − 4c)` of the logistic map conjugate to the quadratic map of `c`.
+    /// The other value, `2 − λ`, gives the same map, reflected through `λ = 1`.
+    pub fn c_to_lambda(c: Complex<T>) -> Complex<T> {
+        let one = T::one();
+        let four = T::from(4.0).unwrap();
+        let root = Complex::new(one - four * c.real, -four * c.imag).sqrt();
+        Complex::new(one + root.real, root.imag)
+    }
+}
+
+/// Checks that a real power, or the real part of a complex one, is finite and above one, so that orbits escape beyond a radius of two.
+fn check_power<T: Float>(power: T) -> Result<(), MandybrotError> {
+    check_finite("power", power)?;
+    if power <= T::one() {
+        return Err(MandybrotError::InvalidParameter {
+            name: "power",
+            value: power.to_f64().unwrap_or(f64::NAN),
+        });
+    }
+    Ok(())
 }
 
 impl<T> Fractal<T>
@@ -22,41 +346,370 @@ where
     T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
 {
     /// Samples a given fractal at the provided complex coordinate.
-    pub fn sample(&self, p: Complex<T>, max_iter: u32) -> u32 {
+    /// If `periodicity` is given, orbits which fall into a cycle are reported as never escaping without running to `max_iter`.
+    pub fn sample(
+        &self,
+        p: Complex<T>,
+        max_iter: u32,
+        periodicity: Option<&Periodicity<T>>,
+    ) -> u32 {
+        self.sample_escape(p, max_iter, periodicity).0
+    }
+
+    /// Samples a given fractal at the provided complex coordinate, returning the iteration count and the final value of `z`.
+    /// For escaping points the final `z` is the first point of the orbit outside the escape radius.
+    pub fn sample_escape(
+        &self,
+        p: Complex<T>,
+        max_iter: u32,
+        periodicity: Option<&Periodicity<T>>,
+    ) -> (u32, Complex<T>) {
+        self.sample_orbit(p, max_iter, periodicity, |_| {})
+    }
+
+    /// Points of the orbit of `p`, not including its starting point, together with the iteration count as returned by `sample`.
+    /// Orbits which escape end at the first point outside the escape radius, and have a count below `max_iter`.
+    #[cfg(feature = "std")]
+    pub fn orbit(&self, p: Complex<T>, max_iter: u32) -> (u32, Vec<Complex<T>>) {
+        let mut orbit = Vec::new();
+        let (n, _) = self.sample_orbit(p, max_iter, None, |z| orbit.push(z));
+        (n, orbit)
+    }
+
+    /// Samples a given fractal at the provided complex coordinate, passing each point of the orbit after the first to `visit`.
+    /// Returns the iteration count and the final value of `z`, as for `sample_escape`.
+    pub fn sample_orbit(
+        &self,
+        p: Complex<T>,
+        max_iter: u32,
+        periodicity: Option<&Periodicity<T>>,
+        mut visit: impl FnMut(Complex<T>),
+    ) -> (u32, Complex<T>) {
+        let visit = &mut visit;
         match self {
-            Fractal::Mandelbrot => mandelbrot(p, max_iter),
-            Fractal::BurningShip => burning_ship(p, max_iter),
-            Fractal::Julia { c } => julia(p, *c, max_iter),
-            Fractal::Tricorn => tricorn(p, max_iter),
-            Fractal::Multibrot { power } => multibrot(p, *power, max_iter),
-            Fractal::Newton { epsilon } => newton(p, *epsilon, max_iter),
-            Fractal::Phoenix { c } => phoenix(p, *c, max_iter),
-            Fractal::CelticMandelbrot => celtic_mandelbrot(p, max_iter),
+            Fractal::Mandelbrot => mandelbrot(p, max_iter, periodicity, visit),
+            Fractal::BurningShip => burning_ship(p, max_iter, visit),
+            Fractal::Julia { c } => julia(p, *c, max_iter, periodicity, visit),
+            Fractal::Tricorn => tricorn(p, max_iter, visit),
+            Fractal::Multibrot { power } => multibrot(p, *power, max_iter, periodicity, visit),
+            Fractal::Multicorn { power } => multicorn(p, *power, max_iter, periodicity, visit),
+            Fractal::RealMultibrot { power } => {
+                multibrot_with(p, |z| z.powf(*power), max_iter, periodicity, visit)
+            }
+            Fractal::ComplexMultibrot { power } => {
+                multibrot_with(p, |z| z.powc(*power), max_iter, periodicity, visit)
+            }
+            Fractal::Newton { epsilon } => newton(p, *epsilon, max_iter, visit),
+            Fractal::Phoenix { c } => phoenix(p, *c, max_iter, visit),
+            Fractal::CelticMandelbrot => celtic_mandelbrot(p, max_iter, visit),
+            Fractal::Collatz { bailout } => collatz(p, *bailout, max_iter, periodicity, visit),
+            Fractal::Lambda => {
+                let half = T::from(0.5).unwrap();
+                lambda(
+                    Complex::new(half, T::zero()),
+                    p,
+                    max_iter,
+                    periodicity,
+                    visit,
+                )
+            }
+            Fractal::LambdaJulia { lambda: l } => lambda(p, *l, max_iter, periodicity, visit),
+            Fractal::MandelbrotOver { system } => {
+                let zero = Complex::new(T::zero(), T::zero());
+                julia_over(*system, zero, p, max_iter, periodicity, visit)
+            }
+            Fractal::JuliaOver { system, c } => {
+                julia_over(*system, p, *c, max_iter, periodicity, visit)
+            }
+            Fractal::Rational { map, start } => {
+                let zero = Complex::new(T::zero(), T::zero());
+                match start {
+                    Some(start) => rational(map, *start, p, max_iter, visit),
+                    None => rational(map, p, zero, max_iter, visit),
+                }
+            }
+            Fractal::Slice { map, axes, fixed } => {
+                sample_slice(*map, *axes, fixed, p, max_iter, periodicity, visit)
+            }
+            Fractal::Hybrid { sequence } => hybrid(sequence, p, max_iter, visit),
+            Fractal::QuaternionMandelbrot { axes, fixed } => {
+                let c = fixed.with(axes[0], p.real).with(axes[1], p.imag);
+                quaternion_julia(Quaternion::zero(), c, *axes, max_iter, visit)
+            }
+            Fractal::QuaternionJulia { c, axes, fixed } => {
+                let z = fixed.with(axes[0], p.real).with(axes[1], p.imag);
+                quaternion_julia(z, *c, *axes, max_iter, visit)
+            }
         }
     }
+
+    /// State at the start of the orbit of point `p`, for loops outside the crate which advance it with `step`.
+    /// Returns `None` for fractals whose orbits are not of single complex numbers, such as slices, quaternions and other number systems.
+    pub fn initial_state(&self, p: Complex<T>) -> Option<OrbitState<T>> {
+        let zero = Complex::new(T::zero(), T::zero());
+        Some(match *self {
+            Fractal::Mandelbrot
+            | Fractal::BurningShip
+            | Fractal::Tricorn
+            | Fractal::Multibrot { .. }
+            | Fractal::Multicorn { .. }
+            | Fractal::RealMultibrot { .. }
+            | Fractal::ComplexMultibrot { .. }
+            | Fractal::Phoenix { .. }
+            | Fractal::CelticMandelbrot
+            | Fractal::Hybrid { .. } => OrbitState::new(zero, p),
+            Fractal::Julia { c } => OrbitState::new(p, c),
+            Fractal::Newton { .. } | Fractal::Collatz { .. } => OrbitState::new(p, zero),
+            Fractal::Lambda => OrbitState::new(Complex::new(T::from(0.5).unwrap(), T::zero()), p),
+            Fractal::LambdaJulia { lambda } => OrbitState::new(p, lambda),
+            Fractal::Rational { start, .. } => match start {
+                Some(start) => OrbitState::new(start, p),
+                None => OrbitState::new(p, zero),
+            },
+            Fractal::MandelbrotOver { .. }
+            | Fractal::JuliaOver { .. }
+            | Fractal::Slice { .. }
+            | Fractal::QuaternionMandelbrot { .. }
+            | Fractal::QuaternionJulia { .. } => return None,
+        })
+    }
+
+    /// Advances an orbit by one step of the fractal's map, with the same step functions as the crate's own loops, leaving every check for escape or convergence to the caller.
+    /// States of fractals for which `initial_state` is `None` are left unchanged.
+    #[inline(always)]
+    pub fn step(&self, state: &mut OrbitState<T>) {
+        let (z, c) = (state.z, state.c);
+        let next = match self {
+            Fractal::Mandelbrot | Fractal::Julia { .. } => mandelbrot_step(z, c),
+            Fractal::BurningShip => burning_ship_step(z, c),
+            Fractal::Tricorn => tricorn_step(z, c),
+            Fractal::Multibrot { power } => multibrot_step(z, c, *power),
+            Fractal::Multicorn { power } => multicorn_step(z, c, *power),
+            Fractal::RealMultibrot { power } => z.powf(*power) + c,
+            Fractal::ComplexMultibrot { power } => z.powc(*power) + c,
+            Fractal::Newton { .. } => newton_step(z).0,
+            Fractal::Phoenix { c: p } => phoenix_step(z, state.previous, c, *p),
+            Fractal::CelticMandelbrot => celtic_step(z, c),
+            Fractal::Collatz { .. } => collatz_step(z),
+            Fractal::Lambda | Fractal::LambdaJulia { .. } => lambda_step(z, c),
+            Fractal::Rational { map, .. } => map.evaluate(z) + c,
+            Fractal::Hybrid { sequence } => {
+                let steps = sequence.steps();
+                steps[state.iteration as usize % steps.len()].apply(z, c)
+            }
+            Fractal::MandelbrotOver { .. }
+            | Fractal::JuliaOver { .. }
+            | Fractal::Slice { .. }
+            | Fractal::QuaternionMandelbrot { .. }
+            | Fractal::QuaternionJulia { .. } => return,
+        };
+        state.advance(next);
+    }
+
+    /// Whether the fractal's map can be differentiated as a complex formula, so that `sample_derivative` can differentiate its orbits.
+    pub fn has_derivative(&self) -> bool {
+        self.sample_derivative(Complex::new(T::zero(), T::zero()), 0, T::one())
+            .is_some()
+    }
+
+    /// Iterates the orbit of point `p` until `|z|` reaches `escape_radius`, carrying the derivative of `z` with respect to `p` along.
+    /// A large `escape_radius`, such as 1000, gives accurate distance estimates from the result.
+    /// Rational maps ignore their own bailouts and only stop on escape.
+    /// The conjugating maps of the Tricorn and Multicorns are differentiated as `DualComplex::conj` does, so their distance estimates are approximate.
+    /// Returns `None` for fractals whose maps are neither holomorphic nor conjugates of holomorphic maps, such as the Burning Ship, or have no single complex formula, such as slices and other number systems.
+    pub fn sample_derivative(
+        &self,
+        p: Complex<T>,
+        max_iter: u32,
+        escape_radius: T,
+    ) -> Option<(u32, DualComplex<T>)> {
+        let zero = DualComplex::constant(Complex::new(T::zero(), T::zero()));
+        let one = DualComplex::constant(Complex::new(T::one(), T::zero()));
+        let half = DualComplex::constant(Complex::new(T::from(0.5).unwrap(), T::zero()));
+        let pixel = DualComplex::variable(p);
+        let (r, n) = (escape_radius, max_iter);
+
+        Some(match self {
+            Fractal::Mandelbrot => formula_with_derivative(zero, pixel, |z, c| z * z + c, r, n),
+            Fractal::Julia { c } => {
+                let c = DualComplex::constant(*c);
+                formula_with_derivative(pixel, c, |z, c| z * z + c, r, n)
+            }
+            Fractal::Tricorn => {
+                formula_with_derivative(zero, pixel, |z, c| z.conj() * z.conj() + c, r, n)
+            }
+            Fractal::Multibrot { power } => {
+                formula_with_derivative(zero, pixel, |z, c| z.powi(*power) + c, r, n)
+            }
+            Fractal::Multicorn { power } => {
+                formula_with_derivative(zero, pixel, |z, c| z.conj().powi(*power) + c, r, n)
+            }
+            Fractal::RealMultibrot { power } => {
+                formula_with_derivative(zero, pixel, |z, c| z.powf(*power) + c, r, n)
+            }
+            Fractal::ComplexMultibrot { power } => {
+                formula_with_derivative(zero, pixel, |z, c| z.powc(*power) + c, r, n)
+            }
+            Fractal::Lambda => formula_with_derivative(half, pixel, |z, l| l * z * (one - z), r, n),
+            Fractal::LambdaJulia { lambda } => {
+                let lambda = DualComplex::constant(*lambda);
+                formula_with_derivative(pixel, lambda, |z, l| l * z * (one - z), r, n)
+            }
+            Fractal::Rational { map, start } => {
+                let step = |z, c| map.evaluate_dual(z) + c;
+                match start {
+                    Some(start) => {
+                        formula_with_derivative(DualComplex::constant(*start), pixel, step, r, n)
+                    }
+                    None => formula_with_derivative(pixel, zero, step, r, n),
+                }
+            }
+            Fractal::BurningShip
+            | Fractal::Newton { .. }
+            | Fractal::Phoenix { .. }
+            | Fractal::CelticMandelbrot
+            | Fractal::Collatz { .. }
+            | Fractal::MandelbrotOver { .. }
+            | Fractal::JuliaOver { .. }
+            | Fractal::Slice { .. }
+            | Fractal::Hybrid { .. }
+            | Fractal::QuaternionMandelbrot { .. }
+            | Fractal::QuaternionJulia { .. } => return None,
+        })
+    }
+}
+
+/// Iterates `z → step(z, c)` until `|z|` reaches `escape_radius`, carrying the derivative of `z` along, for formulas without a hand-written derivative.
+/// Whichever of `z` and `c` is the pixel should be a `DualComplex::variable`, and the other a `DualComplex::constant`.
+pub fn formula_with_derivative<T>(
+    z: DualComplex<T>,
+    c: DualComplex<T>,
+    step: impl Fn(DualComplex<T>, DualComplex<T>) -> DualComplex<T>,
+    escape_radius: T,
+    max_iter: u32,
+) -> (u32, DualComplex<T>)
+where
+    T: Float,
+{
+    let escape_radius_sqr = escape_radius * escape_radius;
+    let mut z = z;
+    let mut n = 0;
+
+    while z.value.norm_sqr() < escape_radius_sqr && n < max_iter {
+        z = step(z, c);
+        n += 1;
+    }
+
+    (n, z)
 }
 
 #[inline(always)]
-fn mandelbrot<T>(c: Complex<T>, max_iter: u32) -> u32
+fn mandelbrot<A, T>(
+    c: A,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, A)
 where
-    T: Copy + Add<Output = T> + Mul<Output = T> + Sub<Output = T> + PartialOrd + NumCast,
+    A: Algebra<T>,
+    T: Copy + Add<Output = T> + Mul<Output = T> + Sub<Output = T> + PartialOrd + Float + NumCast,
 {
     let zero = NumCast::from(0).unwrap();
     let four = NumCast::from(4).unwrap();
 
-    let mut z = Complex::new(zero, zero);
+    let mut z = A::from_plane(Complex::new(zero, zero));
+    let mut cycle = periodicity.map(|p| CycleDetector::new(p, z.to_plane()));
     let mut n = 0;
 
-    while z.norm_sqr() < four && n < max_iter {
+    while z.escape_norm_sqr() < four && n < max_iter {
         let zz = z * z;
         z = zz + c;
         n += 1;
+        visit(z.to_plane());
+
+        if cycle
+            .as_mut()
+            .is_some_and(|cycle| cycle.is_periodic(z.to_plane()))
+        {
+            return (max_iter, z);
+        }
+    }
+
+    (n, z)
+}
+/// Iterates the Mandelbrot set at `c`, returning the iteration count, the atom domain and the period.
+/// The atom domain is the iteration at which `|z|` was smallest.
+/// The period is that of the cycle the orbit fell into, or zero if the orbit escaped or no cycle was found within `max_iter`.
+#[cfg(feature = "std")]
+#[inline(always)]
+pub(crate) fn mandelbrot_atom<T>(
+    c: Complex<T>,
+    max_iter: u32,
+    periodicity: &Periodicity<T>,
+) -> (u32, u32, u32)
+where
+    T: Copy + Add<Output = T> + Mul<Output = T> + Sub<Output = T> + PartialOrd + Float + NumCast,
+{
+    let zero = NumCast::from(0).unwrap();
+    let four = NumCast::from(4).unwrap();
+
+    let mut z = Complex::new(zero, zero);
+    let mut cycle = CycleDetector::new(periodicity, z);
+    let mut min_norm_sqr = T::infinity();
+    let mut domain = 0;
+    let mut n = 0;
+
+    while z.norm_sqr() < four && n < max_iter {
+        z = z * z + c;
+        n += 1;
+
+        let norm_sqr = z.norm_sqr();
+        if norm_sqr < min_norm_sqr {
+            min_norm_sqr = norm_sqr;
+            domain = n;
+        }
+
+        if cycle.is_periodic(z) {
+            let period = minimal_period(z, c, cycle.period(), periodicity.tolerance);
+            return (max_iter, domain, period);
+        }
+    }
+
+    (n, domain, 0)
+}
+
+/// Number of further cycles iterated before reducing a detected period, see `minimal_period`.
+#[cfg(feature = "std")]
+const PERIOD_SETTLE_CYCLES: u32 = 8;
+
+/// Reduces a detected period of `z -> z^2 + c` to the smallest divisor which still returns within `tolerance`.
+/// Orbits converging onto a cycle with a negative multiplier can match a multiple of the period first, so the orbit is first allowed to settle further onto the cycle.
+#[cfg(feature = "std")]
+fn minimal_period<T: Float>(z: Complex<T>, c: Complex<T>, period: u32, tolerance: T) -> u32 {
+    let tolerance_sqr = tolerance * tolerance;
+
+    let mut z = z;
+    for _ in 0..(period * PERIOD_SETTLE_CYCLES) {
+        z = z * z + c;
+    }
+
+    let mut w = z;
+    for p in 1..period {
+        w = w * w + c;
+        if period.is_multiple_of(p) && (w - z).norm_sqr() < tolerance_sqr {
+            return p;
+        }
     }
 
-    n
+    period
 }
+
 #[inline(always)]
-fn burning_ship<T>(c: Complex<T>, max_iter: u32) -> u32
+fn burning_ship<T>(
+    c: Complex<T>,
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
 where
     T: Copy + Add<Output = T> + Mul<Output = T> + Sub<Output = T> + Float + PartialOrd + NumCast, // Add NumCast for explicit conversions
 {
@@ -64,33 +717,90 @@ where
     let mut iter = 0;
 
     while z.norm_sqr() < T::from(4.0).unwrap() && iter < max_iter {
-        z = Complex::new(z.real.abs(), z.imag.abs());
-        z = z * z + c;
+        z = burning_ship_step(z, c);
         iter += 1;
+        visit(z);
     }
 
-    iter
+    (iter, z)
 }
 
 #[inline(always)]
-fn julia<T>(z: Complex<T>, c: Complex<T>, max_iter: u32) -> u32
+fn julia<A, T>(
+    z: A,
+    c: A,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, A)
 where
+    A: Algebra<T>,
     T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
 {
     let four = T::from(4.0).unwrap();
     let mut z = z;
+    let mut cycle = periodicity.map(|p| CycleDetector::new(p, z.to_plane()));
     let mut n = 0;
 
-    while z.norm_sqr() < four && n < max_iter {
+    while z.escape_norm_sqr() < four && n < max_iter {
         z = z * z + c;
         n += 1;
+        visit(z.to_plane());
+
+        if cycle
+            .as_mut()
+            .is_some_and(|cycle| cycle.is_periodic(z.to_plane()))
+        {
+            return (max_iter, z);
+        }
     }
 
-    n
+    (n, z)
+}
+
+/// Iterates `z → z² + c` in the given number system, returning the iteration count and the final value of `z` as a point of the plane.
+fn julia_over<T>(
+    system: NumberSystem,
+    z: Complex<T>,
+    c: Complex<T>,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
+where
+    T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
+{
+    match system {
+        NumberSystem::Complex => julia(z, c, max_iter, periodicity, visit),
+        NumberSystem::Dual => {
+            let (n, z) = julia(
+                DualNumber::from_plane(z),
+                DualNumber::from_plane(c),
+                max_iter,
+                periodicity,
+                visit,
+            );
+            (n, z.to_plane())
+        }
+        NumberSystem::SplitComplex => {
+            let (n, z) = julia(
+                SplitComplex::from_plane(z),
+                SplitComplex::from_plane(c),
+                max_iter,
+                periodicity,
+                visit,
+            );
+            (n, z.to_plane())
+        }
+    }
 }
 
 #[inline(always)]
-pub fn tricorn<T>(c: Complex<T>, max_iter: u32) -> u32
+pub fn tricorn<T>(
+    c: Complex<T>,
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
 where
     T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
 {
@@ -100,33 +810,208 @@ where
     let mut n = 0;
 
     while z.norm_sqr() < four && n < max_iter {
-        z = Complex::new(z.real, -z.imag) * Complex::new(z.real, -z.imag) + c;
+        z = tricorn_step(z, c);
         n += 1;
+        visit(z);
     }
 
-    n
+    (n, z)
 }
 
 #[inline(always)]
-pub fn multibrot<T>(c: Complex<T>, power: u32, max_iter: u32) -> u32
+pub fn multibrot<T>(
+    c: Complex<T>,
+    power: u32,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
 where
     T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
 {
     let zero = T::zero();
     let four = T::from(4.0).unwrap();
     let mut z = Complex::new(zero, zero);
+    let mut cycle = periodicity.map(|p| CycleDetector::new(p, z));
     let mut n = 0;
 
     while z.norm_sqr() < four && n < max_iter {
-        z = z.powi(power) + c;
+        z = multibrot_step(z, c, power);
         n += 1;
+        visit(z);
+
+        if cycle.as_mut().is_some_and(|cycle| cycle.is_periodic(z)) {
+            return (max_iter, z);
+        }
+    }
+
+    (n, z)
+}
+
+/// Iterates `z → conj(z)^power + c` from zero.
+#[inline(always)]
+pub fn multicorn<T>(
+    c: Complex<T>,
+    power: u32,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
+where
+    T: Float,
+{
+    let zero = T::zero();
+    let four = T::from(4.0).unwrap();
+    let mut z = Complex::new(zero, zero);
+    let mut cycle = periodicity.map(|p| CycleDetector::new(p, z));
+    let mut n = 0;
+
+    while z.norm_sqr() < four && n < max_iter {
+        z = multicorn_step(z, c, power);
+        n += 1;
+        visit(z);
+
+        if cycle.as_mut().is_some_and(|cycle| cycle.is_periodic(z)) {
+            return (max_iter, z);
+        }
+    }
+
+    (n, z)
+}
+
+/// Iterates `z → map(z) + c` from `z` until it reaches the map's escape radius, or a step moves it by less than the map's tolerance.
+/// Orbits which land on a pole become infinite, and count as escaped.
+#[inline(always)]
+fn rational<T>(
+    map: &RationalMap<T>,
+    z: Complex<T>,
+    c: Complex<T>,
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
+where
+    T: Float,
+{
+    let escape_radius_sqr = map.escape_radius * map.escape_radius;
+    let tolerance_sqr = map.tolerance * map.tolerance;
+    let mut z = z;
+    let mut n = 0;
+
+    while z.norm_sqr() < escape_radius_sqr && n < max_iter {
+        let next = map.evaluate(z) + c;
+        let step = next - z;
+        z = next;
+        n += 1;
+        visit(z);
+
+        if step.norm_sqr() < tolerance_sqr {
+            break;
+        }
+    }
+
+    (n, z)
+}
+
+/// Iterates the Collatz map from `z`, with `collatz_step`.
+#[inline(always)]
+fn collatz<T>(
+    z: Complex<T>,
+    bailout: T,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
+where
+    T: Float,
+{
+    let mut z = z;
+    let mut cycle = periodicity.map(|p| CycleDetector::new(p, z));
+    let mut n = 0;
+
+    // Orbits which overflow become NaN, and count as escaped
+    while z.imag.abs() < bailout && n < max_iter {
+        z = collatz_step(z);
+        n += 1;
+        visit(z);
+
+        if cycle.as_mut().is_some_and(|cycle| cycle.is_periodic(z)) {
+            return (max_iter, z);
+        }
+    }
+
+    (n, z)
+}
+
+/// Iterates the logistic map `z → λz(1 − z)` from `z`.
+/// Orbits escape once `|λ(1/2 − z)| > 2`, the escape radius of the conjugate quadratic map, so counts match those of the Mandelbrot set.
+#[inline(always)]
+fn lambda<T>(
+    z: Complex<T>,
+    lambda: Complex<T>,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
+where
+    T: Float,
+{
+    let half = Complex::new(T::from(0.5).unwrap(), T::zero());
+    let four = T::from(4.0).unwrap();
+    let mut z = z;
+    let mut cycle = periodicity.map(|p| CycleDetector::new(p, z));
+    let mut n = 0;
+
+    while (lambda * (half - z)).norm_sqr() < four && n < max_iter {
+        z = lambda_step(z, lambda);
+        n += 1;
+        visit(z);
+
+        if cycle.as_mut().is_some_and(|cycle| cycle.is_periodic(z)) {
+            return (max_iter, z);
+        }
+    }
+
+    (n, z)
+}
+
+/// Iterates `z → pow(z) + c` from zero, for Multibrot sets of non-integer powers.
+#[inline(always)]
+pub fn multibrot_with<T>(
+    c: Complex<T>,
+    pow: impl Fn(Complex<T>) -> Complex<T>,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
+where
+    T: Float,
+{
+    let zero = T::zero();
+    let four = T::from(4.0).unwrap();
+    let mut z = Complex::new(zero, zero);
+    let mut cycle = periodicity.map(|p| CycleDetector::new(p, z));
+    let mut n = 0;
+
+    while z.norm_sqr() < four && n < max_iter {
+        z = pow(z) + c;
+        n += 1;
+        visit(z);
+
+        if cycle.as_mut().is_some_and(|cycle| cycle.is_periodic(z)) {
+            return (max_iter, z);
+        }
     }
 
-    n
+    (n, z)
 }
 
 #[inline(always)]
-pub fn newton<T>(c: Complex<T>, epsilon: T, max_iter: u32) -> u32
+pub fn newton<T>(
+    c: Complex<T>,
+    epsilon: T,
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
 where
     T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
 {
@@ -134,10 +1019,9 @@ where
     let mut n = 0;
 
     while n < max_iter {
-        let f = z * z * z - Complex::new(T::one(), T::zero());
-        let df = Complex::new(T::from(3.0).unwrap(), T::zero()) * z * z;
-        let dz = f / df;
-        z = z - dz;
+        let (next, dz) = newton_step(z);
+        z = next;
+        visit(z);
 
         if dz.norm_sqr() < epsilon {
             break;
@@ -146,11 +1030,16 @@ where
         n += 1;
     }
 
-    n
+    (n, z)
 }
 
 #[inline(always)]
-pub fn phoenix<T>(p: Complex<T>, c: Complex<T>, max_iter: u32) -> u32
+pub fn phoenix<T>(
+    p: Complex<T>,
+    c: Complex<T>,
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
 where
     T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
 {
@@ -160,16 +1049,21 @@ where
 
     while z.norm_sqr() < T::from(4.0).unwrap() && n < max_iter {
         let temp = z;
-        z = z * z + c * z_old + p;
+        z = phoenix_step(z, z_old, p, c);
         z_old = temp;
         n += 1;
+        visit(z);
     }
 
-    n
+    (n, z)
 }
 
 #[inline(always)]
-fn celtic_mandelbrot<T>(c: Complex<T>, max_iter: u32) -> u32
+fn celtic_mandelbrot<T>(
+    c: Complex<T>,
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
 where
     T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
 {
@@ -178,12 +1072,9 @@ where
     let mut z = Complex::new(zero, zero);
     let mut n = 0;
     while z.norm_sqr() < four && n < max_iter {
-        // Absolute value applied to the real part difference
-        z = Complex::new(
-            (z.real * z.real - z.imag * z.imag).abs(),
-            T::from(2.0).unwrap() * z.real * z.imag,
-        ) + c;
+        z = celtic_step(z, c);
         n += 1;
+        visit(z);
     }
-    n
+    (n, z)
 }
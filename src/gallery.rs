@@ -0,0 +1,448 @@
+use ndarray::{s, Array2};
+use palette::LinSrgba;
+
+use crate::{
+    error::check_count, post_process, render_attractor_pixels, render_attractor_viewport,
+    render_bifurcation, render_fractal_viewport, render_ifs, render_lsystem,
+    rng_strategy::GALLERY_DOMAIN, Attractor, AttractorParameter, ColourMap, Complex, EscapeMap,
+    Fractal, HybridSequence, HybridStep, Ifs, LSystem, MandybrotError, Normalisation, NumberSystem,
+    PlaneAxis, Polynomial, PostOp, Quaternion, QuaternionAxis, RationalMap, RngStrategy,
+    SamplePattern, SlicePoint, Viewport,
+};
+
+/// One labelled thumbnail of a contact sheet.
+#[derive(Debug, Clone)]
+pub struct GalleryItem {
+    pub name: String,
+    pub image: Array2<LinSrgba>,
+}
+
+/// Number of starting points drawn for each attractor thumbnail.
+const ATTRACTOR_SAMPLES: u32 = 2000;
+
+/// Number of points of a single orbit by which each attractor thumbnail is framed.
+const ATTRACTOR_FIT_ITERATIONS: u32 = 10_000;
+
+/// Number of chaos game samples drawn for each iterated function system thumbnail, and the points of each.
+const IFS_SAMPLES: u32 = 2000;
+const IFS_ITERATIONS: u32 = 100;
+
+/// Number of rewrites of each L-system thumbnail.
+const LSYSTEM_DEPTH: u32 = 4;
+
+/// Renders a thumbnail of every variant of `Fractal` and `Attractor`, and of every built-in iterated function system and L-system, coloured with `colour_map`.
+/// Fractals are drawn at their `default_viewport` with classic parameters, attractors with their `default_parameters` and the logistic map as a bifurcation diagram.
+/// Every render is seeded, so the thumbnails are the same each time and can be compared after a change.
+pub fn gallery_items(
+    thumbnail_resolution: [u32; 2],
+    colour_map: &ColourMap,
+) -> Result<Vec<GalleryItem>, MandybrotError> {
+    let escape_time = [
+        PostOp::Normalise(Normalisation::Linear),
+        PostOp::Gamma(0.5),
+        PostOp::ColourMap(String::new()),
+    ];
+    let density = [
+        PostOp::Normalise(Normalisation::Log),
+        PostOp::ColourMap(String::new()),
+    ];
+    let path = [
+        PostOp::Normalise(Normalisation::Linear),
+        PostOp::ColourMap(String::new()),
+    ];
+    let colour = |values: &Array2<u32>, pipeline: &[PostOp<f64>]| {
+        post_process(values, pipeline, |_| Some(colour_map.clone()))
+    };
+    let rng_strategy = RngStrategy::Seeded(0);
+    let mut items = Vec::new();
+
+    for (name, fractal) in fractals()? {
+        let location = fractal.default_viewport();
+        let image = render_fractal_viewport(
+            &location.viewport(thumbnail_resolution),
+            &fractal,
+            location.max_iter,
+            1,
+            SamplePattern::Grid,
+            None,
+        )?;
+        items.push(GalleryItem {
+            name: name.to_string(),
+            image: colour(&image, &escape_time)?,
+        });
+    }
+
+    let start = Complex::new(0.1, 0.1);
+    let (max_iter, draw_after) = (200, 10);
+    for attractor in attractors() {
+        let image = match attractor {
+            Attractor::Logistic { .. } => render_bifurcation(
+                &Viewport::new(Complex::new(3.4, 0.5), 1.0, thumbnail_resolution),
+                &attractor,
+                AttractorParameter::R,
+                Complex::new(0.5, 0.0),
+                0.1,
+                8,
+                1000,
+                500,
+                &rng_strategy,
+            )?,
+            Attractor::Popcorn { .. } => render_attractor_pixels(
+                &Viewport::new(Complex::new(0.0, 0.0), 4.0, thumbnail_resolution),
+                2,
+                50,
+                0,
+                &attractor,
+            )?,
+            _ => {
+                let orbit = attractor.orbit(start, ATTRACTOR_FIT_ITERATIONS);
+                let viewport = Viewport::fit(
+                    orbit.into_iter().skip(draw_after as usize),
+                    thumbnail_resolution,
+                )
+                .unwrap_or_else(|| Viewport::new(start, 1.0, thumbnail_resolution));
+                render_attractor_viewport(
+                    &viewport,
+                    start,
+                    0.1,
+                    ATTRACTOR_SAMPLES,
+                    max_iter,
+                    draw_after,
+                    &attractor,
+                    &rng_strategy,
+                )?
+            }
+        };
+        items.push(GalleryItem {
+            name: attractor_name(&attractor).to_string(),
+            image: colour(&image, &density)?,
+        });
+    }
+
+    for name in Ifs::<f64>::presets() {
+        let ifs = Ifs::<f64>::preset(name).expect("listed presets exist");
+        let mut rng = rng_strategy.stream(GALLERY_DOMAIN, 0);
+        let points = ifs
+            .chaos_game(Complex::new(0.0, 0.0), &mut rng)
+            .skip(IFS_ITERATIONS as usize)
+            .take((IFS_SAMPLES * IFS_ITERATIONS) as usize);
+        let viewport = Viewport::fit(points, thumbnail_resolution)
+            .expect("the chaos game visits at least one point");
+        let image = render_ifs(&viewport, &ifs, IFS_SAMPLES, IFS_ITERATIONS, &rng_strategy)?;
+        items.push(GalleryItem {
+            name: name.to_string(),
+            image: colour(&image, &density)?,
+        });
+    }
+
+    for name in LSystem::<f64>::presets() {
+        let lsystem = LSystem::<f64>::preset(name).expect("listed presets exist");
+        let viewport = lsystem.fit_viewport(LSYSTEM_DEPTH, thumbnail_resolution)?;
+        let image = render_lsystem(&viewport, &lsystem, LSYSTEM_DEPTH)?;
+        items.push(GalleryItem {
+            name: name.to_string(),
+            image: colour(&image, &path)?,
+        });
+    }
+
+    Ok(items)
+}
+
+/// One fractal of each variant, with classic parameters where it has any.
+fn fractals() -> Result<Vec<(&'static str, Fractal<f64>)>, MandybrotError> {
+    let cubic = Polynomial::new(&[
+        Complex::new(-1.0, 0.0),
+        Complex::new(0.0, 0.0),
+        Complex::new(0.0, 0.0),
+        Complex::new(1.0, 0.0),
+    ])?;
+    let quaternion_c = Quaternion::new(-0.2, 0.4, -0.4, -0.4);
+    let quaternion_axes = [QuaternionAxis::Real, QuaternionAxis::I];
+    let quaternion_zero = Quaternion::new(0.0, 0.0, 0.0, 0.0);
+
+    Ok(vec![
+        ("mandelbrot", Fractal::Mandelbrot),
+        ("burning_ship", Fractal::BurningShip),
+        (
+            "julia",
+            Fractal::Julia {
+                c: Complex::new(-0.8, 0.156),
+            },
+        ),
+        ("tricorn", Fractal::Tricorn),
+        ("multibrot", Fractal::Multibrot { power: 3 }),
+        ("multicorn", Fractal::Multicorn { power: 3 }),
+        ("real_multibrot", Fractal::RealMultibrot { power: 3.5 }),
+        (
+            "complex_multibrot",
+            Fractal::ComplexMultibrot {
+                power: Complex::new(2.0, 0.2),
+            },
+        ),
+        ("newton", Fractal::Newton { epsilon: 1e-4 }),
+        (
+            "phoenix",
+            Fractal::Phoenix {
+                c: Complex::new(-0.2, 1.0),
+            },
+        ),
+        ("celtic_mandelbrot", Fractal::CelticMandelbrot),
+        ("collatz", Fractal::Collatz { bailout: 10.0 }),
+        ("lambda", Fractal::Lambda),
+        (
+            "lambda_julia",
+            Fractal::LambdaJulia {
+                lambda: Complex::new(3.2, 0.1),
+            },
+        ),
+        (
+            "split_mandelbrot",
+            Fractal::MandelbrotOver {
+                system: NumberSystem::SplitComplex,
+            },
+        ),
+        (
+            "split_julia",
+            Fractal::JuliaOver {
+                system: NumberSystem::SplitComplex,
+                c: Complex::new(-0.8, 0.156),
+            },
+        ),
+        (
+            "rational",
+            Fractal::Rational {
+                map: RationalMap::newton(&cubic, 1e-6).expect("a cubic fits a rational map"),
+                start: None,
+            },
+        ),
+        (
+            "slice",
+            Fractal::Slice {
+                map: EscapeMap::Phoenix,
+                axes: [PlaneAxis::Z0Real, PlaneAxis::Z0Imag],
+                fixed: SlicePoint {
+                    c: Complex::new(0.5667, 0.0),
+                    z0: Complex::new(0.0, 0.0),
+                    p: Complex::new(-0.5, 0.0),
+                },
+            },
+        ),
+        (
+            "hybrid",
+            Fractal::Hybrid {
+                sequence: HybridSequence::new(&[
+                    HybridStep::Mandelbrot,
+                    HybridStep::Mandelbrot,
+                    HybridStep::BurningShip,
+                ])?,
+            },
+        ),
+        (
+            "quaternion_mandelbrot",
+            Fractal::QuaternionMandelbrot {
+                axes: quaternion_axes,
+                fixed: quaternion_zero,
+            },
+        ),
+        (
+            "quaternion_julia",
+            Fractal::QuaternionJulia {
+                c: quaternion_c,
+                axes: quaternion_axes,
+                fixed: quaternion_zero,
+            },
+        ),
+    ])
+}
+
+/// One attractor of each variant, with its default parameters.
+fn attractors() -> impl Iterator<Item = Attractor<f64>> {
+    let zero = 0.0;
+    [
+        Attractor::Clifford {
+            a: zero,
+            b: zero,
+            c: zero,
+            d: zero,
+        },
+        Attractor::DeJong {
+            a: zero,
+            b: zero,
+            c: zero,
+            d: zero,
+        },
+        Attractor::Henon { a: zero, b: zero },
+        Attractor::Ikeda { u: zero },
+        Attractor::Tinkerbell {
+            a: zero,
+            b: zero,
+            c: zero,
+            d: zero,
+        },
+        Attractor::Logistic { r: zero },
+        Attractor::Popcorn { h: zero },
+        Attractor::Hopalong {
+            a: zero,
+            b: zero,
+            c: zero,
+        },
+        Attractor::Martin { a: zero },
+    ]
+    .into_iter()
+    .map(|attractor| attractor.default_parameters())
+}
+
+fn attractor_name<T>(attractor: &Attractor<T>) -> &'static str {
+    match attractor {
+        Attractor::Clifford { .. } => "clifford",
+        Attractor::DeJong { .. } => "de_jong",
+        Attractor::Henon { .. } => "henon",
+        Attractor::Ikeda { .. } => "ikeda",
+        Attractor::Tinkerbell { .. } => "tinkerbell",
+        Attractor::Logistic { .. } => "logistic",
+        Attractor::Popcorn { .. } => "popcorn",
+        Attractor::Hopalong { .. } => "hopalong",
+        Attractor::Martin { .. } => "martin",
+    }
+}
+
+/// Empty space around each thumbnail and its label, in pixels.
+const PADDING: usize = 4;
+
+/// Size of each pixel of the label font, in image pixels.
+const LABEL_SCALE: usize = 2;
+
+/// Lays out thumbnails in a grid of `columns`, left to right then top to bottom, each labelled with its name in `text` beneath it, over a `background`.
+/// Labels are written in capitals, with underscores as spaces, and cut short at the width of their thumbnail.
+pub fn contact_sheet(
+    items: &[GalleryItem],
+    columns: u32,
+    background: LinSrgba,
+    text: LinSrgba,
+) -> Result<Array2<LinSrgba>, MandybrotError> {
+    check_count("columns", columns)?;
+    let columns = columns as usize;
+    let rows = items.len().div_ceil(columns);
+    let (cell_height, cell_width) = items.iter().fold((0, 0), |(height, width), item| {
+        let (h, w) = item.image.dim();
+        (height.max(h), width.max(w))
+    });
+    let label_height = GLYPH_HEIGHT * LABEL_SCALE + PADDING;
+    let row_height = cell_height + label_height + PADDING;
+
+    let mut sheet = Array2::from_elem(
+        (
+            PADDING + rows * row_height,
+            PADDING + columns * (cell_width + PADDING),
+        ),
+        background,
+    );
+    for (index, item) in items.iter().enumerate() {
+        let (height, width) = item.image.dim();
+        let y = PADDING + (index / columns) * row_height;
+        let x = PADDING + (index % columns) * (cell_width + PADDING);
+        sheet
+            .slice_mut(s![y..y + height, x..x + width])
+            .assign(&item.image);
+        draw_label(
+            &mut sheet,
+            &item.name,
+            [x, y + height + PADDING],
+            width,
+            text,
+        );
+    }
+
+    Ok(sheet)
+}
+
+/// Renders every thumbnail of `gallery_items` and lays them out in a labelled contact sheet of `columns`, as `contact_sheet`, over black.
+pub fn render_gallery(
+    thumbnail_resolution: [u32; 2],
+    columns: u32,
+    colour_map: &ColourMap,
+) -> Result<Array2<LinSrgba>, MandybrotError> {
+    check_count("columns", columns)?;
+    let items = gallery_items(thumbnail_resolution, colour_map)?;
+    contact_sheet(
+        &items,
+        columns,
+        LinSrgba::new(0.0, 0.0, 0.0, 1.0),
+        LinSrgba::new(1.0, 1.0, 1.0, 1.0),
+    )
+}
+
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+
+/// Writes `label` with its top left corner at `[x, y]`, stopping before it would pass `max_width` pixels.
+fn draw_label(
+    image: &mut Array2<LinSrgba>,
+    label: &str,
+    [x, y]: [usize; 2],
+    max_width: usize,
+    colour: LinSrgba,
+) {
+    let advance = (GLYPH_WIDTH + 1) * LABEL_SCALE;
+    let fits = (max_width + LABEL_SCALE) / advance;
+    for (index, character) in label.chars().take(fits).enumerate() {
+        let left = x + index * advance;
+        for (row, bits) in glyph(character).into_iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits >> (GLYPH_WIDTH - 1 - column) & 1 == 1 {
+                    let top = y + row * LABEL_SCALE;
+                    let left = left + column * LABEL_SCALE;
+                    image
+                        .slice_mut(s![top..top + LABEL_SCALE, left..left + LABEL_SCALE])
+                        .fill(colour);
+                }
+            }
+        }
+    }
+}
+
+/// Rows of a character in a three by five pixel font, top to bottom, with the leftmost pixel in the highest bit.
+/// Letters are drawn as capitals, and characters without a glyph, such as underscores, as spaces.
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT] {
+    match character.to_ascii_uppercase() {
+        'A' => [2, 5, 7, 5, 5],
+        'B' => [6, 5, 6, 5, 6],
+        'C' => [3, 4, 4, 4, 3],
+        'D' => [6, 5, 5, 5, 6],
+        'E' => [7, 4, 6, 4, 7],
+        'F' => [7, 4, 6, 4, 4],
+        'G' => [3, 4, 5, 5, 3],
+        'H' => [5, 5, 7, 5, 5],
+        'I' => [7, 2, 2, 2, 7],
+        'J' => [1, 1, 1, 5, 2],
+        'K' => [5, 5, 6, 5, 5],
+        'L' => [4, 4, 4, 4, 7],
+        'M' => [5, 7, 7, 5, 5],
+        'N' => [6, 5, 5, 5, 5],
+        'O' => [2, 5, 5, 5, 2],
+        'P' => [6, 5, 6, 4, 4],
+        'Q' => [2, 5, 5, 6, 3],
+        'R' => [6, 5, 6, 5, 5],
+        'S' => [3, 4, 2, 1, 6],
+        'T' => [7, 2, 2, 2, 2],
+        'U' => [5, 5, 5, 5, 7],
+        'V' => [5, 5, 5, 5, 2],
+        'W' => [5, 5, 7, 7, 5],
+        'X' => [5, 5, 2, 5, 5],
+        'Y' => [5, 5, 2, 2, 2],
+        'Z' => [7, 1, 2, 4, 7],
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [6, 1, 2, 4, 7],
+        '3' => [6, 1, 2, 1, 6],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 6, 1, 6],
+        '6' => [3, 4, 6, 5, 2],
+        '7' => [7, 1, 2, 2, 2],
+        '8' => [2, 5, 2, 5, 2],
+        '9' => [2, 5, 3, 1, 6],
+        '-' => [0, 0, 7, 0, 0],
+        '.' => [0, 0, 0, 0, 2],
+        _ => [0; GLYPH_HEIGHT],
+    }
+}
@@ -0,0 +1,444 @@
+use core::f32::consts::PI;
+use palette::{LinSrgba, Srgba};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{ColourMap, MandybrotError};
+
+/// Number of evenly spaced colours an imported gradient is sampled at.
+const GRADIENT_SAMPLES: usize = 256;
+
+/// Number of positions along an UltraFractal gradient, which wraps around from the last back to the first.
+const UGR_LENGTH: u32 = 400;
+
+/// File format of a gradient, as written by fractal and graphics programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GradientFormat {
+    /// GIMP gradient, `.ggr`, of segments each blended between two colours.
+    /// Segments blended in HSV are read as if blended in RGB.
+    Ggr,
+    /// UltraFractal gradient, `.ugr`, of colours at positions from 0 to 400 which wrap around. Only the first gradient of a file is read, ignoring its opacity.
+    Ugr,
+    /// GMT colour palette table, `.cpt`, of lines `z0 r0 g0 b0 z1 r1 g1 b1` with colours from 0 to 255.
+    Cpt,
+    /// Comma-separated rows of `red,green,blue` evenly spaced, or `position,red,green,blue` with an optional `alpha`, with colours from 0 to 255 and an optional header row.
+    Csv,
+}
+
+impl GradientFormat {
+    /// Format of a file, from its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, MandybrotError> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map_or(String::new(), |extension| {
+                extension.to_string_lossy().to_lowercase()
+            });
+        match extension.as_str() {
+            "ggr" => Ok(GradientFormat::Ggr),
+            "ugr" => Ok(GradientFormat::Ugr),
+            "cpt" => Ok(GradientFormat::Cpt),
+            "csv" => Ok(GradientFormat::Csv),
+            _ => Err(MandybrotError::UnsupportedFormat(extension)),
+        }
+    }
+
+    /// Parses a gradient written in this format, sampling it at evenly spaced colours.
+    /// Colours are interpolated in sRGB, as the programs which write these formats do.
+    pub fn parse(&self, text: &str) -> Result<ColourMap, MandybrotError> {
+        let sample: Box<dyn Fn(f32) -> [f32; 4]> = match self {
+            GradientFormat::Ggr => {
+                let segments = parse_ggr(text)?;
+                Box::new(move |t| sample_ggr(&segments, t))
+            }
+            GradientFormat::Ugr => {
+                let stops = parse_ugr(text)?;
+                Box::new(move |t| sample_stops(&stops, t))
+            }
+            GradientFormat::Cpt => {
+                let stops = parse_cpt(text)?;
+                Box::new(move |t| sample_stops(&stops, t))
+            }
+            GradientFormat::Csv => {
+                let stops = parse_csv(text)?;
+                Box::new(move |t| sample_stops(&stops, t))
+            }
+        };
+        ColourMap::new(
+            (0..GRADIENT_SAMPLES)
+                .map(|i| {
+                    let [red, green, blue, alpha] =
+                        sample(i as f32 / (GRADIENT_SAMPLES - 1) as f32);
+                    Srgba::new(red, green, blue, alpha).into_linear()
+                })
+                .collect(),
+        )
+    }
+
+    /// Writes a colour map in this format, as evenly spaced stops named `name` where the format has names.
+    pub fn write(
+        &self,
+        colour_map: &ColourMap,
+        name: &str,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        let colours: Vec<[f32; 4]> = colour_map.colours().iter().map(|&c| to_srgb(c)).collect();
+        let last = (colours.len() - 1).max(1) as f32;
+        match self {
+            GradientFormat::Ggr => {
+                writeln!(writer, "GIMP Gradient")?;
+                writeln!(writer, "Name: {}", name)?;
+                let segments = colours.len().saturating_sub(1).max(1);
+                writeln!(writer, "{}", segments)?;
+                for i in 0..segments {
+                    let (left, right) = (i as f32 / last, (i + 1) as f32 / last);
+                    let (a, b) = (colours[i], colours[(i + 1).min(colours.len() - 1)]);
+                    writeln!(
+                        writer,
+                        "{:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} {:.6} 0 0",
+                        left,
+                        (left + right) / 2.0,
+                        right,
+                        a[0],
+                        a[1],
+                        a[2],
+                        a[3],
+                        b[0],
+                        b[1],
+                        b[2],
+                        b[3]
+                    )?;
+                }
+            }
+            GradientFormat::Ugr => {
+                // Long colour maps are resampled to fit the positions of the gradient.
+                let count = colours.len().min(UGR_LENGTH as usize);
+                let spacing = (count - 1).max(1) as f32;
+                writeln!(writer, "{} {{", name)?;
+                writeln!(writer, "gradient:")?;
+                writeln!(writer, "  title=\"{}\" smooth=no", name)?;
+                for i in 0..count {
+                    let [red, green, blue, _] =
+                        to_bytes(to_srgb(colour_map.sample(i as f32 / spacing)));
+                    let index = (i as f32 * (UGR_LENGTH - 1) as f32 / spacing).round() as u32;
+                    let colour = red as u32 | (green as u32) << 8 | (blue as u32) << 16;
+                    writeln!(writer, "  index={} color={}", index, colour)?;
+                }
+                writeln!(writer, "}}")?;
+            }
+            GradientFormat::Cpt => {
+                writeln!(writer, "# {}", name)?;
+                writeln!(writer, "# COLOR_MODEL = RGB")?;
+                for (i, pair) in colours.windows(2).enumerate() {
+                    let [r0, g0, b0, _] = to_bytes(pair[0]);
+                    let [r1, g1, b1, _] = to_bytes(pair[1]);
+                    writeln!(
+                        writer,
+                        "{:.6} {} {} {} {:.6} {} {} {}",
+                        i as f32 / last,
+                        r0,
+                        g0,
+                        b0,
+                        (i + 1) as f32 / last,
+                        r1,
+                        g1,
+                        b1
+                    )?;
+                }
+                if let [colour] = colours[..] {
+                    let [red, green, blue, _] = to_bytes(colour);
+                    writeln!(writer, "0 {red} {green} {blue} 1 {red} {green} {blue}")?;
+                }
+            }
+            GradientFormat::Csv => {
+                writeln!(writer, "position,red,green,blue,alpha")?;
+                for (i, &colour) in colours.iter().enumerate() {
+                    let [red, green, blue, alpha] = to_bytes(colour);
+                    writeln!(
+                        writer,
+                        "{:.6},{},{},{},{}",
+                        i as f32 / last,
+                        red,
+                        green,
+                        blue,
+                        alpha
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ColourMap {
+    /// Reads a gradient file, as GIMP, UltraFractal, GMT or CSV by its extension.
+    pub fn load_gradient(path: impl AsRef<Path>) -> Result<Self, MandybrotError> {
+        let path = path.as_ref();
+        GradientFormat::from_path(path)?.parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Writes the colour map as a gradient file, in the format given by its extension.
+    pub fn save_gradient(&self, path: impl AsRef<Path>, name: &str) -> Result<(), MandybrotError> {
+        let path = path.as_ref();
+        let format = GradientFormat::from_path(path)?;
+        let mut file = BufWriter::new(File::create(path)?);
+        format.write(self, name, &mut file)?;
+        Ok(file.flush()?)
+    }
+}
+
+/// Segment of a GIMP gradient, blending from its left colour to its right colour across `[left, right]`.
+struct GgrSegment {
+    left: f32,
+    middle: f32,
+    right: f32,
+    left_colour: [f32; 4],
+    right_colour: [f32; 4],
+    blending: u32,
+}
+
+fn parse_ggr(text: &str) -> Result<Vec<GgrSegment>, MandybrotError> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == "GIMP Gradient" => {}
+        _ => return Err(syntax(1, "expected 'GIMP Gradient'")),
+    }
+    let mut lines = lines.filter(|(_, line)| !line.trim().is_empty() && !line.starts_with("Name:"));
+    let (number, count) = lines
+        .next()
+        .ok_or_else(|| syntax(2, "expected the number of segments"))?;
+    let count: usize = count
+        .trim()
+        .parse()
+        .map_err(|_| syntax(number + 1, "expected the number of segments"))?;
+
+    let segments = lines
+        .take(count)
+        .map(|(number, line)| {
+            let values = parse_numbers(line, number)?;
+            if values.len() < 11 {
+                return Err(syntax(number + 1, "expected at least 11 numbers"));
+            }
+            Ok(GgrSegment {
+                left: values[0],
+                middle: values[1],
+                right: values[2],
+                left_colour: [values[3], values[4], values[5], values[6]],
+                right_colour: [values[7], values[8], values[9], values[10]],
+                blending: values.get(11).map_or(0, |&blending| blending as u32),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if segments.len() != count || count == 0 {
+        return Err(syntax(
+            text.lines().count().max(1),
+            "expected more segments",
+        ));
+    }
+    Ok(segments)
+}
+
+/// Colour of a GIMP gradient at position `t`, blending within the segment containing it.
+fn sample_ggr(segments: &[GgrSegment], t: f32) -> [f32; 4] {
+    let segment = segments
+        .iter()
+        .find(|segment| t <= segment.right)
+        .unwrap_or(&segments[segments.len() - 1]);
+    let length = segment.right - segment.left;
+    if length <= f32::EPSILON {
+        return segment.right_colour;
+    }
+    let position = ((t - segment.left) / length).clamp(0.0, 1.0);
+    let middle = ((segment.middle - segment.left) / length).clamp(0.0, 1.0);
+    let linear = if position <= middle {
+        if middle <= f32::EPSILON {
+            0.5
+        } else {
+            0.5 * position / middle
+        }
+    } else if 1.0 - middle <= f32::EPSILON {
+        1.0
+    } else {
+        0.5 + 0.5 * (position - middle) / (1.0 - middle)
+    };
+    let factor = match segment.blending {
+        1 => position.powf(0.5f32.ln() / middle.max(f32::EPSILON).ln()),
+        2 => ((linear * PI - PI / 2.0).sin() + 1.0) / 2.0,
+        3 => (1.0 - (linear - 1.0) * (linear - 1.0)).sqrt(),
+        4 => 1.0 - (1.0 - linear * linear).sqrt(),
+        5 if position < middle => 0.0,
+        5 => 1.0,
+        _ => linear,
+    };
+    lerp(segment.left_colour, segment.right_colour, factor)
+}
+
+fn parse_ugr(text: &str) -> Result<Vec<(f32, [f32; 4])>, MandybrotError> {
+    let mut stops = Vec::new();
+    let mut in_gradient = false;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        match line {
+            "gradient:" => in_gradient = true,
+            "opacity:" => in_gradient = false,
+            "}" if !stops.is_empty() => break,
+            _ if in_gradient => {
+                let value = |key: &str| {
+                    line.split_whitespace()
+                        .find_map(|token| token.strip_prefix(key))
+                        .map(|value| {
+                            value
+                                .parse::<u32>()
+                                .map_err(|_| syntax(number + 1, "expected a whole number"))
+                        })
+                        .transpose()
+                };
+                if let (Some(index), Some(colour)) = (value("index=")?, value("color=")?) {
+                    let [red, green, blue, _] = colour.to_le_bytes();
+                    stops.push((index as f32, from_bytes([red, green, blue, u8::MAX])));
+                }
+            }
+            _ => {}
+        }
+    }
+    if stops.is_empty() {
+        return Err(syntax(
+            text.lines().count(),
+            "expected a gradient of colours",
+        ));
+    }
+
+    // The gradient wraps around, so continue it past both ends for interpolation.
+    stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let length = UGR_LENGTH as f32;
+    let (first, last) = (stops[0], stops[stops.len() - 1]);
+    stops.insert(0, (last.0 - length, last.1));
+    stops.push((first.0 + length, first.1));
+    Ok(stops
+        .into_iter()
+        .map(|(index, colour)| (index / (length - 1.0), colour))
+        .collect())
+}
+
+fn parse_cpt(text: &str) -> Result<Vec<(f32, [f32; 4])>, MandybrotError> {
+    let mut stops = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', 'B', 'F', 'N']) {
+            continue;
+        }
+        let values = parse_numbers(&line.replace('/', " "), number)?;
+        if values.len() < 8 {
+            return Err(syntax(number + 1, "expected 'z0 r0 g0 b0 z1 r1 g1 b1'"));
+        }
+        for stop in [&values[0..4], &values[4..8]] {
+            let colour = [stop[1] / 255.0, stop[2] / 255.0, stop[3] / 255.0, 1.0];
+            stops.push((stop[0], colour));
+        }
+    }
+    normalise_positions(stops, text)
+}
+
+fn parse_csv(text: &str) -> Result<Vec<(f32, [f32; 4])>, MandybrotError> {
+    let mut rows = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let values: Result<Vec<f32>, _> = line.split(',').map(|v| v.trim().parse()).collect();
+        match values {
+            Ok(values) => rows.push((number, values)),
+            // A header may come before the first row.
+            Err(_) if rows.is_empty() => {}
+            Err(_) => return Err(syntax(number + 1, "expected numbers")),
+        }
+    }
+
+    let count = rows.len();
+    let stops = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, (number, values))| {
+            let (position, colour) = match values[..] {
+                [red, green, blue] => (
+                    i as f32 / (count - 1).max(1) as f32,
+                    [red, green, blue, 255.0],
+                ),
+                [position, red, green, blue] => (position, [red, green, blue, 255.0]),
+                [position, red, green, blue, alpha] => (position, [red, green, blue, alpha]),
+                _ => return Err(syntax(number + 1, "expected 3, 4 or 5 columns")),
+            };
+            Ok((position, colour.map(|c| c / 255.0)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    normalise_positions(stops, text)
+}
+
+/// Rescales the positions of stops onto `[0, 1]`, keeping their order.
+fn normalise_positions(
+    mut stops: Vec<(f32, [f32; 4])>,
+    text: &str,
+) -> Result<Vec<(f32, [f32; 4])>, MandybrotError> {
+    if stops.is_empty() {
+        return Err(syntax(
+            text.lines().count().max(1),
+            "expected at least one colour",
+        ));
+    }
+    stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (min, max) = (stops[0].0, stops[stops.len() - 1].0);
+    let range = if max > min { max - min } else { 1.0 };
+    Ok(stops
+        .into_iter()
+        .map(|(position, colour)| ((position - min) / range, colour))
+        .collect())
+}
+
+/// Colour at position `t` of stops sorted by position, interpolating between the stops either side.
+fn sample_stops(stops: &[(f32, [f32; 4])], t: f32) -> [f32; 4] {
+    let next = stops.partition_point(|&(position, _)| position <= t);
+    match (next.checked_sub(1).map(|i| stops[i]), stops.get(next)) {
+        (Some((a, left)), Some(&(b, right))) if b > a => lerp(left, right, (t - a) / (b - a)),
+        (Some((_, colour)), _) | (None, Some(&(_, colour))) => colour,
+        (None, None) => [0.0, 0.0, 0.0, 1.0],
+    }
+}
+
+fn lerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+fn parse_numbers(line: &str, number: usize) -> Result<Vec<f32>, MandybrotError> {
+    line.split_whitespace()
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| syntax(number + 1, "expected a number"))
+        })
+        .collect()
+}
+
+fn to_srgb(colour: LinSrgba) -> [f32; 4] {
+    let colour = Srgba::from_linear(colour);
+    [colour.red, colour.green, colour.blue, colour.alpha]
+}
+
+fn to_bytes(colour: [f32; 4]) -> [u8; 4] {
+    colour.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+fn from_bytes(colour: [u8; 4]) -> [f32; 4] {
+    colour.map(|c| c as f32 / 255.0)
+}
+
+fn syntax(line: usize, message: &str) -> MandybrotError {
+    MandybrotError::GradientSyntax {
+        line,
+        message: message.to_string(),
+    }
+}
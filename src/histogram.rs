@@ -0,0 +1,290 @@
+use core::ops::{Add, AddAssign};
+use ndarray::{s, Array2};
+use num_traits::Float;
+
+use crate::{
+    error::check_count, render::par_map_pixels, Fractal, Location, MandybrotError, Periodicity,
+    Viewport,
+};
+
+/// Number of pixels escaping after each number of iterations, for analysing a render without drawing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IterationHistogram {
+    /// Number of pixels whose orbits escaped after each number of iterations, from zero up to `max_iter - 1`.
+    pub counts: Vec<u64>,
+    /// Number of pixels whose orbits did not escape within `max_iter` iterations.
+    pub interior: u64,
+}
+
+impl IterationHistogram {
+    /// Histogram with no pixels, of counts up to `max_iter`.
+    pub fn new(max_iter: u32) -> Self {
+        Self {
+            counts: vec![0; max_iter as usize],
+            interior: 0,
+        }
+    }
+
+    /// Histogram of iteration counts, such as the samples of a `SampleBuffer` or a slice of them.
+    pub fn from_samples<'a>(samples: impl IntoIterator<Item = &'a u32>, max_iter: u32) -> Self {
+        let mut histogram = Self::new(max_iter);
+        for &n in samples {
+            histogram.insert(n);
+        }
+        histogram
+    }
+
+    /// Adds one pixel which escaped after `n` iterations, or did not escape if `n` is at least `max_iter`.
+    pub fn insert(&mut self, n: u32) {
+        match self.counts.get_mut(n as usize) {
+            Some(count) => *count += 1,
+            None => self.interior += 1,
+        }
+    }
+
+    pub fn max_iter(&self) -> u32 {
+        self.counts.len() as u32
+    }
+
+    pub fn num_pixels(&self) -> u64 {
+        self.escaped() + self.interior
+    }
+
+    /// Number of pixels whose orbits escaped.
+    pub fn escaped(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Fraction of pixels whose orbits escaped, or zero for an empty histogram.
+    pub fn escape_ratio(&self) -> f64 {
+        match self.num_pixels() {
+            0 => 0.0,
+            num_pixels => self.escaped() as f64 / num_pixels as f64,
+        }
+    }
+
+    /// Fewest iterations after which any pixel escaped.
+    pub fn min(&self) -> Option<u32> {
+        self.counts
+            .iter()
+            .position(|&count| count > 0)
+            .map(|n| n as u32)
+    }
+
+    /// Most iterations after which any pixel escaped.
+    pub fn max(&self) -> Option<u32> {
+        self.counts
+            .iter()
+            .rposition(|&count| count > 0)
+            .map(|n| n as u32)
+    }
+
+    /// Mean number of iterations of the pixels which escaped.
+    pub fn mean(&self) -> Option<f64> {
+        let escaped = self.escaped();
+        let total: f64 = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(n, &count)| n as f64 * count as f64)
+            .sum();
+        (escaped > 0).then(|| total / escaped as f64)
+    }
+
+    /// Fewest iterations within which at least the fraction `q` of the escaping pixels escaped, such as to choose the range of a normalisation ignoring outliers.
+    pub fn quantile(&self, q: f64) -> Option<u32> {
+        let escaped = self.escaped();
+        if escaped == 0 || q.is_nan() {
+            return None;
+        }
+        let target = ((q.clamp(0.0, 1.0) * escaped as f64).ceil() as u64).max(1);
+        let mut total = 0;
+        self.counts
+            .iter()
+            .position(|&count| {
+                total += count;
+                total >= target
+            })
+            .map(|n| n as u32)
+    }
+
+    /// Number of different outcomes among the pixels, counting the interior as one, where a viewport showing a single outcome is empty of detail.
+    pub fn distinct(&self) -> usize {
+        self.counts.iter().filter(|&&count| count > 0).count() + (self.interior > 0) as usize
+    }
+
+    /// Shannon entropy of the outcomes in bits, counting the interior as one, which is greater for viewports with more varied detail.
+    pub fn entropy(&self) -> f64 {
+        let num_pixels = self.num_pixels() as f64;
+        self.counts
+            .iter()
+            .chain([&self.interior])
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / num_pixels;
+                p * p.recip().log2()
+            })
+            .sum()
+    }
+}
+
+/// Combines two histograms, keeping the larger `max_iter`.
+impl AddAssign<&IterationHistogram> for IterationHistogram {
+    fn add_assign(&mut self, other: &IterationHistogram) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.interior += other.interior;
+    }
+}
+
+impl Add for IterationHistogram {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += &other;
+        self
+    }
+}
+
+/// Samples a fractal once at each pixel centre and returns the histogram of the iteration counts, without colouring an image.
+pub fn render_histogram<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<IterationHistogram, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    let samples = render_counts(viewport, fractal, max_iter, periodicity)?;
+    Ok(IterationHistogram::from_samples(&samples, max_iter))
+}
+
+/// Samples a fractal once at each pixel centre and returns the histogram of the iteration counts of each `tile_size` tile, with rows of tiles down the first axis.
+/// Tiles at the right and bottom edges are smaller if the tile size does not divide the resolution.
+pub fn render_tile_histograms<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+    tile_size: [u32; 2],
+) -> Result<Array2<IterationHistogram>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    check_count("tile_size[0]", tile_size[0])?;
+    check_count("tile_size[1]", tile_size[1])?;
+    let samples = render_counts(viewport, fractal, max_iter, periodicity)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let [tile_width, tile_height] = tile_size;
+    let shape = (
+        y_res.div_ceil(tile_height) as usize,
+        x_res.div_ceil(tile_width) as usize,
+    );
+    Ok(Array2::from_shape_fn(shape, |(row, column)| {
+        let (x, y) = (column * tile_width as usize, row * tile_height as usize);
+        let tile = samples.slice(s![
+            y..(y + tile_height as usize).min(y_res as usize),
+            x..(x + tile_width as usize).min(x_res as usize)
+        ]);
+        IterationHistogram::from_samples(tile, max_iter)
+    }))
+}
+
+/// Viewport zoomed into the most detailed `tile_size` tile of `viewport`, whose iteration counts have the greatest entropy, as the next view of an automatic zoom.
+/// Ties go to the tile nearest the centre of the view, and `None` is returned if no tile shows any detail.
+pub fn find_zoom_target<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+    tile_size: [u32; 2],
+) -> Result<Option<Viewport<T>>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    let tiles = render_tile_histograms(viewport, fractal, max_iter, periodicity, tile_size)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let [tile_width, tile_height] = tile_size;
+    let centre_distance = |row: usize, column: usize| {
+        let x = (column as f64 + 0.5) * tile_width as f64 - x_res as f64 / 2.0;
+        let y = (row as f64 + 0.5) * tile_height as f64 - y_res as f64 / 2.0;
+        x * x + y * y
+    };
+    let best = tiles
+        .indexed_iter()
+        .map(|((row, column), histogram)| (row, column, histogram.entropy()))
+        .filter(|&(_, _, entropy)| entropy > 0.0)
+        .max_by(|a, b| {
+            let nearer = centre_distance(b.0, b.1).total_cmp(&centre_distance(a.0, a.1));
+            a.2.total_cmp(&b.2).then(nearer)
+        });
+
+    Ok(best.map(|(row, column, _)| {
+        let origin = [column as u32 * tile_width, row as u32 * tile_height];
+        let size = [
+            tile_width.min(x_res - origin[0]),
+            tile_height.min(y_res - origin[1]),
+        ];
+        let tile = viewport.crop(origin, size);
+        let zoom = T::from(tile_height.min(y_res)).unwrap() / T::from(y_res).unwrap();
+        Viewport {
+            centre: tile.centre,
+            scale: viewport.scale * zoom,
+            ..*viewport
+        }
+    }))
+}
+
+/// Follows `find_zoom_target` down from `viewport` for up to `num_steps` steps, for a fully automatic zoom.
+/// Returns the framing of each step, ending early if a view shows no detail.
+/// The last centre makes a target for a `ZoomPath`, to render the whole zoom as a video.
+pub fn plan_auto_zoom<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+    tile_size: [u32; 2],
+    num_steps: u32,
+) -> Result<Vec<Location<T>>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    let mut view = *viewport;
+    let mut steps = Vec::with_capacity(num_steps as usize);
+    for _ in 0..num_steps {
+        match find_zoom_target(&view, fractal, max_iter, periodicity, tile_size)? {
+            Some(next) => view = next,
+            None => break,
+        }
+        steps.push(Location::new(view.centre, view.scale, max_iter));
+    }
+    Ok(steps)
+}
+
+/// Iteration count at each pixel centre.
+fn render_counts<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    viewport.validate()?;
+    fractal.validate()?;
+    if let Some(periodicity) = &periodicity {
+        periodicity.validate()?;
+    }
+
+    par_map_pixels(viewport.resolution, true, |[x, y]| {
+        fractal.sample(viewport.pixel_centre(x, y), max_iter, periodicity.as_ref())
+    })
+}
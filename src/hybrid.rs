@@ -0,0 +1,239 @@
+use core::{fmt, marker::PhantomData};
+use num_traits::Float;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    kernel::{
+        burning_ship_step, celtic_step, mandelbrot_step, multibrot_step, multicorn_step,
+        tricorn_step,
+    },
+    Complex, MandybrotError,
+};
+
+/// Greatest number of steps in one repeat of a `HybridSequence`.
+pub const MAX_HYBRID_STEPS: usize = 16;
+
+/// Formula applied at one step of a hybrid fractal, taking `z` to its next value for the pixel `c`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub enum HybridStep<T> {
+    /// `z → z² + c`
+    Mandelbrot,
+    /// `z → (|Re z| + i|Im z|)² + c`
+    BurningShip,
+    /// `z → conj(z)² + c`
+    Tricorn,
+    /// `z → |Re z²| + i Im z² + c`
+    CelticMandelbrot,
+    /// `z → z^power + c`
+    Multibrot { power: u32 },
+    /// `z → conj(z)^power + c`
+    Multicorn { power: u32 },
+    /// `z → z^power + c` of any real power above one.
+    RealMultibrot { power: T },
+}
+
+impl<T> HybridStep<T> {
+    /// Applies a function to every real parameter of the step, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> HybridStep<U> {
+        match self {
+            HybridStep::Mandelbrot => HybridStep::Mandelbrot,
+            HybridStep::BurningShip => HybridStep::BurningShip,
+            HybridStep::Tricorn => HybridStep::Tricorn,
+            HybridStep::CelticMandelbrot => HybridStep::CelticMandelbrot,
+            HybridStep::Multibrot { power } => HybridStep::Multibrot { power },
+            HybridStep::Multicorn { power } => HybridStep::Multicorn { power },
+            HybridStep::RealMultibrot { power } => HybridStep::RealMultibrot { power: f(power) },
+        }
+    }
+}
+
+impl<T: Float> HybridStep<T> {
+    /// Next value of `z` for the pixel `c`.
+    #[inline(always)]
+    pub fn apply(&self, z: Complex<T>, c: Complex<T>) -> Complex<T> {
+        match *self {
+            HybridStep::Mandelbrot => mandelbrot_step(z, c),
+            HybridStep::BurningShip => burning_ship_step(z, c),
+            HybridStep::Tricorn => tricorn_step(z, c),
+            HybridStep::CelticMandelbrot => celtic_step(z, c),
+            HybridStep::Multibrot { power } => multibrot_step(z, c, power),
+            HybridStep::Multicorn { power } => multicorn_step(z, c, power),
+            HybridStep::RealMultibrot { power } => z.powf(power) + c,
+        }
+    }
+
+    /// Power to which the step raises `|z|` once an orbit has escaped.
+    pub fn degree(&self) -> T {
+        match *self {
+            HybridStep::Mandelbrot
+            | HybridStep::BurningShip
+            | HybridStep::Tricorn
+            | HybridStep::CelticMandelbrot => T::one() + T::one(),
+            HybridStep::Multibrot { power } | HybridStep::Multicorn { power } => {
+                T::from(power).unwrap()
+            }
+            HybridStep::RealMultibrot { power } => power,
+        }
+    }
+
+    /// Whether the step maps mirror images in the real axis to mirror images.
+    fn is_mirror_symmetric(&self) -> bool {
+        !matches!(self, HybridStep::BurningShip)
+    }
+
+    /// Checks that the power of the step is above one, so that orbits escape beyond a radius of two.
+    fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            HybridStep::Multibrot { power } | HybridStep::Multicorn { power } if power < 2 => {
+                Err(MandybrotError::InvalidParameter {
+                    name: "power",
+                    value: power as f64,
+                })
+            }
+            HybridStep::RealMultibrot { power } if !(power.is_finite() && power > T::one()) => {
+                Err(MandybrotError::InvalidParameter {
+                    name: "power",
+                    value: power.to_f64().unwrap_or(f64::NAN),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Repeating pattern of formulas iterated by a hybrid fractal, held without allocating so that fractals built from it can be copied freely.
+/// Written as the list of its steps, so `[Mandelbrot, Mandelbrot, BurningShip]` takes two Mandelbrot steps then one Burning Ship step, over and over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HybridSequence<T> {
+    steps: [HybridStep<T>; MAX_HYBRID_STEPS],
+    len: usize,
+}
+
+impl<T> HybridSequence<T> {
+    /// Steps of one repeat of the sequence, in order.
+    pub fn steps(&self) -> &[HybridStep<T>] {
+        &self.steps[..self.len]
+    }
+
+    /// Applies a function to every real parameter of the steps, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> HybridSequence<U> {
+        HybridSequence {
+            steps: self.steps.map(|step| step.map(&f)),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Float> HybridSequence<T> {
+    /// Sequence of the given steps, of which there must be at least one.
+    pub fn new(steps: &[HybridStep<T>]) -> Result<Self, MandybrotError> {
+        if steps.len() > MAX_HYBRID_STEPS {
+            return Err(MandybrotError::InvalidParameter {
+                name: "sequence.len",
+                value: steps.len() as f64,
+            });
+        }
+        let mut sequence = Self::empty();
+        sequence.steps[..steps.len()].copy_from_slice(steps);
+        sequence.len = steps.len();
+        sequence.validate()?;
+        Ok(sequence)
+    }
+
+    fn empty() -> Self {
+        Self {
+            steps: [HybridStep::Mandelbrot; MAX_HYBRID_STEPS],
+            len: 0,
+        }
+    }
+
+    /// Rate at which `|z|` grows per step once an orbit has escaped, the geometric mean of the degrees of the steps.
+    pub fn escape_degree(&self) -> T {
+        let log_sum = self
+            .steps()
+            .iter()
+            .fold(T::zero(), |sum, step| sum + step.degree().ln());
+        (log_sum / T::from(self.len.max(1)).unwrap()).exp()
+    }
+
+    /// Whether every step keeps the mirror symmetry of the plane in the real axis.
+    pub fn is_mirror_symmetric(&self) -> bool {
+        self.steps().iter().all(HybridStep::is_mirror_symmetric)
+    }
+
+    /// Checks that there is at least one step, and that every power is in range.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        if self.len == 0 {
+            return Err(MandybrotError::ZeroCount("sequence"));
+        }
+        self.steps().iter().try_for_each(HybridStep::validate)
+    }
+}
+
+impl<T: Serialize> Serialize for HybridSequence<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        for step in self.steps() {
+            seq.serialize_element(step)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: Float + Deserialize<'de>> Deserialize<'de> for HybridSequence<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StepsVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Float + Deserialize<'de>> Visitor<'de> for StepsVisitor<T> {
+            type Value = HybridSequence<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a list of at most {} steps", MAX_HYBRID_STEPS)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut sequence = HybridSequence::empty();
+                while let Some(step) = seq.next_element()? {
+                    if sequence.len == MAX_HYBRID_STEPS {
+                        return Err(de::Error::invalid_length(MAX_HYBRID_STEPS + 1, &self));
+                    }
+                    sequence.steps[sequence.len] = step;
+                    sequence.len += 1;
+                }
+                Ok(sequence)
+            }
+        }
+
+        deserializer.deserialize_seq(StepsVisitor(PhantomData))
+    }
+}
+
+/// Iterates the steps of a hybrid sequence in turn from `z0 = 0`, returning the iteration count and the final value of `z`.
+/// Every step counts as one iteration, so the escape radius of two is checked between steps.
+#[inline(always)]
+pub(crate) fn hybrid<T: Float>(
+    sequence: &HybridSequence<T>,
+    c: Complex<T>,
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>) {
+    let four = T::from(4.0).unwrap();
+    let mut z = Complex::new(T::zero(), T::zero());
+    let mut n = 0;
+
+    for step in sequence.steps().iter().cycle() {
+        if z.norm_sqr() >= four || n >= max_iter {
+            break;
+        }
+        z = step.apply(z, c);
+        n += 1;
+        visit(z);
+    }
+
+    (n, z)
+}
@@ -0,0 +1,164 @@
+use num_traits::Float;
+use rand::{distr::uniform::SampleUniform, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_finite, check_non_negative, check_positive},
+    Complex, MandybrotError,
+};
+
+/// Affine contraction of an iterated function system, chosen with the given relative probability.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IfsMap<T> {
+    /// Coefficients `[a, b, c, d, e, f]` of the affine map `(a x + b y + c, d x + e y + f)`.
+    pub affine: [T; 6],
+    pub probability: T,
+}
+
+impl<T: Float> IfsMap<T> {
+    pub const fn new(affine: [T; 6], probability: T) -> Self {
+        Self {
+            affine,
+            probability,
+        }
+    }
+
+    /// Applies the map to a point.
+    pub fn apply(&self, p: Complex<T>) -> Complex<T> {
+        let [a, b, c, d, e, f] = self.affine;
+        Complex::new(a * p.real + b * p.imag + c, d * p.real + e * p.imag + f)
+    }
+}
+
+/// Iterated function system, whose attractor is traced by the chaos game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ifs<T> {
+    pub maps: Vec<IfsMap<T>>,
+}
+
+/// Coefficients and probabilities of the built-in iterated function systems.
+const PRESETS: &[(&str, &[IfsMap<f64>])] = &[
+    (
+        "barnsley_fern",
+        &[
+            IfsMap::new([0.0, 0.0, 0.0, 0.0, 0.16, 0.0], 0.01),
+            IfsMap::new([0.85, 0.04, 0.0, -0.04, 0.85, 1.6], 0.85),
+            IfsMap::new([0.2, -0.26, 0.0, 0.23, 0.22, 1.6], 0.07),
+            IfsMap::new([-0.15, 0.28, 0.0, 0.26, 0.24, 0.44], 0.07),
+        ],
+    ),
+    (
+        "sierpinski_triangle",
+        &[
+            IfsMap::new([0.5, 0.0, 0.0, 0.0, 0.5, 0.0], 1.0),
+            IfsMap::new([0.5, 0.0, 0.5, 0.0, 0.5, 0.0], 1.0),
+            IfsMap::new([0.5, 0.0, 0.25, 0.0, 0.5, 0.433_012_701_892_219_3], 1.0),
+        ],
+    ),
+    (
+        "heighway_dragon",
+        &[
+            IfsMap::new([0.5, -0.5, 0.0, 0.5, 0.5, 0.0], 1.0),
+            IfsMap::new([-0.5, -0.5, 1.0, 0.5, -0.5, 0.0], 1.0),
+        ],
+    ),
+    (
+        "levy_dragon",
+        &[
+            IfsMap::new([0.5, -0.5, 0.0, 0.5, 0.5, 0.0], 1.0),
+            IfsMap::new([0.5, 0.5, 0.5, -0.5, 0.5, 0.5], 1.0),
+        ],
+    ),
+];
+
+impl<T: Float> Ifs<T> {
+    pub const fn new(maps: Vec<IfsMap<T>>) -> Self {
+        Self { maps }
+    }
+
+    /// Built-in system by name: one of `barnsley_fern`, `sierpinski_triangle`, `heighway_dragon` or `levy_dragon`.
+    pub fn preset(name: &str) -> Option<Self> {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, maps)| {
+                Self::new(
+                    maps.iter()
+                        .map(|map| {
+                            IfsMap::new(
+                                map.affine.map(|k| T::from(k).unwrap()),
+                                T::from(map.probability).unwrap(),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+    }
+
+    /// Names of the built-in systems.
+    pub fn presets() -> impl Iterator<Item = &'static str> {
+        PRESETS.iter().map(|(name, _)| *name)
+    }
+
+    /// Checks that there is at least one map, that the coefficients are finite, and that the probabilities are non-negative with a positive total.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        if self.maps.is_empty() {
+            return Err(MandybrotError::ZeroCount("maps"));
+        }
+        let mut total = T::zero();
+        for map in &self.maps {
+            for coefficient in map.affine {
+                check_finite("affine", coefficient)?;
+            }
+            check_non_negative("probability", map.probability)?;
+            total = total + map.probability;
+        }
+        check_positive("total probability", total)
+    }
+}
+
+impl<T: Float + SampleUniform> Ifs<T> {
+    /// Applies a randomly chosen map to a point.
+    pub fn iterate(&self, p: Complex<T>, rng: &mut impl Rng) -> Complex<T> {
+        let total = self
+            .maps
+            .iter()
+            .fold(T::zero(), |total, map| total + map.probability);
+        let mut choice = rng.random_range(T::zero()..total);
+        let map = self
+            .maps
+            .iter()
+            .find(|map| {
+                choice = choice - map.probability;
+                choice < T::zero()
+            })
+            .unwrap_or(&self.maps[self.maps.len() - 1]);
+        map.apply(p)
+    }
+
+    /// Infinite sequence of points visited by the chaos game from `start`, which converges onto the attractor.
+    pub fn chaos_game<R: Rng>(&self, start: Complex<T>, rng: R) -> ChaosGame<'_, T, R> {
+        ChaosGame {
+            ifs: self,
+            point: start,
+            rng,
+        }
+    }
+}
+
+/// Iterator over the points of the chaos game, see `Ifs::chaos_game`.
+#[derive(Debug)]
+pub struct ChaosGame<'a, T, R> {
+    ifs: &'a Ifs<T>,
+    point: Complex<T>,
+    rng: R,
+}
+
+impl<T: Float + SampleUniform, R: Rng> Iterator for ChaosGame<'_, T, R> {
+    type Item = Complex<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.point = self.ifs.iterate(self.point, &mut self.rng);
+        Some(self.point)
+    }
+}
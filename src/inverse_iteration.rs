@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{error::check_count, MandybrotError};
+
+/// Strategy for walking the inverse branches of `z → z² + c` when rendering Julia sets by inverse iteration.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum InverseIteration {
+    /// Follow `num_orbits` random walks of `max_iter` steps, choosing a branch at random each step and skipping the first `draw_after` points.
+    Random { num_orbits: u32, draw_after: u32 },
+    /// Visit both branches depth first, up to `max_iter` deep, pruning points whose pixel has already been hit `max_hits` times.
+    /// This balances coverage between the sparse and dense parts of the set.
+    Modified { max_hits: u32 },
+}
+
+impl InverseIteration {
+    /// Checks that at least one orbit is followed, or one hit allowed per pixel.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            InverseIteration::Random { num_orbits, .. } => check_count("num_orbits", num_orbits),
+            InverseIteration::Modified { max_hits } => check_count("max_hits", max_hits),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use num_traits::Float;
+
+use crate::{
+    error::{check_count, check_finite, check_positive},
+    Complex, MandybrotError,
+};
+
+/// Maximum number of Newton steps used to refine a point of an attracting cycle.
+const NEWTON_STEPS: u32 = 32;
+
+/// Numerical classification of the Julia set of `z → z² + c`, see `classify_julia`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JuliaClassification<T> {
+    /// The critical orbit escaped after the given number of iterations, so `c` lies outside the Mandelbrot set and the Julia set is a Cantor dust.
+    CantorDust { escape_iter: u32 },
+    /// The critical orbit is attracted to a cycle, whose immediate basins are the bounded Fatou components, so the Julia set is connected.
+    /// The multiplier is the derivative of the map around the cycle, with a magnitude below one.
+    Attracting {
+        cycle: Vec<Complex<T>>,
+        multiplier: Complex<T>,
+    },
+    /// The critical orbit stayed bounded without settling onto an attracting cycle within the iteration limit.
+    /// The Julia set is connected, with parabolic or Siegel disc components, or none at all, or `c` is too close to the boundary of the Mandelbrot set to tell.
+    Connected,
+}
+
+impl<T> JuliaClassification<T> {
+    /// Whether the Julia set is connected, meaning that `c` lies in the Mandelbrot set.
+    pub fn is_connected(&self) -> bool {
+        !matches!(self, JuliaClassification::CantorDust { .. })
+    }
+
+    /// Period of the attracting cycle, if there is one.
+    pub fn period(&self) -> Option<usize> {
+        match self {
+            JuliaClassification::Attracting { cycle, .. } => Some(cycle.len()),
+            _ => None,
+        }
+    }
+}
+
+/// Determines whether the Julia set of `z → z² + c` is connected, and finds the attracting cycle of the map if it has one.
+/// The critical orbit is iterated up to `max_iter` times to settle, then searched for a cycle of period up to `max_iter` which returns within `tolerance`.
+/// Any cycle found is refined by Newton's method before its multiplier is computed.
+pub fn classify_julia<T: Float>(
+    c: Complex<T>,
+    max_iter: u32,
+    tolerance: T,
+) -> Result<JuliaClassification<T>, MandybrotError> {
+    check_finite("c.real", c.real)?;
+    check_finite("c.imag", c.imag)?;
+    check_count("max_iter", max_iter)?;
+    check_positive("tolerance", tolerance)?;
+
+    let four = T::from(4.0).unwrap();
+    let f = |z: Complex<T>| z * z + c;
+
+    // Settle the critical orbit onto its attractor, if it does not escape.
+    let mut z = Complex::new(T::zero(), T::zero());
+    for n in 0..max_iter {
+        z = f(z);
+        if z.norm_sqr() >= four {
+            return Ok(JuliaClassification::CantorDust { escape_iter: n + 1 });
+        }
+    }
+
+    // Find the period of the cycle the orbit has settled onto.
+    let tolerance_sqr = tolerance * tolerance;
+    let mut w = z;
+    let Some(period) = (1..=max_iter).find(|_| {
+        w = f(w);
+        (w - z).norm_sqr() < tolerance_sqr
+    }) else {
+        return Ok(JuliaClassification::Connected);
+    };
+
+    // Refine a point of the cycle by Newton's method on `f^p(z) - z`.
+    let one = Complex::new(T::one(), T::zero());
+    for _ in 0..NEWTON_STEPS {
+        let (w, derivative) = iterate_with_derivative(z, c, period);
+        let step = (w - z) / (derivative - one);
+        if !step.real.is_finite() || !step.imag.is_finite() {
+            break;
+        }
+        z = z - step;
+        if step.norm_sqr() <= T::epsilon() * T::epsilon() * z.norm_sqr().max(T::one()) {
+            break;
+        }
+    }
+
+    let (_, multiplier) = iterate_with_derivative(z, c, period);
+    if multiplier.norm_sqr() >= T::one() {
+        return Ok(JuliaClassification::Connected);
+    }
+    let cycle = (0..period)
+        .scan(z, |z, _| {
+            let point = *z;
+            *z = f(*z);
+            Some(point)
+        })
+        .collect();
+
+    Ok(JuliaClassification::Attracting { cycle, multiplier })
+}
+
+/// Iterates `z → z² + c` `n` times, returning the final point and the derivative of the composed map at the start.
+fn iterate_with_derivative<T: Float>(
+    z: Complex<T>,
+    c: Complex<T>,
+    n: u32,
+) -> (Complex<T>, Complex<T>) {
+    let two = Complex::new(T::from(2.0).unwrap(), T::zero());
+    (0..n).fold(
+        (z, Complex::new(T::one(), T::zero())),
+        |(z, derivative), _| (z * z + c, two * z * derivative),
+    )
+}
@@ -0,0 +1,122 @@
+use num_traits::Float;
+
+use crate::Complex;
+
+/// `z → z² + c`, the map of the Mandelbrot and Julia sets.
+#[inline(always)]
+pub fn mandelbrot_step<T: Float>(z: Complex<T>, c: Complex<T>) -> Complex<T> {
+    z * z + c
+}
+
+/// `z → (|Re z| + i|Im z|)² + c`, the map of the Burning Ship.
+#[inline(always)]
+pub fn burning_ship_step<T: Float>(z: Complex<T>, c: Complex<T>) -> Complex<T> {
+    let w = Complex::new(z.real.abs(), z.imag.abs());
+    w * w + c
+}
+
+/// `z → conj(z)² + c`, the map of the Tricorn.
+#[inline(always)]
+pub fn tricorn_step<T: Float>(z: Complex<T>, c: Complex<T>) -> Complex<T> {
+    let w = Complex::new(z.real, -z.imag);
+    w * w + c
+}
+
+/// `z → |Re z²| + i Im z² + c`, the map of the Celtic Mandelbrot set.
+#[inline(always)]
+pub fn celtic_step<T: Float>(z: Complex<T>, c: Complex<T>) -> Complex<T> {
+    let two = T::one() + T::one();
+    Complex::new(
+        (z.real * z.real - z.imag * z.imag).abs(),
+        two * z.real * z.imag,
+    ) + c
+}
+
+/// `z → z^power + c`, the map of the Multibrot sets.
+#[inline(always)]
+pub fn multibrot_step<T: Float>(z: Complex<T>, c: Complex<T>, power: u32) -> Complex<T> {
+    z.powi(power) + c
+}
+
+/// `z → conj(z)^power + c`, the map of the Multicorns.
+#[inline(always)]
+pub fn multicorn_step<T: Float>(z: Complex<T>, c: Complex<T>, power: u32) -> Complex<T> {
+    Complex::new(z.real, -z.imag).powi(power) + c
+}
+
+/// `z → z² + c + p z₋₁`, the map of the Phoenix fractal, where `previous` is the point of the orbit before `z`.
+#[inline(always)]
+pub fn phoenix_step<T: Float>(
+    z: Complex<T>,
+    previous: Complex<T>,
+    c: Complex<T>,
+    p: Complex<T>,
+) -> Complex<T> {
+    z * z + c + p * previous
+}
+
+/// One step of Newton's method `z → z − (z³ − 1) / 3z²` for the cube roots of unity, returning the new point and the step taken.
+#[inline(always)]
+pub fn newton_step<T: Float>(z: Complex<T>) -> (Complex<T>, Complex<T>) {
+    let f = z * z * z - Complex::new(T::one(), T::zero());
+    let df = Complex::new(T::from(3.0).unwrap(), T::zero()) * z * z;
+    let dz = f / df;
+    (z - dz, dz)
+}
+
+/// `z → λz(1 − z)`, the logistic map.
+#[inline(always)]
+pub fn lambda_step<T: Float>(z: Complex<T>, lambda: Complex<T>) -> Complex<T> {
+    lambda * z * (Complex::new(T::one(), T::zero()) - z)
+}
+
+/// The Collatz map `z → (z/2)cos²(πz/2) + (3z + 1)sin²(πz/2)`, in the equivalent form `z → (2 + 7z − (2 + 5z)cos(πz)) / 4` which needs only one cosine.
+#[inline(always)]
+pub fn collatz_step<T: Float>(z: Complex<T>) -> Complex<T> {
+    let pi = T::from(core::f64::consts::PI).unwrap();
+    let two = Complex::new(T::from(2.0).unwrap(), T::zero());
+    let five = T::from(5.0).unwrap();
+    let seven = T::from(7.0).unwrap();
+    let quarter = T::from(0.25).unwrap();
+    let cos = z.map(|x| x * pi).cos();
+    (two + z.map(|x| x * seven) - (two + z.map(|x| x * five)) * cos).map(|x| x * quarter)
+}
+
+/// Everything carried from one iteration of an orbit to the next, for loops written outside the crate with `Fractal::initial_state` and `Fractal::step`.
+/// The loop decides when the orbit stops, so it is free to use its own bailouts and gather its own statistics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitState<T> {
+    /// Current point of the orbit.
+    pub z: Complex<T>,
+    /// Point of the orbit before `z`, or zero at the start, for maps with memory such as the Phoenix map.
+    pub previous: Complex<T>,
+    /// Constant of the map for this orbit: the pixel for fractals drawn over their parameter plane, or the fixed parameter of a Julia set.
+    pub c: Complex<T>,
+    /// Number of steps taken.
+    pub iteration: u32,
+}
+
+impl<T: Float> OrbitState<T> {
+    /// State at the start of the orbit of `z` for the constant `c`.
+    pub fn new(z: Complex<T>, c: Complex<T>) -> Self {
+        Self {
+            z,
+            previous: Complex::new(T::zero(), T::zero()),
+            c,
+            iteration: 0,
+        }
+    }
+
+    /// Moves the orbit on to the point `z`, as the result of one step.
+    #[inline(always)]
+    pub fn advance(&mut self, z: Complex<T>) {
+        self.previous = self.z;
+        self.z = z;
+        self.iteration += 1;
+    }
+
+    /// Squared modulus of the current point, to compare with the square of an escape radius.
+    pub fn norm_sqr(&self) -> T {
+        self.z.norm_sqr()
+    }
+}
@@ -1,9 +1,296 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("enable either the std or libm feature");
+#[cfg(all(feature = "strict", feature = "std"))]
+compile_error!("the strict feature takes its maths from libm, so it cannot be combined with std");
+
+#[cfg(feature = "std")]
+mod accumulator;
+mod affine;
+mod algebra;
+#[cfg(feature = "animation")]
+mod animation;
 mod attractor;
+#[cfg(feature = "std")]
+mod attractor_search;
+#[cfg(feature = "std")]
+mod basins;
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "json")]
+mod bookmarks;
+#[cfg(feature = "json")]
+mod cache;
+#[cfg(feature = "std")]
+mod chunked;
+mod colouring;
 mod complex;
+#[cfg(feature = "std")]
+mod compositing;
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+mod contours;
+#[cfg(feature = "std")]
+mod control;
+#[cfg(feature = "std")]
+mod dither;
+#[cfg(feature = "std")]
+mod double_double;
+mod dual_complex;
+#[cfg(feature = "std")]
+mod dynamic;
+#[cfg(feature = "std")]
+mod encoding;
+mod error;
+#[cfg(feature = "std")]
+mod fade;
+#[cfg(feature = "std")]
+mod flame;
 mod fractal;
+#[cfg(feature = "std")]
+mod gallery;
+#[cfg(feature = "std")]
+mod gradient;
+#[cfg(feature = "std")]
+mod histogram;
+mod hybrid;
+#[cfg(feature = "std")]
+mod ifs;
+mod inverse_iteration;
+#[cfg(feature = "std")]
+mod julia_analysis;
+mod kernel;
+#[cfg(feature = "std")]
+mod location_file;
+#[cfg(feature = "std")]
+mod lsystem;
+#[cfg(feature = "std")]
+mod mesh;
+#[cfg(feature = "std")]
+mod metrics;
+#[cfg(feature = "npz")]
+mod npz;
+#[cfg(feature = "std")]
+mod panels;
+#[cfg(feature = "std")]
+mod parameters;
+#[cfg(feature = "std")]
+mod pattern;
+mod periodicity;
+#[cfg(feature = "std")]
+mod pipeline;
+mod plane_slice;
+mod polynomial;
+mod presets;
+mod quaternion;
+#[cfg(feature = "std")]
+mod random_palette;
+#[cfg(feature = "std")]
+mod raw_image;
+#[cfg(feature = "std")]
+mod rays;
+#[cfg(feature = "std")]
+mod realtime;
+#[cfg(feature = "std")]
 mod render;
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "std")]
+mod resample;
+#[cfg(feature = "std")]
+mod rng_strategy;
+#[cfg(feature = "std")]
+mod sample_buffer;
+#[cfg(feature = "std")]
+mod sample_pattern;
+mod sampling;
+#[cfg(feature = "std")]
+mod schedule;
+#[cfg(feature = "std")]
+mod seeded;
+#[cfg(feature = "std")]
+mod shader;
+#[cfg(feature = "json")]
+mod state;
+#[cfg(feature = "std")]
+mod svg;
+#[cfg(feature = "std")]
+mod terminal;
+#[cfg(feature = "std")]
+mod texture;
+#[cfg(feature = "std")]
+mod thumbnail;
+mod tile;
+#[cfg(feature = "server")]
+mod tile_server;
+#[cfg(feature = "std")]
+mod tone_map;
+#[cfg(feature = "std")]
+mod trajectory;
+#[cfg(feature = "video")]
+mod video;
+mod viewport;
+#[cfg(feature = "std")]
+mod volume;
+#[cfg(feature = "std")]
+mod zoom;
 
-pub use attractor::Attractor;
-pub use complex::Complex;
-pub use fractal::Fractal;
-pub use render::{render_attractor, render_fractal};
+#[cfg(feature = "std")]
+pub use accumulator::Accumulator;
+pub use affine::Affine;
+pub use algebra::{Algebra, DualNumber, NumberSystem, SplitComplex};
+#[cfg(feature = "animation")]
+pub use animation::{Animation, AnimationFormat};
+pub use attractor::{Attractor, AttractorParameter, AttractorSeeding, Provenance};
+#[cfg(feature = "std")]
+pub use attractor_search::{
+    search_attractors, AttractorCandidate, AttractorFamily, AttractorSearch,
+};
+#[cfg(feature = "std")]
+pub use basins::{render_basins, BasinMap, BasinSettings, Cycle, Fate};
+#[cfg(feature = "std")]
+pub use batch::{batch_jobs, render_batch, BatchJob, Sweep};
+#[cfg(feature = "json")]
+pub use bookmarks::{Bookmark, BookmarkStack};
+#[cfg(feature = "json")]
+pub use cache::{RenderCache, RenderKey};
+#[cfg(feature = "std")]
+pub use chunked::ChunkedRender;
+pub use colouring::PaletteCycle;
+#[cfg(feature = "std")]
+pub use colouring::{ColourMap, EscapeColouring};
+pub use complex::{Complex, ParseComplexError};
+#[cfg(feature = "png")]
+pub use compositing::load_png;
+#[cfg(feature = "std")]
+pub use compositing::{blend_layers, composite, over, Background, Blend, BlendMode, Transparency};
+#[cfg(feature = "std")]
+pub use config::ConfigFormat;
+#[cfg(feature = "std")]
+pub use contours::{contours, Contour};
+#[cfg(feature = "std")]
+pub use control::{serve_control, RenderControl};
+#[cfg(feature = "std")]
+pub use dither::Dither;
+#[cfg(feature = "std")]
+pub use double_double::{DoubleDouble, ParseDoubleDoubleError};
+pub use dual_complex::DualComplex;
+#[cfg(feature = "std")]
+pub use dynamic::{render_fractal_dyn, Coordinate, FloatPrecision, ParametersDyn};
+#[cfg(feature = "png")]
+pub use encoding::save_png;
+#[cfg(feature = "std")]
+pub use encoding::ColourEncoding;
+pub use error::MandybrotError;
+#[cfg(feature = "std")]
+pub use fade::{render_attractor_sweep, AttractorSweep, Fade};
+#[cfg(feature = "std")]
+pub use flame::{Flame, FlameTransform, Variation};
+pub use fractal::{formula_with_derivative, Fractal};
+#[cfg(feature = "std")]
+pub use gallery::{contact_sheet, gallery_items, render_gallery, GalleryItem};
+#[cfg(feature = "std")]
+pub use gradient::GradientFormat;
+#[cfg(feature = "std")]
+pub use histogram::{
+    find_zoom_target, plan_auto_zoom, render_histogram, render_tile_histograms, IterationHistogram,
+};
+pub use hybrid::{HybridSequence, HybridStep, MAX_HYBRID_STEPS};
+#[cfg(feature = "std")]
+pub use ifs::{ChaosGame, Ifs, IfsMap};
+pub use inverse_iteration::InverseIteration;
+#[cfg(feature = "std")]
+pub use julia_analysis::{classify_julia, JuliaClassification};
+pub use kernel::{
+    burning_ship_step, celtic_step, collatz_step, lambda_step, mandelbrot_step, multibrot_step,
+    multicorn_step, newton_step, phoenix_step, tricorn_step, OrbitState,
+};
+#[cfg(feature = "std")]
+pub use lsystem::{render_lsystem, LSystem};
+#[cfg(feature = "std")]
+pub use mesh::{HeightMesh, MeshFormat};
+#[cfg(feature = "std")]
+pub use metrics::{
+    diff_image, difference, mean_squared_error, psnr, psnr_colour, ssim, ssim_colour,
+};
+#[cfg(feature = "npz")]
+pub use npz::Npz;
+#[cfg(feature = "png")]
+pub use panels::save_panels_png;
+#[cfg(feature = "std")]
+pub use panels::{render_panels, Panel, PanelGrid};
+#[cfg(feature = "std")]
+pub use parameters::{Definition, Job, Layer, Parameters, PARAMETERS_VERSION};
+#[cfg(feature = "std")]
+pub use pattern::{pattern_fill, Pattern};
+pub use periodicity::Periodicity;
+#[cfg(feature = "std")]
+pub use pipeline::{normalise_percentiles, post_process, Normalisation, PostOp};
+pub use plane_slice::{EscapeMap, PlaneAxis, SlicePoint};
+pub use polynomial::{Polynomial, RationalMap, MAX_COEFFICIENTS};
+pub use presets::Location;
+pub use quaternion::{Quaternion, QuaternionAxis};
+#[cfg(feature = "std")]
+pub use random_palette::PaletteScheme;
+#[cfg(feature = "png")]
+pub use raw_image::save_grey16_png;
+#[cfg(feature = "std")]
+pub use raw_image::{save_float_tiff, write_float_tiff};
+#[cfg(feature = "std")]
+pub use rays::{rasterise_polyline, trace_external_ray};
+#[cfg(feature = "std")]
+pub use realtime::RealtimeRenderer;
+#[cfg(feature = "std")]
+pub use render::{
+    render_atom_domains, render_attractor, render_attractor_coloured, render_attractor_pixels,
+    render_attractor_viewport, render_bifurcation, render_buddhabrot, render_buddhabrot_viewport,
+    render_distance, render_dual, render_escape, render_flame, render_fractal,
+    render_fractal_adaptive, render_fractal_coloured, render_fractal_controlled,
+    render_fractal_iteration_map, render_fractal_mean, render_fractal_mirrored,
+    render_fractal_viewport, render_ifs, render_inverse_julia, render_normal_shading,
+    render_normals, render_orbit_soup, render_orbit_statistics, render_with_shader, render_zoom,
+    rerender_fractal, sample_area_iter, sample_area_seq_iter, set_progress_bars,
+};
+#[cfg(feature = "std")]
+pub use report::{render_escape_report, RenderReport, TileStats};
+#[cfg(feature = "std")]
+pub use resample::{box_blur, downsample, ColourSpace};
+#[cfg(feature = "std")]
+pub use rng_strategy::{RngStrategy, RngStream};
+#[cfg(feature = "std")]
+pub use sample_buffer::SampleBuffer;
+#[cfg(feature = "std")]
+pub use sample_pattern::SamplePattern;
+pub use sampling::Sampling;
+#[cfg(feature = "std")]
+pub use schedule::{render_scheduled, CpuDevice, DeviceStats, ScheduleReport, TileDevice};
+#[cfg(feature = "std")]
+pub use seeded::{render_seeded, SeedTarget};
+#[cfg(feature = "std")]
+pub use shader::{PixelSample, PixelShader};
+#[cfg(feature = "json")]
+pub use state::{decode_state, encode_state, STATE_TOKEN_VERSION};
+#[cfg(feature = "std")]
+pub use svg::Svg;
+#[cfg(feature = "std")]
+pub use terminal::{render_ansi, AnsiColour};
+#[cfg(feature = "std")]
+pub use texture::{texture_exterior, PerlinNoise, Texture};
+#[cfg(feature = "std")]
+pub use thumbnail::ThumbnailStrip;
+pub use tile::{render_tile, render_tile_array};
+#[cfg(feature = "server")]
+pub use tile_server::{serve_tiles, TileServer};
+#[cfg(feature = "std")]
+pub use tone_map::ToneMap;
+#[cfg(feature = "std")]
+pub use trajectory::{attractor_points, save_points, OrbitPoint, PointFormat};
+#[cfg(feature = "video")]
+pub use video::{render_zoom_video, VideoEncoder, VideoFormat, VideoSettings};
+pub use viewport::Viewport;
+#[cfg(feature = "std")]
+pub use volume::{render_volume, save_nrrd, volume_spacings, write_nrrd, VolumeSweep};
+#[cfg(feature = "std")]
+pub use zoom::{Precision, ZoomPath};
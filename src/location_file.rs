@@ -0,0 +1,274 @@
+use std::path::Path;
+
+use crate::{
+    error::{check_finite, check_positive},
+    zoom::PERTURBATION_MIN_SCALE,
+    Complex, Coordinate, DoubleDouble, FloatPrecision, Fractal, MandybrotError, ParametersDyn,
+    Precision, SamplePattern,
+};
+
+/// Iteration limit Fractint uses when a parameter set leaves out `maxiter`.
+const FRACTINT_DEFAULT_MAX_ITER: u32 = 150;
+
+impl ParametersDyn {
+    /// Reads a deep-zoom location published by another program, as a Kalles Fraktaler `.kfr` file or a Fractint `.par` file by its extension.
+    /// The location is rendered at the given resolution, see `from_kfr` and `from_par`.
+    pub fn import(path: impl AsRef<Path>, resolution: [u32; 2]) -> Result<Self, MandybrotError> {
+        let path = path.as_ref();
+        let extension = path.extension().map_or(String::new(), |extension| {
+            extension.to_string_lossy().to_lowercase()
+        });
+        let parse = match extension.as_str() {
+            "kfr" => Self::from_kfr,
+            "par" => Self::from_par,
+            _ => return Err(MandybrotError::UnsupportedFormat(extension)),
+        };
+        parse(&std::fs::read_to_string(path)?, resolution)
+    }
+
+    /// Settings of a Kalles Fraktaler location, written as `Key: value` lines such as `Re: -1.74972...`, `Im: 0.0`, `Zoom: 2.5E20` and `Iterations: 3000`.
+    /// `Zoom` is the magnification of a view four units high, the Mandelbrot set, Burning Ship, Celtic and Mandelbar types and their higher `Power`s are read, and settings for colouring and other programs are ignored.
+    /// The centre keeps every digit of the file, and the precision is the cheapest which resolves the pixels.
+    pub fn from_kfr(text: &str, resolution: [u32; 2]) -> Result<Self, MandybrotError> {
+        let entries: Vec<(&str, &str)> = text
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+        let get = |key: &'static str| {
+            entries
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| *value)
+        };
+        let require = |key: &'static str| get(key).ok_or_else(|| missing(key));
+
+        let centre = [
+            coordinate("Re", require("Re")?)?,
+            coordinate("Im", require("Im")?)?,
+        ];
+        let zoom: f64 = number("Zoom", require("Zoom")?)?;
+        check_positive("Zoom", zoom)?;
+        let max_iter: u32 = number("Iterations", require("Iterations")?)?;
+
+        let power: u32 = get("Power").map_or(Ok(2), |power| number("Power", power))?;
+        let fractal_type: u32 = get("FractalType").map_or(Ok(0), |id| number("FractalType", id))?;
+        let fractal = match (fractal_type, power) {
+            (0, 2) => Fractal::Mandelbrot,
+            (0, power) => Fractal::Multibrot { power },
+            (1, 2) => Fractal::BurningShip,
+            (3, 2) => Fractal::CelticMandelbrot,
+            (4, 2) => Fractal::Tricorn,
+            (4, power) => Fractal::Multicorn { power },
+            _ => {
+                return Err(MandybrotError::ConfigValue {
+                    key: "FractalType".into(),
+                    message: format!(
+                        "fractal type {} of power {} is not supported",
+                        fractal_type, power
+                    ),
+                })
+            }
+        };
+
+        Self::imported(centre, 4.0 / zoom, resolution, max_iter, fractal)
+    }
+
+    /// Settings of the first entry of a Fractint parameter file, or of a bare list of its `key=value` settings such as `type=mandel center-mag=-0.745/0.1127/153.8 maxiter=500`.
+    /// The view is read from `center-mag`, whose magnification is of a view two units high, or from `corners`, and `mandel` and `julia` types are read along with the `params` of a Julia set.
+    /// The centre keeps every digit of the file, and the precision is the cheapest which resolves the pixels.
+    pub fn from_par(text: &str, resolution: [u32; 2]) -> Result<Self, MandybrotError> {
+        // An entry is the text between braces, whose lines may end in comments after a semicolon.
+        let body = match text.split_once('{') {
+            Some((_, rest)) => rest.split('}').next().unwrap_or(rest),
+            None => text,
+        };
+        let entries: Vec<(&str, &str)> = body
+            .lines()
+            .map(|line| line.split(';').next().unwrap_or(line))
+            .flat_map(str::split_whitespace)
+            .filter_map(|setting| setting.split_once('='))
+            .collect();
+        let get = |key: &'static str| {
+            entries
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| *value)
+        };
+
+        let (centre, scale) = match (get("center-mag"), get("corners")) {
+            (Some(view), _) => {
+                let fields: Vec<&str> = view.split('/').collect();
+                if fields.len() < 3 {
+                    return Err(MandybrotError::ConfigValue {
+                        key: "center-mag".into(),
+                        message: "expected a centre and magnification as x/y/mag".into(),
+                    });
+                }
+                let magnification: f64 = number("center-mag", fields[2])?;
+                check_positive("center-mag", magnification)?;
+                (
+                    [
+                        coordinate("center-mag", fields[0])?,
+                        coordinate("center-mag", fields[1])?,
+                    ],
+                    2.0 / magnification,
+                )
+            }
+            (None, Some(corners)) => {
+                let bounds = corners
+                    .split('/')
+                    .map(|bound| bound.parse::<DoubleDouble>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| MandybrotError::UnparsableNumber("corners"))?;
+                if bounds.len() < 4 {
+                    return Err(MandybrotError::ConfigValue {
+                        key: "corners".into(),
+                        message: "expected the bounds of the view as xmin/xmax/ymin/ymax".into(),
+                    });
+                }
+                let half = DoubleDouble::from(0.5);
+                let scale = (bounds[3] - bounds[2]).to_f64();
+                check_positive("corners", scale)?;
+                (
+                    [
+                        Coordinate::Text(((bounds[0] + bounds[1]) * half).to_string()),
+                        Coordinate::Text(((bounds[2] + bounds[3]) * half).to_string()),
+                    ],
+                    scale,
+                )
+            }
+            (None, None) => return Err(missing("center-mag")),
+        };
+        let max_iter = get("maxiter").map_or(Ok(FRACTINT_DEFAULT_MAX_ITER), |max_iter| {
+            number("maxiter", max_iter)
+        })?;
+
+        let fractal_type = get("type").unwrap_or("mandel");
+        let fractal = match fractal_type.to_ascii_lowercase().as_str() {
+            "mandel" | "mandelfp" => Fractal::Mandelbrot,
+            "julia" | "juliafp" => {
+                let params = get("params").ok_or_else(|| missing("params"))?;
+                let mut values = params.split('/').map(|value| number("params", value));
+                let real = values.next().unwrap_or(Ok(0.0))?;
+                let imag = values.next().unwrap_or(Ok(0.0))?;
+                Fractal::Julia {
+                    c: Complex::new(real, imag),
+                }
+            }
+            _ => {
+                return Err(MandybrotError::ConfigValue {
+                    key: "type".into(),
+                    message: format!("fractal type '{}' is not supported", fractal_type),
+                })
+            }
+        };
+
+        Self::imported(centre, scale, resolution, max_iter, fractal)
+    }
+
+    /// Settings of an imported location, in the cheapest precision which resolves its pixels.
+    /// Locations deeper than `DoubleDouble` can resolve are rejected, as by `ZoomPath::validate`.
+    fn imported(
+        centre: [Coordinate; 2],
+        scale: f64,
+        resolution: [u32; 2],
+        max_iter: u32,
+        fractal: Fractal<f64>,
+    ) -> Result<Self, MandybrotError> {
+        check_positive("scale", scale)?;
+        if scale < PERTURBATION_MIN_SCALE {
+            return Err(MandybrotError::InvalidParameter {
+                name: "scale",
+                value: scale,
+            });
+        }
+        let pixel_size = scale / resolution[1].max(1) as f64;
+        let precision = match Precision::for_pixel_size(pixel_size) {
+            Precision::Single => FloatPrecision::Single,
+            Precision::Double => FloatPrecision::Double,
+            Precision::DoubleDouble | Precision::Perturbation => FloatPrecision::DoubleDouble,
+        };
+        Ok(Self {
+            precision,
+            centre,
+            scale,
+            resolution,
+            max_iter,
+            fractal,
+            samples_per_pixel: 1,
+            sample_pattern: SamplePattern::default(),
+            periodicity: None,
+        })
+    }
+}
+
+/// Coordinate kept as written, once checked to be a finite decimal number.
+fn coordinate(name: &'static str, text: &str) -> Result<Coordinate, MandybrotError> {
+    let value = text
+        .parse::<DoubleDouble>()
+        .map_err(|_| MandybrotError::UnparsableNumber(name))?;
+    check_finite(name, value.to_f64())?;
+    Ok(Coordinate::Text(text.to_string()))
+}
+
+fn number<N: std::str::FromStr>(name: &'static str, text: &str) -> Result<N, MandybrotError> {
+    text.trim()
+        .parse()
+        .map_err(|_| MandybrotError::UnparsableNumber(name))
+}
+
+fn missing(key: &str) -> MandybrotError {
+    MandybrotError::ConfigValue {
+        key: key.into(),
+        message: "the setting is missing".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kfr(re: &str, zoom: &str) -> String {
+        format!(
+            "Re: {}\r\nIm: 0.0\r\nZoom: {}\r\nIterations: 3000\r\n",
+            re, zoom
+        )
+    }
+
+    #[test]
+    fn deep_locations_use_double_double_precision() {
+        let params = ParametersDyn::from_kfr(&kfr("-1.7497219297", "2.5E20"), [640, 480]).unwrap();
+        assert_eq!(params.precision, FloatPrecision::DoubleDouble);
+        assert_eq!(params.scale, 4.0 / 2.5e20);
+    }
+
+    #[test]
+    fn locations_deeper_than_double_double_are_rejected() {
+        for zoom in ["1E30", "1E300"] {
+            assert!(matches!(
+                ParametersDyn::from_kfr(&kfr("-1.75", zoom), [640, 480]),
+                Err(MandybrotError::InvalidParameter { name: "scale", .. })
+            ));
+        }
+        assert!(matches!(
+            ParametersDyn::from_par(
+                "type=mandel corners=-0.75/-0.75000000000000000000000000000001/0.1/0.10000000000000000000000000000001",
+                [640, 480]
+            ),
+            Err(MandybrotError::InvalidParameter { name: "scale", .. })
+        ));
+    }
+
+    #[test]
+    fn coordinates_must_be_finite() {
+        for re in ["inf", "NaN", "1e400"] {
+            assert!(
+                ParametersDyn::from_kfr(&kfr(re, "1E5"), [640, 480]).is_err(),
+                "{}",
+                re
+            );
+        }
+        assert!(ParametersDyn::from_par("center-mag=NaN/0.1/10", [640, 480]).is_err());
+    }
+}
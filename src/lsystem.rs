@@ -0,0 +1,228 @@
+use ndarray::Array2;
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{error::check_finite, rasterise_polyline, Complex, MandybrotError, Viewport};
+
+/// Lindenmayer system: an axiom rewritten in parallel by production rules, then drawn by a turtle.
+/// The turtle draws forward one unit for `F` or `G`, moves without drawing for `f`, turns by `angle` degrees left for `+` and right for `-`, turns around for `|`, and saves and restores its state for `[` and `]`.
+/// Other symbols, such as the variables `X` and `Y`, only take part in rewriting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LSystem<T> {
+    pub axiom: String,
+    pub rules: BTreeMap<char, String>,
+    /// Turning angle, in degrees.
+    pub angle: T,
+    /// Initial direction of the turtle, in degrees anticlockwise from the real axis.
+    pub heading: T,
+}
+
+/// Axiom, rules, turning angle and initial heading of a built-in L-system.
+struct Preset {
+    name: &'static str,
+    axiom: &'static str,
+    rules: &'static [(char, &'static str)],
+    angle: f64,
+    heading: f64,
+}
+
+const PRESETS: &[Preset] = &[
+    Preset {
+        name: "koch_snowflake",
+        axiom: "F--F--F",
+        rules: &[('F', "F+F--F+F")],
+        angle: 60.0,
+        heading: 0.0,
+    },
+    Preset {
+        name: "hilbert_curve",
+        axiom: "A",
+        rules: &[('A', "+BF-AFA-FB+"), ('B', "-AF+BFB+FA-")],
+        angle: 90.0,
+        heading: 0.0,
+    },
+    Preset {
+        name: "fractal_plant",
+        axiom: "X",
+        rules: &[('X', "F+[[X]-X]-F[-FX]+X"), ('F', "FF")],
+        angle: 25.0,
+        heading: 90.0,
+    },
+];
+
+/// Longest string of symbols an L-system may be expanded to.
+const MAX_SYMBOLS: usize = 1 << 28;
+
+impl<T: Float> LSystem<T> {
+    pub fn new(axiom: &str, rules: &[(char, &str)], angle: T, heading: T) -> Self {
+        Self {
+            axiom: axiom.to_string(),
+            rules: rules
+                .iter()
+                .map(|&(symbol, replacement)| (symbol, replacement.to_string()))
+                .collect(),
+            angle,
+            heading,
+        }
+    }
+
+    /// Built-in system by name: one of `koch_snowflake`, `hilbert_curve` or `fractal_plant`.
+    pub fn preset(name: &str) -> Option<Self> {
+        PRESETS
+            .iter()
+            .find(|preset| preset.name == name)
+            .map(|preset| {
+                Self::new(
+                    preset.axiom,
+                    preset.rules,
+                    T::from(preset.angle).unwrap(),
+                    T::from(preset.heading).unwrap(),
+                )
+            })
+    }
+
+    /// Names of the built-in systems.
+    pub fn presets() -> impl Iterator<Item = &'static str> {
+        PRESETS.iter().map(|preset| preset.name)
+    }
+
+    /// Checks that the angles are finite.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_finite("angle", self.angle)?;
+        check_finite("heading", self.heading)
+    }
+
+    /// Rewrites the axiom `depth` times, failing if the result would be unreasonably long.
+    pub fn expand(&self, depth: u32) -> Result<String, MandybrotError> {
+        let mut symbols = self.axiom.clone();
+        for _ in 0..depth {
+            let mut next = String::with_capacity(symbols.len());
+            for symbol in symbols.chars() {
+                match self.rules.get(&symbol) {
+                    Some(replacement) => next.push_str(replacement),
+                    None => next.push(symbol),
+                }
+                if next.len() > MAX_SYMBOLS {
+                    return Err(MandybrotError::InvalidParameter {
+                        name: "depth",
+                        value: depth as f64,
+                    });
+                }
+            }
+            symbols = next;
+        }
+        Ok(symbols)
+    }
+
+    /// Path drawn by the turtle after rewriting `depth` times, as polylines broken wherever the turtle moves without drawing.
+    /// The turtle starts at the origin and steps one unit at a time.
+    pub fn turtle_path(&self, depth: u32) -> Result<Vec<Vec<Complex<T>>>, MandybrotError> {
+        self.validate()?;
+        let symbols = self.expand(depth)?;
+
+        let to_radians = T::from(std::f64::consts::PI / 180.0).unwrap();
+        let turn = self.angle * to_radians;
+        let half_turn = T::from(std::f64::consts::PI).unwrap();
+
+        let mut position = Complex::new(T::zero(), T::zero());
+        let mut heading = self.heading * to_radians;
+        let mut stack = Vec::new();
+        let mut polylines = Vec::new();
+        let mut current = vec![position];
+
+        for symbol in symbols.chars() {
+            match symbol {
+                'F' | 'G' => {
+                    position = position + Complex::new(heading.cos(), heading.sin());
+                    current.push(position);
+                }
+                'f' | '[' | ']' => {
+                    match symbol {
+                        'f' => position = position + Complex::new(heading.cos(), heading.sin()),
+                        '[' => stack.push((position, heading)),
+                        _ => {
+                            if let Some(state) = stack.pop() {
+                                (position, heading) = state;
+                            }
+                        }
+                    }
+                    if current.len() > 1 {
+                        polylines.push(current);
+                    }
+                    current = vec![position];
+                }
+                '+' => heading = heading + turn,
+                '-' => heading = heading - turn,
+                '|' => heading = heading + half_turn,
+                _ => {}
+            }
+        }
+        if current.len() > 1 {
+            polylines.push(current);
+        }
+
+        Ok(polylines)
+    }
+
+    /// Viewport which fits the whole path drawn after `depth` rewrites, with a small margin, at the given resolution.
+    pub fn fit_viewport(
+        &self,
+        depth: u32,
+        resolution: [u32; 2],
+    ) -> Result<Viewport<T>, MandybrotError> {
+        let polylines = self.turtle_path(depth)?;
+        let (min, max) = polylines.iter().flatten().fold(
+            (
+                Complex::new(T::infinity(), T::infinity()),
+                Complex::new(T::neg_infinity(), T::neg_infinity()),
+            ),
+            |(min, max), p| {
+                (
+                    Complex::new(min.real.min(p.real), min.imag.min(p.imag)),
+                    Complex::new(max.real.max(p.real), max.imag.max(p.imag)),
+                )
+            },
+        );
+        if polylines.is_empty() {
+            return Ok(Viewport::new(
+                Complex::new(T::zero(), T::zero()),
+                T::one(),
+                resolution,
+            ));
+        }
+
+        let two = T::from(2.0).unwrap();
+        let margin = T::from(1.05).unwrap();
+        let aspect_ratio = T::from(resolution[0]).unwrap() / T::from(resolution[1]).unwrap();
+        let scale = (max.imag - min.imag).max((max.real - min.real) / aspect_ratio);
+        Ok(Viewport::new(
+            Complex::new((min.real + max.real) / two, (min.imag + max.imag) / two),
+            scale.max(T::one()) * margin,
+            resolution,
+        ))
+    }
+}
+
+/// Draws an L-system after `depth` rewrites, giving each pixel on the path the one-based index of the last segment drawn through it.
+/// Colouring by this value shades the curve from its start to its end.
+pub fn render_lsystem<T: Float>(
+    viewport: &Viewport<T>,
+    lsystem: &LSystem<T>,
+    depth: u32,
+) -> Result<Array2<u32>, MandybrotError> {
+    viewport.validate()?;
+    let polylines = lsystem.turtle_path(depth)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let mut image = Array2::zeros((y_res as usize, x_res as usize));
+    let mut index = 0u32;
+    for polyline in &polylines {
+        for segment in polyline.windows(2) {
+            index = index.saturating_add(1);
+            rasterise_polyline(&mut image, viewport, segment, index);
+        }
+    }
+
+    Ok(image)
+}
@@ -0,0 +1,159 @@
+use ndarray::Array2;
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{error::check_finite, MandybrotError};
+
+/// File format of an exported mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeshFormat {
+    /// Wavefront OBJ text, which Blender and most modelling tools import.
+    Obj,
+    /// Binary STL, as read by slicers for 3D printing.
+    Stl,
+}
+
+/// A closed triangle mesh of a height field, with a flat base, suitable for 3D printing.
+/// Each pixel is one unit wide, with the first row of the data at the back so that the image reads the right way up from above.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightMesh {
+    pub vertices: Vec<[f32; 3]>,
+    /// Vertex indices of each triangle, wound anticlockwise when viewed from outside.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl HeightMesh {
+    /// Builds a solid whose top surface is `base_thickness + vertical_scale * height` above its base at each pixel.
+    /// Non-finite heights, such as the `NaN` of smoothed iterations inside the set, are raised to the greatest finite height.
+    pub fn new<T: Float>(
+        heights: &Array2<T>,
+        vertical_scale: T,
+        base_thickness: T,
+    ) -> Result<Self, MandybrotError> {
+        check_finite("vertical_scale", vertical_scale)?;
+        check_finite("base_thickness", base_thickness)?;
+        let (rows, cols) = heights.dim();
+        if rows < 2 || cols < 2 {
+            return Err(MandybrotError::EmptyResolution([cols as u32, rows as u32]));
+        }
+
+        let peak = heights
+            .iter()
+            .copied()
+            .filter(|h| h.is_finite())
+            .fold(T::zero(), T::max);
+        let mut vertices = Vec::with_capacity(rows * cols + 2 * (rows + cols));
+        for ((row, col), &height) in heights.indexed_iter() {
+            let height = if height.is_finite() { height } else { peak };
+            vertices.push([
+                col as f32,
+                (rows - 1 - row) as f32,
+                (base_thickness + vertical_scale * height).to_f32().unwrap(),
+            ]);
+        }
+        let top = |row: usize, col: usize| (row * cols + col) as u32;
+
+        let mut triangles = Vec::with_capacity(2 * (rows - 1) * (cols - 1) + 6 * (rows + cols));
+        for row in 0..rows - 1 {
+            for col in 0..cols - 1 {
+                let [a, b, c, d] = [
+                    top(row, col),
+                    top(row, col + 1),
+                    top(row + 1, col + 1),
+                    top(row + 1, col),
+                ];
+                triangles.push([a, d, c]);
+                triangles.push([a, c, b]);
+            }
+        }
+
+        // Walk the boundary of the top surface clockwise when viewed from above, dropping a wall from each edge to the base.
+        let boundary: Vec<(usize, usize)> = (0..cols - 1)
+            .map(|col| (0, col))
+            .chain((0..rows - 1).map(|row| (row, cols - 1)))
+            .chain((1..cols).rev().map(|col| (rows - 1, col)))
+            .chain((1..rows).rev().map(|row| (row, 0)))
+            .collect();
+        let first_base = vertices.len() as u32;
+        for &(row, col) in &boundary {
+            vertices.push([col as f32, (rows - 1 - row) as f32, 0.0]);
+        }
+        let centre = vertices.len() as u32;
+        vertices.push([(cols - 1) as f32 / 2.0, (rows - 1) as f32 / 2.0, 0.0]);
+
+        let num_boundary = boundary.len() as u32;
+        for i in 0..num_boundary {
+            let j = (i + 1) % num_boundary;
+            let (a, b) = (boundary[i as usize], boundary[j as usize]);
+            let [top_a, top_b] = [top(a.0, a.1), top(b.0, b.1)];
+            let [base_a, base_b] = [first_base + i, first_base + j];
+            triangles.push([top_a, top_b, base_b]);
+            triangles.push([top_a, base_b, base_a]);
+            triangles.push([centre, base_a, base_b]);
+        }
+
+        Ok(Self {
+            vertices,
+            triangles,
+        })
+    }
+
+    /// Writes the mesh to the given path.
+    pub fn save(&self, path: impl AsRef<Path>, format: MeshFormat) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        match format {
+            MeshFormat::Obj => self.write_obj(&mut file)?,
+            MeshFormat::Stl => self.write_stl(&mut file)?,
+        }
+        file.flush()
+    }
+
+    pub fn write_obj(&self, writer: &mut impl Write) -> io::Result<()> {
+        for [x, y, z] in &self.vertices {
+            writeln!(writer, "v {} {} {}", x, y, z)?;
+        }
+        // OBJ indices start from one.
+        for [a, b, c] in &self.triangles {
+            writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_stl(&self, writer: &mut impl Write) -> io::Result<()> {
+        let num_triangles = u32::try_from(self.triangles.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Mesh too large"))?;
+        writer.write_all(&[0; 80])?; // Header
+        writer.write_all(&num_triangles.to_le_bytes())?;
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|i| self.vertices[i as usize]);
+            let normal = unit_normal(a, b, c);
+            for value in [normal, a, b, c].iter().flatten() {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            writer.write_all(&0u16.to_le_bytes())?; // Attribute byte count
+        }
+        Ok(())
+    }
+}
+
+/// Unit normal of an anticlockwise triangle, or zero if it is degenerate.
+fn unit_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length > 0.0 {
+        n.map(|x| x / length)
+    } else {
+        [0.0; 3]
+    }
+}
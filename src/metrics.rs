@@ -0,0 +1,183 @@
+use ndarray::{Array2, Zip};
+use num_traits::ToPrimitive;
+use palette::LinSrgba;
+
+use crate::{error::check_positive, ColourMap, MandybrotError};
+
+/// Standard deviation, in pixels, of the Gaussian window over which `ssim` compares local statistics.
+const SSIM_SIGMA: f64 = 1.5;
+
+/// Radius, in pixels, at which the `ssim` window is cut off.
+const SSIM_RADIUS: usize = 5;
+
+/// Absolute difference of two images of values, such as iteration counts, pixel by pixel.
+pub fn difference<A>(a: &Array2<A>, b: &Array2<A>) -> Result<Array2<f64>, MandybrotError>
+where
+    A: Copy + ToPrimitive,
+{
+    check_dims(a.dim(), b.dim())?;
+    Ok((to_f64(a) - to_f64(b)).mapv(f64::abs))
+}
+
+/// Mean of the squared differences between two images of values.
+pub fn mean_squared_error<A>(a: &Array2<A>, b: &Array2<A>) -> Result<f64, MandybrotError>
+where
+    A: Copy + ToPrimitive,
+{
+    check_dims(a.dim(), b.dim())?;
+    Ok(mean_squared(&to_f64(a), &to_f64(b)))
+}
+
+/// Peak signal-to-noise ratio of two images of values in decibels, for values spanning a range of `peak`, such as `max_iter`.
+/// Identical images have an infinite ratio, and each halving of the error adds about 3 dB.
+pub fn psnr<A>(a: &Array2<A>, b: &Array2<A>, peak: f64) -> Result<f64, MandybrotError>
+where
+    A: Copy + ToPrimitive,
+{
+    check_positive("peak", peak)?;
+    Ok(ratio(mean_squared_error(a, b)?, peak))
+}
+
+/// Structural similarity of two images of values, for values spanning a range of `peak`, from one for identical images down towards zero or below.
+/// Unlike `psnr`, it compares the mean, contrast and structure of each neighbourhood, in a Gaussian window of standard deviation 1.5 pixels, so it tracks visible differences more closely.
+pub fn ssim<A>(a: &Array2<A>, b: &Array2<A>, peak: f64) -> Result<f64, MandybrotError>
+where
+    A: Copy + ToPrimitive,
+{
+    check_positive("peak", peak)?;
+    check_dims(a.dim(), b.dim())?;
+    Ok(structural_similarity(&to_f64(a), &to_f64(b), peak))
+}
+
+/// Peak signal-to-noise ratio of two colour images in decibels, over every channel including alpha, each spanning `[0, 1]`.
+pub fn psnr_colour(a: &Array2<LinSrgba>, b: &Array2<LinSrgba>) -> Result<f64, MandybrotError> {
+    check_dims(a.dim(), b.dim())?;
+    let (a, b) = (channels(a), channels(b));
+    let error = a
+        .iter()
+        .zip(&b)
+        .map(|(a, b)| mean_squared(a, b))
+        .sum::<f64>()
+        / 4.0;
+    Ok(ratio(error, 1.0))
+}
+
+/// Structural similarity of two colour images, the mean of `ssim` over every channel including alpha.
+pub fn ssim_colour(a: &Array2<LinSrgba>, b: &Array2<LinSrgba>) -> Result<f64, MandybrotError> {
+    check_dims(a.dim(), b.dim())?;
+    let (a, b) = (channels(a), channels(b));
+    let total = a
+        .iter()
+        .zip(&b)
+        .map(|(a, b)| structural_similarity(a, b, 1.0))
+        .sum::<f64>();
+    Ok(total / 4.0)
+}
+
+/// Visual diff of two colour images, colouring each pixel by the greatest difference in any of its channels.
+/// Differences are scaled so the greatest in the image reaches the end of the colour map, so faint changes show up, and identical images give the start of the map everywhere.
+pub fn diff_image(
+    a: &Array2<LinSrgba>,
+    b: &Array2<LinSrgba>,
+    colour_map: &ColourMap,
+) -> Result<Array2<LinSrgba>, MandybrotError> {
+    check_dims(a.dim(), b.dim())?;
+    let diff = Zip::from(a).and(b).map_collect(|x, y| {
+        [
+            x.red - y.red,
+            x.green - y.green,
+            x.blue - y.blue,
+            x.alpha - y.alpha,
+        ]
+        .into_iter()
+        .fold(0.0_f32, |max, d| max.max(d.abs()))
+    });
+    let max = diff.iter().copied().fold(0.0, f32::max);
+    let scale = if max > 0.0 { 1.0 / max } else { 0.0 };
+    Ok(diff.mapv(|d| colour_map.sample(d * scale)))
+}
+
+/// Checks that two images are the same size.
+fn check_dims(a: (usize, usize), b: (usize, usize)) -> Result<(), MandybrotError> {
+    if a != b {
+        return Err(MandybrotError::BufferSize {
+            expected: a.0 * a.1,
+            found: b.0 * b.1,
+        });
+    }
+    Ok(())
+}
+
+fn to_f64<A: Copy + ToPrimitive>(values: &Array2<A>) -> Array2<f64> {
+    values.mapv(|v| v.to_f64().unwrap_or(f64::NAN))
+}
+
+/// Red, green, blue and alpha channels of a colour image.
+fn channels(image: &Array2<LinSrgba>) -> [Array2<f64>; 4] {
+    [
+        image.mapv(|c| c.red as f64),
+        image.mapv(|c| c.green as f64),
+        image.mapv(|c| c.blue as f64),
+        image.mapv(|c| c.alpha as f64),
+    ]
+}
+
+fn mean_squared(a: &Array2<f64>, b: &Array2<f64>) -> f64 {
+    let sum = Zip::from(a)
+        .and(b)
+        .fold(0.0, |sum, &x, &y| sum + (x - y) * (x - y));
+    sum / a.len().max(1) as f64
+}
+
+/// Signal-to-noise ratio in decibels of a mean squared error, for values spanning `peak`.
+fn ratio(mean_squared_error: f64, peak: f64) -> f64 {
+    10.0 * (peak * peak / mean_squared_error).log10()
+}
+
+/// Mean structural similarity over every pixel, from Gaussian-weighted local means, variances and covariance.
+fn structural_similarity(a: &Array2<f64>, b: &Array2<f64>, peak: f64) -> f64 {
+    let c1 = (0.01 * peak).powi(2);
+    let c2 = (0.03 * peak).powi(2);
+
+    let mean_a = gaussian_blur(a);
+    let mean_b = gaussian_blur(b);
+    let mean_aa = gaussian_blur(&(a * a));
+    let mean_bb = gaussian_blur(&(b * b));
+    let mean_ab = gaussian_blur(&(a * b));
+
+    let mut total = 0.0;
+    Zip::from(&mean_a)
+        .and(&mean_b)
+        .and(&mean_aa)
+        .and(&mean_bb)
+        .and(&mean_ab)
+        .for_each(|&ma, &mb, &maa, &mbb, &mab| {
+            let (var_a, var_b, cov) = (maa - ma * ma, mbb - mb * mb, mab - ma * mb);
+            total += ((2.0 * ma * mb + c1) * (2.0 * cov + c2))
+                / ((ma * ma + mb * mb + c1) * (var_a + var_b + c2));
+        });
+    total / a.len().max(1) as f64
+}
+
+/// Blurs values with a Gaussian window, separably along each axis, renormalising the weights where the window overhangs the edge.
+fn gaussian_blur(values: &Array2<f64>) -> Array2<f64> {
+    let weights: Vec<f64> = (0..=SSIM_RADIUS)
+        .map(|d| (-((d * d) as f64) / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp())
+        .collect();
+    let (height, width) = values.dim();
+    let blur = |get: &dyn Fn(usize) -> f64, i: usize, len: usize| {
+        let (start, end) = (
+            i.saturating_sub(SSIM_RADIUS),
+            (i + SSIM_RADIUS).min(len - 1),
+        );
+        let (sum, weight) = (start..=end).fold((0.0, 0.0), |(sum, weight), j| {
+            let w = weights[i.abs_diff(j)];
+            (sum + w * get(j), weight + w)
+        });
+        sum / weight
+    };
+    let rows = Array2::from_shape_fn((height, width), |(y, x)| {
+        blur(&|j| values[(y, j)], x, width)
+    });
+    Array2::from_shape_fn((height, width), |(y, x)| blur(&|j| rows[(j, x)], y, height))
+}
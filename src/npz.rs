@@ -0,0 +1,247 @@
+use ndarray::Array2;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// A NumPy `.npz` archive of raw per-pixel data and render metadata, for analysis in Python or Julia.
+/// Each entry is stored uncompressed, and count channels use the narrowest unsigned type which holds their values.
+#[derive(Debug, Default)]
+pub struct Npz {
+    entries: Vec<Entry>,
+}
+
+/// An array waiting to be written as a `.npy` file.
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    descr: String,
+    shape: String,
+    data: Vec<u8>,
+}
+
+impl Npz {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a channel of integer counts, stored as `u8`, `u16` or `u32` depending on the largest value.
+    pub fn add_counts(&mut self, name: &str, data: &Array2<u32>) -> &mut Self {
+        let max = data.iter().copied().max().unwrap_or(0);
+        let (descr, bytes): (&str, Vec<u8>) = if max <= u8::MAX as u32 {
+            let narrow = |v: u32| u8::try_from(v).unwrap_or(u8::MAX);
+            ("|u1", data.iter().map(|&v| narrow(v)).collect())
+        } else if max <= u16::MAX as u32 {
+            let narrow = |v: u32| u16::try_from(v).unwrap_or(u16::MAX);
+            (
+                "<u2",
+                data.iter().flat_map(|&v| narrow(v).to_le_bytes()).collect(),
+            )
+        } else {
+            ("<u4", data.iter().flat_map(|&v| v.to_le_bytes()).collect())
+        };
+        self.add_array(name, descr, data.dim(), bytes)
+    }
+
+    /// Adds a channel of floating point values, stored as `f64`.
+    pub fn add_values(&mut self, name: &str, data: &Array2<f64>) -> &mut Self {
+        let bytes: Vec<u8> = data.iter().flat_map(|&v| v.to_le_bytes()).collect();
+        self.add_array(name, "<f8", data.dim(), bytes)
+    }
+
+    /// Adds a metadata string, stored as a zero-dimensional unicode array.
+    pub fn add_metadata(&mut self, name: &str, value: &str) -> &mut Self {
+        let chars: Vec<char> = value.chars().collect();
+        let mut bytes: Vec<u8> = chars
+            .iter()
+            .flat_map(|&c| (c as u32).to_le_bytes())
+            .collect();
+        if chars.is_empty() {
+            bytes.extend(0u32.to_le_bytes());
+        }
+        self.entries.push(Entry {
+            name: format!("{}.npy", name),
+            descr: format!("<U{}", chars.len().max(1)),
+            shape: "()".to_string(),
+            data: bytes,
+        });
+        self
+    }
+
+    fn add_array(
+        &mut self,
+        name: &str,
+        descr: &str,
+        (rows, cols): (usize, usize),
+        bytes: Vec<u8>,
+    ) -> &mut Self {
+        self.entries.push(Entry {
+            name: format!("{}.npy", name),
+            descr: descr.to_string(),
+            shape: format!("({}, {})", rows, cols),
+            data: bytes,
+        });
+        self
+    }
+
+    /// Writes the archive to the given path.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()
+    }
+
+    /// Writes the archive as a zip file with stored (uncompressed) entries.
+    /// Fails without writing anything if two entries share a name, and with `InvalidInput` if the archive is too large for a zip file without the ZIP64 extensions.
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut names = HashSet::new();
+        if let Some(entry) = self.entries.iter().find(|entry| !names.insert(&entry.name)) {
+            return Err(invalid(format!("Duplicate entry: {}", entry.name)));
+        }
+        let num_entries =
+            u16::try_from(self.entries.len()).map_err(|_| invalid("Too many entries"))?;
+
+        let mut offset = 0u64;
+        let mut central_directory = Vec::new();
+
+        for entry in &self.entries {
+            let data = npy(&entry.descr, &entry.shape, &entry.data)?;
+            let size = u32::try_from(data.len()).map_err(|_| invalid("Entry too large"))?;
+            let crc = crc32fast::hash(&data);
+            let fields = zip_entry_fields(crc, size, &entry.name)?;
+
+            let mut header = Vec::with_capacity(30 + entry.name.len());
+            header.extend(0x04034b50u32.to_le_bytes());
+            header.extend(&fields);
+            header.extend(entry.name.as_bytes());
+            writer.write_all(&header)?;
+            writer.write_all(&data)?;
+
+            central_directory.extend(0x02014b50u32.to_le_bytes());
+            central_directory.extend(20u16.to_le_bytes());
+            central_directory.extend(&fields);
+            central_directory.extend(0u16.to_le_bytes()); // Comment length
+            central_directory.extend(0u16.to_le_bytes()); // Disk number
+            central_directory.extend(0u16.to_le_bytes()); // Internal attributes
+            central_directory.extend(0u32.to_le_bytes()); // External attributes
+            central_directory.extend(archive_offset(offset)?.to_le_bytes());
+            central_directory.extend(entry.name.as_bytes());
+
+            offset += (header.len() + data.len()) as u64;
+        }
+
+        let directory_size =
+            u32::try_from(central_directory.len()).map_err(|_| invalid("Archive too large"))?;
+        let directory_offset = archive_offset(offset)?;
+        writer.write_all(&central_directory)?;
+        let mut end = Vec::with_capacity(22);
+        end.extend(0x06054b50u32.to_le_bytes());
+        end.extend(0u16.to_le_bytes()); // Disk number
+        end.extend(0u16.to_le_bytes()); // Central directory disk
+        end.extend(num_entries.to_le_bytes());
+        end.extend(num_entries.to_le_bytes());
+        end.extend(directory_size.to_le_bytes());
+        end.extend(directory_offset.to_le_bytes());
+        end.extend(0u16.to_le_bytes()); // Comment length
+        writer.write_all(&end)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+/// Offset from the start of the archive, which must fit in the 32 bits of a zip file without ZIP64 records.
+fn archive_offset(offset: u64) -> io::Result<u32> {
+    u32::try_from(offset).map_err(|_| invalid("Archive too large"))
+}
+
+/// Fields shared by the local file header and central directory record of a stored zip entry.
+fn zip_entry_fields(crc: u32, size: u32, name: &str) -> io::Result<Vec<u8>> {
+    let name_len = u16::try_from(name.len()).map_err(|_| invalid("Entry name too long"))?;
+    let mut fields = Vec::with_capacity(26);
+    fields.extend(20u16.to_le_bytes()); // Version needed
+    fields.extend(0u16.to_le_bytes()); // Flags
+    fields.extend(0u16.to_le_bytes()); // Stored
+    fields.extend(0u16.to_le_bytes()); // Modification time
+    fields.extend(0x21u16.to_le_bytes()); // Modification date: 1980-01-01
+    fields.extend(crc.to_le_bytes());
+    fields.extend(size.to_le_bytes()); // Compressed size
+    fields.extend(size.to_le_bytes()); // Uncompressed size
+    fields.extend(name_len.to_le_bytes());
+    fields.extend(0u16.to_le_bytes()); // Extra field length
+    Ok(fields)
+}
+
+/// Encodes raw little-endian data as a version 1.0 `.npy` file.
+fn npy(descr: &str, shape: &str, data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        descr, shape
+    );
+    // Pad so the data starts on a 64 byte boundary, as recommended by the format.
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+    let header_len = u16::try_from(header.len()).map_err(|_| invalid("Header too long"))?;
+
+    let mut bytes = Vec::with_capacity(10 + header.len() + data.len());
+    bytes.extend(b"\x93NUMPY\x01\x00");
+    bytes.extend(header_len.to_le_bytes());
+    bytes.extend(header.as_bytes());
+    bytes.extend(data);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_are_stored_in_the_narrowest_type() {
+        let mut npz = Npz::new();
+        npz.add_counts("small", &Array2::from_elem((2, 2), 255))
+            .add_counts("medium", &Array2::from_elem((2, 2), 256))
+            .add_counts("large", &Array2::from_elem((2, 2), 65536));
+        let descrs: Vec<&str> = npz
+            .entries
+            .iter()
+            .map(|entry| entry.descr.as_str())
+            .collect();
+        assert_eq!(descrs, ["|u1", "<u2", "<u4"]);
+        assert_eq!(npz.entries[1].data[..2], 256u16.to_le_bytes());
+
+        let mut archive = Vec::new();
+        npz.write(&mut archive).unwrap();
+        // End of central directory record, holding the number of entries
+        let end = &archive[archive.len() - 22..];
+        assert_eq!(end[..4], 0x06054b50u32.to_le_bytes());
+        assert_eq!(end[10..12], 3u16.to_le_bytes());
+    }
+
+    #[test]
+    fn duplicate_names_and_oversized_archives_are_rejected() {
+        let mut npz = Npz::new();
+        npz.add_metadata("fractal", "Mandelbrot")
+            .add_metadata("fractal", "Julia");
+        let mut archive = Vec::new();
+        let error = npz.write(&mut archive).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(archive.is_empty());
+
+        let long_name = "x".repeat(u16::MAX as usize);
+        let mut npz = Npz::new();
+        npz.add_metadata(&long_name, "too long");
+        let error = npz.write(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let mut npz = Npz::new();
+        for i in 0..=u16::MAX as usize {
+            npz.add_metadata(&i.to_string(), "");
+        }
+        let error = npz.write(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
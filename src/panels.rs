@@ -0,0 +1,172 @@
+use ndarray::{s, Array2};
+use num_traits::Float;
+#[cfg(feature = "png")]
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{error::check_count, MandybrotError, Viewport};
+#[cfg(feature = "png")]
+use crate::{ColourSpace, Dither};
+
+/// Division of a large viewport into a grid of smaller panels, which can be rendered one at a time and stitched without seams.
+/// Panels in the last column and row are cropped to the edge of the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PanelGrid<T> {
+    pub viewport: Viewport<T>,
+    /// Largest resolution of a single panel.
+    pub panel_resolution: [u32; 2],
+}
+
+/// One panel of a `PanelGrid`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Panel<T> {
+    /// Column and row of the panel within the grid.
+    pub index: [u32; 2],
+    /// Pixel of the full image at the top-left corner of the panel.
+    pub origin: [u32; 2],
+    /// Region of the plane covered by the panel, on the same pixel grid as the full image.
+    pub viewport: Viewport<T>,
+}
+
+impl<T: Float> PanelGrid<T> {
+    pub const fn new(viewport: Viewport<T>, panel_resolution: [u32; 2]) -> Self {
+        Self {
+            viewport,
+            panel_resolution,
+        }
+    }
+
+    /// Checks that the viewport is valid and that panels have at least one pixel.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        self.viewport.validate()?;
+        check_count("panel width", self.panel_resolution[0])?;
+        check_count("panel height", self.panel_resolution[1])
+    }
+
+    /// Number of columns and rows of panels.
+    pub fn num_panels(&self) -> [u32; 2] {
+        let [x_res, y_res] = self.viewport.resolution;
+        let [width, height] = self.panel_resolution;
+        [x_res.div_ceil(width), y_res.div_ceil(height)]
+    }
+
+    /// Panel in the given column and row.
+    pub fn panel(&self, column: u32, row: u32) -> Panel<T> {
+        let [x_res, y_res] = self.viewport.resolution;
+        let [width, height] = self.panel_resolution;
+        let origin = [column * width, row * height];
+        let resolution = [width.min(x_res - origin[0]), height.min(y_res - origin[1])];
+        Panel {
+            index: [column, row],
+            origin,
+            viewport: self.viewport.crop(origin, resolution),
+        }
+    }
+
+    /// Every panel, row by row from the top-left.
+    pub fn panels(&self) -> impl Iterator<Item = Panel<T>> + '_ {
+        let [columns, rows] = self.num_panels();
+        (0..rows).flat_map(move |row| (0..columns).map(move |column| self.panel(column, row)))
+    }
+}
+
+/// Renders a large image panel by panel and stitches the panels together in memory.
+/// `render` is called with each panel in turn, and must return an image of the panel's resolution.
+pub fn render_panels<T, P, F>(
+    grid: &PanelGrid<T>,
+    mut render: F,
+) -> Result<Array2<P>, MandybrotError>
+where
+    T: Float,
+    P: Clone + Default,
+    F: FnMut(&Panel<T>) -> Result<Array2<P>, MandybrotError>,
+{
+    grid.validate()?;
+    let [x_res, y_res] = grid.viewport.resolution;
+    let mut image = Array2::default((y_res as usize, x_res as usize));
+    for panel in grid.panels() {
+        let pixels = render_panel(&panel, &mut render)?;
+        let [x, y] = panel.origin.map(|i| i as usize);
+        let (height, width) = pixels.dim();
+        image
+            .slice_mut(s![y..y + height, x..x + width])
+            .assign(&pixels);
+    }
+    Ok(image)
+}
+
+/// Renders a large image panel by panel, streaming it to a PNG file one row of panels at a time.
+/// Only a single row of panels is held in memory, so the image may be far larger than would fit as a whole.
+/// `render` is called with each panel in turn, and must return an image of the panel's resolution.
+/// Colours are rounded to 8 bits with the given `dither`, aligned to the whole image so panels meet without seams.
+#[cfg(feature = "png")]
+pub fn save_panels_png<T, C, F>(
+    grid: &PanelGrid<T>,
+    path: impl AsRef<Path>,
+    dither: Dither,
+    mut render: F,
+) -> Result<(), MandybrotError>
+where
+    T: Float,
+    C: ColourSpace,
+    F: FnMut(&Panel<T>) -> Result<Array2<C>, MandybrotError>,
+{
+    grid.validate()?;
+    dither.validate()?;
+    let [x_res, y_res] = grid.viewport.resolution;
+    let [columns, rows] = grid.num_panels();
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, x_res, y_res);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(std::io::Error::from)?
+        .into_stream_writer()
+        .map_err(std::io::Error::from)?;
+
+    for row in 0..rows {
+        let strip = (0..columns)
+            .map(|column| render_panel(&grid.panel(column, row), &mut render))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut bytes = Vec::with_capacity(x_res as usize * 4);
+        for y in 0..strip[0].nrows() {
+            bytes.clear();
+            let image_y = (row * grid.panel_resolution[1]) as usize + y;
+            for pixels in &strip {
+                for &colour in pixels.row(y) {
+                    let image_x = bytes.len() / 4;
+                    bytes.extend_from_slice(
+                        &dither.encode(colour.into_linear(), [image_x, image_y]),
+                    );
+                }
+            }
+            writer.write_all(&bytes)?;
+        }
+    }
+
+    writer.finish().map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+/// Renders a single panel, checking that the image returned matches its resolution.
+fn render_panel<T, P, F>(panel: &Panel<T>, render: &mut F) -> Result<Array2<P>, MandybrotError>
+where
+    F: FnMut(&Panel<T>) -> Result<Array2<P>, MandybrotError>,
+{
+    let pixels = render(panel)?;
+    let [width, height] = panel.viewport.resolution;
+    let expected = width as usize * height as usize;
+    if pixels.dim() != (height as usize, width as usize) {
+        return Err(MandybrotError::BufferSize {
+            expected,
+            found: pixels.len(),
+        });
+    }
+    Ok(pixels)
+}
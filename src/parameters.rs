@@ -0,0 +1,360 @@
+use num_traits::Float;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{
+    Affine, Attractor, AttractorParameter, AttractorSeeding, Blend, Complex, ConfigFormat, Flame,
+    Fractal, Ifs, LSystem, MandybrotError, Periodicity, PostOp, Provenance, RngStrategy,
+    SamplePattern, Sampling, Viewport,
+};
+
+/// Version of the parameters format written by this version of the crate.
+pub const PARAMETERS_VERSION: u32 = 1;
+
+/// Settings of a single render: the view shared by every kind of image, and the job which selects what is drawn.
+/// Optional settings take their defaults when left out, and `version` records the format the settings were written for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Parameters<T> {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    pub centre: [T; 2],
+    pub scale: T,
+    pub resolution: [u32; 2],
+    /// Map from the untransformed image onto the complex plane about its centre, to rotate, shear or stretch the view.
+    #[serde(default)]
+    pub transform: Affine<T>,
+    /// Width of a pixel relative to its height, for anamorphic output.
+    #[serde(default = "unit")]
+    pub pixel_aspect: T,
+    /// Samples per pixel along each axis, for anti-aliasing.
+    #[serde(default = "one")]
+    pub super_samples: u32,
+    pub max_iter: u32,
+    /// Cycle detection shared by every escape-time job, including those of the layers, so points which fall into a periodic orbit bail out early.
+    #[serde(default)]
+    pub periodicity: Option<Periodicity<T>>,
+    /// Random number streams of jobs which sample randomly.
+    #[serde(default)]
+    pub rng: RngStrategy,
+    pub job: Job<T>,
+    /// Post-processing steps which turn the rendered values into colours, for `post_process`.
+    #[serde(default)]
+    pub pipeline: Vec<PostOp<T>>,
+    /// Further renders of the same view, each coloured by its own pipeline and blended over the image in turn, as by `blend_layers`.
+    #[serde(default)]
+    pub layers: Vec<Layer<T>>,
+}
+
+/// Render blended over the main image of a `Parameters`, sharing its view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Layer<T> {
+    pub job: Job<T>,
+    /// Post-processing steps which turn the layer's values into colours, so each layer is normalised and coloured on its own.
+    #[serde(default)]
+    pub pipeline: Vec<PostOp<T>>,
+    #[serde(default)]
+    pub blend: Blend,
+}
+
+/// What a render draws, with the settings particular to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub enum Job<T> {
+    /// An escape-time fractal.
+    Fractal {
+        fractal: Fractal<T>,
+        #[serde(default)]
+        sample_pattern: SamplePattern,
+        /// Samples only one half of a view straddling the real axis and mirrors it onto the other, as by `render_fractal_mirrored`.
+        #[serde(default)]
+        mirror: bool,
+    },
+    /// The density of an attractor's orbits, or its bifurcation diagram as `bifurcation` is swept along the real axis.
+    Attractor {
+        attractor: Attractor<T>,
+        #[serde(default = "origin")]
+        start: [T; 2],
+        #[serde(default = "unit")]
+        radius: T,
+        #[serde(default = "default_attractor_samples")]
+        num_samples: u32,
+        #[serde(default = "default_draw_after")]
+        draw_after: u32,
+        /// Whether orbits start from `num_samples` random points within `radius` of `start`, or from the pixels of the image. Bifurcation diagrams and provenance colouring always use random points.
+        #[serde(default)]
+        seeding: AttractorSeeding,
+        #[serde(default)]
+        bifurcation: Option<AttractorParameter>,
+        /// Colours each orbit by where it started, rather than drawing the density alone. Not used by bifurcation diagrams.
+        #[serde(default)]
+        provenance: Option<Provenance>,
+    },
+    /// The density of escaping orbits of the Mandelbrot set, as a Buddhabrot.
+    OrbitDensity {
+        sampling: Sampling<T>,
+        #[serde(default = "default_orbit_samples")]
+        num_samples: u32,
+    },
+    /// A fractal flame, coloured by its own colour indices.
+    Flame {
+        flame: Flame<T>,
+        #[serde(default = "default_chaos_samples")]
+        num_samples: u32,
+    },
+    /// The attractor of an iterated function system.
+    Ifs {
+        ifs: Definition<Ifs<T>>,
+        #[serde(default = "default_chaos_samples")]
+        num_samples: u32,
+    },
+    /// An L-system, rewritten `depth` times.
+    LSystem {
+        lsystem: Definition<LSystem<T>>,
+        #[serde(default = "default_depth")]
+        depth: u32,
+    },
+}
+
+/// System given in full, or by the name of a built-in preset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Definition<S> {
+    Custom(S),
+    Preset(String),
+}
+
+impl<T: Float> Definition<Ifs<T>> {
+    pub fn resolve(&self) -> Result<Ifs<T>, MandybrotError> {
+        match self {
+            Definition::Custom(ifs) => Ok(ifs.clone()),
+            Definition::Preset(name) => {
+                Ifs::preset(name).ok_or_else(|| MandybrotError::UnknownPreset(name.clone()))
+            }
+        }
+    }
+}
+
+impl<T: Float> Definition<LSystem<T>> {
+    pub fn resolve(&self) -> Result<LSystem<T>, MandybrotError> {
+        match self {
+            Definition::Custom(lsystem) => Ok(lsystem.clone()),
+            Definition::Preset(name) => {
+                LSystem::preset(name).ok_or_else(|| MandybrotError::UnknownPreset(name.clone()))
+            }
+        }
+    }
+}
+
+impl<T: Float> Parameters<T> {
+    /// Checks that the settings were written for a format this version of the crate reads, that the periodicity checking and layers' opacities are valid, and that each step of the pipelines is, including that every colour map is named.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        if self.version > PARAMETERS_VERSION {
+            return Err(MandybrotError::UnsupportedVersion(self.version));
+        }
+        if let Some(periodicity) = &self.periodicity {
+            periodicity.validate()?;
+        }
+        for op in &self.pipeline {
+            op.validate()?;
+        }
+        for layer in &self.layers {
+            layer.blend.validate()?;
+            for op in &layer.pipeline {
+                op.validate()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Viewport of the rendered image, with its transform and pixel aspect.
+    pub fn viewport(&self) -> Viewport<T> {
+        Viewport::new(
+            Complex::new(self.centre[0], self.centre[1]),
+            self.scale,
+            self.resolution,
+        )
+        .with_transform(self.transform)
+        .with_pixel_aspect(self.pixel_aspect)
+    }
+}
+
+impl<T: Float + DeserializeOwned> Parameters<T> {
+    /// Reads and validates a parameters file, as YAML, JSON or TOML by its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MandybrotError> {
+        let path = path.as_ref();
+        Self::parse(
+            &std::fs::read_to_string(path)?,
+            ConfigFormat::from_path(path)?,
+        )
+    }
+
+    /// Parses and validates settings written in the given format.
+    pub fn parse(text: &str, format: ConfigFormat) -> Result<Self, MandybrotError> {
+        let params: Self = format.parse(text)?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Parses and validates settings written as YAML.
+    pub fn from_yaml(yaml: &str) -> Result<Self, MandybrotError> {
+        Self::parse(yaml, ConfigFormat::Yaml)
+    }
+
+    /// Parses and validates settings written as JSON. Reading it needs the `json` feature.
+    pub fn from_json(json: &str) -> Result<Self, MandybrotError> {
+        Self::parse(json, ConfigFormat::Json)
+    }
+
+    /// Parses and validates settings written as TOML. Reading it needs the `toml` feature.
+    pub fn from_toml(toml: &str) -> Result<Self, MandybrotError> {
+        Self::parse(toml, ConfigFormat::Toml)
+    }
+}
+
+fn current_version() -> u32 {
+    PARAMETERS_VERSION
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn origin<T: Float>() -> [T; 2] {
+    [T::zero(); 2]
+}
+
+fn unit<T: Float>() -> T {
+    T::one()
+}
+
+fn default_attractor_samples() -> u32 {
+    10000
+}
+
+fn default_draw_after() -> u32 {
+    10
+}
+
+fn default_orbit_samples() -> u32 {
+    1000000
+}
+
+fn default_chaos_samples() -> u32 {
+    1000
+}
+
+fn default_depth() -> u32 {
+    4
+}
+
+#[cfg(all(test, any(feature = "json", feature = "toml")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_parameters_are_read() {
+        let json = std::fs::read_to_string("input/lsystem/koch_snowflake.json").unwrap();
+        let params = Parameters::<f64>::from_json(&json).unwrap();
+        assert_eq!(params.resolution, [1024, 1024]);
+        assert!(matches!(params.job, Job::LSystem { depth: 4, .. }));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn invalid_json_names_the_offending_line_or_key() {
+        let error = Parameters::<f64>::from_json("{\n  \"scale\": 1.0,\n  ]").unwrap_err();
+        assert!(
+            matches!(error, MandybrotError::ConfigSyntax { line: 3, .. }),
+            "{}",
+            error
+        );
+
+        let json = std::fs::read_to_string("input/lsystem/koch_snowflake.json")
+            .unwrap()
+            .replace("\"depth\": 4", "\"depth\": \"four\"");
+        match Parameters::<f64>::from_json(&json).unwrap_err() {
+            MandybrotError::ConfigValue { key, .. } => assert_eq!(key, "job.LSystem.depth"),
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn periodicity_is_read_with_the_sampling_settings() {
+        let json = |tolerance: f64| {
+            format!(
+                r#"{{
+                    "centre": [-0.5, 0.0],
+                    "scale": 3.0,
+                    "resolution": [64, 48],
+                    "max_iter": 500,
+                    "periodicity": {{ "tolerance": {}, "check_interval": 8 }},
+                    "job": {{ "Fractal": {{ "fractal": "Mandelbrot" }} }}
+                }}"#,
+                tolerance
+            )
+        };
+        let params = Parameters::<f64>::from_json(&json(1.0e-12)).unwrap();
+        let periodicity = params.periodicity.unwrap();
+        assert_eq!(periodicity.tolerance, 1.0e-12);
+        assert_eq!(periodicity.check_interval, 8);
+
+        assert!(matches!(
+            Parameters::<f64>::from_json(&json(-1.0)),
+            Err(MandybrotError::InvalidParameter {
+                name: "tolerance",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn unnamed_colour_maps_are_rejected() {
+        let json = std::fs::read_to_string("input/lsystem/koch_snowflake.json").unwrap();
+        let mut params = Parameters::<f64>::from_json(&json).unwrap();
+        params.pipeline = vec![PostOp::ColourMap("inferno".to_string())];
+        assert!(params.validate().is_ok());
+        params.pipeline = vec![PostOp::ColourMap(String::new())];
+        assert!(matches!(
+            params.validate(),
+            Err(MandybrotError::UnknownPreset(name)) if name.is_empty()
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_parameters_are_read() {
+        let toml = std::fs::read_to_string("input/ifs/sierpinski_triangle.toml").unwrap();
+        let params = Parameters::<f64>::from_toml(&toml).unwrap();
+        assert_eq!(params.max_iter, 100_000);
+        assert!(matches!(
+            params.job,
+            Job::Ifs {
+                num_samples: 1000,
+                ..
+            }
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn invalid_toml_names_the_offending_line_or_key() {
+        let error = Parameters::<f64>::from_toml("scale = 1.0\nresolution = [1024,").unwrap_err();
+        assert!(
+            matches!(error, MandybrotError::ConfigSyntax { line: 2, .. }),
+            "{}",
+            error
+        );
+
+        let toml = std::fs::read_to_string("input/ifs/sierpinski_triangle.toml")
+            .unwrap()
+            .replace("num_samples = 1000", "num_samples = \"many\"");
+        match Parameters::<f64>::from_toml(&toml).unwrap_err() {
+            MandybrotError::ConfigValue { key, .. } => assert_eq!(key, "job.Ifs.num_samples"),
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::MandybrotError;
+
+/// Black and white fill patterns which distinguish iteration bands without relying on colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pattern {
+    Empty,
+    Dots,
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+    Cross,
+    Solid,
+}
+
+impl Pattern {
+    /// Every pattern, ordered from lightest to darkest.
+    pub const ALL: [Pattern; 8] = [
+        Pattern::Empty,
+        Pattern::Dots,
+        Pattern::Horizontal,
+        Pattern::Vertical,
+        Pattern::Diagonal,
+        Pattern::AntiDiagonal,
+        Pattern::Cross,
+        Pattern::Solid,
+    ];
+
+    /// Whether the pattern inks the given pixel, for lines repeating every `spacing` pixels.
+    pub fn is_ink(&self, x: usize, y: usize, spacing: usize) -> bool {
+        let spacing = spacing.max(2);
+        let diagonal = (x + y).is_multiple_of(spacing);
+        let anti_diagonal = (x + spacing - y % spacing).is_multiple_of(spacing);
+        match self {
+            Pattern::Empty => false,
+            Pattern::Dots => x.is_multiple_of(spacing) && y.is_multiple_of(spacing),
+            Pattern::Horizontal => y.is_multiple_of(spacing),
+            Pattern::Vertical => x.is_multiple_of(spacing),
+            Pattern::Diagonal => diagonal,
+            Pattern::AntiDiagonal => anti_diagonal,
+            Pattern::Cross => diagonal || anti_diagonal,
+            Pattern::Solid => true,
+        }
+    }
+}
+
+/// Renders iteration counts as a high-contrast black and white image, filling each band of iterations with its own pattern.
+/// The `[0, max_iter)` range is split evenly between the patterns, points which never escape are solid, and band edges are outlined.
+/// Returns `0.0` for ink and `1.0` for paper, legible in greyscale print and without colour vision.
+/// `Svg::add_pattern_bands` draws the same bands as vector hatching.
+pub fn pattern_fill(
+    samples: &Array2<u32>,
+    max_iter: u32,
+    patterns: &[Pattern],
+    spacing: usize,
+) -> Result<Array2<f32>, MandybrotError> {
+    if patterns.is_empty() {
+        return Err(MandybrotError::ZeroCount("patterns"));
+    }
+    let (height, width) = samples.dim();
+    let band = |v: u32| band(v, max_iter, patterns.len());
+
+    Ok(Array2::from_shape_fn((height, width), |(y, x)| {
+        let current = band(samples[(y, x)]);
+        let is_edge = (x + 1 < width && band(samples[(y, x + 1)]) != current)
+            || (y + 1 < height && band(samples[(y + 1, x)]) != current);
+        let is_ink = is_edge
+            || match current {
+                Some(band) => patterns[band].is_ink(x, y, spacing),
+                None => true,
+            };
+        if is_ink {
+            0.0
+        } else {
+            1.0
+        }
+    }))
+}
+
+/// Band of `[0, max_iter)` split evenly into `num_bands` which an iteration count falls in, or `None` for points which never escape.
+pub(crate) fn band(value: u32, max_iter: u32, num_bands: usize) -> Option<usize> {
+    if value >= max_iter {
+        None
+    } else {
+        Some((value as u64 * num_bands as u64 / max_iter as u64) as usize)
+    }
+}
+
+/// Lowest iteration count of each band after the first, and `max_iter` for the points which never escape, without repeats.
+pub(crate) fn band_levels(max_iter: u32, num_bands: usize) -> Vec<u32> {
+    let mut levels: Vec<u32> = (1..num_bands as u64)
+        .map(|k| (k * max_iter as u64).div_ceil(num_bands as u64) as u32)
+        .chain([max_iter])
+        .filter(|&level| level > 0)
+        .collect();
+    levels.dedup();
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn band_levels_start_each_band() {
+        for (max_iter, num_bands) in [(100, 8), (5, 8), (7, 3)] {
+            let levels = band_levels(max_iter, num_bands);
+            for v in 1..=max_iter {
+                let starts_band = band(v, max_iter, num_bands) != band(v - 1, max_iter, num_bands);
+                assert_eq!(levels.contains(&v), starts_band);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_pattern_lists_are_errors() {
+        let samples = Array2::from_shape_fn((4, 4), |(y, x)| (x + y) as u32);
+        assert!(matches!(
+            pattern_fill(&samples, 8, &[], 4),
+            Err(MandybrotError::ZeroCount("patterns"))
+        ));
+        let image = pattern_fill(&samples, 8, &Pattern::ALL, 4).unwrap();
+        assert_eq!(image.dim(), (4, 4));
+    }
+}
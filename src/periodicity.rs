@@ -0,0 +1,72 @@
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::check_non_negative, Complex, MandybrotError};
+
+/// Settings for detecting orbits which have fallen into a cycle, so interior points can bail out early.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Periodicity<T> {
+    /// Distance below which two orbit points are considered equal.
+    pub tolerance: T,
+    /// Number of iterations before the reference point is first updated.
+    pub check_interval: u32,
+}
+
+impl<T> Periodicity<T> {
+    /// Applies a function to the tolerance, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Periodicity<U> {
+        Periodicity {
+            tolerance: f(self.tolerance),
+            check_interval: self.check_interval,
+        }
+    }
+}
+
+impl<T: Float> Periodicity<T> {
+    /// Checks that the tolerance is finite and not negative.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_non_negative("tolerance", self.tolerance)
+    }
+}
+
+/// Brent-style cycle detector which compares the orbit against a reference point refreshed at doubling intervals.
+pub(crate) struct CycleDetector<T> {
+    reference: Complex<T>,
+    tolerance_sqr: T,
+    steps: u32,
+    interval: u32,
+}
+
+impl<T: Float> CycleDetector<T> {
+    pub(crate) fn new(periodicity: &Periodicity<T>, start: Complex<T>) -> Self {
+        Self {
+            reference: start,
+            tolerance_sqr: periodicity.tolerance * periodicity.tolerance,
+            steps: 0,
+            interval: periodicity.check_interval.max(1),
+        }
+    }
+
+    /// Returns true if the orbit has returned to the reference point.
+    #[inline(always)]
+    pub(crate) fn is_periodic(&mut self, z: Complex<T>) -> bool {
+        if (z - self.reference).norm_sqr() < self.tolerance_sqr {
+            return true;
+        }
+        self.steps += 1;
+        if self.steps >= self.interval {
+            self.reference = z;
+            self.steps = 0;
+            self.interval = self.interval.saturating_mul(2);
+        }
+        false
+    }
+
+    /// Number of iterations since the reference point was last updated.
+    /// Once `is_periodic` has returned true this is the period of the cycle, or a multiple of it.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub(crate) fn period(&self) -> u32 {
+        self.steps + 1
+    }
+}
@@ -0,0 +1,301 @@
+use ndarray::{Array2, Zip};
+use num_traits::{Float, ToPrimitive};
+use palette::LinSrgba;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    box_blur, composite, downsample,
+    error::{check_count, check_finite, check_light, check_positive},
+    Background, ColourMap, MandybrotError, PaletteCycle, ToneMap,
+};
+
+/// Scaling of raw values, such as iteration counts or densities, into `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Normalisation {
+    /// Divides by the greatest value.
+    Linear,
+    /// Scales `ln(1 + v)` so the greatest value maps to one, lifting faint detail.
+    Log,
+}
+
+/// Step of a post-processing pipeline, which turns raw values into a coloured image.
+/// Steps before `ColourMap` act on the values, and steps after it on the colours; values left uncoloured at the end are drawn in greyscale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub enum PostOp<T> {
+    Normalise(Normalisation),
+    /// Maps the `low` percentile of the values to zero and the `high` percentile to one, clipping those beyond, as `normalise_percentiles`.
+    NormalisePercentiles {
+        low: f64,
+        high: f64,
+    },
+    ToneMap(ToneMap<T>),
+    /// Raises each value to the given power.
+    Gamma(T),
+    /// Replaces each value by the fraction of pixels with lower or equal values, spreading the colours evenly over the image.
+    HistogramEqualise,
+    PaletteCycle(PaletteCycle<T>),
+    /// Lights the values as a height field, `relief` pixels high per unit value, from the `light` direction.
+    /// The colours are darkened by the shading once they are produced.
+    Shade {
+        light: [T; 3],
+        #[serde(default = "unit")]
+        relief: T,
+    },
+    /// Colours the values with a named colour map.
+    ColourMap(String),
+    /// Blurs the colours over the given radius in pixels.
+    Blur(usize),
+    /// Averages each square block of the given size of colours into a single pixel, such as to resolve super samples.
+    Downsample(usize),
+    /// Places the colours over a background.
+    Composite {
+        background: Background,
+    },
+}
+
+fn unit<T: Float>() -> T {
+    T::one()
+}
+
+/// Image partway through a pipeline.
+enum Stage<T> {
+    Values {
+        values: Array2<T>,
+        shading: Option<Array2<T>>,
+    },
+    Colours(Array2<LinSrgba>),
+}
+
+impl<T: Float> Stage<T> {
+    /// Colours of the image, in greyscale if it has not been coloured.
+    fn into_colours(self, colour_map: Option<&ColourMap>) -> Array2<LinSrgba> {
+        match self {
+            Stage::Values { values, shading } => {
+                let mut colours = values.mapv(|v| {
+                    let v = v.to_f32().unwrap_or(0.0);
+                    match colour_map {
+                        Some(colour_map) => colour_map.sample(v),
+                        None => LinSrgba::new(v, v, v, 1.0),
+                    }
+                });
+                if let Some(shading) = shading {
+                    Zip::from(&mut colours)
+                        .and(&shading)
+                        .for_each(|colour, &s| {
+                            let s = s.to_f32().unwrap_or(1.0);
+                            colour.red *= s;
+                            colour.green *= s;
+                            colour.blue *= s;
+                        });
+                }
+                colours
+            }
+            Stage::Colours(colours) => colours,
+        }
+    }
+}
+
+/// Turns raw values, such as the output of a render, into a coloured image by applying each step of a pipeline in turn.
+/// Colour maps are looked up by name with `colour_maps`, such as `ColourMap::preset`.
+pub fn post_process<T, A>(
+    data: &Array2<A>,
+    pipeline: &[PostOp<T>],
+    colour_maps: impl Fn(&str) -> Option<ColourMap>,
+) -> Result<Array2<LinSrgba>, MandybrotError>
+where
+    T: Float,
+    A: Copy + ToPrimitive,
+{
+    let mut stage = Stage::Values {
+        values: data.mapv(|v| T::from(v).unwrap_or(T::zero())),
+        shading: None,
+    };
+
+    for op in pipeline {
+        stage = match (op, stage) {
+            (PostOp::ColourMap(name), stage) => {
+                let colour_map =
+                    colour_maps(name).ok_or_else(|| MandybrotError::UnknownPreset(name.clone()))?;
+                match stage {
+                    Stage::Values { .. } => Stage::Colours(stage.into_colours(Some(&colour_map))),
+                    Stage::Colours(_) => return Err(MandybrotError::PipelineOrder("ColourMap")),
+                }
+            }
+            (PostOp::Blur(radius), stage) => {
+                Stage::Colours(box_blur(&stage.into_colours(None), *radius))
+            }
+            (PostOp::Downsample(factor), stage) => {
+                Stage::Colours(downsample(&stage.into_colours(None), *factor)?)
+            }
+            (PostOp::Composite { background }, stage) => {
+                Stage::Colours(composite(&stage.into_colours(None), background))
+            }
+            (op, Stage::Values { values, shading }) => {
+                let (values, shading) = match op {
+                    PostOp::Normalise(normalisation) => {
+                        (normalise(&values, *normalisation), shading)
+                    }
+                    PostOp::NormalisePercentiles { low, high } => {
+                        (normalise_percentiles(&values, *low, *high)?, shading)
+                    }
+                    PostOp::ToneMap(tone_map) => (tone_map.apply(&values)?, shading),
+                    PostOp::Gamma(gamma) => {
+                        check_positive("gamma", *gamma)?;
+                        (values.mapv(|v| v.max(T::zero()).powf(*gamma)), shading)
+                    }
+                    PostOp::HistogramEqualise => (histogram_equalise(&values), shading),
+                    PostOp::PaletteCycle(cycle) => (values.mapv(|v| cycle.apply(v)), shading),
+                    PostOp::Shade { light, relief } => {
+                        let lit = shade(&values, light, *relief)?;
+                        let shading = match shading {
+                            Some(shading) => shading * &lit,
+                            None => lit,
+                        };
+                        (values, Some(shading))
+                    }
+                    PostOp::ColourMap(_)
+                    | PostOp::Blur(_)
+                    | PostOp::Downsample(_)
+                    | PostOp::Composite { .. } => unreachable!(),
+                };
+                Stage::Values { values, shading }
+            }
+            (op, Stage::Colours(_)) => {
+                return Err(MandybrotError::PipelineOrder(op.name()));
+            }
+        };
+    }
+
+    Ok(stage.into_colours(None))
+}
+
+impl<T: Float> PostOp<T> {
+    /// Checks the settings which do not depend on the image, such as that a colour map is named and a gamma is positive.
+    /// Whether a colour map's name can be found is only known once `post_process` looks it up.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match self {
+            PostOp::ColourMap(name) if name.is_empty() => {
+                Err(MandybrotError::UnknownPreset(name.clone()))
+            }
+            PostOp::ToneMap(tone_map) => tone_map.validate(),
+            PostOp::Gamma(gamma) => check_positive("gamma", *gamma),
+            PostOp::Shade { light, relief } => {
+                check_light(*light)?;
+                check_finite("relief", *relief)
+            }
+            PostOp::Downsample(factor) => {
+                check_count("factor", u32::try_from(*factor).unwrap_or(u32::MAX))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<T> PostOp<T> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PostOp::Normalise(_) => "Normalise",
+            PostOp::NormalisePercentiles { .. } => "NormalisePercentiles",
+            PostOp::ToneMap(_) => "ToneMap",
+            PostOp::Gamma(_) => "Gamma",
+            PostOp::HistogramEqualise => "HistogramEqualise",
+            PostOp::PaletteCycle(_) => "PaletteCycle",
+            PostOp::Shade { .. } => "Shade",
+            PostOp::ColourMap(_) => "ColourMap",
+            PostOp::Blur(_) => "Blur",
+            PostOp::Downsample(_) => "Downsample",
+            PostOp::Composite { .. } => "Composite",
+        }
+    }
+}
+
+/// Scales non-negative values into `[0, 1]`, leaving an image with no positive values black.
+fn normalise<T: Float>(values: &Array2<T>, normalisation: Normalisation) -> Array2<T> {
+    let max = values
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite())
+        .fold(T::zero(), T::max);
+    if max <= T::zero() {
+        return Array2::zeros(values.dim());
+    }
+    match normalisation {
+        Normalisation::Linear => values.mapv(|v| (v / max).max(T::zero())),
+        Normalisation::Log => {
+            let scale = max.ln_1p();
+            values.mapv(|v| v.max(T::zero()).ln_1p() / scale)
+        }
+    }
+}
+
+/// Scales values into `[0, 1]` between their `low` and `high` percentiles, given from 0 to 100, clipping those beyond.
+/// A handful of extreme pixels, such as the brightest points of an attractor, then no longer squeeze the rest of the image into a narrow band of colour.
+/// `NaN`s are ignored when finding the percentiles, and an image with no spread between them is left black.
+pub fn normalise_percentiles<T: Float>(
+    values: &Array2<T>,
+    low: f64,
+    high: f64,
+) -> Result<Array2<T>, MandybrotError> {
+    for (name, value) in [("low", low), ("high", high)] {
+        if !(0.0..=100.0).contains(&value) {
+            return Err(MandybrotError::InvalidParameter { name, value });
+        }
+    }
+    if low >= high {
+        return Err(MandybrotError::InvalidParameter {
+            name: "high",
+            value: high,
+        });
+    }
+
+    // Partial selection finds each percentile without sorting every value.
+    let mut finite: Vec<T> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if finite.is_empty() {
+        return Ok(Array2::zeros(values.dim()));
+    }
+    let last = (finite.len() - 1) as f64;
+    let mut percentile = |p: f64| {
+        let rank = p / 100.0 * last;
+        let below = rank.floor() as usize;
+        let (_, &mut lower, above) =
+            finite.select_nth_unstable_by(below, |a, b| a.partial_cmp(b).unwrap());
+        let upper = above.iter().copied().reduce(T::min).unwrap_or(lower);
+        lower + (upper - lower) * T::from(rank.fract()).unwrap()
+    };
+    let (low, high) = (percentile(low), percentile(high));
+    if high <= low {
+        return Ok(Array2::zeros(values.dim()));
+    }
+    let range = high - low;
+    Ok(values.mapv(|v| ((v - low) / range).max(T::zero()).min(T::one())))
+}
+
+/// Maps each value to its position in the cumulative distribution of all the values.
+fn histogram_equalise<T: Float>(values: &Array2<T>) -> Array2<T> {
+    let mut sorted: Vec<T> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let count = T::from(sorted.len().max(1)).unwrap();
+    values.mapv(|v| T::from(sorted.partition_point(|&x| x <= v)).unwrap() / count)
+}
+
+/// Lambertian shading of the values as a height field, clamping the gradient at the image edges.
+fn shade<T: Float>(
+    values: &Array2<T>,
+    light: &[T; 3],
+    relief: T,
+) -> Result<Array2<T>, MandybrotError> {
+    let light = check_light(*light)?;
+    check_finite("relief", relief)?;
+
+    let (height, width) = values.dim();
+    let half = T::from(0.5).unwrap();
+    Ok(Array2::from_shape_fn((height, width), |(y, x)| {
+        let (x0, x1) = (x.saturating_sub(1), (x + 1).min(width - 1));
+        let (y0, y1) = (y.saturating_sub(1), (y + 1).min(height - 1));
+        let dx = (values[[y, x1]] - values[[y, x0]]) * relief * half;
+        let dy = (values[[y1, x]] - values[[y0, x]]) * relief * half;
+        let norm = (dx * dx + dy * dy + T::one()).sqrt();
+        ((light[2] - dx * light[0] - dy * light[1]) / norm).max(T::zero())
+    }))
+}
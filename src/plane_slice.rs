@@ -0,0 +1,157 @@
+use core::ops::{Add, Mul, Sub};
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::check_finite,
+    kernel::{burning_ship_step, celtic_step, mandelbrot_step, phoenix_step, tricorn_step},
+    periodicity::CycleDetector,
+    Complex, MandybrotError, Periodicity,
+};
+
+/// Escape-time map `z → f(z) + c`, optionally with an extra parameter `p`, whose parameter and dynamic planes can be sliced by `Fractal::Slice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscapeMap {
+    /// `z → z² + c`, whose `c` plane is the Mandelbrot set and whose `z0` planes are its Julia sets.
+    Quadratic,
+    /// `z → (|Re z| + i|Im z|)² + c`.
+    BurningShip,
+    /// `z → conj(z)² + c`.
+    Tricorn,
+    /// `z → |Re(z²)| + i Im(z²) + c`.
+    Celtic,
+    /// `z → z² + c + p z₋₁`, where `z₋₁` is the previous point of the orbit.
+    Phoenix,
+}
+
+/// Coordinate of the space of starting points and parameters of an `EscapeMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlaneAxis {
+    CReal,
+    CImag,
+    Z0Real,
+    Z0Imag,
+    PReal,
+    PImag,
+}
+
+impl PlaneAxis {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlaneAxis::CReal => "c.real",
+            PlaneAxis::CImag => "c.imag",
+            PlaneAxis::Z0Real => "z0.real",
+            PlaneAxis::Z0Imag => "z0.imag",
+            PlaneAxis::PReal => "p.real",
+            PlaneAxis::PImag => "p.imag",
+        }
+    }
+}
+
+/// Values of the additive parameter `c`, starting point `z0` and extra parameter `p` of an `EscapeMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct SlicePoint<T> {
+    pub c: Complex<T>,
+    pub z0: Complex<T>,
+    pub p: Complex<T>,
+}
+
+impl<T> SlicePoint<T> {
+    /// Applies a function to every coordinate, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> SlicePoint<U> {
+        SlicePoint {
+            c: self.c.map(&f),
+            z0: self.z0.map(&f),
+            p: self.p.map(&f),
+        }
+    }
+}
+
+impl<T: Float> SlicePoint<T> {
+    /// The origin of every coordinate, as for the Mandelbrot set's `c` plane.
+    pub fn zero() -> Self {
+        let zero = Complex::new(T::zero(), T::zero());
+        Self {
+            c: zero,
+            z0: zero,
+            p: zero,
+        }
+    }
+
+    /// This point with one coordinate replaced.
+    pub fn with(mut self, axis: PlaneAxis, value: T) -> Self {
+        match axis {
+            PlaneAxis::CReal => self.c.real = value,
+            PlaneAxis::CImag => self.c.imag = value,
+            PlaneAxis::Z0Real => self.z0.real = value,
+            PlaneAxis::Z0Imag => self.z0.imag = value,
+            PlaneAxis::PReal => self.p.real = value,
+            PlaneAxis::PImag => self.p.imag = value,
+        }
+        self
+    }
+}
+
+/// Checks that the two image axes are distinct and the fixed coordinates are finite.
+pub(crate) fn validate_slice<T: Float>(
+    axes: [PlaneAxis; 2],
+    fixed: &SlicePoint<T>,
+) -> Result<(), MandybrotError> {
+    if axes[0] == axes[1] {
+        return Err(MandybrotError::DuplicateAxis(axes[0].name()));
+    }
+    check_finite("c.real", fixed.c.real)?;
+    check_finite("c.imag", fixed.c.imag)?;
+    check_finite("z0.real", fixed.z0.real)?;
+    check_finite("z0.imag", fixed.z0.imag)?;
+    check_finite("p.real", fixed.p.real)?;
+    check_finite("p.imag", fixed.p.imag)
+}
+
+/// Iterates a map from the point of its slice at pixel coordinate `q`, whose real and imaginary parts set the two image axes and the rest are fixed.
+/// Cycle detection is only applied to the quadratic map.
+#[inline(always)]
+pub(crate) fn sample_slice<T>(
+    map: EscapeMap,
+    axes: [PlaneAxis; 2],
+    fixed: &SlicePoint<T>,
+    q: Complex<T>,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>)
+where
+    T: Float + Add<Output = T> + Mul<Output = T> + Sub<Output = T>,
+{
+    let SlicePoint { c, z0, p } = fixed.with(axes[0], q.real).with(axes[1], q.imag);
+    let four = T::from(4.0).unwrap();
+
+    let mut z = z0;
+    let mut z_old = Complex::new(T::zero(), T::zero());
+    let mut cycle = match map {
+        EscapeMap::Quadratic => periodicity.map(|periodicity| CycleDetector::new(periodicity, z)),
+        _ => None,
+    };
+    let mut n = 0;
+
+    while z.norm_sqr() < four && n < max_iter {
+        let next = match map {
+            EscapeMap::Quadratic => mandelbrot_step(z, c),
+            EscapeMap::BurningShip => burning_ship_step(z, c),
+            EscapeMap::Tricorn => tricorn_step(z, c),
+            EscapeMap::Celtic => celtic_step(z, c),
+            EscapeMap::Phoenix => phoenix_step(z, z_old, c, p),
+        };
+        z_old = z;
+        z = next;
+        n += 1;
+        visit(z);
+
+        if cycle.as_mut().is_some_and(|cycle| cycle.is_periodic(z)) {
+            return (max_iter, z);
+        }
+    }
+
+    (n, z)
+}
@@ -0,0 +1,290 @@
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Add, Sub},
+};
+use num_traits::Float;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    error::{check_finite, check_non_negative},
+    Complex, DualComplex, MandybrotError,
+};
+
+/// Greatest number of coefficients of a `Polynomial`, one more than its greatest degree.
+pub const MAX_COEFFICIENTS: usize = 16;
+
+/// Polynomial with complex coefficients, held without allocating so that fractals built from it can be copied freely.
+/// Written as the list of its coefficients from the constant term up, so `[1, 0, 2]` is `1 + 2z²`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Polynomial<T> {
+    coefficients: [Complex<T>; MAX_COEFFICIENTS],
+    len: usize,
+}
+
+impl<T> Polynomial<T> {
+    /// Coefficients from the constant term up.
+    pub fn coefficients(&self) -> &[Complex<T>] {
+        &self.coefficients[..self.len]
+    }
+
+    /// Applies a function to every coefficient, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Polynomial<U> {
+        Polynomial {
+            coefficients: self.coefficients.map(|c| c.map(&f)),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Float> Polynomial<T> {
+    /// Polynomial with the given coefficients, from the constant term up.
+    pub fn new(coefficients: &[Complex<T>]) -> Result<Self, MandybrotError> {
+        check_len(coefficients.len())?;
+        let mut polynomial = Self::zero();
+        polynomial.coefficients[..coefficients.len()].copy_from_slice(coefficients);
+        polynomial.len = coefficients.len();
+        Ok(polynomial)
+    }
+
+    /// Polynomial with the given real coefficients, from the constant term up.
+    pub fn real(coefficients: &[T]) -> Result<Self, MandybrotError> {
+        check_len(coefficients.len())?;
+        let mut polynomial = Self::zero();
+        for (i, &c) in coefficients.iter().enumerate() {
+            polynomial.coefficients[i] = Complex::new(c, T::zero());
+        }
+        polynomial.len = coefficients.len();
+        Ok(polynomial)
+    }
+
+    /// The polynomial `0`, with no coefficients.
+    pub fn zero() -> Self {
+        Self {
+            coefficients: [Complex::new(T::zero(), T::zero()); MAX_COEFFICIENTS],
+            len: 0,
+        }
+    }
+
+    /// The polynomial `z`.
+    pub fn identity() -> Self {
+        Self::real(&[T::zero(), T::one()]).unwrap()
+    }
+
+    /// Highest power with a non-zero coefficient, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        let zero = Complex::new(T::zero(), T::zero());
+        self.coefficients().iter().rposition(|&c| c != zero)
+    }
+
+    /// Value at `z`, by Horner's method.
+    pub fn evaluate(&self, z: Complex<T>) -> Complex<T> {
+        self.coefficients()
+            .iter()
+            .rev()
+            .fold(Complex::new(T::zero(), T::zero()), |acc, &c| acc * z + c)
+    }
+
+    /// Value at `z`, carrying its derivative along.
+    pub fn evaluate_dual(&self, z: DualComplex<T>) -> DualComplex<T> {
+        self.coefficients().iter().rev().fold(
+            DualComplex::constant(Complex::new(T::zero(), T::zero())),
+            |acc, &c| acc * z + DualComplex::constant(c),
+        )
+    }
+
+    /// Derivative with respect to `z`.
+    pub fn derivative(&self) -> Self {
+        let mut derivative = Self::zero();
+        for (i, &c) in self.coefficients().iter().enumerate().skip(1) {
+            let i = T::from(i).unwrap();
+            derivative.coefficients[derivative.len] = Complex::new(c.real * i, c.imag * i);
+            derivative.len += 1;
+        }
+        derivative
+    }
+
+    /// Product of two polynomials, or `None` if its degree is too high to be held.
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let (Some(a), Some(b)) = (self.degree(), other.degree()) else {
+            return Some(Self::zero());
+        };
+        if a + b >= MAX_COEFFICIENTS {
+            return None;
+        }
+        let mut product = Self::zero();
+        product.len = a + b + 1;
+        for (i, &x) in self.coefficients()[..=a].iter().enumerate() {
+            for (j, &y) in other.coefficients()[..=b].iter().enumerate() {
+                product.coefficients[i + j] = product.coefficients[i + j] + x * y;
+            }
+        }
+        Some(product)
+    }
+
+    /// Checks that every coefficient is finite.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        for c in self.coefficients() {
+            check_finite("coefficient.real", c.real)?;
+            check_finite("coefficient.imag", c.imag)?;
+        }
+        Ok(())
+    }
+
+    /// Combines the coefficients of two polynomials term by term.
+    fn zip_with(&self, other: &Self, f: impl Fn(Complex<T>, Complex<T>) -> Complex<T>) -> Self {
+        let mut result = Self::zero();
+        result.len = self.len.max(other.len);
+        for i in 0..result.len {
+            result.coefficients[i] = f(self.coefficients[i], other.coefficients[i]);
+        }
+        result
+    }
+}
+
+/// Checks that a polynomial of `len` coefficients can be held.
+fn check_len(len: usize) -> Result<(), MandybrotError> {
+    if len > MAX_COEFFICIENTS {
+        return Err(MandybrotError::InvalidParameter {
+            name: "coefficients.len",
+            value: len as f64,
+        });
+    }
+    Ok(())
+}
+
+impl<T: Float> Add for Polynomial<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.zip_with(&other, |a, b| a + b)
+    }
+}
+
+impl<T: Float> Sub for Polynomial<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.zip_with(&other, |a, b| a - b)
+    }
+}
+
+impl<T: Serialize> Serialize for Polynomial<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        for c in self.coefficients() {
+            seq.serialize_element(c)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: Float + Deserialize<'de>> Deserialize<'de> for Polynomial<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CoefficientsVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Float + Deserialize<'de>> Visitor<'de> for CoefficientsVisitor<T> {
+            type Value = Polynomial<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a list of at most {} coefficients", MAX_COEFFICIENTS)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut polynomial = Polynomial::zero();
+                while let Some(c) = seq.next_element()? {
+                    if polynomial.len == MAX_COEFFICIENTS {
+                        return Err(de::Error::invalid_length(MAX_COEFFICIENTS + 1, &self));
+                    }
+                    polynomial.coefficients[polynomial.len] = c;
+                    polynomial.len += 1;
+                }
+                Ok(polynomial)
+            }
+        }
+
+        deserializer.deserialize_seq(CoefficientsVisitor(PhantomData))
+    }
+}
+
+/// Rational map `z → numerator(z) / denominator(z)`, iterated until its orbits diverge or converge.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct RationalMap<T> {
+    pub numerator: Polynomial<T>,
+    pub denominator: Polynomial<T>,
+    /// Orbits diverge once `|z|` reaches it, which may be infinite to only stop at poles.
+    pub escape_radius: T,
+    /// Orbits converge once a step moves `z` by less than it, which may be zero to never stop.
+    pub tolerance: T,
+}
+
+impl<T> RationalMap<T> {
+    /// Applies a function to every coefficient and bailout, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> RationalMap<U> {
+        RationalMap {
+            numerator: self.numerator.map(&f),
+            denominator: self.denominator.map(&f),
+            escape_radius: f(self.escape_radius),
+            tolerance: f(self.tolerance),
+        }
+    }
+}
+
+impl<T: Float> RationalMap<T> {
+    pub fn new(
+        numerator: Polynomial<T>,
+        denominator: Polynomial<T>,
+        escape_radius: T,
+        tolerance: T,
+    ) -> Self {
+        Self {
+            numerator,
+            denominator,
+            escape_radius,
+            tolerance,
+        }
+    }
+
+    /// Newton's method `z → z − p(z) / p'(z)` for finding the roots of `p`, whose orbits converge within `tolerance` of a root.
+    /// Returns `None` if `p` is of the greatest degree a polynomial can hold, as `z p'(z)` then cannot be held.
+    pub fn newton(p: &Polynomial<T>, tolerance: T) -> Option<Self> {
+        let derivative = p.derivative();
+        let numerator = Polynomial::identity().checked_mul(&derivative)? - *p;
+        Some(Self::new(numerator, derivative, T::infinity(), tolerance))
+    }
+
+    /// Value of the map at `z`, which is not finite at its poles.
+    pub fn evaluate(&self, z: Complex<T>) -> Complex<T> {
+        self.numerator.evaluate(z) / self.denominator.evaluate(z)
+    }
+
+    /// Value of the map at `z`, carrying its derivative along.
+    pub fn evaluate_dual(&self, z: DualComplex<T>) -> DualComplex<T> {
+        self.numerator.evaluate_dual(z) / self.denominator.evaluate_dual(z)
+    }
+
+    /// Checks that the coefficients are finite, the denominator is not zero, and the bailouts are in range.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        self.numerator.validate()?;
+        self.denominator.validate()?;
+        if self.denominator.degree().is_none() {
+            return Err(MandybrotError::InvalidParameter {
+                name: "denominator",
+                value: 0.0,
+            });
+        }
+        if self.escape_radius.is_nan() || self.escape_radius <= T::zero() {
+            return Err(MandybrotError::InvalidParameter {
+                name: "escape_radius",
+                value: self.escape_radius.to_f64().unwrap_or(f64::NAN),
+            });
+        }
+        check_finite("tolerance", self.tolerance)?;
+        check_non_negative("tolerance", self.tolerance)
+    }
+}
@@ -0,0 +1,81 @@
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{Complex, Viewport};
+
+/// Framing of a region of a fractal: where to look, how closely, and how many iterations resolve its detail.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Location<T> {
+    pub centre: Complex<T>,
+    /// Height of the region in the complex plane.
+    pub scale: T,
+    pub max_iter: u32,
+}
+
+/// Famous locations in the Mandelbrot set.
+const LOCATIONS: &[(&str, Location<f64>)] = &[
+    (
+        "seahorse_valley",
+        Location::new(Complex::new(-0.7453, 0.1127), 6.5e-3, 500),
+    ),
+    (
+        "elephant_valley",
+        Location::new(Complex::new(0.2822, 0.0100), 1.0e-2, 500),
+    ),
+    (
+        "triple_spiral_valley",
+        Location::new(Complex::new(-0.0880, 0.6540), 1.5e-2, 500),
+    ),
+    (
+        "misiurewicz_tip",
+        Location::new(Complex::new(-2.0, 0.0), 2.0e-2, 1000),
+    ),
+    (
+        "misiurewicz_i",
+        Location::new(Complex::new(0.0, 1.0), 2.0e-2, 1000),
+    ),
+    (
+        "misiurewicz_antenna",
+        Location::new(
+            Complex::new(-0.101_096_363_845_622_2, 0.956_286_510_809_141_5),
+            2.0e-3,
+            1000,
+        ),
+    ),
+];
+
+impl<T> Location<T> {
+    pub const fn new(centre: Complex<T>, scale: T, max_iter: u32) -> Self {
+        Self {
+            centre,
+            scale,
+            max_iter,
+        }
+    }
+}
+
+impl<T: Float> Location<T> {
+    /// Famous location in the Mandelbrot set by name: one of `seahorse_valley`, `elephant_valley`, `triple_spiral_valley`,
+    /// or the Misiurewicz points `misiurewicz_tip` (`c = -2`), `misiurewicz_i` (`c = i`) and `misiurewicz_antenna` (the tip of the upper period-three bulb's antenna).
+    pub fn preset(name: &str) -> Option<Self> {
+        LOCATIONS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, location)| Self {
+                centre: location.centre.map(|x| T::from(x).unwrap()),
+                scale: T::from(location.scale).unwrap(),
+                max_iter: location.max_iter,
+            })
+    }
+
+    /// Names of the famous locations.
+    pub fn presets() -> impl Iterator<Item = &'static str> {
+        LOCATIONS.iter().map(|(name, _)| *name)
+    }
+
+    /// Viewport showing the location at the given resolution.
+    pub fn viewport(&self, resolution: [u32; 2]) -> Viewport<T> {
+        Viewport::new(self.centre, self.scale, resolution)
+    }
+}
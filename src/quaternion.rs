@@ -0,0 +1,177 @@
+use core::ops::{Add, Mul, Sub};
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::check_finite, Complex, MandybrotError};
+
+/// Quaternion `real + i i + j j + k k`, where `i² = j² = k² = ijk = −1`.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quaternion<T> {
+    pub real: T,
+    pub i: T,
+    pub j: T,
+    pub k: T,
+}
+
+impl<T> Quaternion<T> {
+    pub const fn new(real: T, i: T, j: T, k: T) -> Self {
+        Self { real, i, j, k }
+    }
+
+    /// Applies a function to every component, as when changing precision.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Quaternion<U> {
+        Quaternion::new(f(self.real), f(self.i), f(self.j), f(self.k))
+    }
+}
+
+impl<T: Float> Quaternion<T> {
+    pub fn zero() -> Self {
+        Self::new(T::zero(), T::zero(), T::zero(), T::zero())
+    }
+
+    /// Component along the given axis.
+    pub fn component(&self, axis: QuaternionAxis) -> T {
+        match axis {
+            QuaternionAxis::Real => self.real,
+            QuaternionAxis::I => self.i,
+            QuaternionAxis::J => self.j,
+            QuaternionAxis::K => self.k,
+        }
+    }
+
+    /// This quaternion with one component replaced.
+    pub fn with(mut self, axis: QuaternionAxis, value: T) -> Self {
+        match axis {
+            QuaternionAxis::Real => self.real = value,
+            QuaternionAxis::I => self.i = value,
+            QuaternionAxis::J => self.j = value,
+            QuaternionAxis::K => self.k = value,
+        }
+        self
+    }
+
+    /// Squared modulus, the sum of the squares of the components.
+    pub fn norm_sqr(&self) -> T {
+        self.real * self.real + self.i * self.i + self.j * self.j + self.k * self.k
+    }
+
+    /// Square, computed with fewer multiplications than the general product.
+    #[inline(always)]
+    pub fn square(self) -> Self {
+        let two_real = self.real + self.real;
+        Self::new(
+            self.real * self.real - self.i * self.i - self.j * self.j - self.k * self.k,
+            two_real * self.i,
+            two_real * self.j,
+            two_real * self.k,
+        )
+    }
+
+    /// Point of the plane spanned by two axes, as the quaternion is drawn in a slice.
+    pub fn project(&self, axes: [QuaternionAxis; 2]) -> Complex<T> {
+        Complex::new(self.component(axes[0]), self.component(axes[1]))
+    }
+
+    /// Checks that every component is finite.
+    pub fn validate(&self, name: [&'static str; 4]) -> Result<(), MandybrotError> {
+        check_finite(name[0], self.real)?;
+        check_finite(name[1], self.i)?;
+        check_finite(name[2], self.j)?;
+        check_finite(name[3], self.k)
+    }
+}
+
+impl<T: Copy + Add<Output = T>> Add for Quaternion<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.real + other.real,
+            self.i + other.i,
+            self.j + other.j,
+            self.k + other.k,
+        )
+    }
+}
+
+impl<T: Copy + Sub<Output = T>> Sub for Quaternion<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(
+            self.real - other.real,
+            self.i - other.i,
+            self.j - other.j,
+            self.k - other.k,
+        )
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Mul for Quaternion<T> {
+    type Output = Self;
+
+    /// Hamilton product, which does not commute.
+    fn mul(self, other: Self) -> Self {
+        let (a, b) = (self, other);
+        Self::new(
+            a.real * b.real - a.i * b.i - a.j * b.j - a.k * b.k,
+            a.real * b.i + a.i * b.real + a.j * b.k - a.k * b.j,
+            a.real * b.j - a.i * b.k + a.j * b.real + a.k * b.i,
+            a.real * b.k + a.i * b.j - a.j * b.i + a.k * b.real,
+        )
+    }
+}
+
+/// Component of a quaternion, one of the two spanning a slice of a quaternion fractal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuaternionAxis {
+    Real,
+    I,
+    J,
+    K,
+}
+
+impl QuaternionAxis {
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuaternionAxis::Real => "real",
+            QuaternionAxis::I => "i",
+            QuaternionAxis::J => "j",
+            QuaternionAxis::K => "k",
+        }
+    }
+}
+
+/// Checks that the two image axes of a quaternion slice are distinct and its fixed components are finite.
+pub(crate) fn validate_quaternion_slice<T: Float>(
+    axes: [QuaternionAxis; 2],
+    fixed: &Quaternion<T>,
+) -> Result<(), MandybrotError> {
+    if axes[0] == axes[1] {
+        return Err(MandybrotError::DuplicateAxis(axes[0].name()));
+    }
+    fixed.validate(["fixed.real", "fixed.i", "fixed.j", "fixed.k"])
+}
+
+/// Iterates `z → z² + c` over the quaternions, returning the iteration count and the final value of `z` projected onto the slice `axes`.
+/// Each point of the orbit after the first is passed to `visit`, projected in the same way.
+#[inline(always)]
+pub(crate) fn quaternion_julia<T: Float>(
+    z: Quaternion<T>,
+    c: Quaternion<T>,
+    axes: [QuaternionAxis; 2],
+    max_iter: u32,
+    visit: &mut impl FnMut(Complex<T>),
+) -> (u32, Complex<T>) {
+    let four = T::from(4.0).unwrap();
+    let mut z = z;
+    let mut n = 0;
+
+    while z.norm_sqr() < four && n < max_iter {
+        z = z.square() + c;
+        n += 1;
+        visit(z.project(axes));
+    }
+
+    (n, z.project(axes))
+}
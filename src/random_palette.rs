@@ -0,0 +1,112 @@
+use core::f32::consts::PI;
+use palette::{FromColor, LinSrgb, LinSrgba, Oklch};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::check_count, rng_strategy::PALETTE_DOMAIN, ColourMap, MandybrotError, RngStrategy,
+};
+
+/// Style of a randomly generated colour map, see `ColourMap::random`.
+/// Every scheme is built in the perceptually uniform Oklch space, so equal steps along the map look like equal changes of colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaletteScheme {
+    /// Hues spread evenly around the colour wheel from a random start, at a similar lightness and chroma.
+    HueSpread,
+    /// Lightness rising steadily from dark to light while the hue drifts, so the map still reads correctly in greyscale.
+    LightnessMonotonic,
+    /// Diverging from a dark random hue through a pale neutral to a dark shade of its complement.
+    Complementary,
+}
+
+impl PaletteScheme {
+    /// Scheme by its name in `snake_case`, such as `hue_spread`.
+    pub fn from_name(name: &str) -> Result<Self, MandybrotError> {
+        match name {
+            "hue_spread" => Ok(PaletteScheme::HueSpread),
+            "lightness_monotonic" => Ok(PaletteScheme::LightnessMonotonic),
+            "complementary" => Ok(PaletteScheme::Complementary),
+            _ => Err(MandybrotError::UnknownPreset(name.to_string())),
+        }
+    }
+}
+
+impl ColourMap {
+    /// Random colour map of `num_colours` stops in the given scheme, drawn reproducibly from `seed`.
+    /// Neighbouring seeds give unrelated palettes, so the index of each job of a batch sweep can serve as its seed.
+    pub fn random(
+        scheme: PaletteScheme,
+        num_colours: usize,
+        seed: u64,
+    ) -> Result<Self, MandybrotError> {
+        check_count("num_colours", num_colours as u32)?;
+
+        let mut rng = RngStrategy::Seeded(seed).stream(PALETTE_DOMAIN, 0);
+        let hue = rng.random_range(0.0..360.0);
+        let last = (num_colours - 1).max(1) as f32;
+        let stops: Vec<(f32, f32, f32)> = match scheme {
+            PaletteScheme::HueSpread => {
+                let lightness = rng.random_range(0.55..0.8);
+                let chroma = rng.random_range(0.1..0.18);
+                let step = 360.0 / num_colours as f32;
+                (0..num_colours)
+                    .map(|i| {
+                        let jitter = rng.random_range(-0.25..0.25) * step;
+                        let l = lightness + rng.random_range(-0.05..0.05);
+                        (l, chroma, hue + i as f32 * step + jitter)
+                    })
+                    .collect()
+            }
+            PaletteScheme::LightnessMonotonic => {
+                let dark = rng.random_range(0.1..0.25);
+                let light = rng.random_range(0.85..0.97);
+                let chroma = rng.random_range(0.1..0.2);
+                let drift = rng.random_range(-180.0..180.0);
+                (0..num_colours)
+                    .map(|i| {
+                        let t = i as f32 / last;
+                        // Chroma peaks midway, as the darkest and lightest colours hold little of it.
+                        (
+                            dark + (light - dark) * t,
+                            chroma * (PI * t).sin(),
+                            hue + drift * t,
+                        )
+                    })
+                    .collect()
+            }
+            PaletteScheme::Complementary => {
+                let dark = rng.random_range(0.25..0.4);
+                let light = rng.random_range(0.88..0.96);
+                let chroma = rng.random_range(0.12..0.2);
+                (0..num_colours)
+                    .map(|i| {
+                        let t = 2.0 * i as f32 / last - 1.0;
+                        let side = if t < 0.0 { hue } else { hue + 180.0 };
+                        let l = light - (light - dark) * t.abs();
+                        (l, chroma * t.abs(), side)
+                    })
+                    .collect()
+            }
+        };
+
+        Self::new(
+            stops
+                .into_iter()
+                .map(|(l, chroma, hue)| in_gamut(l.clamp(0.0, 1.0), chroma, hue))
+                .collect(),
+        )
+    }
+}
+
+/// Opaque colour of the given Oklch lightness and hue, with its chroma reduced until it can be shown in sRGB.
+fn in_gamut(lightness: f32, mut chroma: f32, hue: f32) -> LinSrgba {
+    loop {
+        let colour = LinSrgb::from_color(Oklch::new(lightness, chroma, hue));
+        let channels = [colour.red, colour.green, colour.blue];
+        if chroma < 1e-3 || channels.iter().all(|c| (0.0..=1.0).contains(c)) {
+            let [red, green, blue] = channels.map(|c| c.clamp(0.0, 1.0));
+            return LinSrgba::new(red, green, blue, 1.0);
+        }
+        chroma *= 0.9;
+    }
+}
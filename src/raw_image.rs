@@ -0,0 +1,94 @@
+use ndarray::Array2;
+use num_traits::Float;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Saves counts as a 16-bit greyscale PNG, keeping far more of the range of high iteration or density renders than 8-bit output.
+/// Counts are stored unchanged if they all fit in 16 bits, and otherwise scaled so the largest count is white.
+#[cfg(feature = "png")]
+pub fn save_grey16_png(data: &Array2<u32>, path: impl AsRef<Path>) -> io::Result<()> {
+    let (height, width) = data.dim();
+    let max = data.iter().copied().max().unwrap_or(0);
+    let scale = if max > u16::MAX as u32 {
+        u16::MAX as f64 / max as f64
+    } else {
+        1.0
+    };
+    let bytes: Vec<u8> = data
+        .iter()
+        .flat_map(|&n| ((n as f64 * scale).round() as u16).to_be_bytes())
+        .collect();
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, dimension(width)?, dimension(height)?);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    let mut writer = encoder.write_header().map_err(io::Error::from)?;
+    writer.write_image_data(&bytes).map_err(io::Error::from)?;
+    writer.finish().map_err(io::Error::from)
+}
+
+/// Saves values as an uncompressed 32-bit floating point greyscale TIFF, which keeps the full range of the data for compositing tools.
+pub fn save_float_tiff<T: Float>(data: &Array2<T>, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    write_float_tiff(data, &mut file)?;
+    file.flush()
+}
+
+/// Writes values as a little-endian TIFF with a single strip of 32-bit floating point samples.
+pub fn write_float_tiff<T: Float>(data: &Array2<T>, writer: &mut impl Write) -> io::Result<()> {
+    const SHORT: u16 = 3;
+    const LONG: u16 = 4;
+    const NUM_ENTRIES: u32 = 10;
+    // Header, then the directory of tags, padded to a word boundary.
+    const DATA_OFFSET: u32 = (8 + 2 + NUM_ENTRIES * 12 + 4).next_multiple_of(4);
+
+    let (height, width) = data.dim();
+    let [width, height] = [dimension(width)?, dimension(height)?];
+    let num_bytes = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(4))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Image too large"))?;
+
+    let mut header = Vec::with_capacity(DATA_OFFSET as usize);
+    header.extend(b"II*\0");
+    header.extend(8u32.to_le_bytes());
+    header.extend((NUM_ENTRIES as u16).to_le_bytes());
+    for (tag, kind, value) in [
+        (256, LONG, width),       // Image width
+        (257, LONG, height),      // Image length
+        (258, SHORT, 32),         // Bits per sample
+        (259, SHORT, 1),          // No compression
+        (262, SHORT, 1),          // Black is zero
+        (273, LONG, DATA_OFFSET), // Strip offset
+        (277, SHORT, 1),          // Samples per pixel
+        (278, LONG, height),      // Rows per strip
+        (279, LONG, num_bytes),   // Strip byte count
+        (339, SHORT, 3),          // Floating point samples
+    ] {
+        header.extend((tag as u16).to_le_bytes());
+        header.extend(kind.to_le_bytes());
+        header.extend(1u32.to_le_bytes());
+        // Values shorter than four bytes are left-justified in the value field.
+        match kind {
+            SHORT => header.extend([(value as u16).to_le_bytes(), [0; 2]].concat()),
+            _ => header.extend(value.to_le_bytes()),
+        }
+    }
+    header.extend(0u32.to_le_bytes()); // No further directories
+    header.resize(DATA_OFFSET as usize, 0);
+    writer.write_all(&header)?;
+
+    let bytes: Vec<u8> = data
+        .iter()
+        .flat_map(|v| v.to_f32().unwrap_or(f32::NAN).to_le_bytes())
+        .collect();
+    writer.write_all(&bytes)
+}
+
+fn dimension(size: usize) -> io::Result<u32> {
+    u32::try_from(size).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Image too large"))
+}
@@ -0,0 +1,124 @@
+use ndarray::Array2;
+use num_traits::{Float, FloatConst};
+
+use crate::{Complex, MandybrotError, Viewport};
+
+/// Escape radius of the outermost point of a traced ray.
+const RAY_RADIUS: f64 = 100.0;
+
+/// Maximum Newton steps used to place each point of a ray.
+const MAX_NEWTON_STEPS: u32 = 64;
+
+/// Traces the external ray of the Mandelbrot set at the rational angle `numerator / denominator` turns, from far outside the set inwards.
+///
+/// Each of the `depth` iterations is split into `sharpness` points, and each point is found by Newton's method on `f_c^n(0) = w`, where `w` is the target Böttcher coordinate raised to the appropriate power.
+/// The returned polyline approaches the landing point of the ray as `depth` increases.
+/// The `denominator` must be at least one.
+pub fn trace_external_ray<T: Float + FloatConst>(
+    numerator: u64,
+    denominator: u64,
+    depth: u32,
+    sharpness: u32,
+) -> Result<Vec<Complex<T>>, MandybrotError> {
+    if denominator == 0 {
+        return Err(MandybrotError::ZeroCount("denominator"));
+    }
+    let sharpness = sharpness.max(1);
+    let ln_radius = RAY_RADIUS.ln();
+    let tau = T::TAU();
+
+    // Angle of the target at the current iteration count, doubled exactly as a fraction.
+    let mut angle_numerator = numerator % denominator;
+    let mut c = {
+        let theta = T::from(angle_numerator as f64 / denominator as f64).unwrap() * tau;
+        let r = T::from(RAY_RADIUS).unwrap();
+        Complex::new(r * theta.cos(), r * theta.sin())
+    };
+
+    let mut ray = vec![c];
+    for m in 1..(depth * sharpness) {
+        let iterations = m / sharpness + 1;
+        if m % sharpness == 0 {
+            // Doubled in 128 bits, as twice a numerator above 2^63 overflows
+            angle_numerator = ((angle_numerator as u128 * 2) % denominator as u128) as u64;
+        }
+
+        // |w| = R^(2^(-j/S)), the target radius for the fractional step `j` through this iteration.
+        let fraction = (m % sharpness) as f64 / sharpness as f64;
+        let modulus = T::from((ln_radius * 2.0_f64.powf(-fraction)).exp()).unwrap();
+        let theta = T::from(angle_numerator as f64 / denominator as f64).unwrap() * tau;
+        let target = Complex::new(modulus * theta.cos(), modulus * theta.sin());
+
+        for _ in 0..MAX_NEWTON_STEPS {
+            let zero = Complex::new(T::zero(), T::zero());
+            let one = Complex::new(T::one(), T::zero());
+            let two = Complex::new(T::from(2.0).unwrap(), T::zero());
+            let (mut z, mut dz) = (zero, zero);
+            for _ in 0..iterations {
+                dz = two * z * dz + one;
+                z = z * z + c;
+            }
+            let step = (z - target) / dz;
+            c = c - step;
+            if step.norm_sqr() <= T::epsilon() * T::epsilon() * c.norm_sqr() {
+                break;
+            }
+        }
+        if !(c.real.is_finite() && c.imag.is_finite()) {
+            break;
+        }
+        ray.push(c);
+    }
+
+    Ok(ray)
+}
+
+/// Draws a polyline, such as an external ray, onto an existing render by setting the pixels it passes through to `value`.
+pub fn rasterise_polyline<T: Float>(
+    image: &mut Array2<u32>,
+    viewport: &Viewport<T>,
+    points: &[Complex<T>],
+    value: u32,
+) {
+    let pixel_size = viewport.pixel_size();
+    for segment in points.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let delta = end - start;
+        let length = (delta.real / pixel_size.real)
+            .abs()
+            .max((delta.imag / pixel_size.imag).abs());
+        // Visit at least one point per pixel crossed, capped for segments far outside the view.
+        let steps = length.ceil().to_usize().unwrap_or(0).clamp(1, 1 << 16);
+        for i in 0..=steps {
+            let t = T::from(i).unwrap() / T::from(steps).unwrap();
+            let p = start + Complex::new(delta.real * t, delta.imag * t);
+            if let Some([x, y]) = viewport.complex_to_pixel(&p) {
+                image[[y, x]] = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn angles_with_large_denominators_are_doubled_exactly() {
+        // `u64::MAX` is divisible by three, so this is the angle 1/3 with a denominator above 2^63
+        let large = trace_external_ray::<f64>(u64::MAX / 3, u64::MAX, 8, 4).unwrap();
+        let small = trace_external_ray::<f64>(1, 3, 8, 4).unwrap();
+        assert_eq!(large.len(), small.len());
+        for (a, b) in large.iter().zip(&small) {
+            assert!((*a - *b).norm_sqr() < 1e-18);
+        }
+    }
+
+    #[test]
+    fn zero_denominators_are_rejected() {
+        assert!(matches!(
+            trace_external_ray::<f64>(1, 0, 8, 4),
+            Err(MandybrotError::ZeroCount("denominator"))
+        ));
+    }
+}
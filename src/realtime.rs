@@ -0,0 +1,122 @@
+use num_traits::Float;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+
+use crate::{Complex, Fractal, MandybrotError, Periodicity, Viewport};
+
+/// Renderer which redraws a small Julia set many times a second as its `c` changes, such as in an audio visualiser.
+/// The thread pool and the point of each pixel are created once and reused, so drawing a frame allocates nothing.
+pub struct RealtimeRenderer<T> {
+    viewport: Viewport<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+    /// Centre of each pixel, in row-major order.
+    points: Vec<Complex<T>>,
+    pool: ThreadPool,
+}
+
+impl<T> RealtimeRenderer<T>
+where
+    T: Float + Send + Sync,
+{
+    /// Renderer of the given view, drawing frames on `num_threads` threads, or one per core if zero.
+    pub fn new(
+        viewport: Viewport<T>,
+        max_iter: u32,
+        periodicity: Option<Periodicity<T>>,
+        num_threads: usize,
+    ) -> Result<Self, MandybrotError> {
+        if let Some(periodicity) = &periodicity {
+            periodicity.validate()?;
+        }
+        let pool = ThreadPoolBuilder::new().num_threads(num_threads).build()?;
+        let mut renderer = Self {
+            viewport,
+            max_iter,
+            periodicity,
+            points: Vec::new(),
+            pool,
+        };
+        renderer.set_viewport(viewport)?;
+        Ok(renderer)
+    }
+
+    pub fn viewport(&self) -> &Viewport<T> {
+        &self.viewport
+    }
+
+    pub fn max_iter(&self) -> u32 {
+        self.max_iter
+    }
+
+    /// Number of values in each frame, one per pixel.
+    pub fn num_pixels(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Moves the view, reusing the existing buffers unless the new view has more pixels.
+    pub fn set_viewport(&mut self, viewport: Viewport<T>) -> Result<(), MandybrotError> {
+        viewport.validate()?;
+        let [x_res, y_res] = viewport.resolution;
+        self.points.clear();
+        self.points
+            .extend((0..y_res).flat_map(|y| (0..x_res).map(move |x| viewport.pixel_centre(x, y))));
+        self.viewport = viewport;
+        Ok(())
+    }
+
+    pub fn set_max_iter(&mut self, max_iter: u32) {
+        self.max_iter = max_iter;
+    }
+
+    /// Draws the iteration counts of the Julia set of `c` into `output`, one per pixel in row-major order.
+    pub fn render_into(&self, c: Complex<T>, output: &mut [u32]) -> Result<(), MandybrotError> {
+        self.check_output(output.len())?;
+        let fractal = Fractal::Julia { c };
+        let (max_iter, periodicity) = (self.max_iter, self.periodicity.as_ref());
+        self.pool.install(|| {
+            output
+                .par_iter_mut()
+                .zip(&self.points)
+                .with_min_len(self.viewport.resolution[0] as usize)
+                .for_each(|(count, &p)| *count = fractal.sample(p, max_iter, periodicity));
+        });
+        Ok(())
+    }
+
+    /// Draws the inverse-distance field of an orbit trap into `output`, one value per pixel in row-major order.
+    /// Each value is `1 / (1 + d)`, where `d` is the closest the orbit of the pixel under the Julia map of `c` comes to `trap`, so it rises to one where orbits pass through the trap.
+    pub fn render_trap_into(
+        &self,
+        c: Complex<T>,
+        trap: Complex<T>,
+        output: &mut [f32],
+    ) -> Result<(), MandybrotError> {
+        self.check_output(output.len())?;
+        let fractal = Fractal::Julia { c };
+        let (max_iter, periodicity) = (self.max_iter, self.periodicity.as_ref());
+        self.pool.install(|| {
+            output
+                .par_iter_mut()
+                .zip(&self.points)
+                .with_min_len(self.viewport.resolution[0] as usize)
+                .for_each(|(value, &p)| {
+                    let mut closest = (p - trap).norm_sqr();
+                    fractal.sample_orbit(p, max_iter, periodicity, |z| {
+                        closest = closest.min((z - trap).norm_sqr());
+                    });
+                    *value = (T::one() + closest.sqrt()).recip().to_f32().unwrap_or(0.0);
+                });
+        });
+        Ok(())
+    }
+
+    fn check_output(&self, len: usize) -> Result<(), MandybrotError> {
+        if len != self.points.len() {
+            return Err(MandybrotError::BufferSize {
+                expected: self.points.len(),
+                found: len,
+            });
+        }
+        Ok(())
+    }
+}
@@ -1,16 +1,33 @@
-use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
-use ndarray::Array2;
-use num_traits::{Float, FloatConst, NumCast};
-use rand::{distr::uniform::SampleUniform, rng, Rng};
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use ndarray::{s, Array, Array2, Array3, Axis, Dim, Dimension};
+use num_traits::{Float, FloatConst, NumCast, Zero};
+use palette::LinSrgba;
+use rand::{distr::uniform::SampleUniform, Rng};
 use rayon::prelude::*;
 use std::{
+    collections::HashMap,
     fmt::Display,
-    ops::{Add, Div, Mul, Sub},
+    ops::{Add, AddAssign, Div, Mul, Sub},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
-use crate::{Attractor, Complex, Fractal};
+use crate::{
+    downsample,
+    error::{check_count, check_finite, check_light, check_non_negative, check_positive},
+    fractal::mandelbrot_atom,
+    resample::{premultiply, unpremultiply},
+    rng_strategy::{CHAIN_DOMAIN, FLAME_DOMAIN, IFS_DOMAIN, SEED_DOMAIN, UNIFORM_DOMAIN},
+    seeded::{check_seedable, sample_seeded},
+    shader::smooth_iteration,
+    zoom::{reference_orbit, sample_perturbed},
+    Attractor, AttractorParameter, ColourMap, Complex, DoubleDouble, EscapeColouring, Flame,
+    Fractal, Ifs, InverseIteration, MandybrotError, OrbitState, Periodicity, PixelSample,
+    PixelShader, Precision, Provenance, RenderControl, RngStrategy, SampleBuffer, SamplePattern,
+    Sampling, Viewport, ZoomPath,
+};
 
 /// Renders a fractal with anti-aliasing by sampling multiple points per pixel.
+#[allow(clippy::too_many_arguments)]
 pub fn render_fractal<T>(
     centre: Complex<T>,
     max_iter: u32,
@@ -18,7 +35,194 @@ pub fn render_fractal<T>(
     resolution: [u32; 2],
     fractal: Fractal<T>,
     samples_per_pixel: u32,
-) -> Array2<u32>
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    render_fractal_viewport(
+        &Viewport::new(centre, scale, resolution),
+        &fractal,
+        max_iter,
+        samples_per_pixel,
+        pattern,
+        periodicity,
+    )
+}
+
+/// Renders a fractal over a viewport, which may be rotated, sheared or anamorphic, as `render_fractal`.
+pub fn render_fractal_viewport<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(viewport, fractal, samples_per_pixel, periodicity.as_ref())?;
+
+    par_map_pixels(viewport.resolution, true, |pixel| {
+        sample_pixel(
+            viewport,
+            pixel,
+            fractal,
+            max_iter,
+            samples_per_pixel,
+            pattern,
+            periodicity.as_ref(),
+        )
+    })
+}
+
+/// Renders a fractal as `render_fractal_viewport` does, but gives the exact mean iteration count of each pixel's samples rather than rounding it down.
+/// The fractional part left by anti-aliasing keeps the edges of bands smooth, such as for smooth colouring.
+pub fn render_fractal_mean<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<Array2<f64>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(viewport, fractal, samples_per_pixel, periodicity.as_ref())?;
+
+    let num_samples = (samples_per_pixel as f64).powi(2);
+    par_map_pixels(viewport.resolution, true, |pixel| {
+        let sum = sample_pixel_sum(
+            viewport,
+            pixel,
+            fractal,
+            max_iter,
+            samples_per_pixel,
+            pattern,
+            periodicity.as_ref(),
+        );
+        sum as f64 / num_samples
+    })
+}
+
+/// Renders a fractal as `render_fractal_viewport` does, but samples only one of each pair of pixels which mirror each other in the real axis, copying its count to the other.
+/// Halves the work of views centred on the real axis, giving exactly the same counts. Views off the axis, sample patterns other than `Grid` and fractals without the symmetry are rendered in full.
+pub fn render_fractal_mirrored<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Float + NumCast + Send + Sync,
+{
+    validate_escape_time(viewport, fractal, samples_per_pixel, periodicity.as_ref())?;
+
+    // Only the regular grid places the samples of mirrored pixels at mirrored points.
+    let mirrored = fractal.is_mirror_symmetric() && pattern == SamplePattern::Grid;
+    let mirror = |pixel: [u32; 2]| {
+        mirrored
+            .then(|| mirror_pixel(viewport, pixel, samples_per_pixel))
+            .flatten()
+    };
+    let sample = |pixel: [u32; 2]| {
+        sample_pixel(
+            viewport,
+            pixel,
+            fractal,
+            max_iter,
+            samples_per_pixel,
+            pattern,
+            periodicity.as_ref(),
+        )
+    };
+
+    // Pixels mirroring an earlier pixel in row-major order are left for the copy.
+    let earlier = |[x, y]: [u32; 2], [mx, my]: [u32; 2]| (my, mx) < (y, x);
+    let counts = par_map_pixels(viewport.resolution, true, |pixel| match mirror(pixel) {
+        Some(image) if earlier(pixel, image) => None,
+        _ => Some(sample(pixel)),
+    })?;
+    Ok(Array2::from_shape_fn(counts.dim(), |(y, x)| {
+        let pixel = [x as u32, y as u32];
+        counts[(y, x)]
+            .or_else(|| mirror(pixel).and_then(|[mx, my]| counts[(my as usize, mx as usize)]))
+            .unwrap_or_else(|| sample(pixel))
+    }))
+}
+
+/// Pixel whose samples are exactly the mirror images of this pixel's samples in the real axis, if there is one within the image.
+fn mirror_pixel<T: Float>(
+    viewport: &Viewport<T>,
+    [x, y]: [u32; 2],
+    samples_per_pixel: u32,
+) -> Option<[u32; 2]> {
+    let centre = viewport.pixel_centre(x, y);
+    let grid = viewport.complex_to_grid(&Complex::new(centre.real, -centre.imag));
+    let [x_res, y_res] = viewport.resolution;
+    let mx = grid.real.floor().to_u32().filter(|&mx| mx < x_res)?;
+    let my = grid.imag.floor().to_u32().filter(|&my| my < y_res)?;
+
+    // Rounding can move a sample off the mirror image of its partner, which could change its count.
+    let mut samples = Vec::with_capacity((samples_per_pixel * samples_per_pixel) as usize);
+    let grid_pattern = SamplePattern::Grid;
+    for_each_sample(viewport, [mx, my], samples_per_pixel, grid_pattern, |c| {
+        samples.push(c)
+    });
+    let mut exact = true;
+    for_each_sample(viewport, [x, y], samples_per_pixel, grid_pattern, |c| {
+        exact &= samples.contains(&Complex::new(c.real, -c.imag));
+    });
+    exact.then_some([mx, my])
+}
+
+/// Renders a fractal in colour, anti-aliased by colouring each sample with `colouring` and averaging the colours in linear light, weighted by their alpha.
+/// Averaging the iteration counts before colouring, as `render_fractal` does, blends the colours of neighbouring bands into ones found in neither where the counts change sharply.
+#[allow(clippy::too_many_arguments)]
+pub fn render_fractal_coloured<T>(
+    centre: Complex<T>,
+    max_iter: u32,
+    scale: T,
+    resolution: [u32; 2],
+    fractal: Fractal<T>,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+    colouring: &EscapeColouring<T>,
+    colour_map: &ColourMap,
+) -> Result<Array2<LinSrgba>, MandybrotError>
 where
     T: Copy
         + Add<Output = T>
@@ -30,130 +234,262 @@ where
         + Float
         + Send
         + Sync,
+{
+    let viewport = Viewport::new(centre, scale, resolution);
+    validate_escape_time(&viewport, &fractal, samples_per_pixel, periodicity.as_ref())?;
+    colouring.validate()?;
+
+    let weight = 1.0 / (samples_per_pixel * samples_per_pixel) as f32;
+    par_map_pixels(resolution, true, |pixel| {
+        let mut sum = LinSrgba::new(0.0, 0.0, 0.0, 0.0);
+        for_each_sample(&viewport, pixel, samples_per_pixel, pattern, |c| {
+            let count = fractal.sample(c, max_iter, periodicity.as_ref());
+            sum += premultiply(colouring.colour(count, max_iter, colour_map));
+        });
+        unpremultiply(sum * weight)
+    })
+}
+
+/// Renders a fractal in colour with a `PixelShader`, which is given everything found while sampling each point, anti-aliased by averaging the colours of each pixel's samples in linear light, weighted by their alpha.
+/// The trap distance and derivative of each sample are only computed if the shader asks for them, the derivative by iterating the orbit a second time.
+pub fn render_with_shader<T, S>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+    shader: &S,
+) -> Result<Array2<LinSrgba>, MandybrotError>
+where
+    T: Float + NumCast + Send + Sync,
+    S: PixelShader<T>,
+{
+    validate_escape_time(viewport, fractal, samples_per_pixel, periodicity.as_ref())?;
+    let trap = shader.trap();
+    if let Some(trap) = trap {
+        check_finite("trap.real", trap.real)?;
+        check_finite("trap.imag", trap.imag)?;
+    }
+    let derivative_escape_radius = shader.derivative_escape_radius();
+    if let Some(escape_radius) = derivative_escape_radius {
+        validate_derivative(viewport, fractal, escape_radius)?;
+    }
+
+    let log_degree = fractal.escape_degree().map(T::ln);
+    let weight = 1.0 / (samples_per_pixel * samples_per_pixel) as f32;
+    par_map_pixels(viewport.resolution, true, |pixel| {
+        let mut sum = LinSrgba::new(0.0, 0.0, 0.0, 0.0);
+        for_each_sample(viewport, pixel, samples_per_pixel, pattern, |c| {
+            let mut closest = trap.map(|trap| (c - trap).norm_sqr());
+            let (n, z) = fractal.sample_orbit(c, max_iter, periodicity.as_ref(), |z| {
+                if let (Some(trap), Some(closest)) = (trap, closest.as_mut()) {
+                    *closest = closest.min((z - trap).norm_sqr());
+                }
+            });
+            let smooth = match log_degree {
+                Some(log_degree) if n < max_iter => smooth_iteration(n, z, log_degree),
+                _ => T::nan(),
+            };
+            let derivative = derivative_escape_radius.and_then(|escape_radius| {
+                fractal
+                    .sample_derivative(c, max_iter, escape_radius)
+                    .map(|(_, dz)| dz)
+            });
+            let sample = PixelSample {
+                pixel,
+                point: c,
+                iterations: n,
+                max_iter,
+                smooth,
+                final_z: z,
+                trap_distance: closest.map(T::sqrt),
+                derivative,
+            };
+            sum += premultiply(shader.shade(&sample));
+        });
+        unpremultiply(sum * weight)
+    })
+}
+
+/// Checks the settings shared by escape-time renders.
+pub(crate) fn validate_escape_time<T: Float>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    samples_per_pixel: u32,
+    periodicity: Option<&Periodicity<T>>,
+) -> Result<(), MandybrotError> {
+    viewport.validate()?;
+    fractal.validate()?;
+    check_count("samples_per_pixel", samples_per_pixel)?;
+    periodicity.map_or(Ok(()), Periodicity::validate)
+}
+
+/// Number of consecutive pixels in each unit of parallel work.
+/// Expensive interior pixels cluster in some rows, so units much smaller than a row let idle threads steal what remains rather than wait on the slowest row.
+const PIXELS_PER_TASK: usize = 64;
+
+/// Samples every pixel of an image in parallel, in row-major runs of `PIXELS_PER_TASK` pixels which idle threads steal from busy ones.
+/// Shows a progress bar of the runs if `progress` is set.
+pub(crate) fn par_map_pixels<A, F>(
+    resolution: [u32; 2],
+    progress: bool,
+    sample: F,
+) -> Result<Array2<A>, MandybrotError>
+where
+    A: Send,
+    F: Fn([u32; 2]) -> A + Sync,
 {
     let [x_res, y_res] = resolution;
-    let x_res_t = T::from(x_res).unwrap();
-    let y_res_t = T::from(y_res).unwrap();
-    let aspect_ratio = x_res_t / y_res_t;
-    let width = scale * aspect_ratio;
-    let height = scale;
-    let x_step = width / x_res_t;
-    let y_step = height / y_res_t;
-    let half_x_res = x_res_t / T::from(2).unwrap();
-    let half_y_res = y_res_t / T::from(2).unwrap();
+    let (width, height) = (x_res as usize, y_res as usize);
+    let num_pixels = width * height;
+    let num_tasks = num_pixels.div_ceil(PIXELS_PER_TASK);
 
-    let mut pixels = Array2::<u32>::zeros((y_res as usize, x_res as usize));
+    let tasks = (0..num_tasks).into_par_iter();
+    let run = |task: usize| {
+        let start = task * PIXELS_PER_TASK;
+        (start..(start + PIXELS_PER_TASK).min(num_pixels))
+            .map(|i| sample([(i % width) as u32, (i / width) as u32]))
+    };
+    let pixels: Vec<A> = if progress {
+        let pb = progress_bar(num_tasks as u64)?;
+        tasks.progress_with(pb).flat_map_iter(run).collect()
+    } else {
+        tasks.flat_map_iter(run).collect()
+    };
+
+    Ok(Array2::from_shape_vec((height, width), pixels).expect("one sample per pixel"))
+}
+
+/// Whether renders draw progress bars, see `set_progress_bars`.
+static PROGRESS_BARS: AtomicBool = AtomicBool::new(true);
+
+/// Turns the progress bars drawn by long renders on or off for the whole process, such as to keep benchmarks and logs quiet.
+/// They are on by default, and are only ever drawn to a terminal.
+pub fn set_progress_bars(enabled: bool) {
+    PROGRESS_BARS.store(enabled, Ordering::Relaxed);
+}
 
-    // Create a progress bar for rendering rows.
-    let pb = ProgressBar::new(y_res as u64);
+/// Creates a progress bar in the style shared by all renders, hidden if progress bars are turned off.
+pub(crate) fn progress_bar(len: u64) -> Result<ProgressBar, MandybrotError> {
+    let pb = if PROGRESS_BARS.load(Ordering::Relaxed) {
+        ProgressBar::new(len)
+    } else {
+        ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::hidden())
+    };
     pb.set_style(
         ProgressStyle::with_template(
             "{spinner:.green} [{elapsed_precise}] {wide_bar} {pos}/{len} ETA: {eta}",
-        )
-        .unwrap()
+        )?
         .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
     );
-
-    pixels
-        .as_slice_mut()
-        .unwrap()
-        .par_chunks_mut(x_res as usize)
-        .enumerate()
-        .progress_with(pb)
-        .for_each(|(y, row)| {
-            let y_t = T::from(y).unwrap();
-            let pixel_center_y = centre.imag + (y_t + T::from(0.5).unwrap() - half_y_res) * y_step;
-            for (x, pixel) in row.iter_mut().enumerate() {
-                let x_t = T::from(x).unwrap();
-                let pixel_center_x =
-                    centre.real + (x_t + T::from(0.5).unwrap() - half_x_res) * x_step;
-                let mut sum = 0u32;
-                let samples_t = T::from(samples_per_pixel).unwrap();
-                for i in 0..samples_per_pixel {
-                    let i_t = T::from(i).unwrap();
-                    let offset_x = ((i_t + T::from(0.5).unwrap()) / samples_t
-                        - T::from(0.5).unwrap())
-                        * x_step;
-                    for j in 0..samples_per_pixel {
-                        let j_t = T::from(j).unwrap();
-                        let offset_y = ((j_t + T::from(0.5).unwrap()) / samples_t
-                            - T::from(0.5).unwrap())
-                            * y_step;
-                        let sample_x = pixel_center_x + offset_x;
-                        let sample_y = pixel_center_y + offset_y;
-                        let c = Complex::new(sample_x, sample_y);
-                        sum += fractal.sample(c, max_iter);
-                    }
-                }
-                let total_samples = samples_per_pixel * samples_per_pixel;
-                *pixel = sum / total_samples;
-            }
-        });
-
-    pixels
+    Ok(pb)
 }
 
-fn create_position_to_pixel_mapper<T: Float + NumCast + Display>(
-    offset: Complex<T>,
-    scale: T,
+/// Renders a deep zoom into the Mandelbrot set frame by frame, switching to a more precise numeric backend as the pixels shrink.
+/// Frames are passed to `on_frame` with their index and the backend used as soon as they are rendered, so they can be saved without holding the whole video in memory.
+pub fn render_zoom<F>(
+    zoom: &ZoomPath,
     resolution: [u32; 2],
-) -> impl Fn(&Complex<T>) -> Option<[usize; 2]> {
-    let x_res = T::from(resolution[0]).unwrap();
-    let y_res = T::from(resolution[1]).unwrap();
-    let aspect_ratio = x_res / y_res;
-    let width = scale * aspect_ratio;
-    let height = scale;
-    let half_width = width / T::from(2.0).unwrap();
-    let half_height = height / T::from(2.0).unwrap();
-    let max_x = x_res - T::one();
-    let max_y = y_res - T::one();
-
-    move |p: &Complex<T>| {
-        // Shift the point by the offset to recenter the image.
-        let shifted_real = p.real - offset.real;
-        let shifted_imag = p.imag - offset.imag;
-        let x = ((shifted_real + half_width) / width) * max_x;
-        let y = ((half_height - shifted_imag) / height) * max_y;
-
-        if x >= T::zero() && x < x_res && y >= T::zero() && y < y_res {
-            Some([x.to_usize().unwrap(), y.to_usize().unwrap()])
-        } else {
-            None
-        }
-    }
+    max_iter: u32,
+    mut on_frame: F,
+) -> Result<(), MandybrotError>
+where
+    F: FnMut(u32, Precision, Array2<u32>),
+{
+    try_render_zoom(zoom, resolution, max_iter, |frame, precision, image| {
+        on_frame(frame, precision, image);
+        Ok(())
+    })
 }
 
-fn generate_initial_positions<T>(start: Complex<T>, radius: T, num_samples: u32) -> Vec<Complex<T>>
+/// Renders a deep zoom like `render_zoom`, stopping at the first frame which `on_frame` fails to handle.
+pub(crate) fn try_render_zoom<F>(
+    zoom: &ZoomPath,
+    resolution: [u32; 2],
+    max_iter: u32,
+    mut on_frame: F,
+) -> Result<(), MandybrotError>
 where
-    T: Float + FloatConst + NumCast + SampleUniform,
+    F: FnMut(u32, Precision, Array2<u32>) -> Result<(), MandybrotError>,
 {
-    let mut rng = rng();
-    let mut positions = Vec::with_capacity(num_samples as usize);
-    let zero = T::from(0.0).unwrap();
-    let tau = T::TAU();
-    for _ in 0..num_samples {
-        let theta = rng.random_range(zero..tau);
-        let rho = rng.random_range(zero..radius).sqrt();
-        let x = start.real + rho * theta.cos();
-        let y = start.imag + rho * theta.sin();
-        positions.push(Complex::new(x, y));
+    zoom.validate()?;
+
+    let centre = Complex::new(zoom.target.real.to_f64(), zoom.target.imag.to_f64());
+    let mut orbit = None;
+    for frame in 0..zoom.num_frames {
+        let scale = zoom.scale(frame);
+        let precision = zoom.precision(frame, resolution);
+        let image = match precision {
+            Precision::Single => render_fractal(
+                Complex::new(centre.real as f32, centre.imag as f32),
+                max_iter,
+                scale as f32,
+                resolution,
+                Fractal::Mandelbrot,
+                1,
+                SamplePattern::Grid,
+                None,
+            )?,
+            Precision::Double => render_fractal(
+                centre,
+                max_iter,
+                scale,
+                resolution,
+                Fractal::Mandelbrot,
+                1,
+                SamplePattern::Grid,
+                None,
+            )?,
+            Precision::DoubleDouble => render_fractal(
+                zoom.target,
+                max_iter,
+                DoubleDouble::from_f64(scale),
+                resolution,
+                Fractal::Mandelbrot,
+                1,
+                SamplePattern::Grid,
+                None,
+            )?,
+            Precision::Perturbation => {
+                // The target is the same for every frame, so one reference orbit serves them all.
+                let orbit = orbit.get_or_insert_with(|| reference_orbit(zoom.target, max_iter));
+                let offsets = Viewport::new(Complex::new(0.0, 0.0), scale, resolution);
+                render_perturbed(&offsets, orbit, max_iter)?
+            }
+        };
+        on_frame(frame, precision, image)?;
     }
-    positions
+
+    Ok(())
 }
 
-pub fn render_attractor<T>(
-    centre: Complex<T>,
-    scale: T,
-    resolution: [u32; 2],
+/// Renders the Mandelbrot set by perturbation about a reference orbit, sampled once at each pixel centre.
+/// The `offsets` viewport is centred on the origin, giving the offset of each pixel from the reference point.
+fn render_perturbed(
+    offsets: &Viewport<f64>,
+    orbit: &[Complex<f64>],
+    max_iter: u32,
+) -> Result<Array2<u32>, MandybrotError> {
+    offsets.validate()?;
 
-    start: Complex<T>,
-    radius: T,
-    num_samples: u32,
+    par_map_pixels(offsets.resolution, true, |[x, y]| {
+        sample_perturbed(orbit, offsets.pixel_centre(x, y), max_iter)
+    })
+}
 
+/// Renders the Mandelbrot set and the Julia set for the parameter `c` side by side, as in a classic explorer.
+/// Returns the Mandelbrot render, the Julia render, and the pixel of the Mandelbrot render containing `c` if it is in view.
+#[allow(clippy::type_complexity)]
+pub fn render_dual<T>(
+    c: Complex<T>,
+    mandelbrot_viewport: &Viewport<T>,
+    julia_viewport: &Viewport<T>,
     max_iter: u32,
-    draw_after: u32,
-    attractor: &Attractor<T>,
-) -> Array2<u32>
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<(Array2<u32>, Array2<u32>, Option<[usize; 2]>), MandybrotError>
 where
     T: Copy
         + Add<Output = T>
@@ -163,46 +499,147 @@ where
         + PartialOrd
         + NumCast
         + Float
-        + FloatConst
-        + SampleUniform
         + Send
-        + Sync
-        + Display,
+        + Sync,
 {
-    let initial_positions = generate_initial_positions(start, radius, num_samples);
+    let mandelbrot = render_fractal(
+        mandelbrot_viewport.centre,
+        max_iter,
+        mandelbrot_viewport.scale,
+        mandelbrot_viewport.resolution,
+        Fractal::Mandelbrot,
+        samples_per_pixel,
+        pattern,
+        periodicity,
+    )?;
+    let julia = render_fractal(
+        julia_viewport.centre,
+        max_iter,
+        julia_viewport.scale,
+        julia_viewport.resolution,
+        Fractal::julia_of(c),
+        samples_per_pixel,
+        pattern,
+        periodicity,
+    )?;
+    Ok((mandelbrot, julia, mandelbrot_viewport.complex_to_pixel(&c)))
+}
 
-    // Render and sum attractors concurrently.
-    let pb = ProgressBar::new(initial_positions.len() as u64);
-    pb.set_style(
-        ProgressStyle::with_template(
-            "{spinner:.green} [{elapsed_precise}] {wide_bar} {pos}/{len} ETA: {eta}",
-        )
-        .unwrap()
-        .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
-    );
+/// Renders a fractal in square tiles, doubling the samples per pixel of each tile until the image stops changing.
+/// A tile is finished once the mean change per pixel from its last pass, as a fraction of `max_iter`, falls below `tolerance`, or it reaches `max_samples_per_pixel`.
+/// Returns the image and the number of samples per pixel each tile settled on.
+pub fn render_fractal_adaptive<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    tile_size: u32,
+    tolerance: f64,
+    max_samples_per_pixel: u32,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<(Array2<u32>, Array2<u32>), MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(
+        viewport,
+        fractal,
+        max_samples_per_pixel,
+        periodicity.as_ref(),
+    )?;
+    check_count("tile_size", tile_size)?;
+    check_non_negative("tolerance", tolerance)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let [x_tiles, y_tiles] = [x_res.div_ceil(tile_size), y_res.div_ceil(tile_size)];
 
-    let shape = (resolution[1] as usize, resolution[0] as usize);
-    initial_positions
-        .par_iter()
+    let pb = progress_bar((x_tiles * y_tiles) as u64)?;
+
+    let tiles: Vec<(Array2<f64>, u32)> = (0..x_tiles * y_tiles)
+        .into_par_iter()
         .progress_with(pb)
-        .map(|&pos| {
-            render_attractor_path(
-                pos, centre, max_iter, draw_after, scale, resolution, &attractor,
-            )
+        .map(|index| {
+            let x0 = (index % x_tiles) * tile_size;
+            let y0 = (index / x_tiles) * tile_size;
+            let width = tile_size.min(x_res - x0) as usize;
+            let height = tile_size.min(y_res - y0) as usize;
+
+            let mut sums = Array2::<f64>::zeros((height, width));
+            let mut num_samples = 0;
+            let mut previous: Option<Array2<f64>> = None;
+            while num_samples < max_samples_per_pixel {
+                // Double the samples, so the change estimates the error of the previous pass.
+                let pass_samples = num_samples.max(1).min(max_samples_per_pixel - num_samples);
+                for ((y, x), sum) in sums.indexed_iter_mut() {
+                    let pixel = [x0 + x as u32, y0 + y as u32];
+                    for n in num_samples..(num_samples + pass_samples) {
+                        let [offset_x, offset_y] = r2_offset::<T>(n);
+                        let c = viewport.pixel_to_complex(
+                            T::from(pixel[0]).unwrap() + offset_x,
+                            T::from(pixel[1]).unwrap() + offset_y,
+                        );
+                        *sum += fractal.sample(c, max_iter, periodicity.as_ref()) as f64;
+                    }
+                }
+                num_samples += pass_samples;
+
+                let mean = &sums / num_samples as f64;
+                let converged = previous.as_ref().is_some_and(|previous| {
+                    let change = (&mean - previous).mapv(f64::abs).mean().unwrap_or(0.0);
+                    change / max_iter.max(1) as f64 <= tolerance
+                });
+                if converged {
+                    break;
+                }
+                previous = Some(mean);
+            }
+
+            (sums / num_samples as f64, num_samples)
         })
-        .reduce(|| Array2::zeros(shape), |a, b| a + b)
+        .collect();
+
+    let mut pixels = Array2::<u32>::zeros((y_res as usize, x_res as usize));
+    let mut tile_samples = Array2::<u32>::zeros((y_tiles as usize, x_tiles as usize));
+    for (index, (mean, num_samples)) in tiles.into_iter().enumerate() {
+        let [tile_x, tile_y] = [index % x_tiles as usize, index / x_tiles as usize];
+        let [x0, y0] = [tile_x * tile_size as usize, tile_y * tile_size as usize];
+        let (height, width) = mean.dim();
+        pixels
+            .slice_mut(s![y0..y0 + height, x0..x0 + width])
+            .assign(&mean.mapv(|v| v.round() as u32));
+        tile_samples[(tile_y, tile_x)] = num_samples;
+    }
+
+    Ok((pixels, tile_samples))
 }
 
-/// Renders a single part of a point orbiting an attractor by iterating its dynamics and accumulating hits in a pixel grid.
-fn render_attractor_path<T>(
-    start: Complex<T>,
-    centre: Complex<T>,
+/// Points sampled along each axis of a tile by the first pass of `render_fractal_iteration_map`.
+const PREVIEW_SAMPLES: u32 = 4;
+
+/// Renders a fractal in two passes, the first estimating how many iterations each square tile needs so that the second need not iterate every pixel to `max_iter`.
+/// The first pass samples a coarse grid of `PREVIEW_SAMPLES` by `PREVIEW_SAMPLES` points in each tile to `max_iter`.
+/// A tile in which any of them failed to escape keeps the full `max_iter`, while any other is iterated to its slowest escape, raised by the fraction `margin`, so only the tiles near the set pay for deep iteration.
+/// Samples which reach their tile's limit are counted as `max_iter`, so the image looks like a full render wherever the margin covers the detail the first pass missed.
+/// Returns the image and the iteration limit of each tile.
+#[allow(clippy::too_many_arguments)]
+pub fn render_fractal_iteration_map<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
     max_iter: u32,
-    draw_after: u32,
-    scale: T,
-    resolution: [u32; 2],
-    attractor: &Attractor<T>,
-) -> Array2<u32>
+    tile_size: u32,
+    margin: f64,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<(Array2<u32>, Array2<u32>), MandybrotError>
 where
     T: Copy
         + Add<Output = T>
@@ -213,24 +650,1787 @@ where
         + NumCast
         + Float
         + Send
-        + Sync
-        + std::fmt::Display,
+        + Sync,
 {
-    let [x_res, y_res] = resolution;
+    validate_escape_time(viewport, fractal, samples_per_pixel, periodicity.as_ref())?;
+    check_count("tile_size", tile_size)?;
+    check_non_negative("margin", margin)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let [x_tiles, y_tiles] = [x_res.div_ceil(tile_size), y_res.div_ceil(tile_size)];
+    let periodicity = periodicity.as_ref();
+
+    let pb = progress_bar((x_tiles * y_tiles) as u64)?;
+
+    let tiles: Vec<(Array2<u32>, u32)> = (0..x_tiles * y_tiles)
+        .into_par_iter()
+        .progress_with(pb)
+        .map(|index| {
+            let x0 = (index % x_tiles) * tile_size;
+            let y0 = (index / x_tiles) * tile_size;
+            let width = tile_size.min(x_res - x0);
+            let height = tile_size.min(y_res - y0);
+
+            let preview = T::from(PREVIEW_SAMPLES).unwrap();
+            let half = T::from(0.5).unwrap();
+            let (mut slowest, mut interior) = (0, false);
+            for i in 0..PREVIEW_SAMPLES {
+                for j in 0..PREVIEW_SAMPLES {
+                    let c = viewport.pixel_to_complex(
+                        T::from(x0).unwrap()
+                            + (T::from(i).unwrap() + half) * T::from(width).unwrap() / preview,
+                        T::from(y0).unwrap()
+                            + (T::from(j).unwrap() + half) * T::from(height).unwrap() / preview,
+                    );
+                    let n = fractal.sample(c, max_iter, periodicity);
+                    interior |= n >= max_iter;
+                    slowest = slowest.max(n);
+                }
+            }
+            let limit = if interior {
+                max_iter
+            } else {
+                ((slowest as f64 * (1.0 + margin)).ceil() as u32).clamp(1, max_iter)
+            };
+
+            let tile = Array2::from_shape_fn((height as usize, width as usize), |(y, x)| {
+                let pixel = [x0 + x as u32, y0 + y as u32];
+                let mut sum = 0;
+                for_each_sample(viewport, pixel, samples_per_pixel, pattern, |c| {
+                    let n = fractal.sample(c, limit, periodicity);
+                    sum += if n >= limit { max_iter } else { n } as u64;
+                });
+                (sum / (samples_per_pixel as u64).pow(2)) as u32
+            });
+            (tile, limit)
+        })
+        .collect();
+
     let mut pixels = Array2::<u32>::zeros((y_res as usize, x_res as usize));
-    let pixel_mapper = create_position_to_pixel_mapper(centre, scale, resolution);
+    let mut tile_limits = Array2::<u32>::zeros((y_tiles as usize, x_tiles as usize));
+    for (index, (tile, limit)) in tiles.into_iter().enumerate() {
+        let [tile_x, tile_y] = [index % x_tiles as usize, index / x_tiles as usize];
+        let [x0, y0] = [tile_x * tile_size as usize, tile_y * tile_size as usize];
+        let (height, width) = tile.dim();
+        pixels
+            .slice_mut(s![y0..y0 + height, x0..x0 + width])
+            .assign(&tile);
+        tile_limits[(tile_y, tile_x)] = limit;
+    }
 
-    let mut pos = start;
-    for n in 0..max_iter {
-        pos = attractor.iterate(pos);
+    Ok((pixels, tile_limits))
+}
 
-        if n < draw_after {
-            continue;
-        }
-        if let Some([x, y]) = pixel_mapper(&pos) {
-            pixels[[y, x]] += 1;
-        }
+/// Sub-pixel offset of the `n`th sample of the R2 low-discrepancy sequence, starting at the pixel centre.
+/// Every prefix of the sequence is well spread, so samples can be added a pass at a time.
+fn r2_offset<T: Float>(n: u32) -> [T; 2] {
+    // The plastic number, the unique real root of x³ = x + 1.
+    const PLASTIC: f64 = 1.324_717_957_244_746;
+    let n = n as f64;
+    [
+        T::from((0.5 + n / PLASTIC).fract()).unwrap(),
+        T::from((0.5 + n / (PLASTIC * PLASTIC)).fract()).unwrap(),
+    ]
+}
+
+/// Renders a fractal row by row under the direction of a `RenderControl`, which can pause, cancel, throttle and snapshot it from another thread.
+/// Returns `None` if the render was cancelled.
+pub fn render_fractal_controlled<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+    control: &RenderControl,
+) -> Result<Option<Array2<u32>>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(viewport, fractal, samples_per_pixel, periodicity.as_ref())?;
+
+    let [x_res, y_res] = viewport.resolution;
+    control.start((y_res as usize, x_res as usize));
+
+    let mut y = 0;
+    while y < y_res && control.proceed() {
+        let batch = (control.batch_size() as u32).min(y_res - y);
+        let rows: Vec<Vec<u32>> = (y..y + batch)
+            .into_par_iter()
+            .map(|y| {
+                (0..x_res)
+                    .map(|x| {
+                        sample_pixel(
+                            viewport,
+                            [x, y],
+                            fractal,
+                            max_iter,
+                            samples_per_pixel,
+                            pattern,
+                            periodicity.as_ref(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        control.write_rows(y as usize, &rows);
+        y += batch;
     }
 
-    pixels
+    Ok(control.finish())
+}
+
+/// Renders a fractal sampled once at each pixel centre, recording the escape iteration and the final value of `z`.
+/// The final `z` gives the escape angle used to study external rays, see `SampleBuffer::escape_angle`.
+pub fn render_escape<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<SampleBuffer<T>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(viewport, fractal, 1, periodicity.as_ref())?;
+
+    let pixels = par_map_pixels(viewport.resolution, false, |[x, y]| {
+        fractal.sample_escape(viewport.pixel_centre(x, y), max_iter, periodicity.as_ref())
+    })?;
+
+    Ok(SampleBuffer {
+        final_z: Some(pixels.mapv(|(_, z)| z)),
+        ..SampleBuffer::new(*viewport, pixels.mapv(|(n, _)| n))
+    })
+}
+
+/// Iteration count at each pixel centre of a viewport as `(x, y, count)`, sampled in parallel as the iterator is consumed, without holding the image in memory.
+/// The iterator is indexed in row-major order, so collecting it keeps that order.
+pub fn sample_area_iter<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<impl IndexedParallelIterator<Item = (u32, u32, u32)>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    validate_escape_time(viewport, fractal, 1, periodicity.as_ref())?;
+
+    let (viewport, fractal) = (*viewport, *fractal);
+    let [x_res, y_res] = viewport.resolution;
+    let width = x_res as usize;
+    Ok((0..width * y_res as usize).into_par_iter().map(move |i| {
+        let (x, y) = ((i % width) as u32, (i / width) as u32);
+        let c = viewport.pixel_centre(x, y);
+        (x, y, fractal.sample(c, max_iter, periodicity.as_ref()))
+    }))
+}
+
+/// Iteration count at each pixel centre of a viewport as `(x, y, count)`, sampled one at a time in row-major order as the iterator is consumed.
+/// Suits consumers which need the pixels in order, such as an image encoder writing rows as they arrive.
+pub fn sample_area_seq_iter<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<impl Iterator<Item = (u32, u32, u32)>, MandybrotError>
+where
+    T: Float,
+{
+    validate_escape_time(viewport, fractal, 1, periodicity.as_ref())?;
+
+    let (viewport, fractal) = (*viewport, *fractal);
+    let [x_res, y_res] = viewport.resolution;
+    Ok((0..y_res).flat_map(move |y| {
+        (0..x_res).map(move |x| {
+            let c = viewport.pixel_centre(x, y);
+            (x, y, fractal.sample(c, max_iter, periodicity.as_ref()))
+        })
+    }))
+}
+
+/// Renders a fractal sampled once at each pixel centre, recording the escape iteration, the final value of `z`, and the smallest, largest and mean `|z|` over each orbit.
+/// The statistics cover every point of the orbit after the starting point, including the first point outside the escape radius for orbits which escape.
+/// Points which are not iterated at all, such as with a `max_iter` of zero, are given statistics of `NaN`.
+pub fn render_orbit_statistics<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<SampleBuffer<T>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(viewport, fractal, 1, periodicity.as_ref())?;
+
+    let pixels = par_map_pixels(viewport.resolution, false, |[x, y]| {
+        let c = viewport.pixel_centre(x, y);
+        let mut lowest = T::infinity();
+        let mut highest = T::zero();
+        let mut total = T::zero();
+        let mut count = 0u32;
+        let (n, z) = fractal.sample_orbit(c, max_iter, periodicity.as_ref(), |z| {
+            let modulus = z.norm_sqr().sqrt();
+            lowest = lowest.min(modulus);
+            highest = highest.max(modulus);
+            total = total + modulus;
+            count += 1;
+        });
+        let [min, max, mean] = if count > 0 {
+            [lowest, highest, total / T::from(count).unwrap()]
+        } else {
+            [T::nan(); 3]
+        };
+        (n, z, [min, max, mean])
+    })?;
+
+    Ok(SampleBuffer {
+        final_z: Some(pixels.mapv(|(_, z, _)| z)),
+        min_modulus: Some(pixels.mapv(|(_, _, [min, _, _])| min)),
+        max_modulus: Some(pixels.mapv(|(_, _, [_, max, _])| max)),
+        mean_modulus: Some(pixels.mapv(|(_, _, [_, _, mean])| mean)),
+        ..SampleBuffer::new(*viewport, pixels.mapv(|(n, _, _)| n))
+    })
+}
+
+/// Renders the estimated distance from each pixel centre to the boundary of a fractal, in units of the complex plane, from the derivatives of the orbits.
+/// Pixels whose orbits do not escape past `escape_radius` within `max_iter` iterations are given a distance of zero.
+pub fn render_distance<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    escape_radius: T,
+) -> Result<Array2<T>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    validate_derivative(viewport, fractal, escape_radius)?;
+
+    par_map_pixels(viewport.resolution, true, |[x, y]| {
+        match fractal.sample_derivative(viewport.pixel_centre(x, y), max_iter, escape_radius) {
+            Some((n, z)) if n < max_iter => z.distance_estimate(),
+            _ => T::zero(),
+        }
+    })
+}
+
+/// Renders a fractal sampled once at each pixel centre, recording the escape iteration, the final value of `z` and the normal of the escape potential.
+/// The normals come from the derivatives of the orbits rather than differences between neighbouring pixels, so they are free of banding at any resolution.
+pub fn render_normals<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    escape_radius: T,
+) -> Result<SampleBuffer<T>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    validate_derivative(viewport, fractal, escape_radius)?;
+
+    let nan = Complex::new(T::nan(), T::nan());
+    let pixels = par_map_pixels(viewport.resolution, true, |[x, y]| {
+        match fractal.sample_derivative(viewport.pixel_centre(x, y), max_iter, escape_radius) {
+            Some((n, z)) if n < max_iter => (n, z.value, z.normal()),
+            Some((n, z)) => (n, z.value, nan),
+            None => (max_iter, nan, nan),
+        }
+    })?;
+
+    Ok(SampleBuffer {
+        final_z: Some(pixels.mapv(|(_, z, _)| z)),
+        normal: Some(pixels.mapv(|(_, _, normal)| normal)),
+        ..SampleBuffer::new(*viewport, pixels.mapv(|(n, _, _)| n))
+    })
+}
+
+/// Renders a fractal's shading as a surface rising away from the set, lit from the `light` direction given as `[real, imag, up]`, with `render_normals`.
+/// Lower `height`s tilt the surface further, exaggerating the relief.
+/// Pixels whose orbits do not escape past `escape_radius` within `max_iter` iterations are given a shading of zero.
+pub fn render_normal_shading<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    escape_radius: T,
+    light: [T; 3],
+    height: T,
+) -> Result<Array2<T>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    check_light(light)?;
+    check_positive("height", height)?;
+    let buffer = render_normals(viewport, fractal, max_iter, escape_radius)?;
+    Ok(buffer
+        .normal_shading(light, height)?
+        .expect("normals were rendered"))
+}
+
+/// Checks the settings shared by renders from the derivatives of orbits.
+fn validate_derivative<T: Float>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    escape_radius: T,
+) -> Result<(), MandybrotError> {
+    viewport.validate()?;
+    fractal.validate()?;
+    check_positive("escape_radius", escape_radius)?;
+    if escape_radius <= T::one() {
+        return Err(MandybrotError::InvalidParameter {
+            name: "escape_radius",
+            value: escape_radius.to_f64().unwrap_or(f64::NAN),
+        });
+    }
+    if !fractal.has_derivative() {
+        return Err(MandybrotError::NoDerivative);
+    }
+    Ok(())
+}
+
+/// Renders the Mandelbrot set sampled once at each pixel centre, recording the atom domain and period of each point.
+/// The atom domain is the iteration at which `|z|` was smallest, and the period is that of the hyperbolic component containing interior points.
+/// Points which escape, or whose cycle is not found within `max_iter`, are given a period of zero.
+pub fn render_atom_domains<T>(
+    viewport: &Viewport<T>,
+    max_iter: u32,
+    periodicity: &Periodicity<T>,
+) -> Result<SampleBuffer<T>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(viewport, &Fractal::Mandelbrot, 1, Some(periodicity))?;
+
+    let pixels = par_map_pixels(viewport.resolution, false, |[x, y]| {
+        mandelbrot_atom(viewport.pixel_centre(x, y), max_iter, periodicity)
+    })?;
+
+    Ok(SampleBuffer {
+        atom_domain: Some(pixels.mapv(|(_, domain, _)| domain)),
+        period: Some(pixels.mapv(|(_, _, period)| period)),
+        ..SampleBuffer::new(*viewport, pixels.mapv(|(n, _, _)| n))
+    })
+}
+
+/// Re-renders a fractal for a new viewport, reusing the pixels of a previous render which remain visible.
+/// If the new viewport is the previous one panned by a whole number of pixels, with the same transform and pixel aspect, the samples are shifted and only the newly exposed rectangles are rendered.
+/// Otherwise the whole image is rendered again.
+pub fn rerender_fractal<T>(
+    previous: SampleBuffer<T>,
+    viewport: Viewport<T>,
+    max_iter: u32,
+    fractal: &Fractal<T>,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<SampleBuffer<T>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    validate_escape_time(&viewport, fractal, samples_per_pixel, periodicity.as_ref())?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let (width, height) = (x_res as usize, y_res as usize);
+    let mut samples = Array2::<u32>::zeros((height, width));
+
+    let dirty = match pixel_shift(&previous.viewport, &viewport) {
+        Some([dx, dy]) => {
+            // Copy the pixels which are visible in both viewports.
+            let (old_height, old_width) = previous.samples.dim();
+            let (old_height, old_width) = (old_height as isize, old_width as isize);
+            for ((y, x), pixel) in samples.indexed_iter_mut() {
+                let (old_x, old_y) = (x as isize + dx, y as isize + dy);
+                if (0..old_width).contains(&old_x) && (0..old_height).contains(&old_y) {
+                    *pixel = previous.samples[(old_y as usize, old_x as usize)];
+                }
+            }
+            exposed_rectangles([dx, dy], [width, height])
+        }
+        None => vec![[0, 0, width, height]],
+    };
+
+    for [x0, y0, x1, y1] in dirty {
+        let resolution = [(x1 - x0) as u32, (y1 - y0) as u32];
+        let exposed = par_map_pixels(resolution, false, |[x, y]| {
+            sample_pixel(
+                &viewport,
+                [x0 as u32 + x, y0 as u32 + y],
+                fractal,
+                max_iter,
+                samples_per_pixel,
+                pattern,
+                periodicity.as_ref(),
+            )
+        })?;
+        samples.slice_mut(s![y0..y1, x0..x1]).assign(&exposed);
+    }
+
+    Ok(SampleBuffer::new(viewport, samples))
+}
+
+/// Whole number of pixels the new viewport is panned by relative to the old one, if it is a pure pan.
+/// The pan is measured along the old viewport's pixel axes, so views which are rotated, sheared or stretched alike can still be panned.
+fn pixel_shift<T: Float>(old: &Viewport<T>, new: &Viewport<T>) -> Option<[isize; 2]> {
+    if old.resolution != new.resolution
+        || old.scale != new.scale
+        || old.transform != new.transform
+        || old.pixel_aspect != new.pixel_aspect
+    {
+        return None;
+    }
+    let [x_res, y_res] = new.resolution;
+    let two = T::from(2.0).unwrap();
+    let (half_width, half_height) = (T::from(x_res)? / two, T::from(y_res)? / two);
+    let grid = old.complex_to_grid(&new.pixel_to_complex(half_width, half_height));
+    let (dx, dy) = (grid.real - half_width, grid.imag - half_height);
+    let tolerance = T::from(1.0e-6).unwrap();
+    if !dx.is_finite()
+        || !dy.is_finite()
+        || (dx - dx.round()).abs() > tolerance
+        || (dy - dy.round()).abs() > tolerance
+    {
+        return None;
+    }
+    Some([dx.round().to_isize()?, dy.round().to_isize()?])
+}
+
+/// Non-overlapping `[x0, y0, x1, y1]` rectangles of an image which are not covered after shifting it by `[dx, dy]` pixels.
+fn exposed_rectangles([dx, dy]: [isize; 2], [width, height]: [usize; 2]) -> Vec<[usize; 4]> {
+    let clamp = |v: isize, max: usize| v.clamp(0, max as isize) as usize;
+
+    // Columns exposed on the left or right span the full height.
+    let (kept_x0, kept_x1) = (clamp(-dx, width), clamp(width as isize - dx, width));
+    let (kept_y0, kept_y1) = (clamp(-dy, height), clamp(height as isize - dy, height));
+
+    let mut rectangles = Vec::new();
+    if kept_x0 >= kept_x1 || kept_y0 >= kept_y1 {
+        rectangles.push([0, 0, width, height]);
+        return rectangles;
+    }
+    if kept_x0 > 0 {
+        rectangles.push([0, 0, kept_x0, height]);
+    }
+    if kept_x1 < width {
+        rectangles.push([kept_x1, 0, width, height]);
+    }
+    // Rows exposed above or below only span the kept columns.
+    if kept_y0 > 0 {
+        rectangles.push([kept_x0, 0, kept_x1, kept_y0]);
+    }
+    if kept_y1 < height {
+        rectangles.push([kept_x0, kept_y1, kept_x1, height]);
+    }
+    rectangles
+}
+
+/// Samples a single pixel at the points of a sample pattern, returning the mean iteration count rounded down.
+pub(crate) fn sample_pixel<T>(
+    viewport: &Viewport<T>,
+    pixel: [u32; 2],
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<&Periodicity<T>>,
+) -> u32
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
+{
+    let sum = sample_pixel_sum(
+        viewport,
+        pixel,
+        fractal,
+        max_iter,
+        samples_per_pixel,
+        pattern,
+        periodicity,
+    );
+    (sum / (samples_per_pixel as u64).pow(2)) as u32
+}
+
+/// Total iteration count of the samples of a single pixel, held in 64 bits so that it cannot overflow however high `max_iter` and the number of samples are.
+fn sample_pixel_sum<T>(
+    viewport: &Viewport<T>,
+    pixel: [u32; 2],
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    periodicity: Option<&Periodicity<T>>,
+) -> u64
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
+{
+    let mut sum = 0;
+    for_each_sample(viewport, pixel, samples_per_pixel, pattern, |c| {
+        sum += fractal.sample(c, max_iter, periodicity) as u64;
+    });
+    sum
+}
+
+/// Calls `f` with each point of a sample pattern within a pixel.
+fn for_each_sample<T: Float>(
+    viewport: &Viewport<T>,
+    [x, y]: [u32; 2],
+    samples_per_pixel: u32,
+    pattern: SamplePattern,
+    mut f: impl FnMut(Complex<T>),
+) {
+    let x_t = T::from(x).unwrap();
+    let y_t = T::from(y).unwrap();
+
+    if pattern == SamplePattern::Grid {
+        // The regular grid needs no per-pixel state, so is sampled directly.
+        let half = T::from(0.5).unwrap();
+        let samples_t = T::from(samples_per_pixel).unwrap();
+        for i in 0..samples_per_pixel {
+            let offset_x = (T::from(i).unwrap() + half) / samples_t;
+            for j in 0..samples_per_pixel {
+                let offset_y = (T::from(j).unwrap() + half) / samples_t;
+                f(viewport.pixel_to_complex(x_t + offset_x, y_t + offset_y));
+            }
+        }
+    } else {
+        let width = viewport.resolution[0];
+        for [offset_x, offset_y] in pattern.offsets::<T>([x, y], width, samples_per_pixel) {
+            f(viewport.pixel_to_complex(x_t + offset_x, y_t + offset_y));
+        }
+    }
+}
+
+/// Renders a soft, probabilistic image by averaging each pixel over a Gaussian-weighted spread of starting points.
+/// The spread moves the initial `z` of each orbit and keeps its constant `c`, so each pixel averages a small "soup" of nearby orbits: around the pixel for Julia sets, and around `z0 = 0` for the Mandelbrot family.
+/// Unlike anti-aliasing, the `spread` (the standard deviation, in complex-plane units) does not depend on the pixel size.
+/// Orbits are stopped once `|z|` passes two, so only fractals which can be seeded, as for `render_seeded`, are supported.
+#[allow(clippy::too_many_arguments)]
+pub fn render_orbit_soup<T>(
+    centre: Complex<T>,
+    max_iter: u32,
+    scale: T,
+    resolution: [u32; 2],
+    fractal: &Fractal<T>,
+    num_starts: u32,
+    spread: T,
+    periodicity: Option<Periodicity<T>>,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + FloatConst
+        + Send
+        + Sync,
+{
+    let viewport = Viewport::new(centre, scale, resolution);
+    validate_escape_time(&viewport, fractal, 1, periodicity.as_ref())?;
+    check_count("num_starts", num_starts)?;
+    check_non_negative("spread", spread)?;
+    check_seedable(fractal)?;
+    let starts = soup_offsets(num_starts, spread);
+    let total_weight = starts.iter().fold(T::zero(), |acc, &(_, w)| acc + w);
+
+    par_map_pixels(resolution, true, |[x, y]| {
+        let start = fractal
+            .initial_state(viewport.pixel_centre(x, y))
+            .expect("checked that the fractal has an initial state");
+        let sum = starts.iter().fold(T::zero(), |acc, &(offset, weight)| {
+            let state = OrbitState {
+                z: start.z + offset,
+                ..start
+            };
+            let (n, _) = sample_seeded(fractal, state, max_iter, periodicity.as_ref());
+            acc + weight * T::from(n).unwrap()
+        });
+        (sum / total_weight).round().to_u32().unwrap_or(u32::MAX)
+    })
+}
+
+/// Deterministic starting point offsets laid out on a Vogel spiral, paired with their Gaussian weights.
+fn soup_offsets<T: Float + FloatConst>(num_starts: u32, spread: T) -> Vec<(Complex<T>, T)> {
+    let two = T::from(2.0).unwrap();
+    // Cover three standard deviations, beyond which the weights are negligible.
+    let radius = T::from(3.0).unwrap() * spread;
+    let golden_angle = T::PI() * (T::from(3.0).unwrap() - T::from(5.0).unwrap().sqrt());
+
+    (0..num_starts)
+        .map(|i| {
+            let i = T::from(i).unwrap();
+            let rho = radius * ((i + T::from(0.5).unwrap()) / T::from(num_starts).unwrap()).sqrt();
+            let theta = i * golden_angle;
+            let weight = if spread > T::zero() {
+                (-(rho * rho) / (two * spread * spread)).exp()
+            } else {
+                T::one()
+            };
+            (Complex::new(rho * theta.cos(), rho * theta.sin()), weight)
+        })
+        .collect()
+}
+
+pub(crate) fn generate_initial_positions<T>(
+    start: Complex<T>,
+    radius: T,
+    num_samples: u32,
+    rng_strategy: &RngStrategy,
+) -> Vec<Complex<T>>
+where
+    T: Float + FloatConst + NumCast + SampleUniform,
+{
+    let mut positions = Vec::with_capacity(num_samples as usize);
+    let zero = T::from(0.0).unwrap();
+    let tau = T::TAU();
+    for chunk in 0..num_samples.div_ceil(INITIAL_POSITIONS_PER_CHUNK) {
+        let mut rng = rng_strategy.orbit_stream(chunk as u64);
+        let remaining = num_samples - chunk * INITIAL_POSITIONS_PER_CHUNK;
+        for _ in 0..remaining.min(INITIAL_POSITIONS_PER_CHUNK) {
+            let theta = rng.random_range(zero..tau);
+            let rho = rng.random_range(zero..radius).sqrt();
+            let x = start.real + rho * theta.cos();
+            let y = start.imag + rho * theta.sin();
+            positions.push(Complex::new(x, y));
+        }
+    }
+    positions
+}
+
+/// Number of initial positions drawn from each random number stream.
+const INITIAL_POSITIONS_PER_CHUNK: u32 = 1024;
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_attractor<T>(
+    centre: Complex<T>,
+    scale: T,
+    resolution: [u32; 2],
+
+    start: Complex<T>,
+    radius: T,
+    num_samples: u32,
+
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &Attractor<T>,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + FloatConst
+        + SampleUniform
+        + Send
+        + Sync
+        + Display,
+{
+    render_attractor_viewport(
+        &Viewport::new(centre, scale, resolution),
+        start,
+        radius,
+        num_samples,
+        max_iter,
+        draw_after,
+        attractor,
+        rng_strategy,
+    )
+}
+
+/// Renders the density of an attractor's orbits over a viewport, which may be rotated, sheared or anamorphic, as `render_attractor`.
+#[allow(clippy::too_many_arguments)]
+pub fn render_attractor_viewport<T>(
+    viewport: &Viewport<T>,
+    start: Complex<T>,
+    radius: T,
+    num_samples: u32,
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &Attractor<T>,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + FloatConst
+        + SampleUniform
+        + Send
+        + Sync
+        + Display,
+{
+    viewport.validate()?;
+    attractor.validate()?;
+    check_finite("start.real", start.real)?;
+    check_finite("start.imag", start.imag)?;
+    check_positive("radius", radius)?;
+    check_count("num_samples", num_samples)?;
+
+    let initial_positions = generate_initial_positions(start, radius, num_samples, rng_strategy);
+
+    // Render and sum attractors concurrently, a fixed chunk of starting points at a time.
+    let [x_res, y_res] = viewport.resolution;
+    let shape = (y_res as usize, x_res as usize);
+    let num_chunks = num_samples.div_ceil(ATTRACTOR_SAMPLES_PER_CHUNK);
+    sum_grids(num_chunks, Dim(shape), |chunk| {
+        let mut pixels = Array2::<u32>::zeros(shape);
+        let start = (chunk * ATTRACTOR_SAMPLES_PER_CHUNK) as usize;
+        for &pos in initial_positions
+            .iter()
+            .skip(start)
+            .take(ATTRACTOR_SAMPLES_PER_CHUNK as usize)
+        {
+            render_attractor_path(
+                pos,
+                viewport,
+                max_iter,
+                draw_after,
+                attractor,
+                |p| p,
+                |[x, y]| pixels[[y, x]] += 1,
+            );
+        }
+        pixels
+    })
+}
+
+/// Renders the density of short orbits of an attractor started from the centre of every `spacing`th pixel of a viewport in each direction, rather than from random points.
+/// Maps such as Pickover's popcorn are drawn this way, their orbits tracing a field across the whole image instead of settling onto a single attractor.
+pub fn render_attractor_pixels<T>(
+    viewport: &Viewport<T>,
+    spacing: u32,
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &Attractor<T>,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync
+        + Display,
+{
+    viewport.validate()?;
+    attractor.validate()?;
+    check_count("spacing", spacing)?;
+
+    // Render and sum the orbits of each row of starting pixels concurrently.
+    let [x_res, y_res] = viewport.resolution;
+    let shape = (y_res as usize, x_res as usize);
+    sum_grids(y_res.div_ceil(spacing), Dim(shape), |row| {
+        let mut pixels = Array2::<u32>::zeros(shape);
+        for x in (0..x_res).step_by(spacing as usize) {
+            render_attractor_path(
+                viewport.pixel_centre(x, row * spacing),
+                viewport,
+                max_iter,
+                draw_after,
+                attractor,
+                |p| p,
+                |[x, y]| pixels[[y, x]] += 1,
+            );
+        }
+        pixels
+    })
+}
+
+/// Renders an attractor in colour, colouring each orbit by where it started so that orbits from different places, such as different basins or arms, can be told apart.
+/// Each starting point within `radius` of `start` is given a position in `colour_map` by its `provenance`, and each pixel shows the average colour of the orbits through it, shaded by its log density.
+#[allow(clippy::too_many_arguments)]
+pub fn render_attractor_coloured<T>(
+    viewport: &Viewport<T>,
+    start: Complex<T>,
+    radius: T,
+    num_samples: u32,
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &Attractor<T>,
+    provenance: Provenance,
+    colour_map: &ColourMap,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<LinSrgba>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + FloatConst
+        + SampleUniform
+        + Send
+        + Sync
+        + Display,
+{
+    viewport.validate()?;
+    attractor.validate()?;
+    check_finite("start.real", start.real)?;
+    check_finite("start.imag", start.imag)?;
+    check_positive("radius", radius)?;
+    check_count("num_samples", num_samples)?;
+
+    let initial_positions = generate_initial_positions(start, radius, num_samples, rng_strategy);
+
+    // Accumulate the summed colour and the number of hits of each pixel.
+    let [x_res, y_res] = viewport.resolution;
+    let shape = (y_res as usize, x_res as usize, 4);
+    let num_chunks = num_samples.div_ceil(ATTRACTOR_SAMPLES_PER_CHUNK);
+    let histogram = sum_grids(num_chunks, Dim(shape), |chunk| {
+        let mut histogram = Array3::<f64>::zeros(shape);
+        let first = (chunk * ATTRACTOR_SAMPLES_PER_CHUNK) as usize;
+        for &pos in initial_positions
+            .iter()
+            .skip(first)
+            .take(ATTRACTOR_SAMPLES_PER_CHUNK as usize)
+        {
+            let t = provenance.position(pos, start, radius);
+            let rgb = colour_map.sample(t.to_f32().unwrap_or(0.0));
+            render_attractor_path(
+                pos,
+                viewport,
+                max_iter,
+                draw_after,
+                attractor,
+                |p| p,
+                |[x, y]| {
+                    histogram[[y, x, 0]] += rgb.red as f64;
+                    histogram[[y, x, 1]] += rgb.green as f64;
+                    histogram[[y, x, 2]] += rgb.blue as f64;
+                    histogram[[y, x, 3]] += 1.0;
+                },
+            );
+        }
+        histogram
+    })?;
+
+    // Scale the average colour by the log density, relative to the densest pixel.
+    let max_hits = histogram
+        .index_axis(Axis(2), 3)
+        .fold(0.0_f64, |max, &hits| max.max(hits));
+    let norm = max_hits.ln_1p().max(f64::MIN_POSITIVE);
+    Ok(Array2::from_shape_fn((shape.0, shape.1), |(y, x)| {
+        let hits = histogram[[y, x, 3]];
+        if hits == 0.0 {
+            return LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        }
+        let brightness = hits.ln_1p() / norm / hits;
+        LinSrgba::new(
+            (histogram[[y, x, 0]] * brightness) as f32,
+            (histogram[[y, x, 1]] * brightness) as f32,
+            (histogram[[y, x, 2]] * brightness) as f32,
+            1.0,
+        )
+    }))
+}
+
+/// Number of attractor starting points drawn into each grid before the grids are summed.
+const ATTRACTOR_SAMPLES_PER_CHUNK: u32 = 16;
+
+/// Renders a single part of a point orbiting an attractor by iterating its dynamics and accumulating hits in a pixel grid.
+/// Each point of the orbit is placed on the plane by `plot`.
+fn render_attractor_path<T>(
+    start: Complex<T>,
+    viewport: &Viewport<T>,
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &Attractor<T>,
+    plot: impl Fn(Complex<T>) -> Complex<T>,
+    mut hit: impl FnMut([usize; 2]),
+) where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync
+        + std::fmt::Display,
+{
+    for (_, pos) in attractor_path(start, max_iter, draw_after, attractor) {
+        if let Some(pixel) = viewport.complex_to_pixel(&plot(pos)) {
+            hit(pixel);
+        }
+    }
+}
+
+/// Points of the orbit of a single starting point of an attractor, after its first `draw_after` of `max_iter` iterations, each with the index of the iteration which reached it.
+pub(crate) fn attractor_path<T: Float>(
+    start: Complex<T>,
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &Attractor<T>,
+) -> impl Iterator<Item = (u32, Complex<T>)> + '_ {
+    let mut pos = start;
+    (0..max_iter).filter_map(move |n| {
+        pos = attractor.iterate(pos);
+        (n >= draw_after).then_some((n, pos))
+    })
+}
+
+/// Renders a bifurcation diagram of an attractor, sweeping `parameter` along the real axis of the viewport and plotting the real part of each visited point up the imaginary axis.
+/// Each pixel column iterates `num_samples` starting points within `radius` of `start`, drawing each after the first `draw_after` of its `max_iter` iterations.
+#[allow(clippy::too_many_arguments)]
+pub fn render_bifurcation<T>(
+    viewport: &Viewport<T>,
+    attractor: &Attractor<T>,
+    parameter: AttractorParameter,
+
+    start: Complex<T>,
+    radius: T,
+    num_samples: u32,
+
+    max_iter: u32,
+    draw_after: u32,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + FloatConst
+        + SampleUniform
+        + Send
+        + Sync
+        + Display,
+{
+    viewport.validate()?;
+    attractor.validate()?;
+    let mut swept = *attractor;
+    swept.set_parameter(parameter, viewport.centre.real)?;
+    check_finite("start.real", start.real)?;
+    check_finite("start.imag", start.imag)?;
+    check_positive("radius", radius)?;
+    check_count("num_samples", num_samples)?;
+
+    let initial_positions = generate_initial_positions(start, radius, num_samples, rng_strategy);
+
+    // Render and sum the columns concurrently, a fixed chunk of columns at a time.
+    let [x_res, y_res] = viewport.resolution;
+    let shape = (y_res as usize, x_res as usize);
+    let num_chunks = x_res.div_ceil(BIFURCATION_COLUMNS_PER_CHUNK);
+    sum_grids(num_chunks, Dim(shape), |chunk| {
+        let mut pixels = Array2::<u32>::zeros(shape);
+        let first = chunk * BIFURCATION_COLUMNS_PER_CHUNK;
+        for column in first..(first + BIFURCATION_COLUMNS_PER_CHUNK).min(x_res) {
+            let value = viewport.pixel_centre(column, 0).real;
+            let mut swept = swept;
+            // The parameter was found above, so setting it again cannot fail.
+            let _ = swept.set_parameter(parameter, value);
+            for &pos in &initial_positions {
+                render_attractor_path(
+                    pos,
+                    viewport,
+                    max_iter,
+                    draw_after,
+                    &swept,
+                    |p| Complex::new(value, p.real),
+                    |[x, y]| pixels[[y, x]] += 1,
+                );
+            }
+        }
+        pixels
+    })
+}
+
+/// Number of bifurcation diagram columns drawn into each grid before the grids are summed.
+const BIFURCATION_COLUMNS_PER_CHUNK: u32 = 8;
+
+/// Renders a Julia set by inverse iteration, accumulating the backwards orbit of its repelling fixed point in a pixel grid.
+/// This resolves thin filaments which escape-time rendering misses.
+pub fn render_inverse_julia<T>(
+    viewport: &Viewport<T>,
+    c: Complex<T>,
+    max_iter: u32,
+    method: &InverseIteration,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    viewport.validate()?;
+    Fractal::julia_of(c).validate()?;
+    method.validate()?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let shape = (y_res as usize, x_res as usize);
+
+    // Of the two fixed points (1 ± √(1 - 4c)) / 2, the repelling one has the larger modulus.
+    let one = Complex::new(T::one(), T::zero());
+    let four = T::from(4.0).unwrap();
+    let root = (one - Complex::new(four * c.real, four * c.imag)).sqrt();
+    let fixed_point = (one + root) / T::from(2.0).unwrap();
+
+    Ok(match *method {
+        InverseIteration::Random {
+            num_orbits,
+            draw_after,
+        } => sum_grids(num_orbits, Dim(shape), |orbit| {
+            let mut pixels = Array2::<u32>::zeros(shape);
+            let mut rng = rng_strategy.orbit_stream(orbit as u64);
+            let mut z = fixed_point;
+            for n in 0..max_iter {
+                z = (z - c).sqrt();
+                if rng.random_bool(0.5) {
+                    z = -z;
+                }
+                if n < draw_after {
+                    continue;
+                }
+                if let Some([x, y]) = viewport.complex_to_pixel(&z) {
+                    pixels[[y, x]] += 1;
+                }
+            }
+            pixels
+        })?,
+        InverseIteration::Modified { max_hits } => {
+            // Hits are counted on the viewport's pixel grid extended over the whole plane, so off-screen branches are pruned too.
+            let cell = |z: &Complex<T>| {
+                let grid = viewport.complex_to_grid(z);
+                Some([grid.real.floor().to_i64()?, grid.imag.floor().to_i64()?])
+            };
+
+            let mut hits = HashMap::new();
+            let mut stack = vec![(fixed_point, 0)];
+            while let Some((z, depth)) = stack.pop() {
+                let Some(cell) = cell(&z) else {
+                    continue;
+                };
+                let count = hits.entry(cell).or_insert(0);
+                if *count >= max_hits {
+                    continue;
+                }
+                *count += 1;
+                if depth < max_iter {
+                    let preimage = (z - c).sqrt();
+                    stack.push((preimage, depth + 1));
+                    stack.push((-preimage, depth + 1));
+                }
+            }
+
+            let mut pixels = Array2::<u32>::zeros(shape);
+            for ([x, y], count) in hits {
+                if (0..x_res as i64).contains(&x) && (0..y_res as i64).contains(&y) {
+                    pixels[[y as usize, x as usize]] = count;
+                }
+            }
+            pixels
+        }
+    })
+}
+
+/// Half-width of the square region of the complex plane from which Buddhabrot orbits are started.
+const BUDDHABROT_RADIUS: f64 = 2.0;
+
+/// Probability that a Metropolis–Hastings proposal is a fresh uniform sample rather than a local mutation.
+const LARGE_MUTATION_PROBABILITY: f64 = 0.1;
+
+/// Number of Metropolis–Hastings chains, fixed so that the split of work does not depend on the machine.
+const NUM_CHAINS: u32 = 64;
+
+/// Number of samples drawn from each random number stream.
+const SAMPLES_PER_CHUNK: u32 = 4096;
+
+/// Number of grids rendered per thread in each batch of `sum_grids`.
+const GRIDS_PER_THREAD: u32 = 4;
+
+/// Maximum number of uniform samples used to estimate the mean contribution and seed the Metropolis–Hastings chains.
+const MAX_SEED_SAMPLES: u32 = 100_000;
+
+/// Renders the Buddhabrot by accumulating the orbits of escaping Mandelbrot points in a pixel grid.
+pub fn render_buddhabrot<T>(
+    centre: Complex<T>,
+    scale: T,
+    resolution: [u32; 2],
+    num_samples: u32,
+    max_iter: u32,
+    sampling: &Sampling<T>,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync
+        + Display,
+{
+    render_buddhabrot_viewport(
+        &Viewport::new(centre, scale, resolution),
+        num_samples,
+        max_iter,
+        sampling,
+        rng_strategy,
+    )
+}
+
+/// Renders the Buddhabrot over a viewport, which may be rotated, sheared or anamorphic, as `render_buddhabrot`.
+pub fn render_buddhabrot_viewport<T>(
+    viewport: &Viewport<T>,
+    num_samples: u32,
+    max_iter: u32,
+    sampling: &Sampling<T>,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync
+        + Display,
+{
+    let [x_res, y_res] = viewport.resolution;
+    let shape = (y_res as usize, x_res as usize);
+    viewport.validate()?;
+    sampling.validate()?;
+    check_count("num_samples", num_samples)?;
+
+    let density = match *sampling {
+        Sampling::Uniform => {
+            let num_chunks = num_samples.div_ceil(SAMPLES_PER_CHUNK);
+            sum_grids(num_chunks, Dim(shape), |chunk| {
+                let mut density = Array2::<f64>::zeros(shape);
+                let mut rng = rng_strategy.stream(UNIFORM_DOMAIN, chunk as u64);
+                for _ in chunk_range(chunk, num_samples) {
+                    let c = random_buddhabrot_seed(&mut rng);
+                    if let Some(orbit) = buddhabrot_orbit(c, max_iter) {
+                        splat_orbit(&orbit, viewport, 1.0, &mut density);
+                    }
+                }
+                density
+            })?
+        }
+        Sampling::MetropolisHastings {
+            mutation_size,
+            burn_in,
+        } => {
+            // Estimate the mean contribution of a uniform sample, keeping any contributing samples as chain seeds.
+            let num_seed_samples = num_samples.min(MAX_SEED_SAMPLES);
+            let seeds: Vec<(Complex<T>, u32)> = (0..num_seed_samples.div_ceil(SAMPLES_PER_CHUNK))
+                .into_par_iter()
+                .flat_map_iter(|chunk| {
+                    let mut rng = rng_strategy.stream(SEED_DOMAIN, chunk as u64);
+                    chunk_range(chunk, num_seed_samples)
+                        .map(|_| {
+                            let c = random_buddhabrot_seed(&mut rng);
+                            let contribution = buddhabrot_orbit(c, max_iter)
+                                .map_or(0, |orbit| orbit_contribution(&orbit, viewport));
+                            (c, contribution)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .filter(|&(_, contribution)| contribution > 0)
+                .collect();
+            if seeds.is_empty() {
+                return Ok(Array2::zeros(shape));
+            }
+            let total_contribution: f64 = seeds.iter().map(|&(_, f)| f as f64).sum();
+            let mean_contribution = total_contribution / num_seed_samples as f64;
+
+            let num_chains = NUM_CHAINS.clamp(1, num_samples.max(1));
+            let steps_per_chain = num_samples / num_chains;
+            let step_size = mutation_size * viewport.scale;
+
+            sum_grids(num_chains, Dim(shape), |chain| {
+                let mut density = Array2::<f64>::zeros(shape);
+                let mut rng = rng_strategy.stream(CHAIN_DOMAIN, chain as u64);
+                let (mut c, mut contribution) = seeds[chain as usize % seeds.len()];
+                let mut orbit = buddhabrot_orbit(c, max_iter).unwrap_or_default();
+
+                for step in 0..(burn_in + steps_per_chain) {
+                    let proposal = if rng.random_bool(LARGE_MUTATION_PROBABILITY) {
+                        random_buddhabrot_seed(&mut rng)
+                    } else {
+                        let theta = rng.random_range(0.0..std::f64::consts::TAU);
+                        let rho = rng.random_range(0.0..1.0_f64).sqrt();
+                        c + Complex::new(
+                            step_size * T::from(rho * theta.cos()).unwrap(),
+                            step_size * T::from(rho * theta.sin()).unwrap(),
+                        )
+                    };
+
+                    if let Some(proposed_orbit) = buddhabrot_orbit(proposal, max_iter) {
+                        let proposed_contribution = orbit_contribution(&proposed_orbit, viewport);
+                        let acceptance = proposed_contribution as f64 / contribution as f64;
+                        if proposed_contribution > 0
+                            && (acceptance >= 1.0 || rng.random_bool(acceptance))
+                        {
+                            c = proposal;
+                            contribution = proposed_contribution;
+                            orbit = proposed_orbit;
+                        }
+                    }
+
+                    if step >= burn_in {
+                        let weight = mean_contribution / contribution as f64;
+                        splat_orbit(&orbit, viewport, weight, &mut density);
+                    }
+                }
+
+                density
+            })?
+        }
+    };
+
+    Ok(density.mapv(|v| v.round() as u32))
+}
+
+/// Range of sample indices handled by the given chunk.
+fn chunk_range(chunk: u32, num_samples: u32) -> std::ops::Range<u32> {
+    let start = chunk * SAMPLES_PER_CHUNK;
+    start..(start + SAMPLES_PER_CHUNK).min(num_samples)
+}
+
+/// Draws a uniformly distributed Buddhabrot starting point.
+fn random_buddhabrot_seed<T: Float + NumCast>(rng: &mut impl Rng) -> Complex<T> {
+    Complex::new(
+        T::from(rng.random_range(-BUDDHABROT_RADIUS..BUDDHABROT_RADIUS)).unwrap(),
+        T::from(rng.random_range(-BUDDHABROT_RADIUS..BUDDHABROT_RADIUS)).unwrap(),
+    )
+}
+
+/// Iterates the Mandelbrot map from the origin, returning the orbit if it escapes within `max_iter` iterations.
+fn buddhabrot_orbit<T: Float>(c: Complex<T>, max_iter: u32) -> Option<Vec<Complex<T>>> {
+    let four = T::from(4.0).unwrap();
+    let mut z = Complex::new(T::zero(), T::zero());
+    let mut orbit = Vec::new();
+
+    for _ in 0..max_iter {
+        z = z * z + c;
+        if z.norm_sqr() > four {
+            return Some(orbit);
+        }
+        orbit.push(z);
+    }
+
+    None
+}
+
+/// Counts the orbit points which land inside the viewport.
+fn orbit_contribution<T: Float>(orbit: &[Complex<T>], viewport: &Viewport<T>) -> u32 {
+    orbit
+        .iter()
+        .filter(|z| viewport.complex_to_pixel(z).is_some())
+        .count() as u32
+}
+
+/// Adds the weighted orbit points to the density grid.
+fn splat_orbit<T: Float>(
+    orbit: &[Complex<T>],
+    viewport: &Viewport<T>,
+    weight: f64,
+    density: &mut Array2<f64>,
+) {
+    for z in orbit {
+        if let Some([x, y]) = viewport.complex_to_pixel(z) {
+            density[[y, x]] += weight;
+        }
+    }
+}
+
+/// Renders `num_grids` grids in parallel and sums them in order of their index.
+/// The result is bit-identical whatever the number of threads and however they are scheduled, even for floating point grids.
+/// Grids are rendered a batch at a time, so only a few are held in memory at once.
+fn sum_grids<A, D, F>(num_grids: u32, shape: D, render: F) -> Result<Array<A, D>, MandybrotError>
+where
+    A: Clone + Zero + AddAssign + Send,
+    D: Dimension,
+    F: Fn(u32) -> Array<A, D> + Sync + Send,
+{
+    let pb = progress_bar(num_grids as u64)?;
+    let batch_size = GRIDS_PER_THREAD * rayon::current_num_threads() as u32;
+
+    let mut total = Array::zeros(shape);
+    for start in (0..num_grids).step_by(batch_size as usize) {
+        let end = (start + batch_size).min(num_grids);
+        let grids: Vec<_> = (start..end).into_par_iter().map(&render).collect();
+        for grid in grids {
+            total += &grid;
+        }
+        pb.inc((end - start) as u64);
+    }
+    pb.finish();
+
+    Ok(total)
+}
+
+/// Renders a fractal flame by the chaos game, colouring each pixel by the average colour of the points which landed in it and shading it by its log density.
+/// Each of the `num_samples` points starts at random in `[-1, 1]²` and is iterated `max_iter` times, with the first few iterations skipped while it settles onto the attractor.
+/// The flame is rendered at `super_samples` times the resolution in each direction and then downsampled, to anti-alias it.
+#[allow(clippy::too_many_arguments)]
+pub fn render_flame<T>(
+    viewport: &Viewport<T>,
+    flame: &Flame<T>,
+    num_samples: u32,
+    max_iter: u32,
+    super_samples: u32,
+    colour_map: &ColourMap,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<LinSrgba>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + FloatConst
+        + SampleUniform
+        + Send
+        + Sync,
+{
+    viewport.validate()?;
+    flame.validate()?;
+    check_count("num_samples", num_samples)?;
+    check_count("super_samples", super_samples)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let resolution = [x_res * super_samples, y_res * super_samples];
+    let fine = viewport.with_resolution(resolution);
+    let shape = (resolution[1] as usize, resolution[0] as usize, 4);
+
+    // Accumulate the summed colour and the number of hits of each pixel.
+    let num_chunks = num_samples.div_ceil(CHAOS_GAME_SAMPLES_PER_CHUNK);
+    let histogram = sum_grids(num_chunks, Dim(shape), |chunk| {
+        let mut histogram = Array3::<f64>::zeros(shape);
+        let mut rng = rng_strategy.stream(FLAME_DOMAIN, chunk as u64);
+        let start = chunk * CHAOS_GAME_SAMPLES_PER_CHUNK;
+        for _ in start..(start + CHAOS_GAME_SAMPLES_PER_CHUNK).min(num_samples) {
+            let mut p = random_chaos_game_point(&mut rng);
+            let mut colour = T::from(rng.random_range(0.0..1.0)).unwrap();
+            for n in 0..max_iter {
+                (p, colour) = flame.iterate(p, colour, &mut rng);
+                if !p.real.is_finite() || !p.imag.is_finite() {
+                    // Some variations blow up near singularities, so restart the point.
+                    p = random_chaos_game_point(&mut rng);
+                    continue;
+                }
+                if n < CHAOS_GAME_FUSE_ITERATIONS {
+                    continue;
+                }
+                if let Some([x, y]) = fine.complex_to_pixel(&p) {
+                    let rgb = colour_map.sample(colour.to_f32().unwrap_or(0.0));
+                    histogram[[y, x, 0]] += rgb.red as f64;
+                    histogram[[y, x, 1]] += rgb.green as f64;
+                    histogram[[y, x, 2]] += rgb.blue as f64;
+                    histogram[[y, x, 3]] += 1.0;
+                }
+            }
+        }
+        histogram
+    })?;
+
+    // Scale the average colour by the log density, relative to the densest pixel.
+    let max_hits = histogram
+        .index_axis(Axis(2), 3)
+        .fold(0.0_f64, |max, &hits| max.max(hits));
+    let norm = max_hits.ln_1p().max(f64::MIN_POSITIVE);
+    let inv_gamma = 1.0 / flame.gamma.to_f64().unwrap();
+    let image = Array2::from_shape_fn((shape.0, shape.1), |(y, x)| {
+        let hits = histogram[[y, x, 3]];
+        if hits == 0.0 {
+            return LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        }
+        let brightness = (hits.ln_1p() / norm).powf(inv_gamma) / hits;
+        LinSrgba::new(
+            (histogram[[y, x, 0]] * brightness) as f32,
+            (histogram[[y, x, 1]] * brightness) as f32,
+            (histogram[[y, x, 2]] * brightness) as f32,
+            1.0,
+        )
+    });
+
+    Ok(if super_samples > 1 {
+        downsample(&image, super_samples as usize)?
+    } else {
+        image
+    })
+}
+
+/// Renders the attractor of an iterated function system by the chaos game, accumulating hits in a pixel grid.
+/// Each of the `num_samples` points starts at random in `[-1, 1]²` and is iterated `max_iter` times, with the first few iterations skipped while it settles onto the attractor.
+pub fn render_ifs<T>(
+    viewport: &Viewport<T>,
+    ifs: &Ifs<T>,
+    num_samples: u32,
+    max_iter: u32,
+    rng_strategy: &RngStrategy,
+) -> Result<Array2<u32>, MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + SampleUniform
+        + Send
+        + Sync,
+{
+    viewport.validate()?;
+    ifs.validate()?;
+    check_count("num_samples", num_samples)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let shape = (y_res as usize, x_res as usize);
+
+    let num_chunks = num_samples.div_ceil(CHAOS_GAME_SAMPLES_PER_CHUNK);
+    sum_grids(num_chunks, Dim(shape), |chunk| {
+        let mut pixels = Array2::<u32>::zeros(shape);
+        let mut rng = rng_strategy.stream(IFS_DOMAIN, chunk as u64);
+        let start = chunk * CHAOS_GAME_SAMPLES_PER_CHUNK;
+        for _ in start..(start + CHAOS_GAME_SAMPLES_PER_CHUNK).min(num_samples) {
+            let p = random_chaos_game_point(&mut rng);
+            for p in ifs
+                .chaos_game(p, &mut rng)
+                .take(max_iter as usize)
+                .skip(CHAOS_GAME_FUSE_ITERATIONS as usize)
+            {
+                if let Some([x, y]) = viewport.complex_to_pixel(&p) {
+                    pixels[[y, x]] += 1;
+                }
+            }
+        }
+        pixels
+    })
+}
+
+/// Draws a uniformly distributed starting point in `[-1, 1]²`.
+fn random_chaos_game_point<T: Float + NumCast>(rng: &mut impl Rng) -> Complex<T> {
+    Complex::new(
+        T::from(rng.random_range(-1.0..1.0)).unwrap(),
+        T::from(rng.random_range(-1.0..1.0)).unwrap(),
+    )
+}
+
+/// Number of chaos game samples drawn from each random number stream.
+const CHAOS_GAME_SAMPLES_PER_CHUNK: u32 = 16;
+
+/// Number of iterations a chaos game sample takes to settle onto the attractor before it is plotted.
+const CHAOS_GAME_FUSE_ITERATIONS: u32 = 20;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Affine, FlameTransform, Variation};
+
+    /// Runs a render on a pool of the given number of threads.
+    fn with_threads<R: Send>(num_threads: usize, render: impl FnOnce() -> R + Send) -> R {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap()
+            .install(render)
+    }
+
+    /// FNV-1a hash of a grid of counts, in row-major order.
+    fn checksum(counts: &Array2<u32>) -> u64 {
+        counts
+            .iter()
+            .flat_map(|count| count.to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    #[test]
+    fn zoom_double_double_frames_agree_with_perturbation() {
+        let target = Complex::new(
+            "-0.743643887037158704752191506114774".parse().unwrap(),
+            "0.131825904205311970493132056385139".parse().unwrap(),
+        );
+        let zoom = ZoomPath::new(target, 1.0e-15, 30);
+        let resolution = [32, 32];
+        let max_iter = 5000;
+
+        let mut handover = None;
+        try_render_zoom(&zoom, resolution, max_iter, |frame, precision, image| {
+            if precision == Precision::DoubleDouble && handover.is_none() {
+                handover = Some((frame, image));
+            }
+            Ok(())
+        })
+        .unwrap();
+        let (frame, direct) = handover.unwrap();
+
+        let orbit = reference_orbit(target, max_iter);
+        let offsets = Viewport::new(Complex::new(0.0, 0.0), zoom.scale(frame), resolution);
+        let perturbed = render_perturbed(&offsets, &orbit, max_iter).unwrap();
+        let matching = direct
+            .iter()
+            .zip(&perturbed)
+            .filter(|(a, b)| a == b)
+            .count();
+        assert!(
+            matching * 100 >= direct.len() * 95,
+            "{matching} of {}",
+            direct.len()
+        );
+    }
+
+    #[test]
+    fn escape_time_render_matches_golden_output() {
+        let render = || {
+            render_fractal(
+                Complex::new(-0.75, 0.0),
+                256,
+                2.5,
+                [64, 48],
+                Fractal::Mandelbrot,
+                4,
+                SamplePattern::Grid,
+                None,
+            )
+            .unwrap()
+        };
+        let serial = with_threads(1, render);
+        let parallel = with_threads(4, render);
+        assert_eq!(serial, parallel);
+        assert_eq!(checksum(&serial), 0xd622_8adf_12a8_26d9);
+    }
+
+    #[test]
+    fn rerendering_reuses_pixels_only_for_pans_along_the_pixel_axes() {
+        let fractal = Fractal::Mandelbrot;
+        let render = |viewport: &Viewport<f64>| {
+            render_fractal_viewport(viewport, &fractal, 64, 1, SamplePattern::Grid, None).unwrap()
+        };
+        let rerender = |previous: &Viewport<f64>, viewport: Viewport<f64>| {
+            let previous = SampleBuffer::new(*previous, render(previous));
+            rerender_fractal(
+                previous,
+                viewport,
+                64,
+                &fractal,
+                1,
+                SamplePattern::Grid,
+                None,
+            )
+            .unwrap()
+            .samples
+        };
+        let plain = Viewport::new(Complex::new(-0.75, 0.1), 2.5, [32, 24]);
+        let rotated = plain.with_transform(Affine::rotation(0.3));
+
+        // Changing only the transform must render every pixel again.
+        assert_eq!(rerender(&plain, rotated), render(&rotated));
+        let stretched = plain.with_pixel_aspect(1.5);
+        assert_eq!(rerender(&plain, stretched), render(&stretched));
+
+        // A pan of whole pixels along the rotated axes reuses the overlap.
+        let step = rotated.pixel_size();
+        let offset = Affine::rotation(0.3).apply(Complex::new(3.0 * step.real, -2.0 * step.imag));
+        let panned = Viewport {
+            centre: rotated.centre + offset,
+            ..rotated
+        };
+        assert_eq!(pixel_shift(&rotated, &panned), Some([3, -2]));
+        assert_eq!(rerender(&rotated, panned), render(&panned));
+    }
+
+    #[test]
+    fn orbit_soup_moves_the_start_of_the_orbit_and_keeps_c() {
+        let (centre, scale, resolution) = (Complex::new(-0.75, 0.0), 2.5, [32, 24]);
+        let plain = render_fractal(
+            centre,
+            64,
+            scale,
+            resolution,
+            Fractal::Mandelbrot,
+            1,
+            SamplePattern::Grid,
+            None,
+        )
+        .unwrap();
+        let soup = |spread| {
+            render_orbit_soup(
+                centre,
+                64,
+                scale,
+                resolution,
+                &Fractal::Mandelbrot,
+                8,
+                spread,
+                None,
+            )
+            .unwrap()
+        };
+        assert_eq!(soup(0.0), plain);
+
+        // Starting points within a small disc about zero stay bounded for `c` deep inside the main cardioid
+        let inside = plain
+            .indexed_iter()
+            .filter(|&(_, &n)| n == 64)
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let soft = soup(0.05);
+        assert!(inside.iter().any(|&index| soft[index] == 64));
+        assert_ne!(soft, plain);
+    }
+
+    #[test]
+    fn seeded_orbit_density_renders_are_independent_of_thread_count() {
+        let rng = RngStrategy::Seeded(7);
+
+        let buddhabrot = || {
+            render_buddhabrot(
+                Complex::new(-0.5, 0.0),
+                3.0,
+                [32, 32],
+                2_000,
+                64,
+                &Sampling::MetropolisHastings {
+                    mutation_size: 0.05,
+                    burn_in: 16,
+                },
+                &rng,
+            )
+            .unwrap()
+        };
+        assert_eq!(with_threads(1, buddhabrot), with_threads(4, buddhabrot));
+
+        let attractor = Attractor::Clifford {
+            a: -1.4,
+            b: 1.6,
+            c: 1.0,
+            d: 0.7,
+        };
+        let attractor = || {
+            render_attractor(
+                Complex::new(0.0, 0.0),
+                5.0,
+                [32, 32],
+                Complex::new(0.0, 0.0),
+                1.0,
+                64,
+                1_000,
+                10,
+                &attractor,
+                &rng,
+            )
+            .unwrap()
+        };
+        assert_eq!(with_threads(1, attractor), with_threads(4, attractor));
+
+        let flame = Flame {
+            transforms: [(0.0, 0.0), (0.5, 0.0), (0.0, 0.5)]
+                .into_iter()
+                .enumerate()
+                .map(|(i, (x, y))| FlameTransform {
+                    weight: 1.0,
+                    affine: [0.5, 0.0, x, 0.0, 0.5, y],
+                    variations: vec![(Variation::Linear, 1.0)],
+                    colour: i as f64 / 2.0,
+                })
+                .collect(),
+            gamma: 2.2,
+        };
+        let colour_map = ColourMap::preset("viridis").unwrap();
+        let flame = || {
+            render_flame(
+                &Viewport::new(Complex::new(0.5, 0.5), 1.2, [32, 32]),
+                &flame,
+                64,
+                200,
+                2,
+                &colour_map,
+                &rng,
+            )
+            .unwrap()
+        };
+        assert_eq!(with_threads(1, flame), with_threads(4, flame));
+    }
 }
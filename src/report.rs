@@ -0,0 +1,223 @@
+use core::ops::{Add, Div, Mul, Sub};
+use indicatif::ParallelProgressIterator;
+use ndarray::{s, Array2};
+use num_traits::{Float, NumCast};
+use palette::LinSrgba;
+use rayon::prelude::*;
+use std::time::{Duration, Instant};
+
+use crate::{
+    error::check_count, render::progress_bar, ColourMap, Complex, Fractal, MandybrotError,
+    Periodicity, SampleBuffer, Viewport,
+};
+
+/// Measurements of the render of one tile of an image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileStats {
+    /// Pixel at the top left of the tile.
+    pub origin: [u32; 2],
+    /// Width and height of the tile, smaller than the tile size at the right and bottom edges of the image.
+    pub size: [u32; 2],
+    /// Wall-clock time spent rendering the tile.
+    pub duration: Duration,
+    /// Total number of iterations over every pixel of the tile.
+    pub iterations: u64,
+    /// Number of pixels whose orbits escaped.
+    pub escaped: u32,
+    /// Index of the rayon worker thread which rendered the tile.
+    pub thread: Option<usize>,
+}
+
+impl TileStats {
+    pub fn num_pixels(&self) -> u32 {
+        self.size[0] * self.size[1]
+    }
+
+    /// Fraction of the tile's pixels whose orbits escaped.
+    pub fn escape_ratio(&self) -> f64 {
+        self.escaped as f64 / self.num_pixels() as f64
+    }
+}
+
+/// Timings and iteration totals of a render, tile by tile, for tuning `max_iter` and diagnosing uneven work between threads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderReport {
+    pub resolution: [u32; 2],
+    pub tile_size: [u32; 2],
+    /// Every tile, in row-major order.
+    pub tiles: Vec<TileStats>,
+    /// Wall-clock time of the whole render.
+    pub duration: Duration,
+}
+
+impl RenderReport {
+    pub fn total_iterations(&self) -> u64 {
+        self.tiles.iter().map(|tile| tile.iterations).sum()
+    }
+
+    /// Fraction of all pixels whose orbits escaped.
+    pub fn escape_ratio(&self) -> f64 {
+        let escaped: u64 = self.tiles.iter().map(|tile| tile.escaped as u64).sum();
+        let [x_res, y_res] = self.resolution;
+        escaped as f64 / (x_res as u64 * y_res as u64) as f64
+    }
+
+    /// Time spent rendering tiles, summed over every thread.
+    pub fn compute_time(&self) -> Duration {
+        self.tiles.iter().map(|tile| tile.duration).sum()
+    }
+
+    /// Iterations per second of wall-clock time.
+    pub fn throughput(&self) -> f64 {
+        self.total_iterations() as f64 / self.duration.as_secs_f64()
+    }
+
+    /// Time spent rendering tiles on each worker thread, by thread index.
+    pub fn thread_times(&self) -> Vec<Duration> {
+        let mut times = Vec::new();
+        for tile in &self.tiles {
+            let thread = tile.thread.unwrap_or(0);
+            if times.len() <= thread {
+                times.resize(thread + 1, Duration::ZERO);
+            }
+            times[thread] += tile.duration;
+        }
+        times
+    }
+
+    /// Ratio of the slowest tile's time to the mean tile time, which is large when a few expensive tiles hold up the render.
+    pub fn tile_imbalance(&self) -> f64 {
+        let slowest = self.tiles.iter().map(|tile| tile.duration).max();
+        let mean = self.compute_time().as_secs_f64() / self.tiles.len() as f64;
+        slowest.map_or(1.0, |slowest| slowest.as_secs_f64() / mean)
+    }
+
+    /// Time per pixel of the tile containing each pixel, in seconds, as an image of where the render spent its time.
+    pub fn cost_map(&self) -> Array2<f64> {
+        let [x_res, y_res] = self.resolution;
+        let mut cost = Array2::zeros((y_res as usize, x_res as usize));
+        for tile in &self.tiles {
+            let [x, y] = tile.origin.map(|i| i as usize);
+            let [width, height] = tile.size.map(|i| i as usize);
+            cost.slice_mut(s![y..y + height, x..x + width])
+                .fill(tile.duration.as_secs_f64() / tile.num_pixels() as f64);
+        }
+        cost
+    }
+
+    /// The cost map coloured from the cheapest tile at the start of the colour map to the most expensive at its end.
+    pub fn heat_map(&self, colour_map: &ColourMap) -> Array2<LinSrgba> {
+        let cost = self.cost_map();
+        let (min, max) = cost
+            .iter()
+            .fold((f64::INFINITY, 0.0_f64), |(min, max), &c| {
+                (min.min(c), max.max(c))
+            });
+        let range = (max - min).max(f64::MIN_POSITIVE);
+        cost.mapv(|c| colour_map.sample(((c - min) / range) as f32))
+    }
+}
+
+/// Measurements of a tile, with the count and final `z` of each of its pixels in row-major order.
+type RenderedTile<T> = (TileStats, Vec<(u32, Complex<T>)>);
+
+/// Renders a fractal sampled once at each pixel centre as `render_escape`, a `tile_size` tile at a time, timing each tile.
+pub fn render_escape_report<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+    tile_size: [u32; 2],
+) -> Result<(SampleBuffer<T>, RenderReport), MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + PartialOrd
+        + NumCast
+        + Float
+        + Send
+        + Sync,
+{
+    viewport.validate()?;
+    fractal.validate()?;
+    if let Some(periodicity) = &periodicity {
+        periodicity.validate()?;
+    }
+    check_count("tile_size[0]", tile_size[0])?;
+    check_count("tile_size[1]", tile_size[1])?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let [tile_width, tile_height] = tile_size;
+    let origins: Vec<[u32; 2]> = (0..y_res)
+        .step_by(tile_height as usize)
+        .flat_map(|y| (0..x_res).step_by(tile_width as usize).map(move |x| [x, y]))
+        .collect();
+
+    // Create a progress bar for rendering tiles.
+    let pb = progress_bar(origins.len() as u64)?;
+
+    let start = Instant::now();
+    let tiles: Vec<RenderedTile<T>> = origins
+        .into_par_iter()
+        .progress_with(pb)
+        .map(|origin| {
+            let size = [
+                tile_width.min(x_res - origin[0]),
+                tile_height.min(y_res - origin[1]),
+            ];
+            let tile_start = Instant::now();
+            let mut iterations = 0;
+            let mut escaped = 0;
+            let mut pixels = Vec::with_capacity((size[0] * size[1]) as usize);
+            for y in origin[1]..origin[1] + size[1] {
+                for x in origin[0]..origin[0] + size[0] {
+                    let c = viewport.pixel_centre(x, y);
+                    let (n, z) = fractal
+                        .sample_orbit(c, max_iter, periodicity.as_ref(), |_| iterations += 1);
+                    if n < max_iter {
+                        escaped += 1;
+                    }
+                    pixels.push((n, z));
+                }
+            }
+            let stats = TileStats {
+                origin,
+                size,
+                duration: tile_start.elapsed(),
+                iterations,
+                escaped,
+                thread: rayon::current_thread_index(),
+            };
+            (stats, pixels)
+        })
+        .collect();
+    let duration = start.elapsed();
+
+    let zero = Complex::new(T::zero(), T::zero());
+    let mut samples = Array2::<u32>::zeros((y_res as usize, x_res as usize));
+    let mut final_z = Array2::from_elem((y_res as usize, x_res as usize), zero);
+    for (stats, pixels) in &tiles {
+        let [x0, y0] = stats.origin.map(|i| i as usize);
+        let width = stats.size[0] as usize;
+        for (i, &(n, z)) in pixels.iter().enumerate() {
+            let (x, y) = (x0 + i % width, y0 + i / width);
+            samples[[y, x]] = n;
+            final_z[[y, x]] = z;
+        }
+    }
+
+    let report = RenderReport {
+        resolution: viewport.resolution,
+        tile_size,
+        tiles: tiles.into_iter().map(|(stats, _)| stats).collect(),
+        duration,
+    };
+    let buffer = SampleBuffer {
+        final_z: Some(final_z),
+        ..SampleBuffer::new(*viewport, samples)
+    };
+    Ok((buffer, report))
+}
@@ -0,0 +1,198 @@
+use ndarray::Array2;
+use palette::{LinSrgba, Srgba};
+
+use crate::{error::check_count, MandybrotError};
+
+/// Colour types which can be resampled, by converting to linear light and back.
+/// Averaging gamma-encoded values directly darkens edges, so every filter in this module works in linear light.
+/// Colours are also weighted by their alpha while averaged, so transparent pixels do not tint their neighbours.
+pub trait ColourSpace: Copy {
+    fn into_linear(self) -> LinSrgba;
+    fn from_linear(colour: LinSrgba) -> Self;
+}
+
+impl ColourSpace for LinSrgba {
+    #[inline(always)]
+    fn into_linear(self) -> LinSrgba {
+        self
+    }
+
+    #[inline(always)]
+    fn from_linear(colour: LinSrgba) -> Self {
+        colour
+    }
+}
+
+impl ColourSpace for Srgba {
+    #[inline(always)]
+    fn into_linear(self) -> LinSrgba {
+        Srgba::into_linear(self)
+    }
+
+    #[inline(always)]
+    fn from_linear(colour: LinSrgba) -> Self {
+        Srgba::from_linear(colour)
+    }
+}
+
+/// Averages each `factor` × `factor` block of super samples into a single pixel.
+/// The factor must be at least one and divide both image dimensions.
+pub fn downsample<C: ColourSpace>(
+    input: &Array2<C>,
+    factor: usize,
+) -> Result<Array2<C>, MandybrotError> {
+    let (height, width) = input.dim();
+
+    check_count("factor", u32::try_from(factor).unwrap_or(u32::MAX))?;
+    if height % factor != 0 || width % factor != 0 {
+        return Err(MandybrotError::InvalidParameter {
+            name: "factor",
+            value: factor as f64,
+        });
+    }
+
+    let averages: Vec<C> = input
+        .exact_chunks((factor, factor))
+        .into_iter()
+        .map(|chunk| {
+            let sum = chunk
+                .iter()
+                .fold(LinSrgba::new(0.0, 0.0, 0.0, 0.0), |acc, &v| {
+                    acc + premultiply(v.into_linear())
+                });
+            C::from_linear(unpremultiply(sum / (factor * factor) as f32))
+        })
+        .collect();
+
+    Ok(
+        Array2::from_shape_vec((height / factor, width / factor), averages)
+            .expect("one average per block"),
+    )
+}
+
+/// Blurs an image by averaging each pixel with its neighbours within `radius` pixels, clamped at the image edges.
+pub fn box_blur<C: ColourSpace>(input: &Array2<C>, radius: usize) -> Array2<C> {
+    let (height, width) = input.dim();
+    let linear = input.mapv(|v| premultiply(v.into_linear()));
+
+    Array2::from_shape_fn((height, width), |(y, x)| {
+        let (y0, y1) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        let (x0, x1) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+        let mut sum = LinSrgba::new(0.0, 0.0, 0.0, 0.0);
+        for yy in y0..y1 {
+            for xx in x0..x1 {
+                sum += linear[(yy, xx)];
+            }
+        }
+        C::from_linear(unpremultiply(sum / ((y1 - y0) * (x1 - x0)) as f32))
+    })
+}
+
+/// Scales the colour channels by the alpha, so colours can be averaged by their coverage.
+pub(crate) fn premultiply(colour: LinSrgba) -> LinSrgba {
+    LinSrgba::new(
+        colour.red * colour.alpha,
+        colour.green * colour.alpha,
+        colour.blue * colour.alpha,
+        colour.alpha,
+    )
+}
+
+/// Undoes `premultiply`, leaving fully transparent colours black.
+pub(crate) fn unpremultiply(colour: LinSrgba) -> LinSrgba {
+    if colour.alpha <= 0.0 {
+        return LinSrgba::new(0.0, 0.0, 0.0, 0.0);
+    }
+    LinSrgba::new(
+        colour.red / colour.alpha,
+        colour.green / colour.alpha,
+        colour.blue / colour.alpha,
+        colour.alpha,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn downsampling_srgb_averages_in_linear_light() {
+        let black = Srgba::new(0.0, 0.0, 0.0, 1.0);
+        let white = Srgba::new(1.0, 1.0, 1.0, 1.0);
+        let checker = array![[black, white], [white, black]];
+
+        let grey = downsample(&checker, 2).unwrap()[(0, 0)];
+        let expected = Srgba::from_linear(LinSrgba::new(0.5, 0.5, 0.5, 1.0));
+        assert!(close(grey.red, expected.red));
+        // Averaging the encoded values would give a darker 0.5
+        assert!(grey.red > 0.7);
+    }
+
+    #[test]
+    fn srgb_and_linear_images_resample_alike() {
+        let linear = Array2::from_shape_fn((4, 4), |(y, x)| {
+            LinSrgba::new(x as f32 / 3.0, y as f32 / 3.0, 0.25, 1.0)
+        });
+        let encoded = linear.mapv(Srgba::from_linear);
+
+        for (a, b) in downsample(&linear, 2)
+            .unwrap()
+            .iter()
+            .zip(downsample(&encoded, 2).unwrap().iter())
+            .chain(
+                box_blur(&linear, 1)
+                    .iter()
+                    .zip(box_blur(&encoded, 1).iter()),
+            )
+        {
+            let b = ColourSpace::into_linear(*b);
+            assert!(close(a.red, b.red) && close(a.green, b.green) && close(a.blue, b.blue));
+        }
+    }
+
+    #[test]
+    fn transparent_pixels_do_not_tint_their_neighbours() {
+        let clear_red = LinSrgba::new(1.0, 0.0, 0.0, 0.0);
+        let blue = LinSrgba::new(0.0, 0.0, 1.0, 1.0);
+        let image = array![[clear_red, blue], [blue, clear_red]];
+
+        for colour in [
+            downsample(&image, 2).unwrap()[(0, 0)],
+            box_blur(&image, 1)[(0, 0)],
+        ] {
+            assert!(close(colour.red, 0.0));
+            assert!(close(colour.blue, 1.0));
+            assert!(close(colour.alpha, 0.5));
+        }
+    }
+
+    #[test]
+    fn downsampling_factors_must_divide_the_image() {
+        let image = Array2::from_elem((4, 6), Srgba::new(0.2, 0.4, 0.6, 1.0));
+        assert!(matches!(
+            downsample(&image, 0),
+            Err(MandybrotError::ZeroCount("factor"))
+        ));
+        assert!(matches!(
+            downsample(&image, 4),
+            Err(MandybrotError::InvalidParameter { name: "factor", .. })
+        ));
+        assert_eq!(downsample(&image, 2).unwrap().dim(), (2, 3));
+    }
+
+    #[test]
+    fn blurring_leaves_flat_images_unchanged() {
+        let colour = Srgba::new(0.2, 0.4, 0.6, 1.0);
+        let image = Array2::from_elem((3, 5), colour);
+        for radius in [0, 2] {
+            for blurred in box_blur(&image, radius).iter() {
+                assert!(close(blurred.red, colour.red) && close(blurred.blue, colour.blue));
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// Source of randomness shared by every stochastic feature of the crate.
+/// Work is split into independent streams (per chunk, pixel or orbit), so seeded renders are reproducible however the work is scheduled across threads.
+/// Streams are currently ChaCha8 generators, which give the same numbers on every platform and, unlike `StdRng`, are not replaced between versions of `rand`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RngStrategy {
+    /// Seed every stream from system entropy, so each render differs.
+    #[default]
+    Entropy,
+    /// Derive every stream from a fixed seed, so renders are reproducible.
+    Seeded(u64),
+}
+
+impl RngStrategy {
+    /// Random number generator for the given stream of a named domain of work.
+    /// Distinct domains give independent streams even when their indices coincide.
+    pub fn stream(&self, domain: u64, index: u64) -> RngStream {
+        RngStream(match self {
+            RngStrategy::Entropy => ChaCha8Rng::from_rng(&mut rand::rng()),
+            RngStrategy::Seeded(seed) => {
+                ChaCha8Rng::seed_from_u64(splitmix64(seed ^ splitmix64(domain ^ splitmix64(index))))
+            }
+        })
+    }
+
+    /// Random number generator for a single pixel of a `width` pixel wide image.
+    pub fn pixel_stream(&self, x: u32, y: u32, width: u32) -> RngStream {
+        self.stream(PIXEL_DOMAIN, y as u64 * width as u64 + x as u64)
+    }
+
+    /// Random number generator for a single orbit, or chunk of orbits.
+    pub fn orbit_stream(&self, index: u64) -> RngStream {
+        self.stream(ORBIT_DOMAIN, index)
+    }
+}
+
+/// A stream of random numbers drawn from an `RngStrategy`, used through the `rand` traits.
+/// The generator behind it is an implementation detail, which may change without changing the API.
+#[derive(Debug, Clone)]
+pub struct RngStream(ChaCha8Rng);
+
+impl RngCore for RngStream {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+}
+
+// Domains of every kind of random stream in the crate.
+// Each must differ from every other, or two kinds of work given the same seed would draw the same numbers.
+
+/// Per-pixel streams.
+pub(crate) const PIXEL_DOMAIN: u64 = 0x9e37_79b9_7f4a_7c15;
+/// Per-orbit streams.
+pub(crate) const ORBIT_DOMAIN: u64 = 0xbf58_476d_1ce4_e5b9;
+/// Buddhabrot starting points drawn uniformly.
+pub(crate) const UNIFORM_DOMAIN: u64 = 1;
+/// Buddhabrot starting points of Metropolis-Hastings chains.
+pub(crate) const SEED_DOMAIN: u64 = 2;
+/// Mutations of Buddhabrot Metropolis-Hastings chains.
+pub(crate) const CHAIN_DOMAIN: u64 = 3;
+/// Fractal flame sampling.
+pub(crate) const FLAME_DOMAIN: u64 = 4;
+/// Iterated function system sampling.
+pub(crate) const IFS_DOMAIN: u64 = 5;
+/// Framing of each iterated function system in the gallery.
+pub(crate) const GALLERY_DOMAIN: u64 = 6;
+/// Placement of the points of the blue noise dither mask.
+pub(crate) const BLUE_NOISE_DOMAIN: u64 = 0;
+/// Parameters of each attractor search candidate.
+pub(crate) const SEARCH_DOMAIN: u64 = 0x94d0_49bb_1331_11eb;
+/// Random palettes.
+pub(crate) const PALETTE_DOMAIN: u64 = 7;
+
+/// Every stream domain, which must be distinct.
+#[cfg(test)]
+const DOMAINS: [u64; 11] = [
+    PIXEL_DOMAIN,
+    ORBIT_DOMAIN,
+    UNIFORM_DOMAIN,
+    SEED_DOMAIN,
+    CHAIN_DOMAIN,
+    FLAME_DOMAIN,
+    IFS_DOMAIN,
+    GALLERY_DOMAIN,
+    BLUE_NOISE_DOMAIN,
+    SEARCH_DOMAIN,
+    PALETTE_DOMAIN,
+];
+
+/// SplitMix64 finaliser, used to decorrelate neighbouring seeds and stream indices.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_domains_are_distinct() {
+        for (i, a) in DOMAINS.iter().enumerate() {
+            for b in &DOMAINS[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_streams_differ_between_domains() {
+        use rand::RngCore;
+
+        let rng = RngStrategy::Seeded(42);
+        let first: Vec<u64> = DOMAINS
+            .iter()
+            .map(|&domain| rng.stream(domain, 0).next_u64())
+            .collect();
+        for (i, a) in first.iter().enumerate() {
+            assert!(!first[i + 1..].contains(a));
+        }
+        assert_eq!(
+            rng.stream(PALETTE_DOMAIN, 3).next_u64(),
+            rng.stream(PALETTE_DOMAIN, 3).next_u64()
+        );
+    }
+}
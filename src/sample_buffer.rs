@@ -0,0 +1,104 @@
+use ndarray::{Array2, Zip};
+use num_traits::Float;
+
+use crate::{
+    error::{check_light, check_positive},
+    shader::smooth_iteration,
+    Complex, MandybrotError, Viewport,
+};
+
+/// Per-pixel samples of a render, together with the viewport they were taken over.
+/// Optional channels hold extra per-pixel data for renders which request it.
+#[derive(Debug, Clone)]
+pub struct SampleBuffer<T> {
+    pub viewport: Viewport<T>,
+    pub samples: Array2<u32>,
+    /// Final value of `z` at each pixel centre.
+    pub final_z: Option<Array2<Complex<T>>>,
+    /// Iteration at which `|z|` was smallest, identifying the atom domain of each pixel centre.
+    pub atom_domain: Option<Array2<u32>>,
+    /// Period of the hyperbolic component containing each pixel centre, or zero where none was found.
+    pub period: Option<Array2<u32>>,
+    /// Smallest `|z|` over the orbit of each pixel centre.
+    pub min_modulus: Option<Array2<T>>,
+    /// Largest `|z|` over the orbit of each pixel centre.
+    pub max_modulus: Option<Array2<T>>,
+    /// Mean `|z|` over the orbit of each pixel centre.
+    pub mean_modulus: Option<Array2<T>>,
+    /// Unit direction in which the escape potential grows fastest at each pixel centre, from the derivative of its orbit, or `NaN` where the orbit did not escape.
+    pub normal: Option<Array2<Complex<T>>>,
+}
+
+impl<T> SampleBuffer<T> {
+    pub const fn new(viewport: Viewport<T>, samples: Array2<u32>) -> Self {
+        Self {
+            viewport,
+            samples,
+            final_z: None,
+            atom_domain: None,
+            period: None,
+            min_modulus: None,
+            max_modulus: None,
+            mean_modulus: None,
+            normal: None,
+        }
+    }
+}
+
+impl<T: Float> SampleBuffer<T> {
+    /// Argument of `z` at escape for each pixel, or `NaN` where the orbit did not escape.
+    pub fn escape_angle(&self, max_iter: u32) -> Option<Array2<T>> {
+        let final_z = self.final_z.as_ref()?;
+        Some(Zip::from(&self.samples).and(final_z).map_collect(|&n, z| {
+            if n < max_iter {
+                z.imag.atan2(z.real)
+            } else {
+                T::nan()
+            }
+        }))
+    }
+
+    /// Shading of each pixel from its normal, as a surface rising away from the set lit from the `light` direction given as `[real, imag, up]`.
+    /// Lower `height`s tilt the surface further, exaggerating the relief, and pixels whose orbits did not escape are given a shading of zero.
+    /// Returns `None` if the buffer has no normals.
+    pub fn normal_shading(
+        &self,
+        light: [T; 3],
+        height: T,
+    ) -> Result<Option<Array2<T>>, MandybrotError> {
+        let light = check_light(light)?;
+        check_positive("height", height)?;
+        let Some(normal) = self.normal.as_ref() else {
+            return Ok(None);
+        };
+        let slant = (T::one() + height * height).sqrt();
+        Ok(Some(normal.mapv(|u| {
+            let shade = (u.real * light[0] + u.imag * light[1] + height * light[2]) / slant;
+            if shade.is_nan() {
+                T::zero()
+            } else {
+                shade.max(T::zero())
+            }
+        })))
+    }
+
+    /// Continuous escape iteration for each pixel, removing the banding of integer counts, or `NaN` where the orbit did not escape.
+    /// Assumes a quadratic map with an escape radius of two, as for the Mandelbrot and Julia sets.
+    pub fn smooth_iterations(&self, max_iter: u32) -> Option<Array2<T>> {
+        self.smooth_iterations_of_degree(max_iter, T::one() + T::one())
+    }
+
+    /// Continuous escape iteration for each pixel as `smooth_iterations`, for a map which raises `|z|` to the power `degree`, such as a Multibrot set.
+    /// `Fractal::escape_degree` gives the degree of each fractal.
+    pub fn smooth_iterations_of_degree(&self, max_iter: u32, degree: T) -> Option<Array2<T>> {
+        let final_z = self.final_z.as_ref()?;
+        let log_degree = degree.ln();
+        Some(Zip::from(&self.samples).and(final_z).map_collect(|&n, z| {
+            if n < max_iter {
+                smooth_iteration(n, *z, log_degree)
+            } else {
+                T::nan()
+            }
+        }))
+    }
+}
@@ -0,0 +1,136 @@
+use num_traits::Float;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::RngStrategy;
+
+/// Seed of the per-pixel streams used by random patterns, fixed so that anti-aliased renders are reproducible.
+const PATTERN_SEED: u64 = 0x5851_f42d_4c95_7f2d;
+
+/// Number of random candidates considered for each blue-noise sample.
+const BLUE_NOISE_CANDIDATES: u32 = 8;
+
+/// Arrangement of the anti-aliasing samples within each pixel.
+/// Every pattern takes the square of `samples_per_pixel` samples, and every random choice is drawn from a stream fixed for each pixel, so renders are reproducible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SamplePattern {
+    /// Centres of a regular sub-pixel grid, which can alias structured detail.
+    #[default]
+    Grid,
+    /// One uniformly random point within each cell of the sub-pixel grid.
+    Jittered,
+    /// The Halton sequence in bases two and three, randomly shifted for each pixel.
+    Halton,
+    /// The two-dimensional Sobol sequence, randomly shifted for each pixel.
+    Sobol,
+    /// Points spread evenly without any regular structure, placed by Mitchell's best-candidate algorithm.
+    BlueNoise,
+}
+
+impl SamplePattern {
+    /// Offsets of the samples of pixel `(x, y)` of a `width` pixel wide image, within the unit square of the pixel.
+    pub fn offsets<T: Float>(
+        &self,
+        [x, y]: [u32; 2],
+        width: u32,
+        samples_per_pixel: u32,
+    ) -> Vec<[T; 2]> {
+        let n = samples_per_pixel as usize;
+        let count = n * n;
+        let mut rng = RngStrategy::Seeded(PATTERN_SEED).pixel_stream(x, y, width);
+
+        let offsets: Vec<[f64; 2]> = match self {
+            SamplePattern::Grid | SamplePattern::Jittered => {
+                let jitter = *self == SamplePattern::Jittered;
+                (0..count)
+                    .map(|k| {
+                        let (i, j) = (k / n, k % n);
+                        let [dx, dy] = if jitter {
+                            [rng.random::<f64>(), rng.random::<f64>()]
+                        } else {
+                            [0.5, 0.5]
+                        };
+                        [(i as f64 + dx) / n as f64, (j as f64 + dy) / n as f64]
+                    })
+                    .collect()
+            }
+            SamplePattern::Halton | SamplePattern::Sobol => {
+                let shift = [rng.random::<f64>(), rng.random::<f64>()];
+                (0..count as u32)
+                    .map(|k| {
+                        let [u, v] = match self {
+                            SamplePattern::Halton => [radical_inverse(k, 2), radical_inverse(k, 3)],
+                            _ => sobol(k),
+                        };
+                        [(u + shift[0]).fract(), (v + shift[1]).fract()]
+                    })
+                    .collect()
+            }
+            SamplePattern::BlueNoise => {
+                let mut points: Vec<[f64; 2]> = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let best = (0..BLUE_NOISE_CANDIDATES)
+                        .map(|_| [rng.random::<f64>(), rng.random::<f64>()])
+                        .map(|candidate| {
+                            let distance = points
+                                .iter()
+                                .map(|&point| toroidal_distance_sqr(candidate, point))
+                                .fold(f64::INFINITY, f64::min);
+                            (candidate, distance)
+                        })
+                        .fold(([0.5, 0.5], -1.0), |best, candidate| {
+                            if candidate.1 > best.1 {
+                                candidate
+                            } else {
+                                best
+                            }
+                        });
+                    points.push(best.0);
+                }
+                points
+            }
+        };
+
+        offsets
+            .into_iter()
+            .map(|[u, v]| [T::from(u).unwrap(), T::from(v).unwrap()])
+            .collect()
+    }
+}
+
+/// The `index`th element of the van der Corput sequence in the given base.
+fn radical_inverse(mut index: u32, base: u32) -> f64 {
+    let mut result = 0.0;
+    let mut scale = 1.0 / base as f64;
+    while index > 0 {
+        result += (index % base) as f64 * scale;
+        index /= base;
+        scale /= base as f64;
+    }
+    result
+}
+
+/// The `index`th point of the first two dimensions of the Sobol sequence.
+fn sobol(mut index: u32) -> [f64; 2] {
+    let (mut x, mut y) = (0u32, 0u32);
+    // The first dimension's direction numbers are single bits, and the second's follow the primitive polynomial `x + 1`.
+    let (mut vx, mut vy) = (1u32 << 31, 1u32 << 31);
+    while index > 0 {
+        if index & 1 == 1 {
+            x ^= vx;
+            y ^= vy;
+        }
+        index >>= 1;
+        vx >>= 1;
+        vy ^= vy >> 1;
+    }
+    let scale = 1.0 / (1u64 << 32) as f64;
+    [x as f64 * scale, y as f64 * scale]
+}
+
+/// Squared distance between two points of the unit square, wrapping around its edges so that neighbouring pixels' samples also stay apart.
+fn toroidal_distance_sqr(a: [f64; 2], b: [f64; 2]) -> f64 {
+    let wrap = |d: f64| d.abs().min(1.0 - d.abs());
+    let [dx, dy] = [wrap(a[0] - b[0]), wrap(a[1] - b[1])];
+    dx * dx + dy * dy
+}
@@ -0,0 +1,26 @@
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::check_positive, MandybrotError};
+
+/// Strategy used to choose the starting points of orbits in orbit-density renders.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Sampling<T> {
+    /// Draw every starting point uniformly from the sampling region.
+    Uniform,
+    /// Random-walk between starting points whose orbits pass through the viewport.
+    /// The `mutation_size` is a fraction of the viewport scale, and the first `burn_in` steps of each chain are discarded.
+    MetropolisHastings { mutation_size: T, burn_in: u32 },
+}
+
+impl<T: Float> Sampling<T> {
+    /// Checks that the mutation size is finite and greater than zero.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            Sampling::Uniform => Ok(()),
+            Sampling::MetropolisHastings { mutation_size, .. } => {
+                check_positive("mutation_size", mutation_size)
+            }
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use core::ops::{Add, Mul, Sub};
+use ndarray::{s, Array2, ArrayViewMut2};
+use num_traits::{Float, NumCast};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{error::check_count, Fractal, MandybrotError, Viewport};
+
+/// Device which renders tiles of an escape-time fractal for `render_scheduled`, such as a pool of CPU cores or a GPU.
+/// Implement it for a GPU backend to render on the GPU and spare CPU cores at once.
+pub trait TileDevice<T>: Sync {
+    /// Name of the device, as shown in a `ScheduleReport`.
+    fn name(&self) -> String;
+
+    /// Renders the pixels `[x, x + width) × [y, y + height)` of a viewport into a row-major buffer holding one value per pixel,
+    /// sampled once at each pixel centre as by `render_tile`.
+    fn render_tile(
+        &self,
+        viewport: &Viewport<T>,
+        fractal: &Fractal<T>,
+        max_iter: u32,
+        origin: [u32; 2],
+        resolution: [u32; 2],
+        buffer: &mut [u32],
+    ) -> Result<(), MandybrotError>;
+}
+
+/// Pool of CPU threads which renders the rows of each tile in parallel.
+#[derive(Debug)]
+pub struct CpuDevice {
+    pool: ThreadPool,
+}
+
+impl CpuDevice {
+    /// Device of its own pool of the given number of threads, so that it can run beside other devices without sharing their cores.
+    pub fn new(num_threads: u32) -> Result<Self, MandybrotError> {
+        check_count("num_threads", num_threads)?;
+        Ok(Self {
+            pool: ThreadPoolBuilder::new()
+                .num_threads(num_threads as usize)
+                .build()?,
+        })
+    }
+}
+
+impl<T> TileDevice<T> for CpuDevice
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + PartialOrd
+        + Float
+        + NumCast
+        + Send
+        + Sync,
+{
+    fn name(&self) -> String {
+        format!("cpu ({} threads)", self.pool.current_num_threads())
+    }
+
+    fn render_tile(
+        &self,
+        viewport: &Viewport<T>,
+        fractal: &Fractal<T>,
+        max_iter: u32,
+        [x0, y0]: [u32; 2],
+        [width, height]: [u32; 2],
+        buffer: &mut [u32],
+    ) -> Result<(), MandybrotError> {
+        let expected = width as usize * height as usize;
+        if buffer.len() != expected {
+            return Err(MandybrotError::BufferSize {
+                expected,
+                found: buffer.len(),
+            });
+        }
+        if width == 0 {
+            return Ok(());
+        }
+        self.pool.install(|| {
+            buffer
+                .par_chunks_mut(width as usize)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    for (x, pixel) in row.iter_mut().enumerate() {
+                        let c = viewport.pixel_centre(x0 + x as u32, y0 + y as u32);
+                        *pixel = fractal.sample(c, max_iter, None);
+                    }
+                })
+        });
+        Ok(())
+    }
+}
+
+/// Work done by one device during `render_scheduled`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceStats {
+    pub name: String,
+    pub num_tiles: u32,
+    pub num_pixels: u64,
+    /// Time the device spent rendering.
+    pub busy: Duration,
+}
+
+impl DeviceStats {
+    /// Pixels rendered per second of busy time.
+    pub fn throughput(&self) -> f64 {
+        self.num_pixels as f64 / self.busy.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Share of the work each device took in `render_scheduled`, in the order the devices were given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleReport {
+    pub devices: Vec<DeviceStats>,
+    /// Wall-clock time of the whole render.
+    pub duration: Duration,
+}
+
+/// Tiles not yet handed out, and the throughput measured on each device so far.
+struct Queue {
+    next: usize,
+    stats: Vec<DeviceStats>,
+}
+
+impl Queue {
+    /// Number of tiles to give a device next.
+    /// Each device starts with a single tile to measure its throughput, then takes half of its share of the remaining tiles by throughput,
+    /// so fast devices are kept busy with large batches while the last tiles are spread finely enough for every device to finish together.
+    fn batch_size(&self, device: usize, num_tiles: usize) -> usize {
+        let remaining = num_tiles - self.next;
+        let stats = &self.stats[device];
+        if remaining == 0 || stats.num_tiles == 0 {
+            return remaining.min(1);
+        }
+        let measured = self.stats.iter().filter(|stats| stats.num_tiles > 0);
+        let total: f64 = measured.map(DeviceStats::throughput).sum();
+        // Devices still measuring their first tile count as average ones.
+        let unmeasured = self
+            .stats
+            .iter()
+            .filter(|stats| stats.num_tiles == 0)
+            .count();
+        let average = total / (self.stats.len() - unmeasured) as f64;
+        let share = stats.throughput() / (total + average * unmeasured as f64);
+        ((remaining as f64 * share / 2.0).ceil() as usize).clamp(1, remaining)
+    }
+}
+
+/// Renders a fractal a tile at a time across several devices at once, such as GPUs and spare CPU cores, returning the counts and how the work was shared.
+/// Devices take tiles from a shared queue in batches sized by their measured throughput, so faster devices render more of the image and all finish at about the same time.
+/// Each pixel is sampled once at its centre, so the image is the same whichever device renders each tile.
+pub fn render_scheduled<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    tile_size: [u32; 2],
+    devices: &[&dyn TileDevice<T>],
+) -> Result<(Array2<u32>, ScheduleReport), MandybrotError>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + PartialOrd
+        + Float
+        + NumCast
+        + Send
+        + Sync,
+{
+    viewport.validate()?;
+    fractal.validate()?;
+    check_count("tile_size[0]", tile_size[0])?;
+    check_count("tile_size[1]", tile_size[1])?;
+    check_count("devices", devices.len() as u32)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let tiles: Vec<[u32; 4]> = (0..y_res)
+        .step_by(tile_size[1] as usize)
+        .flat_map(|y| {
+            (0..x_res).step_by(tile_size[0] as usize).map(move |x| {
+                [
+                    x,
+                    y,
+                    tile_size[0].min(x_res - x),
+                    tile_size[1].min(y_res - y),
+                ]
+            })
+        })
+        .collect();
+
+    let start = Instant::now();
+    let queue = Mutex::new(Queue {
+        next: 0,
+        stats: devices
+            .iter()
+            .map(|device| DeviceStats {
+                name: device.name(),
+                num_tiles: 0,
+                num_pixels: 0,
+                busy: Duration::ZERO,
+            })
+            .collect(),
+    });
+    let image = Mutex::new(Array2::zeros((y_res as usize, x_res as usize)));
+    let failed = AtomicBool::new(false);
+
+    let results: Vec<Result<(), MandybrotError>> = thread::scope(|scope| {
+        let workers: Vec<_> = devices
+            .iter()
+            .enumerate()
+            .map(|(index, device)| {
+                let (queue, image, failed, tiles) = (&queue, &image, &failed, &tiles);
+                scope.spawn(move || -> Result<(), MandybrotError> {
+                    let mut buffer = Vec::new();
+                    loop {
+                        let batch = {
+                            let mut queue = lock(queue);
+                            let size = queue.batch_size(index, tiles.len());
+                            let batch = queue.next..queue.next + size;
+                            queue.next = batch.end;
+                            batch
+                        };
+                        if batch.is_empty() || failed.load(Ordering::Relaxed) {
+                            return Ok(());
+                        }
+
+                        for &[x, y, width, height] in &tiles[batch] {
+                            let tile_start = Instant::now();
+                            buffer.clear();
+                            buffer.resize(width as usize * height as usize, 0);
+                            if let Err(error) = device.render_tile(
+                                viewport,
+                                fractal,
+                                max_iter,
+                                [x, y],
+                                [width, height],
+                                &mut buffer,
+                            ) {
+                                failed.store(true, Ordering::Relaxed);
+                                return Err(error);
+                            }
+                            let busy = tile_start.elapsed();
+
+                            let tile = ArrayViewMut2::from_shape(
+                                (height as usize, width as usize),
+                                &mut buffer,
+                            )
+                            .expect("buffer holds one value per pixel");
+                            lock(image)
+                                .slice_mut(s![
+                                    y as usize..(y + height) as usize,
+                                    x as usize..(x + width) as usize
+                                ])
+                                .assign(&tile);
+
+                            let mut queue = lock(queue);
+                            let stats = &mut queue.stats[index];
+                            stats.num_tiles += 1;
+                            stats.num_pixels += width as u64 * height as u64;
+                            stats.busy += busy;
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("tile device panicked"))
+            .collect()
+    });
+    results.into_iter().collect::<Result<(), _>>()?;
+
+    let report = ScheduleReport {
+        devices: queue
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stats,
+        duration: start.elapsed(),
+    };
+    let image = image.into_inner().unwrap_or_else(PoisonError::into_inner);
+    Ok((image, report))
+}
+
+/// Locks shared state, recovering it if a device panicked while holding it, as every update leaves it consistent.
+fn lock<S>(mutex: &Mutex<S>) -> MutexGuard<'_, S> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render_tile, Complex};
+
+    /// Device which renders correctly but slowly, standing in for a slower card.
+    struct SlowDevice(CpuDevice);
+
+    impl TileDevice<f64> for SlowDevice {
+        fn name(&self) -> String {
+            "slow".into()
+        }
+
+        fn render_tile(
+            &self,
+            viewport: &Viewport<f64>,
+            fractal: &Fractal<f64>,
+            max_iter: u32,
+            origin: [u32; 2],
+            resolution: [u32; 2],
+            buffer: &mut [u32],
+        ) -> Result<(), MandybrotError> {
+            thread::sleep(Duration::from_millis(20));
+            self.0
+                .render_tile(viewport, fractal, max_iter, origin, resolution, buffer)
+        }
+    }
+
+    struct FailingDevice;
+
+    impl TileDevice<f64> for FailingDevice {
+        fn name(&self) -> String {
+            "failing".into()
+        }
+
+        fn render_tile(
+            &self,
+            _viewport: &Viewport<f64>,
+            _fractal: &Fractal<f64>,
+            _max_iter: u32,
+            _origin: [u32; 2],
+            _resolution: [u32; 2],
+            _buffer: &mut [u32],
+        ) -> Result<(), MandybrotError> {
+            Err(MandybrotError::ZeroCount("device"))
+        }
+    }
+
+    fn viewport() -> Viewport<f64> {
+        Viewport::new(Complex::new(-0.75, 0.0), 2.5, [67, 41])
+    }
+
+    #[test]
+    fn scheduled_renders_match_a_single_tile_render() {
+        let fractal = Fractal::Mandelbrot;
+        let mut expected = Array2::zeros((41, 67));
+        render_tile(
+            &viewport(),
+            &fractal,
+            128,
+            None,
+            expected.as_slice_mut().unwrap(),
+        )
+        .unwrap();
+
+        let (fast, slow) = (
+            CpuDevice::new(2).unwrap(),
+            SlowDevice(CpuDevice::new(1).unwrap()),
+        );
+        let (image, report) =
+            render_scheduled(&viewport(), &fractal, 128, [8, 8], &[&fast, &slow]).unwrap();
+        assert_eq!(image, expected);
+
+        let [fast, slow] = [&report.devices[0], &report.devices[1]];
+        assert_eq!(fast.num_tiles + slow.num_tiles, 9 * 6);
+        assert_eq!(fast.num_pixels + slow.num_pixels, 67 * 41);
+        assert!(fast.num_tiles > slow.num_tiles, "{:?}", report);
+    }
+
+    #[test]
+    fn invalid_schedules_are_errors() {
+        let fractal = Fractal::Mandelbrot;
+        let cpu = CpuDevice::new(1).unwrap();
+        assert!(matches!(
+            render_scheduled(&viewport(), &fractal, 16, [8, 8], &[]),
+            Err(MandybrotError::ZeroCount("devices"))
+        ));
+        assert!(render_scheduled(&viewport(), &fractal, 16, [0, 8], &[&cpu]).is_err());
+        assert!(CpuDevice::new(0).is_err());
+        assert!(matches!(
+            render_scheduled(&viewport(), &fractal, 16, [8, 8], &[&cpu, &FailingDevice]),
+            Err(MandybrotError::ZeroCount("device"))
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+use ndarray::Array2;
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    periodicity::CycleDetector, render::par_map_pixels, Complex, Fractal, MandybrotError,
+    OrbitState, Periodicity, SampleBuffer, Viewport,
+};
+
+/// Which part of each orbit a seeded render takes from its array of seeds, see `render_seeded`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeedTarget {
+    /// Each orbit starts from its seed instead of the fractal's usual starting point, keeping the constant of the map.
+    /// For the Mandelbrot family the constant is the pixel, so the image is the set perturbed by the seeds.
+    InitialZ,
+    /// Each orbit keeps the fractal's usual starting point, and the map's additive constant is its seed.
+    /// For Julia sets the starting point is the pixel, so every pixel is drawn from the Julia set of a different constant.
+    Constant,
+}
+
+/// Renders a fractal with one part of the orbit of every pixel taken from a seed, such as a point derived from the colour of an input image, warping the image through the fractal's map.
+/// `seeds` holds one value per pixel, indexed `[y, x]` as the render is, and replaces the starting `z` or the additive constant of each orbit depending on `target`.
+/// Orbits are stopped once `|z|` passes two, and the result records the iteration count and the final `z` of each pixel.
+/// Only fractals which start from `Fractal::initial_state` and escape as a power of `|z|` can be seeded.
+pub fn render_seeded<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    seeds: &Array2<Complex<T>>,
+    target: SeedTarget,
+) -> Result<SampleBuffer<T>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    viewport.validate()?;
+    fractal.validate()?;
+    check_seedable(fractal)?;
+    let [x_res, y_res] = viewport.resolution;
+    let expected = x_res as usize * y_res as usize;
+    if seeds.dim() != (y_res as usize, x_res as usize) {
+        return Err(MandybrotError::BufferSize {
+            expected,
+            found: seeds.len(),
+        });
+    }
+
+    let pixels = par_map_pixels(viewport.resolution, false, |[x, y]| {
+        let seed = seeds[(y as usize, x as usize)];
+        let mut state = fractal
+            .initial_state(viewport.pixel_centre(x, y))
+            .expect("checked that the fractal has an initial state");
+        match target {
+            SeedTarget::InitialZ => state.z = seed,
+            SeedTarget::Constant => state.c = seed,
+        }
+        sample_seeded(fractal, state, max_iter, None)
+    })?;
+
+    Ok(SampleBuffer {
+        final_z: Some(pixels.mapv(|(_, z)| z)),
+        ..SampleBuffer::new(*viewport, pixels.mapv(|(n, _)| n))
+    })
+}
+
+/// Checks that a fractal's orbits can be started from a seeded state, as `render_seeded` requires.
+pub(crate) fn check_seedable<T: Float>(fractal: &Fractal<T>) -> Result<(), MandybrotError> {
+    let zero = Complex::new(T::zero(), T::zero());
+    if fractal.escape_degree().is_none() || fractal.initial_state(zero).is_none() {
+        return Err(MandybrotError::NotSeedable);
+    }
+    Ok(())
+}
+
+/// Iterates an orbit from a seeded state until `|z|` passes two, returning the iteration count and the final `z`.
+/// If `periodicity` is given, orbits which fall into a cycle are reported as never escaping, as by `Fractal::sample`.
+pub(crate) fn sample_seeded<T: Float>(
+    fractal: &Fractal<T>,
+    mut state: OrbitState<T>,
+    max_iter: u32,
+    periodicity: Option<&Periodicity<T>>,
+) -> (u32, Complex<T>) {
+    let four = T::from(4.0).unwrap();
+    let mut detector = periodicity.map(|periodicity| CycleDetector::new(periodicity, state.z));
+    while state.norm_sqr() < four && state.iteration < max_iter {
+        fractal.step(&mut state);
+        if let Some(detector) = detector.as_mut() {
+            if detector.is_periodic(state.z) {
+                return (max_iter, state.z);
+            }
+        }
+    }
+    (state.iteration, state.z)
+}
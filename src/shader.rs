@@ -0,0 +1,67 @@
+use num_traits::Float;
+use palette::LinSrgba;
+
+use crate::{Complex, DualComplex};
+
+/// Everything found while sampling one point of an escape-time render, for a `PixelShader` to colour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelSample<T> {
+    /// Pixel the sample lies in.
+    pub pixel: [u32; 2],
+    /// Point of the complex plane sampled.
+    pub point: Complex<T>,
+    /// Iteration at which the orbit escaped, or `max_iter` if it did not.
+    pub iterations: u32,
+    /// Iteration limit of the render.
+    pub max_iter: u32,
+    /// Continuous escape iteration, as `SampleBuffer::smooth_iterations_of_degree`, or `NaN` where the orbit did not escape or the fractal has no `escape_degree`.
+    pub smooth: T,
+    /// Last value of `z`: the first point outside the escape radius for orbits which escaped.
+    pub final_z: Complex<T>,
+    /// Closest the orbit, including the sampled point itself, came to the shader's `trap`, if it has one.
+    pub trap_distance: Option<T>,
+    /// Value of `z` and its derivative with respect to the point, once the orbit passed the shader's `derivative_escape_radius` or at `max_iter`, if it has one.
+    /// `DualComplex::distance_estimate` gives the distance to the boundary of the set from it.
+    pub derivative: Option<DualComplex<T>>,
+}
+
+impl<T> PixelSample<T> {
+    /// Whether the orbit escaped within `max_iter` iterations.
+    pub fn escaped(&self) -> bool {
+        self.iterations < self.max_iter
+    }
+}
+
+/// Colouring of each sample of an escape-time render from all that is known about it, for `render_with_shader`.
+/// Shaders are called from many threads at once within the render loop, so they need no passes over separate arrays of counts, angles and distances.
+/// Any `Fn(&PixelSample<T>) -> LinSrgba` closure is a shader which needs no trap or derivative.
+pub trait PixelShader<T>: Sync {
+    /// Colour of a sample, in linear light with alpha.
+    fn shade(&self, sample: &PixelSample<T>) -> LinSrgba;
+
+    /// Point to which the distance of each orbit is measured, for `PixelSample::trap_distance`.
+    fn trap(&self) -> Option<Complex<T>> {
+        None
+    }
+
+    /// Escape radius to which each orbit is iterated a second time carrying its derivative, for `PixelSample::derivative`.
+    /// Large radii, such as 1000, give accurate distance estimates.
+    fn derivative_escape_radius(&self) -> Option<T> {
+        None
+    }
+}
+
+impl<T, F> PixelShader<T> for F
+where
+    F: Fn(&PixelSample<T>) -> LinSrgba + Sync,
+{
+    fn shade(&self, sample: &PixelSample<T>) -> LinSrgba {
+        self(sample)
+    }
+}
+
+/// Continuous escape iteration of an orbit which escaped after `n` iterations at `z`, for a map which raises `|z|` to a power whose log is `log_degree`.
+pub(crate) fn smooth_iteration<T: Float>(n: u32, z: Complex<T>, log_degree: T) -> T {
+    let log_modulus = z.norm_sqr().ln() / (T::one() + T::one());
+    T::from(n + 1).unwrap() - log_modulus.ln() / log_degree
+}
@@ -0,0 +1,114 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use num_traits::Float;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{config::from_json_str, MandybrotError, Parameters};
+
+/// Version of the state token encoding written by this version of the crate, which follows the `mb` at the start of every token.
+pub const STATE_TOKEN_VERSION: u32 = 1;
+
+const TOKEN_PREFIX: &str = "mb";
+
+/// Encodes the full state of a render, from what is drawn and the view to the iteration limit, colour pipeline and random seed, as a single copy-pastable token for sharing an exact view.
+/// The token is `mb1_` followed by the parameters as compact JSON in unpadded base64url, and `decode_state` reads it back.
+pub fn encode_state<T: Serialize>(parameters: &Parameters<T>) -> Result<String, MandybrotError> {
+    let json = serde_json::to_vec(parameters).map_err(|err| MandybrotError::ConfigValue {
+        key: String::new(),
+        message: err.to_string(),
+    })?;
+    Ok(format!(
+        "{}{}_{}",
+        TOKEN_PREFIX,
+        STATE_TOKEN_VERSION,
+        URL_SAFE_NO_PAD.encode(json)
+    ))
+}
+
+/// Reads and validates the parameters of a token written by `encode_state`, ignoring whitespace around it.
+/// Tokens of a newer encoding are rejected as `UnsupportedVersion`, and damaged ones as `InvalidToken`.
+pub fn decode_state<T: Float + DeserializeOwned>(
+    token: &str,
+) -> Result<Parameters<T>, MandybrotError> {
+    let (version, data) = token
+        .trim()
+        .strip_prefix(TOKEN_PREFIX)
+        .and_then(|rest| rest.split_once('_'))
+        .ok_or(MandybrotError::InvalidToken(
+            "it does not start with 'mb1_'",
+        ))?;
+    let version: u32 = version
+        .parse()
+        .map_err(|_| MandybrotError::InvalidToken("its version is not a number"))?;
+    if version > STATE_TOKEN_VERSION {
+        return Err(MandybrotError::UnsupportedVersion(version));
+    }
+    let bytes = URL_SAFE_NO_PAD
+        .decode(data)
+        .map_err(|_| MandybrotError::InvalidToken("it is not valid base64url"))?;
+    let json = String::from_utf8(bytes)
+        .map_err(|_| MandybrotError::InvalidToken("it does not hold text"))?;
+
+    let parameters: Parameters<T> = from_json_str(&json)?;
+    parameters.validate()?;
+    Ok(parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RngStrategy;
+
+    fn parameters() -> Parameters<f64> {
+        Parameters::from_json(
+            r#"{
+                "centre": [-0.743643887037151, 0.131825904205330],
+                "scale": 1.0e-10,
+                "resolution": [640, 480],
+                "max_iter": 5000,
+                "rng": { "Seeded": 18446744073709551557 },
+                "job": { "Fractal": { "fractal": "Mandelbrot" } }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn tokens_round_trip_exactly() {
+        let params = parameters();
+        let token = encode_state(&params).unwrap();
+        assert!(token.starts_with("mb1_"));
+        assert!(token[4..]
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+
+        let decoded: Parameters<f64> = decode_state(&format!(" {}\n", token)).unwrap();
+        assert!(matches!(
+            decoded.rng,
+            RngStrategy::Seeded(18_446_744_073_709_551_557)
+        ));
+        assert_eq!(decoded.centre, params.centre);
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", params));
+        assert_eq!(encode_state(&decoded).unwrap(), token);
+    }
+
+    #[test]
+    fn damaged_tokens_are_rejected() {
+        let token = encode_state(&parameters()).unwrap();
+        for damaged in [
+            &token[1..],
+            &token[..token.len() - 5],
+            "mb1_not*base64",
+            "mbx_e30",
+        ] {
+            assert!(
+                decode_state::<f64>(damaged).is_err(),
+                "{} was accepted",
+                damaged
+            );
+        }
+        assert!(matches!(
+            decode_state::<f64>(&token.replacen("mb1_", "mb2_", 1)),
+            Err(MandybrotError::UnsupportedVersion(2))
+        ));
+    }
+}
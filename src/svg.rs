@@ -0,0 +1,224 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use ndarray::Array2;
+
+use crate::{
+    contours,
+    pattern::{band, band_levels},
+    Contour, MandybrotError, Pattern,
+};
+
+/// An SVG image of stroked contours, for plotters, laser cutters and other vector output.
+/// Coordinates are in pixels of the image the contours were extracted from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Svg {
+    resolution: [u32; 2],
+    defs: Vec<String>,
+    paths: Vec<String>,
+}
+
+impl Svg {
+    pub fn new(resolution: [u32; 2]) -> Self {
+        Self {
+            resolution,
+            defs: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
+
+    /// Adds contours as unfilled paths, stroked in the given CSS colour and width in pixels.
+    pub fn add_contours(
+        &mut self,
+        contours: &[Contour],
+        stroke: &str,
+        stroke_width: f64,
+    ) -> &mut Self {
+        for contour in contours.iter().filter(|contour| contour.points.len() > 1) {
+            let mut d = String::new();
+            for (i, [x, y]) in contour.points.iter().enumerate() {
+                let command = if i == 0 { 'M' } else { 'L' };
+                // Writing to a string cannot fail.
+                let _ = write!(d, "{}{:.2} {:.2} ", command, x, y);
+            }
+            if contour.closed {
+                d.push('Z');
+            }
+            self.paths.push(format!(
+                "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" data-level=\"{}\"/>",
+                d.trim_end(),
+                stroke,
+                stroke_width,
+                contour.level
+            ));
+        }
+        self
+    }
+
+    /// Adds iteration bands filled with black hatching patterns and outlined by their contours, the vector form of `pattern_fill`.
+    /// The `[0, max_iter)` range is split evenly between the patterns, with lines repeating every `spacing` pixels, and points which never escape are solid.
+    /// Fills follow the pixel grid, while the outlines are smooth contours stroked `stroke_width` pixels wide.
+    pub fn add_pattern_bands(
+        &mut self,
+        samples: &Array2<u32>,
+        max_iter: u32,
+        patterns: &[Pattern],
+        spacing: usize,
+        stroke_width: f64,
+    ) -> Result<&mut Self, MandybrotError> {
+        if patterns.is_empty() {
+            return Err(MandybrotError::ZeroCount("patterns"));
+        }
+        let spacing = spacing.max(2);
+
+        // One path of row runs per band, with the last for the points which never escape
+        let mut runs = vec![String::new(); patterns.len() + 1];
+        for (y, row) in samples.rows().into_iter().enumerate() {
+            let mut start = 0;
+            while start < row.len() {
+                let current = band(row[start], max_iter, patterns.len());
+                let end = (start..row.len())
+                    .find(|&x| band(row[x], max_iter, patterns.len()) != current)
+                    .unwrap_or(row.len());
+                let d = &mut runs[current.unwrap_or(patterns.len())];
+                let _ = write!(d, "M{} {}h{}v1h-{}Z", start, y, end - start, end - start);
+                start = end;
+            }
+        }
+
+        for (i, d) in runs.iter().enumerate().filter(|(_, d)| !d.is_empty()) {
+            let fill = match patterns.get(i) {
+                None | Some(Pattern::Solid) => "black".to_string(),
+                Some(Pattern::Empty) => continue,
+                Some(&pattern) => {
+                    let id = format!("{}-{}", pattern_name(pattern), spacing);
+                    if !self
+                        .defs
+                        .iter()
+                        .any(|def| def.contains(&format!("id=\"{}\"", id)))
+                    {
+                        self.defs.push(pattern_def(&id, pattern, spacing));
+                    }
+                    format!("url(#{})", id)
+                }
+            };
+            self.paths.push(format!(
+                "<path d=\"{}\" fill=\"{}\" stroke=\"none\"/>",
+                d, fill
+            ));
+        }
+
+        let outlines = contours(samples, &band_levels(max_iter, patterns.len()));
+        Ok(self.add_contours(&outlines, "black", stroke_width))
+    }
+
+    /// Writes the image to the given path.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let [width, height] = self.resolution;
+        writeln!(
+            writer,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+            width, height
+        )?;
+        if !self.defs.is_empty() {
+            writeln!(writer, "  <defs>")?;
+            for def in &self.defs {
+                writeln!(writer, "    {}", def)?;
+            }
+            writeln!(writer, "  </defs>")?;
+        }
+        for path in &self.paths {
+            writeln!(writer, "  {}", path)?;
+        }
+        writeln!(writer, "</svg>")
+    }
+}
+
+/// Identifier of a pattern in SVG definitions.
+fn pattern_name(pattern: Pattern) -> &'static str {
+    match pattern {
+        Pattern::Empty => "empty",
+        Pattern::Dots => "dots",
+        Pattern::Horizontal => "horizontal",
+        Pattern::Vertical => "vertical",
+        Pattern::Diagonal => "diagonal",
+        Pattern::AntiDiagonal => "anti-diagonal",
+        Pattern::Cross => "cross",
+        Pattern::Solid => "solid",
+    }
+}
+
+/// SVG pattern tile which repeats every `spacing` pixels, inking the same lines as `Pattern::is_ink`.
+/// Diagonal lines continue a little past the corners of the tile, so that neighbouring tiles join without gaps.
+fn pattern_def(id: &str, pattern: Pattern, spacing: usize) -> String {
+    let s = spacing;
+    let diagonal = format!(
+        "M-1 1L1 -1M0 {s}L{s} 0M{0} {1}L{1} {0}",
+        s - 1,
+        s + 1,
+        s = s
+    );
+    let anti_diagonal = format!(
+        "M-1 {0}L1 {1}M0 0L{s} {s}M{0} -1L{1} 1",
+        s - 1,
+        s + 1,
+        s = s
+    );
+    let content = match pattern {
+        Pattern::Dots => "<rect width=\"1\" height=\"1\"/>".to_string(),
+        Pattern::Horizontal => format!("<rect width=\"{}\" height=\"1\"/>", s),
+        Pattern::Vertical => format!("<rect width=\"1\" height=\"{}\"/>", s),
+        Pattern::Diagonal => format!("<path d=\"{}\" stroke=\"black\"/>", diagonal),
+        Pattern::AntiDiagonal => format!("<path d=\"{}\" stroke=\"black\"/>", anti_diagonal),
+        Pattern::Cross => format!(
+            "<path d=\"{}{}\" stroke=\"black\"/>",
+            diagonal, anti_diagonal
+        ),
+        Pattern::Empty => String::new(),
+        Pattern::Solid => format!("<rect width=\"{0}\" height=\"{0}\"/>", s),
+    };
+    format!(
+        "<pattern id=\"{}\" width=\"{2}\" height=\"{2}\" patternUnits=\"userSpaceOnUse\">{}</pattern>",
+        id, content, s
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_bands_are_filled_and_outlined() {
+        // Two bands of escaping points either side of a column of points which never escape
+        let samples = Array2::from_shape_fn((4, 6), |(_, x)| [1, 1, 6, 6, 9, 9][x]);
+        let mut svg = Svg::new([6, 4]);
+        svg.add_pattern_bands(&samples, 8, &[Pattern::Dots, Pattern::Diagonal], 4, 0.5)
+            .unwrap();
+        let mut output = Vec::new();
+        svg.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("<pattern id=\"dots-4\""));
+        assert!(output.contains("<pattern id=\"diagonal-4\""));
+        assert!(output.contains("M0 0h2v1h-2Z"));
+        assert!(output.contains("fill=\"url(#diagonal-4)\""));
+        assert!(output.contains("M4 3h2v1h-2Z"));
+        assert!(output.contains("fill=\"black\""));
+        assert_eq!(output.matches("data-level").count(), 2);
+
+        assert!(matches!(
+            Svg::new([6, 4]).add_pattern_bands(&samples, 8, &[], 4, 0.5),
+            Err(MandybrotError::ZeroCount("patterns"))
+        ));
+    }
+}
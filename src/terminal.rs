@@ -0,0 +1,89 @@
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::{ColourSpace, Dither};
+
+/// Upper half block, drawn with the upper pixel of a cell as its foreground and the lower as its background.
+const HALF_BLOCK: char = '▀';
+
+/// Colours a terminal can draw, by the escape codes it understands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnsiColour {
+    /// The 6 × 6 × 6 colour cube and 24 greys of 256-colour terminals, understood almost everywhere.
+    Palette256,
+    /// 24-bit colour, understood by most modern terminals.
+    #[default]
+    TrueColour,
+}
+
+impl AnsiColour {
+    /// Appends the escape code selecting an sRGB colour as the foreground, or as the background if not `foreground`.
+    fn escape(&self, [red, green, blue]: [u8; 3], foreground: bool, text: &mut String) {
+        let layer = if foreground { 38 } else { 48 };
+        match self {
+            AnsiColour::TrueColour => {
+                let _ = write!(text, "\x1b[{};2;{};{};{}m", layer, red, green, blue);
+            }
+            AnsiColour::Palette256 => {
+                let _ = write!(
+                    text,
+                    "\x1b[{};5;{}m",
+                    layer,
+                    palette_index([red, green, blue])
+                );
+            }
+        }
+    }
+}
+
+/// Draws an image as text for a terminal, two pixels to each character cell, such as for a quick preview over SSH.
+/// Each line of text covers two rows of pixels and ends by resetting the colours; an odd last row is drawn over the terminal's own background.
+/// Transparent pixels are drawn by their colour alone.
+pub fn render_ansi<C: ColourSpace>(image: &Array2<C>, colour: AnsiColour) -> String {
+    let (height, width) = image.dim();
+    let mut text = String::with_capacity(height.div_ceil(2) * (width * 40 + 5));
+    let encode = |x: usize, y: usize| {
+        let [red, green, blue, _] = Dither::None.encode(image[[y, x]].into_linear(), [x, y]);
+        [red, green, blue]
+    };
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            colour.escape(encode(x, y), true, &mut text);
+            if y + 1 < height {
+                colour.escape(encode(x, y + 1), false, &mut text);
+            }
+            text.push(HALF_BLOCK);
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// Nearest colour of the 256-colour palette, from its colour cube or its ramp of greys.
+fn palette_index(rgb: [u8; 3]) -> u8 {
+    const CUBE_LEVELS: [i32; 6] = [0, 95, 135, 175, 215, 255];
+    let distance = |a: [i32; 3]| {
+        a.iter()
+            .zip(rgb)
+            .map(|(&a, b)| (a - b as i32).pow(2))
+            .sum::<i32>()
+    };
+    let nearest_level = |channel: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE_LEVELS[i] - channel as i32).abs())
+            .unwrap()
+    };
+
+    let [r, g, b] = rgb.map(nearest_level);
+    let cube = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+    let mean = rgb.iter().map(|&c| c as i32).sum::<i32>() / 3;
+    let grey_step = ((mean - 8 + 5) / 10).clamp(0, 23);
+    let grey = 8 + 10 * grey_step;
+
+    if distance([grey; 3]) < distance(cube) {
+        232 + grey_step as u8
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
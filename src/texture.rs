@@ -0,0 +1,161 @@
+use ndarray::{Array2, Zip};
+use num_traits::Float;
+use palette::LinSrgba;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+use crate::{error::check_count, ColourMap, MandybrotError, SampleBuffer};
+
+/// Tileable fractal Perlin noise over the unit square, optionally domain-warped by a second layer of itself for swirling "tie-dye" patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerlinNoise {
+    /// Number of lattice cells across the unit square at the coarsest octave.
+    pub frequency: u32,
+    /// Number of octaves, each with twice the frequency and half the amplitude of the last.
+    pub octaves: u32,
+    /// Distance by which sample points are displaced by the warping layer, in lattice cells.
+    pub warp: f32,
+    pub seed: u32,
+}
+
+impl PerlinNoise {
+    pub const fn new(frequency: u32, octaves: u32, warp: f32, seed: u32) -> Self {
+        Self {
+            frequency,
+            octaves,
+            warp,
+            seed,
+        }
+    }
+
+    /// Checks that the noise has at least one octave of at least one cell.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_count("frequency", self.frequency)?;
+        check_count("octaves", self.octaves)
+    }
+
+    /// Noise value at `(u, v)`, in the range `[0, 1]`, repeating with a period of one in both directions.
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        let [x, y] = [u, v].map(|t| t * self.frequency as f32);
+        let (x, y) = if self.warp == 0.0 {
+            (x, y)
+        } else {
+            // Offset the warping layer's seed so it is uncorrelated with the pattern it warps
+            let seed = self.seed.wrapping_add(0x9E37_79B9);
+            (
+                x + self.warp * self.fractal(x, y, seed),
+                y + self.warp * self.fractal(x + 0.5, y + 0.5, seed.wrapping_add(1)),
+            )
+        };
+        // Values far from the mean are rare, so the range is stretched and the extremes clipped to use more of a colour map
+        (0.5 + self.fractal(x, y, self.seed)).clamp(0.0, 1.0)
+    }
+
+    /// Sum of octaves of gradient noise, normalised to roughly `[-1, 1]`.
+    fn fractal(&self, x: f32, y: f32, seed: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        for octave in 0..self.octaves.min(16) {
+            let scale = (1u32 << octave) as f32;
+            let period = self.frequency << octave;
+            sum += amplitude * gradient_noise(x * scale, y * scale, period, seed);
+            total += amplitude;
+            amplitude *= 0.5;
+        }
+        // Two dimensional gradient noise has a range of `±1/sqrt(2)`
+        sum * std::f32::consts::SQRT_2 / total
+    }
+}
+
+/// Perlin gradient noise on a lattice which wraps every `period` cells.
+fn gradient_noise(x: f32, y: f32, period: u32, seed: u32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let wrap = |i: f32, offset: i64| (i as i64 + offset).rem_euclid(period as i64) as u32;
+
+    let dot = |dx: i64, dy: i64| {
+        let angle = hash(wrap(x0, dx), wrap(y0, dy), seed) as f32 / u32::MAX as f32 * TAU;
+        angle.cos() * (fx - dx as f32) + angle.sin() * (fy - dy as f32)
+    };
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let (sx, sy) = (fade(fx), fade(fy));
+    lerp(
+        lerp(dot(0, 0), dot(1, 0), sx),
+        lerp(dot(0, 1), dot(1, 1), sx),
+        sy,
+    )
+}
+
+/// Mixes lattice coordinates and a seed into a well distributed integer.
+fn hash(x: u32, y: u32, seed: u32) -> u32 {
+    let mut h = seed ^ x.wrapping_mul(0x27D4_EB2D) ^ y.wrapping_mul(0x1656_67B1);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xC2B2_AE35);
+    h ^ (h >> 16)
+}
+
+/// Source of colours for texture mapping, looked up by coordinates which wrap over the unit square.
+#[derive(Debug, Clone)]
+pub enum Texture {
+    /// An image, sampled with bilinear filtering.
+    Image(Array2<LinSrgba>),
+    /// Procedural noise, coloured by a colour map.
+    Noise(PerlinNoise, ColourMap),
+}
+
+impl Texture {
+    /// Colour at `(u, v)`, wrapping outside of `[0, 1)`.
+    pub fn sample(&self, u: f32, v: f32) -> LinSrgba {
+        match self {
+            Texture::Image(image) => {
+                let (height, width) = image.dim();
+                let x = (u - u.floor()) * width as f32 - 0.5;
+                let y = (v - v.floor()) * height as f32 - 0.5;
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let pixel = |dx: f32, dy: f32| {
+                    let px = (x0 + dx).rem_euclid(width as f32) as usize;
+                    let py = (y0 + dy).rem_euclid(height as f32) as usize;
+                    image[(py.min(height - 1), px.min(width - 1))]
+                };
+                (pixel(0.0, 0.0) * (1.0 - fx) + pixel(1.0, 0.0) * fx) * (1.0 - fy)
+                    + (pixel(0.0, 1.0) * (1.0 - fx) + pixel(1.0, 1.0) * fx) * fy
+            }
+            Texture::Noise(noise, colour_map) => colour_map.sample(noise.sample(u, v)),
+        }
+    }
+}
+
+/// Colours the exterior of an escape-time render by mapping each pixel's escape angle and smooth iteration onto a texture.
+/// The angle runs once around the texture horizontally, and each band between whole iterations spans it vertically, with `repeat` tiling it in each direction.
+/// Pixels which did not escape are given the `interior` colour.
+/// Returns `None` if the buffer does not hold final values of `z`, as recorded by `render_escape`.
+pub fn texture_exterior<T: Float>(
+    buffer: &SampleBuffer<T>,
+    max_iter: u32,
+    texture: &Texture,
+    repeat: [u32; 2],
+    interior: LinSrgba,
+) -> Option<Array2<LinSrgba>> {
+    let angle = buffer.escape_angle(max_iter)?;
+    let smooth = buffer.smooth_iterations(max_iter)?;
+    let [u_repeat, v_repeat] = repeat.map(|r| r as f32);
+
+    Some(
+        Zip::from(&angle)
+            .and(&smooth)
+            .map_collect(|&angle, &smooth| match (angle.to_f32(), smooth.to_f32()) {
+                (Some(angle), Some(smooth)) if angle.is_finite() && smooth.is_finite() => {
+                    let u = (angle / TAU + 0.5) * u_repeat;
+                    let v = smooth * v_repeat;
+                    texture.sample(u, v)
+                }
+                _ => interior,
+            }),
+    )
+}
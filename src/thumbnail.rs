@@ -0,0 +1,116 @@
+use ndarray::{s, Array2, ArrayView2};
+
+use crate::MandybrotError;
+
+/// A strip of small animation frames stored side by side in a single array, for timeline scrubbing.
+#[derive(Debug, Clone)]
+pub struct ThumbnailStrip<T> {
+    thumbnail_resolution: [u32; 2],
+    num_thumbnails: usize,
+    data: Array2<T>,
+}
+
+impl<T: Clone> ThumbnailStrip<T> {
+    /// Creates a strip with space for `num_thumbnails` frames, each initially filled with `fill`.
+    pub fn new(
+        num_thumbnails: usize,
+        thumbnail_resolution: [u32; 2],
+        fill: T,
+    ) -> Result<Self, MandybrotError> {
+        if num_thumbnails == 0 {
+            return Err(MandybrotError::ZeroCount("num_thumbnails"));
+        }
+        let [width, height] = thumbnail_resolution;
+        Ok(Self {
+            thumbnail_resolution,
+            num_thumbnails,
+            data: Array2::from_elem((height as usize, width as usize * num_thumbnails), fill),
+        })
+    }
+
+    /// Resolution of each thumbnail.
+    pub fn thumbnail_resolution(&self) -> [u32; 2] {
+        self.thumbnail_resolution
+    }
+
+    /// Number of thumbnails the strip holds.
+    pub fn num_thumbnails(&self) -> usize {
+        self.num_thumbnails
+    }
+
+    /// Thumbnail slot which represents the given frame of an animation with `num_frames` frames.
+    pub fn slot(&self, frame: usize, num_frames: usize) -> usize {
+        (frame * self.num_thumbnails / num_frames.max(1)).min(self.num_thumbnails.saturating_sub(1))
+    }
+
+    /// Shrinks a full-resolution frame into the given slot by taking the sample nearest each thumbnail pixel centre.
+    /// The slot must be within the strip, and the frame must have pixels to sample.
+    pub fn set(&mut self, slot: usize, frame: &Array2<T>) -> Result<(), MandybrotError> {
+        if slot >= self.num_thumbnails {
+            return Err(MandybrotError::InvalidParameter {
+                name: "slot",
+                value: slot as f64,
+            });
+        }
+        let (frame_height, frame_width) = frame.dim();
+        if frame_height == 0 || frame_width == 0 {
+            return Err(MandybrotError::EmptyResolution([
+                frame_width as u32,
+                frame_height as u32,
+            ]));
+        }
+        let [width, height] = self.thumbnail_resolution;
+        let (width, height) = (width as usize, height as usize);
+
+        let mut thumbnail = self
+            .data
+            .slice_mut(s![.., slot * width..(slot + 1) * width]);
+        for ((y, x), pixel) in thumbnail.indexed_iter_mut() {
+            let source_y = ((2 * y + 1) * frame_height) / (2 * height);
+            let source_x = ((2 * x + 1) * frame_width) / (2 * width);
+            *pixel = frame[(source_y, source_x)].clone();
+        }
+        Ok(())
+    }
+
+    /// View of the thumbnail in the given slot.
+    pub fn get(&self, slot: usize) -> ArrayView2<'_, T> {
+        let width = self.thumbnail_resolution[0] as usize;
+        self.data.slice(s![.., slot * width..(slot + 1) * width])
+    }
+
+    /// The whole strip, with thumbnails ordered left to right.
+    pub fn data(&self) -> &Array2<T> {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_shrunk_into_their_slots() {
+        let mut strip = ThumbnailStrip::new(3, [2, 2], 0).unwrap();
+        let frame = Array2::from_shape_fn((4, 4), |(y, x)| (10 * y + x) as u32);
+        strip.set(1, &frame).unwrap();
+
+        assert_eq!(strip.get(0), Array2::<u32>::zeros((2, 2)));
+        assert_eq!(strip.get(1), ndarray::array![[11_u32, 13], [31, 33]]);
+        assert_eq!(strip.slot(9, 10), 2);
+    }
+
+    #[test]
+    fn invalid_slots_and_empty_frames_are_errors() {
+        let mut strip = ThumbnailStrip::new(3, [2, 2], 0).unwrap();
+        assert!(matches!(
+            strip.set(3, &Array2::zeros((4, 4))),
+            Err(MandybrotError::InvalidParameter { name: "slot", .. })
+        ));
+        assert!(matches!(
+            strip.set(0, &Array2::zeros((0, 4))),
+            Err(MandybrotError::EmptyResolution([4, 0]))
+        ));
+        assert_eq!(strip.data(), &Array2::<u32>::zeros((2, 6)));
+    }
+}
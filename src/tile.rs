@@ -0,0 +1,124 @@
+use core::ops::{Add, Mul, Sub};
+use num_traits::{Float, NumCast};
+
+use crate::{Fractal, MandybrotError, Periodicity, Viewport};
+
+/// Renders a fractal into a caller-provided row-major buffer, sampled once at each pixel centre.
+/// Nothing is allocated, so tiles can be re-rendered every frame into memory owned by a game engine or visualiser.
+/// The buffer must hold exactly one value per pixel of the viewport.
+pub fn render_tile<T>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+    buffer: &mut [u32],
+) -> Result<(), MandybrotError>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
+{
+    viewport.validate()?;
+    fractal.validate()?;
+    if let Some(periodicity) = &periodicity {
+        periodicity.validate()?;
+    }
+
+    let [x_res, y_res] = viewport.resolution;
+    let expected = x_res as usize * y_res as usize;
+    if buffer.len() != expected {
+        return Err(MandybrotError::BufferSize {
+            expected,
+            found: buffer.len(),
+        });
+    }
+
+    for (y, row) in buffer.chunks_exact_mut(x_res as usize).enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let c = viewport.pixel_centre(x as u32, y as u32);
+            *pixel = fractal.sample(c, max_iter, periodicity.as_ref());
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders a fractal into a fixed-size `W` by `H` tile, see `render_tile`.
+/// The viewport resolution must be `[W, H]`, so that each row of the tile is a row of the image.
+pub fn render_tile_array<T, const W: usize, const H: usize>(
+    viewport: &Viewport<T>,
+    fractal: &Fractal<T>,
+    max_iter: u32,
+    periodicity: Option<Periodicity<T>>,
+    tile: &mut [[u32; W]; H],
+) -> Result<(), MandybrotError>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
+{
+    let found = [W as u32, H as u32];
+    if viewport.resolution != found {
+        return Err(MandybrotError::TileShape {
+            expected: viewport.resolution,
+            found,
+        });
+    }
+    render_tile(
+        viewport,
+        fractal,
+        max_iter,
+        periodicity,
+        tile.as_flattened_mut(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Complex;
+
+    #[test]
+    fn tiles_must_match_the_viewport_resolution() {
+        let fractal = Fractal::Mandelbrot;
+        let mut tile = [[0; 4]; 2];
+
+        let transposed = Viewport::new(Complex::new(-0.5, 0.0), 2.0, [2, 4]);
+        assert!(matches!(
+            render_tile_array(&transposed, &fractal, 16, None, &mut tile),
+            Err(MandybrotError::TileShape {
+                expected: [2, 4],
+                found: [4, 2],
+            })
+        ));
+
+        let viewport = Viewport::new(Complex::new(-0.5, 0.0), 2.0, [4, 2]);
+        render_tile_array(&viewport, &fractal, 16, None, &mut tile).unwrap();
+        let mut buffer = [0; 8];
+        render_tile(&viewport, &fractal, 16, None, &mut buffer).unwrap();
+        assert_eq!(tile.as_flattened(), buffer);
+    }
+
+    /// FNV-1a hash of a buffer of counts.
+    #[cfg(feature = "strict")]
+    fn checksum(counts: &[u32]) -> u64 {
+        counts
+            .iter()
+            .flat_map(|count| count.to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn strict_tiles_match_golden_output() {
+        let viewport = Viewport::new(Complex::new(-0.5, 0.0), 2.5, [64, 48]);
+        let mut buffer = [0; 64 * 48];
+
+        render_tile(&viewport, &Fractal::Mandelbrot, 256, None, &mut buffer).unwrap();
+        assert_eq!(checksum(&buffer), 0x9e6d_f845_a843_9b09);
+
+        let fractal = Fractal::ComplexMultibrot {
+            power: Complex::new(2.5, 0.5),
+        };
+        render_tile(&viewport, &fractal, 256, None, &mut buffer).unwrap();
+        assert_eq!(checksum(&buffer), 0xad92_2b26_a5ab_5da7);
+    }
+}
@@ -0,0 +1,472 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use core::ops::{Add, Mul, Sub};
+use num_traits::{Float, NumCast};
+use palette::{LinSrgba, Srgba};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    net::{TcpListener, ToSocketAddrs},
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tower_http::timeout::TimeoutLayer;
+
+use crate::{render_tile, Affine, ColourMap, Complex, Fractal, MandybrotError, Viewport};
+
+/// Deepest zoom level served, beyond which tiles would be narrower than the precision of `f64` can resolve.
+const MAX_ZOOM: u32 = 40;
+
+/// Settings of a slippy-map tile server, see `serve_tiles`.
+/// Zoom level zero is a single tile covering a square of side `extent` about `centre`, and each level doubles the number of tiles in each direction.
+#[derive(Debug, Clone)]
+pub struct TileServer<T> {
+    /// Fractals which can be requested, by the name used in the tile path.
+    pub fractals: HashMap<String, Fractal<T>>,
+    pub centre: Complex<T>,
+    pub extent: T,
+    /// Width and height of each tile, in pixels.
+    pub tile_size: u32,
+    pub max_iter: u32,
+    pub colour_map: ColourMap,
+    /// Number of encoded tiles kept in memory, with the least recently used dropped first.
+    pub cache_size: usize,
+    /// Number of tiles rendered at once, with further requests waiting their turn.
+    pub max_renders: usize,
+    /// Time allowed to answer a request, including any wait for a render, before responding with `408 Request Timeout`.
+    pub request_timeout: Duration,
+}
+
+impl<T: Float> TileServer<T> {
+    /// Server of 256 pixel tiles of the given fractals, with the Mandelbrot set's bounding square at zoom level zero.
+    pub fn new(
+        fractals: HashMap<String, Fractal<T>>,
+        max_iter: u32,
+        colour_map: ColourMap,
+    ) -> Self {
+        Self {
+            fractals,
+            centre: Complex::new(T::from(-0.5).unwrap(), T::zero()),
+            extent: T::from(4.0).unwrap(),
+            tile_size: 256,
+            max_iter,
+            colour_map,
+            cache_size: 1024,
+            max_renders: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Viewport covering tile `(x, y)` at zoom level `z`, if the tile exists.
+    /// As in slippy maps, `x` runs east along the real axis and `y` runs south from the top, so tile `(0, 0)` holds the largest imaginary parts.
+    /// The viewport is reflected so that its rows of pixels also run from the top of the tile down.
+    pub fn tile_viewport(&self, z: u32, x: u64, y: u64) -> Option<Viewport<T>> {
+        if z > MAX_ZOOM || x >> z != 0 || y >> z != 0 {
+            return None;
+        }
+        let size = self.extent / T::from(1u64 << z).unwrap();
+        let half = T::from(0.5).unwrap();
+        let corner = Complex::new(
+            self.centre.real - self.extent * half,
+            self.centre.imag - self.extent * half,
+        );
+        let row_from_bottom = (1u64 << z) - 1 - y;
+        Some(
+            Viewport::new(
+                Complex::new(
+                    corner.real + (T::from(x).unwrap() + half) * size,
+                    corner.imag + (T::from(row_from_bottom).unwrap() + half) * size,
+                ),
+                size,
+                [self.tile_size; 2],
+            )
+            .with_transform(Affine::scaling(T::one(), -T::one())),
+        )
+    }
+}
+
+impl<T> TileServer<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd + Float + NumCast,
+{
+    /// Renders a tile and encodes it as a PNG image, returning `None` if the fractal or tile does not exist.
+    /// Pixels are coloured by the logarithm of their iteration count, with the interior black.
+    pub fn render_png(
+        &self,
+        fractal: &str,
+        z: u32,
+        x: u64,
+        y: u64,
+    ) -> Result<Option<Vec<u8>>, MandybrotError> {
+        let (Some(fractal), Some(viewport)) =
+            (self.fractals.get(fractal), self.tile_viewport(z, x, y))
+        else {
+            return Ok(None);
+        };
+
+        let mut counts = vec![0; self.tile_size as usize * self.tile_size as usize];
+        render_tile(&viewport, fractal, self.max_iter, None, &mut counts)?;
+
+        let log_max = ((self.max_iter + 1) as f32).ln();
+        let interior = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut pixels = Vec::with_capacity(counts.len() * 4);
+        for n in counts {
+            let colour = if n >= self.max_iter {
+                interior
+            } else {
+                self.colour_map.sample(((n + 1) as f32).ln() / log_max)
+            };
+            let colour: Srgba<u8> = Srgba::from_linear(colour);
+            pixels.extend_from_slice(&[colour.red, colour.green, colour.blue, colour.alpha]);
+        }
+
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.tile_size, self.tile_size);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::from)?;
+        writer.write_image_data(&pixels).map_err(io::Error::from)?;
+        writer.finish().map_err(io::Error::from)?;
+        Ok(Some(bytes))
+    }
+}
+
+/// Fractal name, zoom level and position of a tile.
+type TileKey = (String, u32, u64, u64);
+
+/// Least recently used cache of encoded tiles.
+/// Each tile is stamped with the time it was last used, and the stamps are kept in order so the oldest is found in logarithmic time.
+#[derive(Debug, Default)]
+struct TileCache {
+    tiles: HashMap<TileKey, (Arc<Vec<u8>>, u64)>,
+    last_used: BTreeMap<u64, TileKey>,
+    clock: u64,
+}
+
+impl TileCache {
+    fn get(&mut self, key: &TileKey) -> Option<Arc<Vec<u8>>> {
+        let (tile, last_used) = self.tiles.get_mut(key)?;
+        self.clock += 1;
+        let key = self.last_used.remove(last_used)?;
+        *last_used = self.clock;
+        self.last_used.insert(self.clock, key);
+        Some(Arc::clone(tile))
+    }
+
+    fn insert(&mut self, key: TileKey, tile: Arc<Vec<u8>>, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        if let Some((_, last_used)) = self.tiles.remove(&key) {
+            self.last_used.remove(&last_used);
+        }
+        while self.tiles.len() >= capacity {
+            let Some((_, oldest)) = self.last_used.pop_first() else {
+                break;
+            };
+            self.tiles.remove(&oldest);
+        }
+        self.clock += 1;
+        self.last_used.insert(self.clock, key.clone());
+        self.tiles.insert(key, (tile, self.clock));
+    }
+}
+
+/// Locks the cache, recovering it if a request panicked while holding the lock.
+/// Every update leaves the cache consistent, so it remains safe to use.
+fn lock_cache(cache: &Mutex<TileCache>) -> MutexGuard<'_, TileCache> {
+    cache.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// State shared between requests.
+struct Shared<T> {
+    server: Arc<TileServer<T>>,
+    cache: Mutex<TileCache>,
+    renders: Arc<Semaphore>,
+}
+
+/// Serves rendered tiles over HTTP at `/{fractal}/{z}/{x}/{y}.png`, in the slippy-map scheme used by Leaflet and OpenLayers.
+/// Connections are handled asynchronously, while at most `max_renders` tiles are rendered at once on blocking threads,
+/// and recently requested tiles are cached.
+/// The server runs on the returned thread until the process exits.
+pub fn serve_tiles<T>(
+    server: Arc<TileServer<T>>,
+    address: impl ToSocketAddrs,
+) -> io::Result<JoinHandle<()>>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + PartialOrd
+        + Float
+        + NumCast
+        + Send
+        + Sync
+        + 'static,
+{
+    serve_listener(server, TcpListener::bind(address)?)
+}
+
+fn serve_listener<T>(
+    server: Arc<TileServer<T>>,
+    listener: TcpListener,
+) -> io::Result<JoinHandle<()>>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + PartialOrd
+        + Float
+        + NumCast
+        + Send
+        + Sync
+        + 'static,
+{
+    if server.max_renders == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            MandybrotError::ZeroCount("max_renders"),
+        ));
+    }
+    listener.set_nonblocking(true)?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    let listener = {
+        let _guard = runtime.enter();
+        tokio::net::TcpListener::from_std(listener)?
+    };
+
+    let timeout =
+        TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, server.request_timeout);
+    let shared = Arc::new(Shared {
+        renders: Arc::new(Semaphore::new(server.max_renders)),
+        cache: Mutex::new(TileCache::default()),
+        server,
+    });
+    let router = Router::new()
+        .route("/{fractal}/{z}/{x}/{y}", get(tile::<T>))
+        .layer(timeout)
+        .with_state(shared);
+
+    Ok(thread::spawn(move || {
+        // Errors accepting a connection are retried by `serve`, so it only returns once the listener is closed.
+        let _ = runtime.block_on(async { axum::serve(listener, router).await });
+    }))
+}
+
+async fn tile<T>(
+    State(shared): State<Arc<Shared<T>>>,
+    Path((fractal, z, x, y)): Path<(String, u32, u64, String)>,
+) -> Response
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + PartialOrd
+        + Float
+        + NumCast
+        + Send
+        + Sync
+        + 'static,
+{
+    let Some(y) = y.strip_suffix(".png").and_then(|y| y.parse().ok()) else {
+        return respond(
+            StatusCode::NOT_FOUND,
+            "text/plain",
+            b"no such tile".to_vec(),
+        );
+    };
+    let key = (fractal, z, x, y);
+    if let Some(tile) = lock_cache(&shared.cache).get(&key) {
+        return respond(StatusCode::OK, "image/png", tile.to_vec());
+    }
+
+    let Ok(permit) = Arc::clone(&shared.renders).acquire_owned().await else {
+        return respond(StatusCode::SERVICE_UNAVAILABLE, "text/plain", Vec::new());
+    };
+    let server = Arc::clone(&shared.server);
+    let (fractal, ..) = key.clone();
+    let rendered = tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        server.render_png(&fractal, z, x, y)
+    })
+    .await;
+
+    match rendered {
+        Ok(Ok(Some(tile))) => {
+            let tile = Arc::new(tile);
+            lock_cache(&shared.cache).insert(key, Arc::clone(&tile), shared.server.cache_size);
+            respond(StatusCode::OK, "image/png", tile.to_vec())
+        }
+        Ok(Ok(None)) => respond(
+            StatusCode::NOT_FOUND,
+            "text/plain",
+            b"no such tile".to_vec(),
+        ),
+        Ok(Err(error)) => respond(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "text/plain",
+            error.to_string().into_bytes(),
+        ),
+        // The render panicked.
+        Err(_) => respond(StatusCode::INTERNAL_SERVER_ERROR, "text/plain", Vec::new()),
+    }
+}
+
+fn respond(status: StatusCode, content_type: &'static str, body: Vec<u8>) -> Response {
+    (
+        status,
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    /// Sends a request to the server and returns the status line and body of the response.
+    fn request(address: &str, method: &str, path: &str) -> (String, Vec<u8>) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            method, path, address
+        )
+        .unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8(response[..split].to_vec()).unwrap();
+        let status = head.lines().next().unwrap().to_string();
+        (status, response[split + 4..].to_vec())
+    }
+
+    fn start() -> String {
+        let fractals = HashMap::from([("mandelbrot".to_string(), Fractal::Mandelbrot)]);
+        let mut server = TileServer::<f64>::new(fractals, 64, ColourMap::preset("magma").unwrap());
+        server.tile_size = 16;
+        server.max_renders = 1;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        serve_listener(Arc::new(server), listener).unwrap();
+        address
+    }
+
+    #[test]
+    fn tiles_are_served_as_png_images() {
+        let address = start();
+        for _ in 0..2 {
+            let (status, body) = request(&address, "GET", "/mandelbrot/1/0/1.png");
+            assert_eq!(status, "HTTP/1.1 200 OK");
+            assert!(body.starts_with(b"\x89PNG"));
+        }
+    }
+
+    #[test]
+    fn bad_requests_are_rejected() {
+        let address = start();
+        for path in [
+            "/julia/0/0/0.png",
+            "/mandelbrot/1/2/0.png",
+            "/mandelbrot/0/0/0.jpg",
+            "/mandelbrot/0/0",
+        ] {
+            let (status, _) = request(&address, "GET", path);
+            assert_eq!(status, "HTTP/1.1 404 Not Found", "{}", path);
+        }
+        let (status, _) = request(&address, "POST", "/mandelbrot/0/0/0.png");
+        assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+    }
+
+    #[test]
+    fn many_concurrent_requests_share_the_render_limit() {
+        let address = start();
+        let clients: Vec<_> = (0..16)
+            .map(|i| {
+                let address = address.clone();
+                thread::spawn(move || {
+                    request(&address, "GET", &format!("/mandelbrot/4/{}/7.png", i))
+                })
+            })
+            .collect();
+        for client in clients {
+            assert_eq!(client.join().unwrap().0, "HTTP/1.1 200 OK");
+        }
+    }
+
+    #[test]
+    fn tiles_are_counted_from_the_top_left() {
+        let server =
+            TileServer::<f64>::new(HashMap::new(), 64, ColourMap::preset("magma").unwrap());
+        let viewport = server.tile_viewport(1, 0, 0).unwrap();
+        let [width, height] = viewport.resolution;
+
+        // Tile (0, 0) at zoom level one is the top-left quadrant of [-2.5, 1.5] × [-2, 2]
+        let top_left = viewport.pixel_to_complex(0.0, 0.0);
+        let bottom_right = viewport.pixel_to_complex(width as f64, height as f64);
+        assert!((top_left.real + 2.5).abs() < 1e-12 && (top_left.imag - 2.0).abs() < 1e-12);
+        assert!((bottom_right.real + 0.5).abs() < 1e-12 && bottom_right.imag.abs() < 1e-12);
+
+        let below = server.tile_viewport(1, 0, 1).unwrap();
+        assert!(below.centre.imag < viewport.centre.imag);
+    }
+
+    #[test]
+    fn least_recently_used_tiles_are_dropped_first() {
+        let key = |x| ("mandelbrot".to_string(), 2, x, 0);
+        let mut cache = TileCache::default();
+        for x in 0..3 {
+            cache.insert(key(x), Arc::new(vec![x as u8]), 3);
+        }
+        assert!(cache.get(&key(0)).is_some());
+        cache.insert(key(3), Arc::new(vec![3]), 3);
+        assert!(cache.get(&key(1)).is_none());
+
+        // Storing a tile again replaces it without dropping another
+        cache.insert(key(3), Arc::new(vec![4]), 3);
+        assert_eq!(cache.tiles.len(), 3);
+        assert_eq!(cache.last_used.len(), 3);
+        assert_eq!(cache.get(&key(3)).as_deref(), Some(&vec![4]));
+        for x in [0, 2] {
+            assert!(cache.get(&key(x)).is_some());
+        }
+    }
+
+    #[test]
+    fn poisoned_cache_is_recovered() {
+        let cache = Arc::new(Mutex::new(TileCache::default()));
+        let poisoner = Arc::clone(&cache);
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("request failed while holding the cache");
+        })
+        .join();
+        assert!(cache.is_poisoned());
+
+        let key = ("mandelbrot".to_string(), 0, 0, 0);
+        lock_cache(&cache).insert(key.clone(), Arc::new(vec![1, 2, 3]), 4);
+        assert_eq!(
+            lock_cache(&cache).get(&key).as_deref(),
+            Some(&vec![1, 2, 3])
+        );
+    }
+}
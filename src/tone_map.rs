@@ -0,0 +1,86 @@
+use ndarray::Array2;
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::check_positive, MandybrotError};
+
+/// Operator which compresses the dynamic range of a density image into `[0, 1]`, ready for colour mapping.
+/// Densities are first scaled so the average non-zero pixel has a value of `exposure`, so the same settings suit renders of any sample count.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ToneMap<T> {
+    /// Clips everything brighter than one.
+    Linear { exposure: T },
+    /// Logarithmic compression, scaled so the brightest pixel maps to one.
+    Log { exposure: T },
+    /// Reinhard's operator, which maps `white` (or infinity if not given) to one.
+    Reinhard { exposure: T, white: Option<T> },
+    /// Filmic curve fitted to the ACES reference tone map, with a toe which deepens the shadows.
+    Aces { exposure: T },
+}
+
+impl<T: Float> ToneMap<T> {
+    /// Checks that the exposure and white point are positive.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            ToneMap::Linear { exposure }
+            | ToneMap::Log { exposure }
+            | ToneMap::Aces { exposure } => check_positive("exposure", exposure),
+            ToneMap::Reinhard { exposure, white } => {
+                check_positive("exposure", exposure)?;
+                match white {
+                    Some(white) => check_positive("white", white),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
+    fn exposure(&self) -> T {
+        match *self {
+            ToneMap::Linear { exposure }
+            | ToneMap::Log { exposure }
+            | ToneMap::Reinhard { exposure, .. }
+            | ToneMap::Aces { exposure } => exposure,
+        }
+    }
+
+    /// Tone maps an image of densities, such as orbit counts, into values in `[0, 1]`.
+    pub fn apply(&self, densities: &Array2<T>) -> Result<Array2<T>, MandybrotError> {
+        self.validate()?;
+
+        let (sum, count) = densities
+            .iter()
+            .filter(|&&v| v > T::zero())
+            .fold((T::zero(), 0), |(sum, count), &v| (sum + v, count + 1));
+        if count == 0 {
+            return Ok(Array2::zeros(densities.dim()));
+        }
+        let scale = self.exposure() * T::from(count).unwrap() / sum;
+
+        let one = T::one();
+        let mapped = match *self {
+            ToneMap::Linear { .. } => densities.mapv(|v| (v * scale).min(one)),
+            ToneMap::Log { .. } => {
+                let max = densities.iter().fold(T::zero(), |max, &v| max.max(v));
+                let norm = (max * scale).ln_1p();
+                densities.mapv(|v| (v * scale).ln_1p() / norm)
+            }
+            ToneMap::Reinhard { white, .. } => {
+                let inv_white_sq = white.map_or(T::zero(), |white| one / (white * white));
+                densities.mapv(|v| {
+                    let x = v * scale;
+                    (x * (one + x * inv_white_sq) / (one + x)).min(one)
+                })
+            }
+            ToneMap::Aces { .. } => {
+                let [a, b, c, d, e] = [2.51, 0.03, 2.43, 0.59, 0.14].map(|k| T::from(k).unwrap());
+                densities.mapv(|v| {
+                    let x = v * scale;
+                    (x * (a * x + b) / (x * (c * x + d) + e)).min(one)
+                })
+            }
+        };
+
+        Ok(mapped.mapv(|v| v.max(T::zero())))
+    }
+}
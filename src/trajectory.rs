@@ -0,0 +1,292 @@
+use num_traits::{Float, FloatConst, NumCast};
+use rand::distr::uniform::SampleUniform;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    error::{check_count, check_finite, check_positive},
+    render::{attractor_path, generate_initial_positions},
+    Attractor, Complex, MandybrotError, RngStrategy,
+};
+
+/// Number of points in each row group of a Parquet file, which is buffered in memory while it is written.
+#[cfg(feature = "parquet")]
+const PARQUET_ROWS_PER_GROUP: usize = 1 << 20;
+
+/// Point visited by the orbit of an attractor, as exported by `attractor_points` rather than drawn into pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct OrbitPoint<T> {
+    pub position: Complex<T>,
+    /// Index of the iteration which reached the point, counting from zero.
+    pub iteration: u32,
+    /// Index of the starting point whose orbit this is.
+    pub start_id: u32,
+}
+
+/// Points of the orbits which `render_attractor_viewport` would draw, in order of starting point and then iteration, without rasterising them.
+/// The starting points are drawn the same way, so a seeded `rng_strategy` gives the points behind a seeded render.
+/// Points are produced lazily, so orbits of any length can be streamed to a file with `PointFormat::write`.
+#[allow(clippy::too_many_arguments)]
+pub fn attractor_points<'a, T>(
+    start: Complex<T>,
+    radius: T,
+    num_samples: u32,
+    max_iter: u32,
+    draw_after: u32,
+    attractor: &'a Attractor<T>,
+    rng_strategy: &RngStrategy,
+) -> Result<impl Iterator<Item = OrbitPoint<T>> + 'a, MandybrotError>
+where
+    T: Float + FloatConst + NumCast + SampleUniform,
+{
+    attractor.validate()?;
+    check_finite("start.real", start.real)?;
+    check_finite("start.imag", start.imag)?;
+    check_positive("radius", radius)?;
+    check_count("num_samples", num_samples)?;
+
+    let positions = generate_initial_positions(start, radius, num_samples, rng_strategy);
+    Ok(positions
+        .into_iter()
+        .enumerate()
+        .flat_map(move |(start_id, start)| {
+            attractor_path(start, max_iter, draw_after, attractor).map(
+                move |(iteration, position)| OrbitPoint {
+                    position,
+                    iteration,
+                    start_id: start_id as u32,
+                },
+            )
+        }))
+}
+
+/// File format for orbit points, each a row of columns `x`, `y`, `iteration` and `start_id`, for analysis in pandas or Polars, or rendering in tools such as datashader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PointFormat {
+    /// Comma-separated text with a header row.
+    Csv,
+    /// Apache Parquet, with positions as doubles and indices as 64-bit integers. Writing it needs the `parquet` feature.
+    Parquet,
+}
+
+impl PointFormat {
+    /// Format of a file, from its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, MandybrotError> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map_or(String::new(), |extension| {
+                extension.to_string_lossy().to_lowercase()
+            });
+        match extension.as_str() {
+            "csv" => Ok(PointFormat::Csv),
+            "parquet" => Ok(PointFormat::Parquet),
+            _ => Err(MandybrotError::UnsupportedFormat(extension)),
+        }
+    }
+
+    /// Writes points in this format, returning how many were written.
+    pub fn write<T: Float>(
+        &self,
+        points: impl IntoIterator<Item = OrbitPoint<T>>,
+        writer: &mut (impl Write + Send),
+    ) -> io::Result<u64> {
+        match self {
+            PointFormat::Csv => write_csv(points, writer),
+            PointFormat::Parquet => write_parquet(points, writer),
+        }
+    }
+}
+
+/// Saves points to a file, in the format given by its extension, returning how many were written.
+pub fn save_points<T: Float>(
+    points: impl IntoIterator<Item = OrbitPoint<T>>,
+    path: impl AsRef<Path>,
+) -> Result<u64, MandybrotError> {
+    let format = PointFormat::from_path(&path)?;
+    let mut file = BufWriter::new(File::create(path)?);
+    let count = format.write(points, &mut file)?;
+    file.flush()?;
+    Ok(count)
+}
+
+fn write_csv<T: Float>(
+    points: impl IntoIterator<Item = OrbitPoint<T>>,
+    writer: &mut impl Write,
+) -> io::Result<u64> {
+    writeln!(writer, "x,y,iteration,start_id")?;
+    let mut count = 0;
+    for point in points {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            point.position.real.to_f64().unwrap_or(f64::NAN),
+            point.position.imag.to_f64().unwrap_or(f64::NAN),
+            point.iteration,
+            point.start_id
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Schema of a Parquet file of points.
+#[cfg(feature = "parquet")]
+const PARQUET_SCHEMA: &str = "message points {
+    required double x;
+    required double y;
+    required int64 iteration;
+    required int64 start_id;
+}";
+
+/// Writes points as a Parquet file with the `parquet` crate, a row group at a time.
+#[cfg(feature = "parquet")]
+fn write_parquet<T: Float>(
+    points: impl IntoIterator<Item = OrbitPoint<T>>,
+    writer: &mut (impl Write + Send),
+) -> io::Result<u64> {
+    use parquet::{
+        data_type::{DoubleType, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+    use std::sync::Arc;
+
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let properties = WriterProperties::builder()
+        .set_created_by("mandybrot".to_string())
+        .build();
+    let mut file = SerializedFileWriter::new(writer, schema, Arc::new(properties))?;
+    let (mut x, mut y, mut iteration, mut start_id) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut num_rows = 0;
+
+    let mut points = points.into_iter().peekable();
+    while points.peek().is_some() {
+        x.clear();
+        y.clear();
+        iteration.clear();
+        start_id.clear();
+        for point in points.by_ref().take(PARQUET_ROWS_PER_GROUP) {
+            x.push(point.position.real.to_f64().unwrap_or(f64::NAN));
+            y.push(point.position.imag.to_f64().unwrap_or(f64::NAN));
+            iteration.push(point.iteration as i64);
+            start_id.push(point.start_id as i64);
+        }
+
+        let mut row_group = file.next_row_group()?;
+        for column in [&x, &y] {
+            let mut writer = row_group.next_column()?.expect("schema has four columns");
+            writer
+                .typed::<DoubleType>()
+                .write_batch(column, None, None)?;
+            writer.close()?;
+        }
+        for column in [&iteration, &start_id] {
+            let mut writer = row_group.next_column()?.expect("schema has four columns");
+            writer
+                .typed::<Int64Type>()
+                .write_batch(column, None, None)?;
+            writer.close()?;
+        }
+        row_group.close()?;
+        num_rows += x.len() as u64;
+    }
+
+    file.close()?;
+    Ok(num_rows)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet<T: Float>(
+    _points: impl IntoIterator<Item = OrbitPoint<T>>,
+    _writer: &mut (impl Write + Send),
+) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        MandybrotError::UnsupportedFormat("parquet".into()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> Vec<OrbitPoint<f64>> {
+        let attractor = Attractor::Clifford {
+            a: -1.4,
+            b: 1.6,
+            c: 1.0,
+            d: 0.7,
+        };
+        attractor_points(
+            Complex::new(0.0, 0.0),
+            1.0,
+            3,
+            50,
+            10,
+            &attractor,
+            &RngStrategy::Seeded(7),
+        )
+        .unwrap()
+        .collect()
+    }
+
+    #[test]
+    fn csv_has_a_row_per_point() {
+        let points = points();
+        let mut csv = Vec::new();
+        assert_eq!(
+            PointFormat::Csv.write(points.clone(), &mut csv).unwrap(),
+            points.len() as u64
+        );
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("x,y,iteration,start_id"));
+        assert_eq!(lines.count(), points.len());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_files_read_back_with_the_parquet_crate() {
+        use parquet::{
+            file::reader::{FileReader, SerializedFileReader},
+            record::RowAccessor,
+        };
+
+        let points = points();
+        let path =
+            std::env::temp_dir().join(format!("mandybrot-points-{}.parquet", std::process::id()));
+        assert_eq!(
+            save_points(points.clone(), &path).unwrap(),
+            points.len() as u64
+        );
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let names: Vec<_> = schema
+            .columns()
+            .iter()
+            .map(|column| column.name())
+            .collect();
+        assert_eq!(names, ["x", "y", "iteration", "start_id"]);
+
+        let rows = reader.get_row_iter(None).unwrap();
+        let mut count = 0;
+        for (row, point) in rows.zip(&points) {
+            let row = row.unwrap();
+            assert_eq!(row.get_double(0).unwrap(), point.position.real);
+            assert_eq!(row.get_double(1).unwrap(), point.position.imag);
+            assert_eq!(row.get_long(2).unwrap(), point.iteration as i64);
+            assert_eq!(row.get_long(3).unwrap(), point.start_id as i64);
+            count += 1;
+        }
+        assert_eq!(count, points.len());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use ndarray::Array2;
+use palette::{LinSrgba, Srgb};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    path::Path,
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+use crate::{
+    error::check_count, over, render::try_render_zoom, ColourSpace, MandybrotError, Precision,
+    ZoomPath,
+};
+
+/// Container and codec of an encoded video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VideoFormat {
+    /// H.264 in an MP4 container.
+    Mp4,
+    /// VP9 in a WebM container.
+    WebM,
+}
+
+/// Settings of an encoded video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoSettings {
+    pub resolution: [u32; 2],
+    pub fps: u32,
+    /// Target bitrate, in kilobits per second.
+    pub bitrate: u32,
+    pub format: VideoFormat,
+}
+
+impl VideoSettings {
+    pub const fn new(resolution: [u32; 2], fps: u32, bitrate: u32, format: VideoFormat) -> Self {
+        Self {
+            resolution,
+            fps,
+            bitrate,
+            format,
+        }
+    }
+
+    /// Checks that the resolution is non-empty and even, as chroma subsampling requires, and that the rates are non-zero.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        let [x_res, y_res] = self.resolution;
+        if x_res == 0 || y_res == 0 {
+            return Err(MandybrotError::EmptyResolution(self.resolution));
+        }
+        for (name, value) in [("resolution[0]", x_res), ("resolution[1]", y_res)] {
+            if !value.is_multiple_of(2) {
+                return Err(MandybrotError::InvalidParameter {
+                    name,
+                    value: value as f64,
+                });
+            }
+        }
+        check_count("fps", self.fps)?;
+        check_count("bitrate", self.bitrate)
+    }
+}
+
+/// Streams frames into an `ffmpeg` subprocess, which must be on the `PATH`.
+/// Frames are encoded as they are written, so a video of any length can be produced without holding its frames in memory.
+#[derive(Debug)]
+pub struct VideoEncoder {
+    resolution: [u32; 2],
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl VideoEncoder {
+    /// Starts encoding a video to the given path, overwriting any existing file.
+    pub fn create(
+        path: impl AsRef<Path>,
+        settings: &VideoSettings,
+    ) -> Result<Self, MandybrotError> {
+        settings.validate()?;
+
+        let [x_res, y_res] = settings.resolution;
+        let codec: &[&str] = match settings.format {
+            VideoFormat::Mp4 => &["-c:v", "libx264", "-movflags", "+faststart"],
+            VideoFormat::WebM => &["-c:v", "libvpx-vp9"],
+        };
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgb24"])
+            .args(["-s", &format!("{}x{}", x_res, y_res)])
+            .args(["-r", &settings.fps.to_string()])
+            .args(["-i", "-"])
+            .args(codec)
+            .args(["-b:v", &format!("{}k", settings.bitrate)])
+            .args(["-pix_fmt", "yuv420p"])
+            .arg(path.as_ref())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("The encoder's input is piped");
+
+        Ok(Self {
+            resolution: settings.resolution,
+            child,
+            stdin,
+        })
+    }
+
+    /// Encodes the next frame, which must match the video's resolution.
+    /// Transparent colours are composited onto black.
+    pub fn write_frame<C: ColourSpace>(&mut self, frame: &Array2<C>) -> Result<(), MandybrotError> {
+        let (height, width) = frame.dim();
+        if [width, height] != self.resolution.map(|res| res as usize) {
+            return Err(MandybrotError::BufferSize {
+                expected: (self.resolution[0] * self.resolution[1]) as usize,
+                found: width * height,
+            });
+        }
+
+        let bytes: Vec<u8> = frame
+            .iter()
+            .flat_map(|&colour| {
+                let opaque = over(colour.into_linear(), LinSrgba::new(0.0, 0.0, 0.0, 1.0));
+                let pixel: Srgb<u8> = Srgb::from_linear(opaque.color);
+                [pixel.red, pixel.green, pixel.blue]
+            })
+            .collect();
+        self.stdin.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Finishes the video, waiting for the encoder to exit.
+    pub fn finish(self) -> Result<(), MandybrotError> {
+        let Self {
+            mut child, stdin, ..
+        } = self;
+        drop(stdin);
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("ffmpeg exited with {}", status)).into());
+        }
+        Ok(())
+    }
+}
+
+/// Renders a zoom into the Mandelbrot set straight to a video, colouring each frame of iteration counts with `colour`.
+/// Frames are encoded as soon as they are rendered, so memory use does not grow with the length of the zoom.
+pub fn render_zoom_video<F>(
+    zoom: &ZoomPath,
+    max_iter: u32,
+    settings: &VideoSettings,
+    path: impl AsRef<Path>,
+    mut colour: F,
+) -> Result<(), MandybrotError>
+where
+    F: FnMut(u32, Precision, &Array2<u32>) -> Array2<LinSrgba>,
+{
+    zoom.validate()?;
+    let mut encoder = VideoEncoder::create(path, settings)?;
+    try_render_zoom(
+        zoom,
+        settings.resolution,
+        max_iter,
+        |frame, precision, data| encoder.write_frame(&colour(frame, precision, &data)),
+    )?;
+    encoder.finish()
+}
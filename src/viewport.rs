@@ -0,0 +1,186 @@
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_finite, check_positive},
+    Affine, Complex, MandybrotError,
+};
+
+/// Region of the complex plane covered by a rendered image, and the mapping between its pixels and complex coordinates.
+/// Pixel `(x, y)` covers the continuous range `[x, x + 1) × [y, y + 1)`, with `x` increasing along the real axis and `y` along the imaginary axis,
+/// before the `transform` rotates, shears or stretches the image about its centre.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub struct Viewport<T> {
+    pub centre: Complex<T>,
+    /// Height of the image in the complex plane.
+    pub scale: T,
+    pub resolution: [u32; 2],
+    /// Map from offsets from the centre of the untransformed image to offsets in the complex plane.
+    #[serde(default)]
+    pub transform: Affine<T>,
+    /// Width of a pixel relative to its height, as displayed, for anamorphic output whose pixels are not square.
+    #[serde(default = "unit")]
+    pub pixel_aspect: T,
+}
+
+fn unit<T: Float>() -> T {
+    T::one()
+}
+
+impl<T: Float> Viewport<T> {
+    pub fn new(centre: Complex<T>, scale: T, resolution: [u32; 2]) -> Self {
+        Self {
+            centre,
+            scale,
+            resolution,
+            transform: Affine::identity(),
+            pixel_aspect: T::one(),
+        }
+    }
+
+    /// The same viewport with its image mapped onto the complex plane through `transform`, about its centre.
+    pub fn with_transform(self, transform: Affine<T>) -> Self {
+        Self { transform, ..self }
+    }
+
+    /// The same viewport for display with pixels `pixel_aspect` times as wide as they are tall.
+    pub fn with_pixel_aspect(self, pixel_aspect: T) -> Self {
+        Self {
+            pixel_aspect,
+            ..self
+        }
+    }
+
+    /// Viewport which frames every finite point, with a small margin, at the given resolution, or `None` if there are none.
+    /// Points which all coincide are framed in a region one unit high.
+    pub fn fit(points: impl IntoIterator<Item = Complex<T>>, resolution: [u32; 2]) -> Option<Self> {
+        let (min, max) = points
+            .into_iter()
+            .filter(|p| p.real.is_finite() && p.imag.is_finite())
+            .fold(None, |bounds: Option<(Complex<T>, Complex<T>)>, p| {
+                Some(bounds.map_or((p, p), |(min, max)| {
+                    (
+                        Complex::new(min.real.min(p.real), min.imag.min(p.imag)),
+                        Complex::new(max.real.max(p.real), max.imag.max(p.imag)),
+                    )
+                }))
+            })?;
+
+        let two = T::from(2.0).unwrap();
+        let margin = T::from(1.05).unwrap();
+        let aspect_ratio = T::from(resolution[0]).unwrap() / T::from(resolution[1].max(1)).unwrap();
+        let scale = (max.imag - min.imag).max((max.real - min.real) / aspect_ratio);
+        Some(Self::new(
+            Complex::new((min.real + max.real) / two, (min.imag + max.imag) / two),
+            if scale > T::zero() {
+                scale * margin
+            } else {
+                T::one()
+            },
+            resolution,
+        ))
+    }
+
+    /// The same region of the complex plane, divided into a different number of pixels.
+    pub fn with_resolution(self, resolution: [u32; 2]) -> Self {
+        Self { resolution, ..self }
+    }
+
+    /// Checks that the viewport covers at least one pixel of a finite, non-empty region of the complex plane.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        let [x_res, y_res] = self.resolution;
+        if x_res == 0 || y_res == 0 {
+            return Err(MandybrotError::EmptyResolution(self.resolution));
+        }
+        check_finite("centre.real", self.centre.real)?;
+        check_finite("centre.imag", self.centre.imag)?;
+        check_positive("scale", self.scale)?;
+        check_positive("pixel_aspect", self.pixel_aspect)?;
+        self.transform.validate()
+    }
+
+    /// Size of the image in the complex plane, before the transform.
+    pub fn size(&self) -> Complex<T> {
+        let [x_res, y_res] = self.resolution;
+        let aspect_ratio = T::from(x_res).unwrap() / T::from(y_res).unwrap() * self.pixel_aspect;
+        Complex::new(self.scale * aspect_ratio, self.scale)
+    }
+
+    /// Size of a single pixel in the complex plane, before the transform.
+    pub fn pixel_size(&self) -> Complex<T> {
+        let [x_res, y_res] = self.resolution;
+        let size = self.size();
+        Complex::new(
+            size.real / T::from(x_res).unwrap(),
+            size.imag / T::from(y_res).unwrap(),
+        )
+    }
+
+    /// Converts continuous pixel coordinates into a point in the complex plane.
+    pub fn pixel_to_complex(&self, x: T, y: T) -> Complex<T> {
+        let [x_res, y_res] = self.resolution;
+        let two = T::from(2.0).unwrap();
+        let step = self.pixel_size();
+        let offset = self.transform.apply(Complex::new(
+            (x - T::from(x_res).unwrap() / two) * step.real,
+            (y - T::from(y_res).unwrap() / two) * step.imag,
+        ));
+        self.centre + offset
+    }
+
+    /// Converts a point in the complex plane into continuous pixel coordinates, undoing `pixel_to_complex`.
+    /// Points beyond the image continue its pixel grid.
+    pub fn complex_to_grid(&self, c: &Complex<T>) -> Complex<T> {
+        let [x_res, y_res] = self.resolution;
+        let two = T::from(2.0).unwrap();
+        let step = self.pixel_size();
+        let offset = *c - self.centre;
+        let offset = match self.transform.inverse() {
+            Some(inverse) => inverse.apply(offset),
+            None => Complex::new(T::nan(), T::nan()),
+        };
+        Complex::new(
+            offset.real / step.real + T::from(x_res).unwrap() / two,
+            offset.imag / step.imag + T::from(y_res).unwrap() / two,
+        )
+    }
+
+    /// Point in the complex plane at the centre of the given pixel.
+    pub fn pixel_centre(&self, x: u32, y: u32) -> Complex<T> {
+        let half = T::from(0.5).unwrap();
+        self.pixel_to_complex(T::from(x).unwrap() + half, T::from(y).unwrap() + half)
+    }
+
+    /// Viewport covering the pixels `[x, x + width) × [y, y + height)` of this one, on exactly the same pixel grid.
+    /// The region may extend beyond the image, continuing the grid.
+    pub fn crop(&self, origin: [u32; 2], resolution: [u32; 2]) -> Self {
+        let [x, y] = origin;
+        let [width, height] = resolution;
+        let two = T::from(2.0).unwrap();
+        let centre = self.pixel_to_complex(
+            T::from(x).unwrap() + T::from(width).unwrap() / two,
+            T::from(y).unwrap() + T::from(height).unwrap() / two,
+        ) - self.transform.translation;
+        Self {
+            centre,
+            scale: self.pixel_size().imag * T::from(height).unwrap(),
+            resolution,
+            ..*self
+        }
+    }
+
+    /// Converts a point in the complex plane into the `[x, y]` index of the pixel containing it, if it lies within the image.
+    pub fn complex_to_pixel(&self, c: &Complex<T>) -> Option<[usize; 2]> {
+        let [x_res, y_res] = self.resolution;
+        let x_res = T::from(x_res).unwrap();
+        let y_res = T::from(y_res).unwrap();
+        let Complex { real: x, imag: y } = self.complex_to_grid(c);
+
+        if x >= T::zero() && x < x_res && y >= T::zero() && y < y_res {
+            Some([x.to_usize().unwrap(), y.to_usize().unwrap()])
+        } else {
+            None
+        }
+    }
+}
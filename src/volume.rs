@@ -0,0 +1,208 @@
+use indicatif::ParallelProgressIterator;
+use ndarray::{Array3, Axis};
+use num_traits::Float;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    error::{check_count, check_finite},
+    plane_slice::{sample_slice, validate_slice},
+    render::{par_map_pixels, progress_bar},
+    Complex, EscapeMap, Fractal, MandybrotError, PlaneAxis, SlicePoint, Viewport,
+};
+
+/// Parameter swept along the depth of a volume, each slice of which is an image of the viewport at one value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Float + Deserialize<'de>"))]
+pub enum VolumeSweep<T> {
+    /// Julia sets whose `c` moves along a straight line.
+    Julia { from: Complex<T>, to: Complex<T> },
+    /// Multibrot sets `z → z^power + c` whose real power varies continuously.
+    MultibrotPower { from: T, to: T },
+    /// A third coordinate of an escape map's space of starting points and parameters, with the image spanning the first two `axes`.
+    Slice {
+        map: EscapeMap,
+        axes: [PlaneAxis; 3],
+        fixed: SlicePoint<T>,
+        from: T,
+        to: T,
+    },
+}
+
+impl<T: Float> VolumeSweep<T> {
+    /// Checks that the ends of the sweep are finite, the powers above one and the slice axes distinct.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        match *self {
+            VolumeSweep::Julia { from, to } => {
+                check_finite("from.real", from.real)?;
+                check_finite("from.imag", from.imag)?;
+                check_finite("to.real", to.real)?;
+                check_finite("to.imag", to.imag)
+            }
+            VolumeSweep::MultibrotPower { from, to } => {
+                Fractal::RealMultibrot { power: from }.validate()?;
+                Fractal::RealMultibrot { power: to }.validate()
+            }
+            VolumeSweep::Slice {
+                axes,
+                fixed,
+                from,
+                to,
+                ..
+            } => {
+                validate_slice([axes[0], axes[1]], &fixed)?;
+                if axes[2] == axes[0] || axes[2] == axes[1] {
+                    return Err(MandybrotError::DuplicateAxis(axes[2].name()));
+                }
+                check_finite("from", from)?;
+                check_finite("to", to)
+            }
+        }
+    }
+
+    /// Iteration count of point `p` of the slice a fraction `t` of the way through the sweep.
+    fn sample(&self, p: Complex<T>, t: T, max_iter: u32) -> u32 {
+        let lerp = |from: T, to: T| from + (to - from) * t;
+        match *self {
+            VolumeSweep::Julia { from, to } => {
+                let c = Complex::new(lerp(from.real, to.real), lerp(from.imag, to.imag));
+                Fractal::Julia { c }.sample(p, max_iter, None)
+            }
+            VolumeSweep::MultibrotPower { from, to } => {
+                let power = lerp(from, to);
+                Fractal::RealMultibrot { power }.sample(p, max_iter, None)
+            }
+            VolumeSweep::Slice {
+                map,
+                axes,
+                fixed,
+                from,
+                to,
+            } => {
+                let fixed = fixed.with(axes[2], lerp(from, to));
+                sample_slice(
+                    map,
+                    [axes[0], axes[1]],
+                    &fixed,
+                    p,
+                    max_iter,
+                    None,
+                    &mut |_| {},
+                )
+                .0
+            }
+        }
+    }
+
+    /// Distance the swept parameter moves from one end of the sweep to the other.
+    fn length(&self) -> T {
+        match *self {
+            VolumeSweep::Julia { from, to } => {
+                let (dx, dy) = (to.real - from.real, to.imag - from.imag);
+                (dx * dx + dy * dy).sqrt()
+            }
+            VolumeSweep::MultibrotPower { from, to } | VolumeSweep::Slice { from, to, .. } => {
+                (to - from).abs()
+            }
+        }
+    }
+}
+
+/// Samples a volume of iteration counts, a "CT scan" of a fractal whose depth sweeps a parameter through `num_slices` evenly spaced values.
+/// The volume is indexed `[slice, y, x]`, each slice an image of the viewport, ready for volume rendering or saving with `save_nrrd`.
+pub fn render_volume<T>(
+    viewport: &Viewport<T>,
+    sweep: &VolumeSweep<T>,
+    num_slices: u32,
+    max_iter: u32,
+) -> Result<Array3<u32>, MandybrotError>
+where
+    T: Float + Send + Sync,
+{
+    viewport.validate()?;
+    sweep.validate()?;
+    check_count("num_slices", num_slices)?;
+
+    let [x_res, y_res] = viewport.resolution;
+    let mut volume = Array3::<u32>::zeros((num_slices as usize, y_res as usize, x_res as usize));
+    let last = T::from((num_slices - 1).max(1)).unwrap();
+
+    // Create a progress bar for rendering slices.
+    let pb = progress_bar(num_slices as u64)?;
+
+    volume
+        .axis_iter_mut(Axis(0))
+        .into_par_iter()
+        .enumerate()
+        .progress_with(pb)
+        .try_for_each(|(k, mut slice)| {
+            let t = T::from(k).unwrap() / last;
+            let image = par_map_pixels(viewport.resolution, false, |[x, y]| {
+                sweep.sample(viewport.pixel_centre(x, y), t, max_iter)
+            })?;
+            slice.assign(&image);
+            Ok::<_, MandybrotError>(())
+        })?;
+
+    Ok(volume)
+}
+
+/// Spacing between neighbouring voxels along the x, y and depth axes of a volume rendered by `render_volume`, for placing it in a viewer.
+pub fn volume_spacings<T: Float>(
+    viewport: &Viewport<T>,
+    sweep: &VolumeSweep<T>,
+    num_slices: u32,
+) -> [f64; 3] {
+    let pixel = viewport.pixel_size();
+    let depth = if num_slices > 1 {
+        sweep.length().to_f64().unwrap() / (num_slices - 1) as f64
+    } else {
+        1.0
+    };
+    [
+        pixel.real.to_f64().unwrap(),
+        pixel.imag.to_f64().unwrap(),
+        depth,
+    ]
+}
+
+/// Saves a volume of counts as an NRRD file, which ParaView and other volume viewers can read.
+pub fn save_nrrd(
+    volume: &Array3<u32>,
+    spacings: [f64; 3],
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    write_nrrd(volume, spacings, &mut file)?;
+    file.flush()
+}
+
+/// Writes a volume of counts as an NRRD file of raw little-endian 32-bit samples, with `x` varying fastest and the depth slowest.
+pub fn write_nrrd(
+    volume: &Array3<u32>,
+    spacings: [f64; 3],
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let (depth, height, width) = volume.dim();
+    writeln!(writer, "NRRD0004")?;
+    writeln!(writer, "type: uint32")?;
+    writeln!(writer, "dimension: 3")?;
+    writeln!(writer, "sizes: {} {} {}", width, height, depth)?;
+    writeln!(
+        writer,
+        "spacings: {} {} {}",
+        spacings[0], spacings[1], spacings[2]
+    )?;
+    writeln!(writer, "encoding: raw")?;
+    writeln!(writer, "endian: little")?;
+    writeln!(writer)?;
+    for &count in volume {
+        writer.write_all(&count.to_le_bytes())?;
+    }
+    Ok(())
+}
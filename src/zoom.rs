@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{check_count, check_finite, check_positive},
+    Complex, DoubleDouble, MandybrotError,
+};
+
+/// Numeric backend used to render a frame of a zoom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Precision {
+    /// Every pixel is iterated in `f32`.
+    Single,
+    /// Every pixel is iterated in `f64`.
+    Double,
+    /// Every pixel is iterated in `DoubleDouble`.
+    DoubleDouble,
+    /// Pixels are iterated as `f64` offsets from a reference orbit computed in `DoubleDouble` precision.
+    Perturbation,
+}
+
+/// Pixel size below which `f32` can no longer resolve neighbouring pixels near the Mandelbrot set.
+const SINGLE_MIN_PIXEL_SIZE: f64 = 1.0e-5;
+
+/// Pixel size below which `f64` can no longer resolve neighbouring pixels near the Mandelbrot set.
+const DOUBLE_MIN_PIXEL_SIZE: f64 = 1.0e-13;
+
+/// Pixel size below which frames need so many iterations that iterating every pixel in `DoubleDouble` costs more than perturbation about one reference orbit.
+const DOUBLE_DOUBLE_MIN_PIXEL_SIZE: f64 = 1.0e-18;
+
+/// Smallest scale which `DoubleDouble` coordinates can resolve.
+pub(crate) const PERTURBATION_MIN_SCALE: f64 = 1.0e-28;
+
+/// Scale of the first frame of a zoom, showing the whole Mandelbrot set.
+const START_SCALE: f64 = 3.0;
+
+impl Precision {
+    /// Cheapest backend which can resolve pixels of the given size.
+    pub fn for_pixel_size(pixel_size: f64) -> Self {
+        if pixel_size > SINGLE_MIN_PIXEL_SIZE {
+            Precision::Single
+        } else if pixel_size > DOUBLE_MIN_PIXEL_SIZE {
+            Precision::Double
+        } else if pixel_size > DOUBLE_DOUBLE_MIN_PIXEL_SIZE {
+            Precision::DoubleDouble
+        } else {
+            Precision::Perturbation
+        }
+    }
+}
+
+/// Exponential zoom into the Mandelbrot set, from the whole set down to `final_scale` over `num_frames` frames, centred on `target`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ZoomPath {
+    pub target: Complex<DoubleDouble>,
+    pub final_scale: f64,
+    pub num_frames: u32,
+}
+
+impl ZoomPath {
+    pub const fn new(target: Complex<DoubleDouble>, final_scale: f64, num_frames: u32) -> Self {
+        Self {
+            target,
+            final_scale,
+            num_frames,
+        }
+    }
+
+    /// Checks that the zoom has frames and ends at a scale which can be resolved.
+    pub fn validate(&self) -> Result<(), MandybrotError> {
+        check_count("num_frames", self.num_frames)?;
+        check_finite("target.real", self.target.real.to_f64())?;
+        check_finite("target.imag", self.target.imag.to_f64())?;
+        check_positive("final_scale", self.final_scale)?;
+        if self.final_scale < PERTURBATION_MIN_SCALE {
+            return Err(MandybrotError::InvalidParameter {
+                name: "final_scale",
+                value: self.final_scale,
+            });
+        }
+        Ok(())
+    }
+
+    /// Scale of the given frame, shrinking by a constant factor each frame.
+    pub fn scale(&self, frame: u32) -> f64 {
+        if self.num_frames <= 1 {
+            return self.final_scale;
+        }
+        let t = frame as f64 / (self.num_frames - 1) as f64;
+        START_SCALE * (self.final_scale / START_SCALE).powf(t)
+    }
+
+    /// Numeric backend needed to render the given frame at the given resolution.
+    pub fn precision(&self, frame: u32, resolution: [u32; 2]) -> Precision {
+        Precision::for_pixel_size(self.scale(frame) / resolution[1] as f64)
+    }
+}
+
+/// Iterates the Mandelbrot map at `c` in `DoubleDouble` precision, returning the orbit rounded to `f64`.
+/// The orbit starts with `z = 0` and ends at the first escaping point, or after `max_iter` iterations.
+pub(crate) fn reference_orbit(c: Complex<DoubleDouble>, max_iter: u32) -> Vec<Complex<f64>> {
+    let mut z = Complex::new(DoubleDouble::default(), DoubleDouble::default());
+    let mut orbit = Vec::with_capacity(max_iter as usize + 1);
+    orbit.push(Complex::new(0.0, 0.0));
+
+    for _ in 0..max_iter {
+        z = z * z + c;
+        let rounded = Complex::new(z.real.to_f64(), z.imag.to_f64());
+        orbit.push(rounded);
+        if rounded.norm_sqr() > 4.0 {
+            break;
+        }
+    }
+
+    orbit
+}
+
+/// Samples the Mandelbrot set at `reference + dc`, iterating the offset from the reference orbit in `f64`.
+/// The offset is rebased onto the start of the reference orbit whenever the full orbit passes closer to the origin than the offset is large, or the reference orbit runs out, which avoids glitches without needing further references.
+pub(crate) fn sample_perturbed(orbit: &[Complex<f64>], dc: Complex<f64>, max_iter: u32) -> u32 {
+    let mut dz = Complex::new(0.0, 0.0);
+    let mut m = 0;
+
+    for n in 1..=max_iter {
+        let reference = orbit[m];
+        dz = (reference + reference) * dz + dz * dz + dc;
+        m += 1;
+
+        let z = orbit[m] + dz;
+        if z.norm_sqr() > 4.0 {
+            return n;
+        }
+        if z.norm_sqr() < dz.norm_sqr() || m == orbit.len() - 1 {
+            dz = z;
+            m = 0;
+        }
+    }
+
+    max_iter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precision_hands_over_at_each_pixel_size_limit() {
+        let limits = [
+            (SINGLE_MIN_PIXEL_SIZE, Precision::Single, Precision::Double),
+            (
+                DOUBLE_MIN_PIXEL_SIZE,
+                Precision::Double,
+                Precision::DoubleDouble,
+            ),
+            (
+                DOUBLE_DOUBLE_MIN_PIXEL_SIZE,
+                Precision::DoubleDouble,
+                Precision::Perturbation,
+            ),
+        ];
+        for (limit, above, at) in limits {
+            assert_eq!(Precision::for_pixel_size(limit * 1.01), above);
+            assert_eq!(Precision::for_pixel_size(limit), at);
+        }
+    }
+
+    #[test]
+    fn zoom_passes_through_every_precision_in_order() {
+        let target = Complex::new(DoubleDouble::from_f64(-0.75), DoubleDouble::from_f64(0.1));
+        let zoom = ZoomPath::new(target, 1.0e-25, 100);
+        let precisions: Vec<_> = (0..zoom.num_frames)
+            .map(|frame| zoom.precision(frame, [64, 64]))
+            .collect();
+        let mut changes = precisions.clone();
+        changes.dedup();
+        assert_eq!(
+            changes,
+            [
+                Precision::Single,
+                Precision::Double,
+                Precision::DoubleDouble,
+                Precision::Perturbation,
+            ]
+        );
+
+        // The first DoubleDouble frame is the first whose pixels are no larger than the f64 limit.
+        let handover = precisions
+            .iter()
+            .position(|&precision| precision == Precision::DoubleDouble)
+            .unwrap() as u32;
+        assert!(zoom.scale(handover) / 64.0 <= DOUBLE_MIN_PIXEL_SIZE);
+        assert!(zoom.scale(handover - 1) / 64.0 > DOUBLE_MIN_PIXEL_SIZE);
+    }
+}